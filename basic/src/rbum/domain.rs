@@ -1,10 +1,12 @@
 pub mod rbum_cert;
 pub mod rbum_cert_conf;
 pub mod rbum_domain;
+pub mod rbum_event_outbox;
 pub mod rbum_item;
 pub mod rbum_item_attr;
 pub mod rbum_kind;
 pub mod rbum_kind_attr;
+pub mod rbum_migration;
 pub mod rbum_rel;
 pub mod rbum_rel_attr;
 pub mod rbum_rel_env;
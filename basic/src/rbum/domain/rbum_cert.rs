@@ -5,6 +5,8 @@ use tardis::db::sea_orm::prelude::*;
 use tardis::db::sea_orm::*;
 use tardis::db::sea_query::{ColumnDef, Index, IndexCreateStatement, Table, TableCreateStatement};
 
+use crate::rbum::helper::rbum_dialect_helper;
+
 /// Credential or authentication instance model
 ///
 /// Uniform use of cert refers to credentials or authentication
@@ -65,7 +67,7 @@ impl TardisActiveModel for ActiveModel {
         }
     }
 
-    fn create_table_statement(_: DbBackend) -> TableCreateStatement {
+    fn create_table_statement(db: DbBackend) -> TableCreateStatement {
         Table::create()
             .table(Entity.table_ref())
             .if_not_exists()
@@ -85,7 +87,7 @@ impl TardisActiveModel for ActiveModel {
             .col(ColumnDef::new(Column::OwnPaths).not_null().string())
             .col(ColumnDef::new(Column::Owner).not_null().string())
             .col(ColumnDef::new(Column::CreateTime).extra("DEFAULT CURRENT_TIMESTAMP".to_string()).date_time())
-            .col(ColumnDef::new(Column::UpdateTime).extra("DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP".to_string()).date_time())
+            .col(&mut rbum_dialect_helper::update_time_column_def(Column::UpdateTime, db))
             .to_owned()
     }
 
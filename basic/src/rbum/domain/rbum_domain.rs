@@ -4,6 +4,8 @@ use tardis::db::sea_orm::prelude::*;
 use tardis::db::sea_orm::*;
 use tardis::db::sea_query::{ColumnDef, Index, IndexCreateStatement, Table, TableCreateStatement};
 
+use crate::rbum::helper::rbum_dialect_helper;
+
 /// Resource domain model
 ///
 /// The resource domain is the unit of ownership of the resource, indicating the owner of the resource.
@@ -39,7 +41,7 @@ impl TardisActiveModel for ActiveModel {
         }
     }
 
-    fn create_table_statement(_: DbBackend) -> TableCreateStatement {
+    fn create_table_statement(db: DbBackend) -> TableCreateStatement {
         Table::create()
             .table(Entity.table_ref())
             .if_not_exists()
@@ -54,7 +56,7 @@ impl TardisActiveModel for ActiveModel {
             .col(ColumnDef::new(Column::OwnPaths).not_null().string())
             .col(ColumnDef::new(Column::Owner).not_null().string())
             .col(ColumnDef::new(Column::CreateTime).extra("DEFAULT CURRENT_TIMESTAMP".to_string()).date_time())
-            .col(ColumnDef::new(Column::UpdateTime).extra("DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP".to_string()).date_time())
+            .col(&mut rbum_dialect_helper::update_time_column_def(Column::UpdateTime, db))
             // With Scope
             .col(ColumnDef::new(Column::ScopeLevel).not_null().tiny_integer())
             .to_owned()
@@ -0,0 +1,86 @@
+use sea_orm::EntityName;
+use tardis::basic::dto::TardisContext;
+use tardis::db::reldb_client::TardisActiveModel;
+use tardis::db::sea_orm::prelude::*;
+use tardis::db::sea_orm::*;
+use tardis::db::sea_query::{ColumnDef, Index, IndexCreateStatement, Table, TableCreateStatement};
+
+use crate::rbum::helper::rbum_dialect_helper;
+
+/// Event outbox model
+///
+/// Messages are written to this table in the same transaction as the business data,
+/// and are delivered to MQ by the relay after the transaction is committed
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "rbum_event_outbox")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    /// MQ topic
+    pub topic: String,
+    /// MQ header, json format
+    pub header: String,
+    /// MQ message body
+    pub message: String,
+    /// Associated record id, messages of the same record are delivered in order
+    pub record_id: String,
+    /// Sequence number of the messages of the record, the last one of the record plus one, used to keep the delivery order
+    pub seq: i64,
+    pub published: bool,
+    /// Time when the message is published in milliseconds, used to purge the published messages \
+    /// added by the migration step 4, so it is not in the create table statement
+    pub published_time: i64,
+    /// The message is claimed by a relay until this time in milliseconds \
+    /// added by the migration step 4, so it is not in the create table statement
+    pub relay_expire_time: i64,
+    pub retry_times: u32,
+    pub last_error: String,
+
+    pub own_paths: String,
+    pub owner: String,
+    pub create_time: DateTime,
+    pub update_time: DateTime,
+}
+
+impl TardisActiveModel for ActiveModel {
+    fn fill_cxt(&mut self, ctx: &TardisContext, is_insert: bool) {
+        if is_insert {
+            self.own_paths = Set(ctx.own_paths.to_string());
+            self.owner = Set(ctx.owner.to_string());
+        }
+    }
+
+    fn create_table_statement(db: DbBackend) -> TableCreateStatement {
+        Table::create()
+            .table(Entity.table_ref())
+            .if_not_exists()
+            .col(ColumnDef::new(Column::Id).not_null().string().primary_key())
+            // Specific
+            .col(ColumnDef::new(Column::Topic).not_null().string())
+            .col(ColumnDef::new(Column::Header).not_null().text())
+            .col(ColumnDef::new(Column::Message).not_null().text())
+            .col(ColumnDef::new(Column::RecordId).not_null().string())
+            .col(ColumnDef::new(Column::Seq).not_null().big_integer())
+            .col(ColumnDef::new(Column::Published).not_null().boolean())
+            .col(ColumnDef::new(Column::RetryTimes).not_null().unsigned())
+            .col(ColumnDef::new(Column::LastError).not_null().text())
+            // Basic
+            .col(ColumnDef::new(Column::OwnPaths).not_null().string())
+            .col(ColumnDef::new(Column::Owner).not_null().string())
+            .col(ColumnDef::new(Column::CreateTime).extra("DEFAULT CURRENT_TIMESTAMP".to_string()).date_time())
+            .col(&mut rbum_dialect_helper::update_time_column_def(Column::UpdateTime, db))
+            .to_owned()
+    }
+
+    fn create_index_statement() -> Vec<IndexCreateStatement> {
+        vec![
+            Index::create().name(&format!("idx-{}-{}", Entity.table_name(), Column::Published.to_string())).table(Entity).col(Column::Published).col(Column::Seq).to_owned(),
+            Index::create().name(&format!("idx-{}-{}", Entity.table_name(), Column::RecordId.to_string())).table(Entity).col(Column::RecordId).to_owned(),
+        ]
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
@@ -0,0 +1,59 @@
+use sea_orm::EntityName;
+use tardis::basic::dto::TardisContext;
+use tardis::db::reldb_client::TardisActiveModel;
+use tardis::db::sea_orm::prelude::*;
+use tardis::db::sea_orm::*;
+use tardis::db::sea_query::{ColumnDef, Index, IndexCreateStatement, Table, TableCreateStatement};
+
+/// Migration history model
+///
+/// Each applied migration step of a module is recorded here, see ``rbum_migration_helper``
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "rbum_migration")]
+pub struct Model {
+    /// Format: ``<module>:<version>``
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    /// Module code, e.g. ``rbum`` or ``iam``
+    pub module: String,
+    pub version: u32,
+    pub name: String,
+    /// Checksum of the step statements, used to detect steps modified after they are applied
+    pub checksum: String,
+
+    pub create_time: DateTime,
+}
+
+impl TardisActiveModel for ActiveModel {
+    fn fill_cxt(&mut self, _: &TardisContext, _: bool) {}
+
+    fn create_table_statement(_: DbBackend) -> TableCreateStatement {
+        Table::create()
+            .table(Entity.table_ref())
+            .if_not_exists()
+            .col(ColumnDef::new(Column::Id).not_null().string().primary_key())
+            // Specific
+            .col(ColumnDef::new(Column::Module).not_null().string())
+            .col(ColumnDef::new(Column::Version).not_null().unsigned())
+            .col(ColumnDef::new(Column::Name).not_null().string())
+            .col(ColumnDef::new(Column::Checksum).not_null().string())
+            // Basic
+            .col(ColumnDef::new(Column::CreateTime).extra("DEFAULT CURRENT_TIMESTAMP".to_string()).date_time())
+            .to_owned()
+    }
+
+    fn create_index_statement() -> Vec<IndexCreateStatement> {
+        vec![Index::create()
+            .name(&format!("idx-{}-{}", Entity.table_name(), Column::Module.to_string()))
+            .table(Entity)
+            .col(Column::Module)
+            .col(Column::Version)
+            .unique()
+            .to_owned()]
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
@@ -4,6 +4,8 @@ use tardis::db::sea_orm::prelude::*;
 use tardis::db::sea_orm::*;
 use tardis::db::sea_query::{ColumnDef, Index, IndexCreateStatement, Table, TableCreateStatement};
 
+use crate::rbum::helper::rbum_dialect_helper;
+
 /// Relationship attribute condition model
 ///
 /// This model is used to further qualify the conditions under which the relationship is established
@@ -16,6 +18,9 @@ pub struct Model {
     /// if true, it means the limitation of the relationship source,
     /// otherwise it is the limitation of the relationship target resource
     pub is_from: bool,
+    /// [Operator](crate::rbum::rbum_enumeration::RbumRelAttrOpKind) between the attribute value and ``value`` \
+    /// added by the migration step 2, so it is not in the create table statement
+    pub op: u8,
     /// Attribute value
     pub value: String,
     /// Attribute name, redundant field
@@ -42,7 +47,7 @@ impl TardisActiveModel for ActiveModel {
         }
     }
 
-    fn create_table_statement(_: DbBackend) -> TableCreateStatement {
+    fn create_table_statement(db: DbBackend) -> TableCreateStatement {
         Table::create()
             .table(Entity.table_ref())
             .if_not_exists()
@@ -58,7 +63,7 @@ impl TardisActiveModel for ActiveModel {
             .col(ColumnDef::new(Column::OwnPaths).not_null().string())
             .col(ColumnDef::new(Column::Owner).not_null().string())
             .col(ColumnDef::new(Column::CreateTime).extra("DEFAULT CURRENT_TIMESTAMP".to_string()).date_time())
-            .col(ColumnDef::new(Column::UpdateTime).extra("DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP".to_string()).date_time())
+            .col(&mut rbum_dialect_helper::update_time_column_def(Column::UpdateTime, db))
             .to_owned()
     }
 
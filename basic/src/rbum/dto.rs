@@ -1,15 +1,20 @@
+pub mod rbum_archive_dto;
+pub mod rbum_batch_dto;
 pub mod rbum_cert_conf_dto;
 pub mod rbum_cert_dto;
+pub mod rbum_cursor_dto;
 pub mod rbum_domain_dto;
 pub mod rbum_filer_dto;
 pub mod rbum_item_attr_dto;
 pub mod rbum_item_dto;
 pub mod rbum_kind_attr_dto;
 pub mod rbum_kind_dto;
+pub mod rbum_recycle_dto;
 pub mod rbum_rel_agg_dto;
 pub mod rbum_rel_attr_dto;
 pub mod rbum_rel_dto;
 pub mod rbum_rel_env_dto;
+pub mod rbum_scope_dto;
 pub mod rbum_set_cate_dto;
 pub mod rbum_set_dto;
 pub mod rbum_set_item_dto;
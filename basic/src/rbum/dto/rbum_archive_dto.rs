@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use tardis::chrono::{DateTime, Utc};
+
+/// Portable archive of all records under an own paths, see ``RbumArchiveServ``
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumArchive {
+    /// Version of the archive format, see ``RbumArchiveServ::VERSION``
+    pub version: u32,
+    /// Own paths of the exported records
+    pub own_paths: String,
+    pub export_time: DateTime<Utc>,
+    /// Tables in the order they should be imported
+    pub tables: Vec<RbumArchiveTable>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumArchiveTable {
+    pub table_name: String,
+    /// Records of the table, all columns are included
+    pub records: Vec<tardis::serde_json::Value>,
+}
+
+/// Summary of an archive, it is the ``manifest.json`` of the bundle, see ``rbum_archive_helper``
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RbumArchiveManifest {
+    pub version: u32,
+    pub own_paths: String,
+    pub export_time: DateTime<Utc>,
+    /// Tables in the order they should be imported
+    pub tables: Vec<RbumArchiveManifestTable>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RbumArchiveManifestTable {
+    pub table_name: String,
+    pub count: u64,
+}
+
+/// Result of an import
+///
+/// Nothing is imported when there are conflicts.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumArchiveImportResp {
+    pub imported: Vec<RbumArchiveImportTableResp>,
+    pub conflicts: Vec<RbumArchiveConflictResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumArchiveImportTableResp {
+    pub table_name: String,
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumArchiveConflictResp {
+    pub table_name: String,
+    /// Id of the record in the archive
+    pub id: String,
+    pub message: String,
+}
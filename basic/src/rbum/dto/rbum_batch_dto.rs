@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tardis::basic::error::TardisError;
+
+/// Result of a batch operation
+///
+/// The records are validated independently, a record rejected by the checks or the ``before_*`` hooks is reported in ``failed``
+/// and does not stop the others.
+///
+/// Note: all records share the transaction of the caller, there is no savepoint per record,
+/// so the writes made by a ``before_*`` hook of a failed record are not undone,
+/// and an error in the writes or the ``after_*`` hooks aborts the whole batch.
+/// Callers who need all-or-nothing semantics should roll back when ``failed`` is not empty.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumBatchResp {
+    /// Ids of the succeeded records, in the order of the requests
+    pub succeed_ids: Vec<String>,
+    pub failed: Vec<RbumBatchFailedResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumBatchFailedResp {
+    /// Index of the record in the requests
+    pub index: u32,
+    pub id: Option<String>,
+    pub error: String,
+}
+
+impl RbumBatchResp {
+    pub fn add_failed(&mut self, index: usize, id: Option<&str>, error: &TardisError) {
+        self.failed.push(RbumBatchFailedResp {
+            index: index as u32,
+            id: id.map(|id| id.to_string()),
+            error: format!("{:?}", error),
+        });
+    }
+
+    /// Merge the failed records of a sub batch, ``indexes`` maps the indexes of the sub batch to this batch.
+    ///
+    /// Returns the indexes (of this batch) and ids of the succeeded records of the sub batch.
+    pub fn merge_failed(&mut self, sub_resp: RbumBatchResp, indexes: &[usize]) -> Vec<(usize, String)> {
+        let failed_indexes = sub_resp.failed.iter().map(|failed| failed.index as usize).collect::<HashSet<usize>>();
+        for mut failed in sub_resp.failed {
+            failed.index = indexes[failed.index as usize] as u32;
+            self.failed.push(failed);
+        }
+        indexes.iter().enumerate().filter(|(sub_idx, _)| !failed_indexes.contains(sub_idx)).map(|(_, idx)| *idx).zip(sub_resp.succeed_ids.into_iter()).collect()
+    }
+}
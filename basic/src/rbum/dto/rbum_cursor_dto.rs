@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use tardis::web::poem_openapi::types::{ParseFromJSON, ToJSON};
+
+use crate::rbum::rbum_enumeration::RbumCursorSortKind;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumCursorReq {
+    /// Sort key, the id is always used as the second key
+    pub sort_by: RbumCursorSortKind,
+    pub desc: bool,
+    pub page_size: u64,
+    /// Continuation token returned by the previous page, ``None`` means the first page
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumCursorPage<T>
+where
+    T: ParseFromJSON + ToJSON + Serialize + Send + Sync,
+{
+    pub page_size: u64,
+    /// Continuation token of the next page, ``None`` means there are no more records
+    pub next_cursor: Option<String>,
+    pub records: Vec<T>,
+}
+
+impl<T> RbumCursorPage<T>
+where
+    T: ParseFromJSON + ToJSON + Serialize + Send + Sync,
+{
+    pub fn map<U, F>(self, fun: F) -> RbumCursorPage<U>
+    where
+        U: ParseFromJSON + ToJSON + Serialize + Send + Sync,
+        F: FnMut(T) -> U,
+    {
+        RbumCursorPage {
+            page_size: self.page_size,
+            next_cursor: self.next_cursor,
+            records: self.records.into_iter().map(fun).collect(),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use std::default::Default;
 
 use serde::{Deserialize, Serialize};
+use tardis::chrono::{DateTime, Utc};
 
-use crate::rbum::rbum_enumeration::{RbumCertRelKind, RbumCertStatusKind, RbumRelFromKind, RbumScopeLevelKind, RbumSetCateLevelQueryKind};
+use crate::rbum::rbum_enumeration::{RbumCertRelKind, RbumCertStatusKind, RbumOperatorKind, RbumRelFromKind, RbumScopeLevelKind, RbumSetCateLevelQueryKind, RbumSortFieldKind};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
@@ -14,14 +15,50 @@ pub struct RbumBasicFilterReq {
     pub own_paths: Option<String>,
     pub with_sub_own_paths: bool,
     pub ids: Option<Vec<String>>,
+    pub exclude_ids: Option<Vec<String>>,
+    pub owners: Option<Vec<String>>,
     pub scope_level: Option<RbumScopeLevelKind>,
     pub enabled: Option<bool>,
+    /// Fuzzy match, ``%name%``
     pub name: Option<String>,
+    pub exact_name: Option<String>,
+    /// Prefix match, ``code%``
     pub code: Option<String>,
+    pub exact_code: Option<String>,
     pub rbum_kind_id: Option<String>,
+    pub rbum_kind_ids: Option<Vec<String>>,
     pub rbum_domain_id: Option<String>,
 
+    /// Inclusive range of the create time
+    pub create_time_start: Option<DateTime<Utc>>,
+    pub create_time_end: Option<DateTime<Utc>>,
+    /// Inclusive range of the update time
+    pub update_time_start: Option<DateTime<Utc>>,
+    pub update_time_end: Option<DateTime<Utc>>,
+
     pub desc_by_sort: Option<bool>,
+    /// Ordered list of sort fields, applied after ``desc_by_sort``.
+    ///
+    /// Cursor pagination has its own order, so it should be empty there.
+    pub sorts: Option<Vec<RbumSortReq>>,
+}
+
+/// Fetch the basic filter of the filters used by ``RbumCrudOperation``
+pub trait RbumBasicFilterFetcher {
+    fn basic(&self) -> &RbumBasicFilterReq;
+}
+
+impl RbumBasicFilterFetcher for RbumBasicFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumSortReq {
+    pub field: RbumSortFieldKind,
+    pub desc: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -33,6 +70,12 @@ pub struct RbumCertConfFilterReq {
     pub rel_rbum_item_id: Option<String>,
 }
 
+impl RbumBasicFilterFetcher for RbumCertConfFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 #[serde(default)]
@@ -45,6 +88,12 @@ pub struct RbumCertFilterReq {
     pub rel_rbum_cert_conf_id: Option<String>,
 }
 
+impl RbumBasicFilterFetcher for RbumCertFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 #[serde(default)]
@@ -52,6 +101,12 @@ pub struct RbumKindAttrFilterReq {
     pub basic: RbumBasicFilterReq,
 }
 
+impl RbumBasicFilterFetcher for RbumKindAttrFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 #[serde(default)]
@@ -61,6 +116,12 @@ pub struct RbumItemAttrFilterReq {
     pub rel_rbum_kind_attr_id: Option<String>,
 }
 
+impl RbumBasicFilterFetcher for RbumItemAttrFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 #[serde(default)]
@@ -73,6 +134,12 @@ pub struct RbumRelFilterReq {
     pub to_own_paths: Option<String>,
 }
 
+impl RbumBasicFilterFetcher for RbumRelFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 #[serde(default)]
@@ -81,6 +148,12 @@ pub struct RbumRelExtFilterReq {
     pub rel_rbum_rel_id: Option<String>,
 }
 
+impl RbumBasicFilterFetcher for RbumRelExtFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 pub struct RbumSetFilterReq {
@@ -89,6 +162,12 @@ pub struct RbumSetFilterReq {
     pub kind: Option<String>,
 }
 
+impl RbumBasicFilterFetcher for RbumSetFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 pub struct RbumSetCateFilterReq {
@@ -99,6 +178,12 @@ pub struct RbumSetCateFilterReq {
     pub find_filter: Option<RbumSetCateLevelQueryKind>,
 }
 
+impl RbumBasicFilterFetcher for RbumSetCateFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 pub struct RbumSetItemFilterReq {
@@ -108,6 +193,12 @@ pub struct RbumSetItemFilterReq {
     pub rel_rbum_item_id: Option<String>,
 }
 
+impl RbumBasicFilterFetcher for RbumSetItemFilterReq {
+    fn basic(&self) -> &RbumBasicFilterReq {
+        &self.basic
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 #[serde(default)]
@@ -118,10 +209,24 @@ pub struct RbumItemRelFilterReq {
     pub rel_item_id: Option<String>,
 }
 
+/// Predicate on an extended attribute value of the item
+///
+/// The attribute is looked up by name from the [kind attributes](crate::rbum::domain::rbum_kind_attr::Model) of the item kind,
+/// the value is read from the ext table if the attribute is a ``main_column``, otherwise from ``rbum_item_attr``.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumItemAttrPredicateReq {
+    pub attr_name: String,
+    pub op: RbumOperatorKind,
+    /// ``In`` uses all values, other operators use the first value
+    pub values: Vec<String>,
+}
+
 pub trait RbumItemFilterFetcher {
     fn basic(&self) -> &RbumBasicFilterReq;
     fn rel(&self) -> &Option<RbumItemRelFilterReq>;
     fn rel2(&self) -> &Option<RbumItemRelFilterReq>;
+    fn attrs(&self) -> &Option<Vec<RbumItemAttrPredicateReq>>;
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -130,6 +235,7 @@ pub trait RbumItemFilterFetcher {
 pub struct RbumItemBasicFilterReq {
     pub basic: RbumBasicFilterReq,
     pub rel: Option<RbumItemRelFilterReq>,
+    pub attrs: Option<Vec<RbumItemAttrPredicateReq>>,
 }
 
 impl RbumItemFilterFetcher for RbumItemBasicFilterReq {
@@ -142,4 +248,7 @@ impl RbumItemFilterFetcher for RbumItemBasicFilterReq {
     fn rel2(&self) -> &Option<RbumItemRelFilterReq> {
         &self.rel
     }
+    fn attrs(&self) -> &Option<Vec<RbumItemAttrPredicateReq>> {
+        &self.attrs
+    }
 }
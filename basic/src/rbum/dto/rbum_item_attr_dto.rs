@@ -64,3 +64,11 @@ pub struct RbumItemAttrDetailResp {
     pub create_time: DateTime<Utc>,
     pub update_time: DateTime<Utc>,
 }
+
+/// Validation error of an extended attribute value, see ``rbum_kind_attr_helper``
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumItemAttrValidateErrorResp {
+    pub name: String,
+    pub message: String,
+}
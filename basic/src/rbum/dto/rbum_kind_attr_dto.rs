@@ -141,3 +141,34 @@ pub struct RbumKindAttrDetailResp {
 
     pub scope_level: RbumScopeLevelKind,
 }
+
+/// Attributes of a kind rendered as a JSON Schema document and a form layout, see ``rbum_kind_attr_helper``
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumKindAttrSchemaResp {
+    pub json_schema: tardis::serde_json::Value,
+    pub form: Vec<RbumKindAttrFormRowResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumKindAttrFormRowResp {
+    pub items: Vec<RbumKindAttrFormItemResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumKindAttrFormItemResp {
+    pub name: String,
+    pub label: String,
+    pub note: String,
+    pub data_type: RbumDataTypeKind,
+    pub widget_type: RbumWidgetTypeKind,
+    pub default_value: String,
+    pub options: String,
+    pub required: bool,
+    pub min_length: u32,
+    pub max_length: u32,
+    pub action: String,
+    pub ext: String,
+}
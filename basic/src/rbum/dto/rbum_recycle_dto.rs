@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use tardis::chrono::{DateTime, Utc};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumDeletedResp {
+    pub id: String,
+    pub own_paths: String,
+    pub deleter: String,
+    pub delete_time: DateTime<Utc>,
+    /// Record before deletion, all columns are included
+    pub content: tardis::serde_json::Value,
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use crate::rbum::dto::rbum_rel_attr_dto::RbumRelAttrDetailResp;
 use crate::rbum::dto::rbum_rel_dto::{RbumRelAddReq, RbumRelDetailResp};
 use crate::rbum::dto::rbum_rel_env_dto::RbumRelEnvDetailResp;
-use crate::rbum::rbum_enumeration::RbumRelEnvKind;
+use crate::rbum::rbum_enumeration::{RbumRelAttrOpKind, RbumRelEnvKind};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
@@ -18,6 +18,8 @@ pub struct RbumRelAggAddReq {
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 pub struct RbumRelAttrAggAddReq {
     pub is_from: bool,
+    /// Default is [RbumRelAttrOpKind::Eq]
+    pub op: Option<RbumRelAttrOpKind>,
     #[cfg_attr(feature = "default", oai(validator(min_length = "2", max_length = "2000")))]
     pub value: String,
     #[cfg_attr(feature = "default", oai(validator(min_length = "2", max_length = "255")))]
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 use tardis::chrono::{DateTime, Utc};
 
+use crate::rbum::rbum_enumeration::RbumRelAttrOpKind;
+
 #[derive(Serialize, Deserialize, Debug)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 pub struct RbumRelAttrAddReq {
     pub is_from: bool,
+    /// Default is [RbumRelAttrOpKind::Eq]
+    pub op: Option<RbumRelAttrOpKind>,
     #[cfg_attr(feature = "default", oai(validator(min_length = "2", max_length = "2000")))]
     pub value: String,
     #[cfg_attr(feature = "default", oai(validator(min_length = "2", max_length = "255")))]
@@ -19,6 +23,7 @@ pub struct RbumRelAttrAddReq {
 #[derive(Serialize, Deserialize, Debug)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
 pub struct RbumRelAttrModifyReq {
+    pub op: Option<RbumRelAttrOpKind>,
     #[cfg_attr(feature = "default", oai(validator(min_length = "2", max_length = "2000")))]
     pub value: Option<String>,
     #[cfg_attr(feature = "default", oai(validator(min_length = "2", max_length = "255")))]
@@ -30,6 +35,7 @@ pub struct RbumRelAttrModifyReq {
 pub struct RbumRelAttrDetailResp {
     pub id: String,
     pub is_from: bool,
+    pub op: RbumRelAttrOpKind,
     pub value: String,
     pub name: String,
     pub record_only: bool,
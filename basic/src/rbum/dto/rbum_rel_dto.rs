@@ -47,6 +47,53 @@ pub struct RbumRelCheckReq {
     pub to_rbum_item_id: String,
     pub from_attrs: HashMap<String, String>,
     pub to_attrs: HashMap<String, String>,
+    /// Environment of the request, used by the environment conditions (e.g. [crate::rbum::rbum_enumeration::RbumRelEnvKind::Ips]) of the relationships
+    pub env: Option<RbumRelCheckEnvReq>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumRelCheckEnvReq {
+    /// Address of the peer that sends the request
+    pub client_ip: Option<String>,
+    /// Addresses in the ``X-Forwarded-For`` header, the first one is the original client.
+    /// They are followed from the right only through the proxies in ``trusted_proxy_ips`` of the rbum config,
+    /// so the addresses prepended by the client are ignored
+    pub forwarded_ips: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumRelTraverseReq {
+    /// Item to start from
+    #[cfg_attr(feature = "default", oai(validator(min_length = "2", max_length = "255")))]
+    pub rbum_item_id: String,
+    /// If true, the relationships are followed from the source to the target, otherwise from the target to the source
+    pub is_from: bool,
+    /// Tags of the hops in order, e.g. ``["X", "Y"]`` means the items reached via ``X`` then ``Y``.
+    /// If empty, each hop can use any tag
+    pub tags: Vec<String>,
+    /// Maximum number of hops, ignored if ``tags`` is not empty
+    pub max_depth: u8,
+    /// Only the items of the kind are returned
+    pub rbum_kind_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumRelTraverseResp {
+    pub rbum_item_id: String,
+    /// Minimum number of hops from the start item
+    pub depth: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object, tardis::db::sea_orm::FromQueryResult))]
+pub struct RbumRelTraverseHopResp {
+    pub id: String,
+    pub tag: String,
+    pub from_rbum_id: String,
+    pub to_rbum_item_id: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
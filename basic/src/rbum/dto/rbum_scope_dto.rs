@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rbum::rbum_enumeration::{RbumScopeLevelKind, RbumScopeRuleKind};
+
+/// Why a record is visible or not, see ``rbum_scope_helper::explain_scope``
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumScopeExplainResp {
+    pub visible: bool,
+    pub record_own_paths: String,
+    pub record_scope_level: Option<RbumScopeLevelKind>,
+    /// Own paths of the filter (or the context) compared with the record's own paths
+    pub filter_own_paths: String,
+    /// Own paths of the context, only used by the rules when the filter has no own paths
+    pub ctx_own_paths: String,
+    /// Rules in the order they are evaluated, the evaluation stops at the first matched rule
+    pub rules: Vec<RbumScopeExplainRuleResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Object))]
+pub struct RbumScopeExplainRuleResp {
+    pub rule: RbumScopeRuleKind,
+    pub matched: bool,
+    pub message: String,
+}
@@ -1,2 +1,10 @@
+#[cfg(feature = "default")]
+pub mod rbum_archive_helper;
+pub mod rbum_cursor_helper;
+pub mod rbum_dialect_helper;
+pub mod rbum_etag_helper;
 pub mod rbum_event_helper;
+pub mod rbum_kind_attr_helper;
+pub mod rbum_migration_helper;
 pub mod rbum_scope_helper;
+pub mod rbum_uri_helper;
@@ -0,0 +1,161 @@
+//! Readers and writers of the archives, see ``RbumArchiveServ``
+//!
+//! The records are read and written chunk by chunk, so the bundle (ZIP) archives of large own paths are not held in memory.
+//! The JSON archive (``RbumArchive``) is held in memory and is intended for small own paths, e.g. the web api and the tenant templates.
+//!
+//! Layout of the bundle:
+//!
+//! ```text
+//! manifest.json                  RbumArchiveManifest
+//! tables/<table name>/<n>.json   JSON array of the n-th chunk of the records
+//! ```
+use std::io::{Read, Seek, Write};
+
+use tardis::basic::error::TardisError;
+use tardis::basic::result::TardisResult;
+use tardis::serde_json::Value as JsonValue;
+use zip::result::ZipError;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::rbum::dto::rbum_archive_dto::{RbumArchive, RbumArchiveManifest, RbumArchiveManifestTable, RbumArchiveTable};
+
+/// Max number of records in a chunk
+pub const CHUNK_SIZE: usize = 1000;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+pub trait RbumArchiveReader {
+    fn manifest(&self) -> TardisResult<RbumArchiveManifest>;
+
+    /// Read the ``chunk``-th chunk of the records of the table, return ``None`` if there are no more chunks
+    fn read_chunk(&mut self, table_name: &str, chunk: usize) -> TardisResult<Option<Vec<JsonValue>>>;
+}
+
+pub trait RbumArchiveWriter {
+    /// Write the records of the table, the tables are written in the import order and every table has at least one (maybe empty) chunk
+    fn write_chunk(&mut self, table_name: &str, chunk: usize, records: Vec<JsonValue>) -> TardisResult<()>;
+
+    fn finish(&mut self, manifest: &RbumArchiveManifest) -> TardisResult<()>;
+}
+
+impl RbumArchiveReader for RbumArchive {
+    fn manifest(&self) -> TardisResult<RbumArchiveManifest> {
+        Ok(RbumArchiveManifest {
+            version: self.version,
+            own_paths: self.own_paths.clone(),
+            export_time: self.export_time,
+            tables: self
+                .tables
+                .iter()
+                .map(|table| RbumArchiveManifestTable {
+                    table_name: table.table_name.clone(),
+                    count: table.records.len() as u64,
+                })
+                .collect(),
+        })
+    }
+
+    fn read_chunk(&mut self, table_name: &str, chunk: usize) -> TardisResult<Option<Vec<JsonValue>>> {
+        Ok(self.tables.iter().find(|table| table.table_name == table_name).and_then(|table| table.records.chunks(CHUNK_SIZE).nth(chunk)).map(|records| records.to_vec()))
+    }
+}
+
+impl RbumArchiveWriter for RbumArchive {
+    fn write_chunk(&mut self, table_name: &str, _: usize, records: Vec<JsonValue>) -> TardisResult<()> {
+        match self.tables.iter_mut().find(|table| table.table_name == table_name) {
+            Some(table) => table.records.extend(records),
+            None => self.tables.push(RbumArchiveTable {
+                table_name: table_name.to_string(),
+                records,
+            }),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, manifest: &RbumArchiveManifest) -> TardisResult<()> {
+        self.version = manifest.version;
+        self.own_paths = manifest.own_paths.clone();
+        self.export_time = manifest.export_time;
+        Ok(())
+    }
+}
+
+/// Bundle (ZIP) archive reader
+pub struct RbumArchiveBundleReader<R: Read + Seek> {
+    zip: ZipArchive<R>,
+    manifest: RbumArchiveManifest,
+}
+
+impl<R: Read + Seek> RbumArchiveBundleReader<R> {
+    pub fn new(reader: R) -> TardisResult<Self> {
+        let mut zip = ZipArchive::new(reader).map_err(to_tardis_error)?;
+        let manifest = tardis::serde_json::from_reader(zip.by_name(MANIFEST_FILE_NAME).map_err(to_tardis_error)?)
+            .map_err(|error| TardisError::FormatError(format!("illegal archive manifest: {}", error)))?;
+        Ok(RbumArchiveBundleReader { zip, manifest })
+    }
+}
+
+impl<R: Read + Seek> RbumArchiveReader for RbumArchiveBundleReader<R> {
+    fn manifest(&self) -> TardisResult<RbumArchiveManifest> {
+        Ok(self.manifest.clone())
+    }
+
+    fn read_chunk(&mut self, table_name: &str, chunk: usize) -> TardisResult<Option<Vec<JsonValue>>> {
+        match self.zip.by_name(&get_chunk_file_name(table_name, chunk)) {
+            Ok(file) => Ok(Some(
+                tardis::serde_json::from_reader(file).map_err(|error| TardisError::FormatError(format!("illegal records of {}: {}", table_name, error)))?,
+            )),
+            Err(ZipError::FileNotFound) => Ok(None),
+            Err(error) => Err(to_tardis_error(error)),
+        }
+    }
+}
+
+/// Bundle (ZIP) archive writer
+pub struct RbumArchiveBundleWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    finished_writer: Option<W>,
+}
+
+impl<W: Write + Seek> RbumArchiveBundleWriter<W> {
+    pub fn new(writer: W) -> Self {
+        RbumArchiveBundleWriter {
+            zip: ZipWriter::new(writer),
+            finished_writer: None,
+        }
+    }
+
+    /// Return the underlying writer after finishing
+    pub fn into_inner(self) -> TardisResult<W> {
+        self.finished_writer.ok_or_else(|| TardisError::InternalError("archive bundle is not finished".to_string()))
+    }
+
+    fn write_file(&mut self, file_name: &str, value: &impl serde::Serialize) -> TardisResult<()> {
+        self.zip.start_file(file_name, FileOptions::default().compression_method(CompressionMethod::Deflated)).map_err(to_tardis_error)?;
+        tardis::serde_json::to_writer(&mut self.zip, value).map_err(|error| TardisError::FormatError(format!("write {} failed: {}", file_name, error)))
+    }
+}
+
+impl<W: Write + Seek> RbumArchiveWriter for RbumArchiveBundleWriter<W> {
+    fn write_chunk(&mut self, table_name: &str, chunk: usize, records: Vec<JsonValue>) -> TardisResult<()> {
+        self.write_file(&get_chunk_file_name(table_name, chunk), &records)
+    }
+
+    fn finish(&mut self, manifest: &RbumArchiveManifest) -> TardisResult<()> {
+        self.write_file(MANIFEST_FILE_NAME, manifest)?;
+        self.finished_writer = Some(self.zip.finish().map_err(to_tardis_error)?);
+        Ok(())
+    }
+}
+
+fn get_chunk_file_name(table_name: &str, chunk: usize) -> String {
+    format!("tables/{}/{}.json", table_name, chunk)
+}
+
+fn to_tardis_error(error: ZipError) -> TardisError {
+    match error {
+        ZipError::Io(error) => error.into(),
+        _ => TardisError::FormatError(format!("illegal archive bundle: {}", error)),
+    }
+}
@@ -0,0 +1,125 @@
+//! Keyset (cursor) pagination
+//!
+//! Records are ordered by the sort key and then by the id, the continuation token contains the sort key and the id of the last record of the page,
+//! so the next page is fetched with ``(key, id) > (last key, last id)`` instead of an offset.
+//! Unlike the offset pagination, rows will not be skipped or duplicated when data changes between pages.
+use serde::{Deserialize, Serialize};
+use tardis::basic::result::TardisResult;
+use tardis::chrono::{DateTime, Utc};
+use tardis::db::sea_query::{Alias, Cond, Expr, Order, SelectStatement, Value};
+use tardis::serde_json::Value as JsonValue;
+use tardis::web::poem_openapi::types::{ParseFromJSON, ToJSON};
+use tardis::{TardisFuns, TardisFunsInst};
+
+use crate::rbum::dto::rbum_cursor_dto::{RbumCursorPage, RbumCursorReq};
+use crate::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
+use crate::rbum::rbum_enumeration::RbumCursorSortKind;
+use crate::rbum::serv::rbum_crud_serv::ID_FIELD;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct RbumCursorToken {
+    pub sort_by: RbumCursorSortKind,
+    pub desc: bool,
+    pub value: JsonValue,
+    pub id: String,
+}
+
+/// Reject the sorts of the filter, the cursor has its own order and the filter sorts would break the keyset.
+pub fn check_filter_sorts<'a>(filter: &RbumBasicFilterReq, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+    if filter.desc_by_sort.is_some() || filter.sorts.as_ref().map(|sorts| !sorts.is_empty()).unwrap_or(false) {
+        return Err(funs.err().bad_request("cursor", "paginate", "sorts of the filter can not be used with cursor"));
+    }
+    Ok(())
+}
+
+/// Add the cursor condition, order and limit to the query.
+///
+/// ``table_name`` is the table that contains the sort key and the id.
+/// One more record than the page size is fetched to know whether there is a next page.
+pub fn package_cursor_query<'a>(query: &mut SelectStatement, table_name: &str, cursor_req: &RbumCursorReq, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+    if cursor_req.page_size == 0 {
+        return Err(funs.err().bad_request("cursor", "paginate", "page size must be greater than 0"));
+    }
+    let sort_field = Alias::new(cursor_req.sort_by.to_field_name());
+    if let Some(cursor) = &cursor_req.cursor {
+        let token = decode_cursor(cursor, funs)?;
+        if token.sort_by != cursor_req.sort_by || token.desc != cursor_req.desc {
+            return Err(funs.err().bad_request("cursor", "paginate", "cursor does not match the sort"));
+        }
+        let value = to_sort_value(&cursor_req.sort_by, &token.value, funs)?;
+        let key_col = || Expr::tbl(Alias::new(table_name), sort_field.clone());
+        let id_col = || Expr::tbl(Alias::new(table_name), ID_FIELD.clone());
+        let cond = if cursor_req.desc {
+            Cond::any().add(key_col().lt(value.clone())).add(Cond::all().add(key_col().eq(value)).add(id_col().lt(token.id.as_str())))
+        } else {
+            Cond::any().add(key_col().gt(value.clone())).add(Cond::all().add(key_col().eq(value)).add(id_col().gt(token.id.as_str())))
+        };
+        query.cond_where(cond);
+    }
+    let order = if cursor_req.desc { Order::Desc } else { Order::Asc };
+    query.order_by((Alias::new(table_name), sort_field), order.clone());
+    query.order_by((Alias::new(table_name), ID_FIELD.clone()), order);
+    query.limit(cursor_req.page_size + 1);
+    Ok(())
+}
+
+/// Build the page from the records fetched by the query packaged with ``package_cursor_query``.
+///
+/// The sort key and the id are read from the serialized records, so they must be included in the response.
+pub fn package_cursor_page<'a, T>(mut records: Vec<T>, cursor_req: &RbumCursorReq, funs: &TardisFunsInst<'a>) -> TardisResult<RbumCursorPage<T>>
+where
+    T: ParseFromJSON + ToJSON + Serialize + Send + Sync,
+{
+    let next_cursor = if records.len() as u64 > cursor_req.page_size {
+        records.truncate(cursor_req.page_size as usize);
+        match records.last() {
+            Some(last_record) => {
+                let last_record = TardisFuns::json.str_to_json(&TardisFuns::json.obj_to_string(last_record)?)?;
+                let value = last_record.get(cursor_req.sort_by.to_field_name()).cloned().ok_or_else(|| {
+                    funs.err().internal_error(
+                        "cursor",
+                        "paginate",
+                        &format!("sort key {} is not included in the response", cursor_req.sort_by.to_field_name()),
+                    )
+                })?;
+                let id = last_record.get("id").and_then(|id| id.as_str()).ok_or_else(|| funs.err().internal_error("cursor", "paginate", "id is not included in the response"))?;
+                Some(encode_cursor(&RbumCursorToken {
+                    sort_by: cursor_req.sort_by.clone(),
+                    desc: cursor_req.desc,
+                    value,
+                    id: id.to_string(),
+                })?)
+            }
+            None => None,
+        }
+    } else {
+        None
+    };
+    Ok(RbumCursorPage {
+        page_size: cursor_req.page_size,
+        next_cursor,
+        records,
+    })
+}
+
+fn encode_cursor(token: &RbumCursorToken) -> TardisResult<String> {
+    Ok(TardisFuns::crypto.base64.encode(&TardisFuns::json.obj_to_string(token)?))
+}
+
+fn decode_cursor<'a>(cursor: &str, funs: &TardisFunsInst<'a>) -> TardisResult<RbumCursorToken> {
+    TardisFuns::crypto
+        .base64
+        .decode(cursor)
+        .and_then(|token| TardisFuns::json.str_to_obj::<RbumCursorToken>(&token))
+        .map_err(|_| funs.err().bad_request("cursor", "paginate", "cursor is invalid"))
+}
+
+fn to_sort_value<'a>(sort_by: &RbumCursorSortKind, value: &JsonValue, funs: &TardisFunsInst<'a>) -> TardisResult<Value> {
+    let sort_value = match sort_by {
+        RbumCursorSortKind::CreateTime | RbumCursorSortKind::UpdateTime => {
+            value.as_str().and_then(|value| DateTime::parse_from_rfc3339(value).ok()).map(|value| Value::from(value.with_timezone(&Utc)))
+        }
+        RbumCursorSortKind::Sort => value.as_i64().map(Value::from),
+    };
+    sort_value.ok_or_else(|| funs.err().bad_request("cursor", "paginate", "cursor is invalid"))
+}
@@ -0,0 +1,129 @@
+//! Differences between the database backends
+//!
+//! MySQL, Postgres and SQLite are supported. Note that ``LIKE`` is case-insensitive for ASCII in SQLite and with the default MySQL collations,
+//! but case-sensitive in Postgres, the own paths are matched by prefixes, so the ids in them must not differ only in case.
+use tardis::db::sea_orm::{DbBackend, Statement, StatementBuilder};
+use tardis::db::sea_query::{Alias, ColumnDef, Cond, Expr, Func, Iden, IntoIden, LockBehavior, LockType, SelectStatement, SimpleExpr};
+
+/// The update time column is refreshed by the database, SQLite does not support ``ON UPDATE``, see ``package_update_time_trigger``
+pub fn update_time_column_def<C: IntoIden>(column: C, db_kind: DbBackend) -> ColumnDef {
+    let extra = match db_kind {
+        DbBackend::MySql => "DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP",
+        DbBackend::Postgres | DbBackend::Sqlite => "DEFAULT CURRENT_TIMESTAMP",
+    };
+    ColumnDef::new(column).extra(extra.to_string()).date_time().to_owned()
+}
+
+/// Trigger that refreshes the update time column in SQLite, unless it is set by the update statement
+pub fn package_update_time_trigger(table_name: &str, update_time_column: &str, db_kind: DbBackend) -> Option<RawSql> {
+    if db_kind != DbBackend::Sqlite {
+        return None;
+    }
+    Some(RawSql(format!(
+        r#"CREATE TRIGGER IF NOT EXISTS "trg-{table_name}-{column}" AFTER UPDATE ON "{table_name}" FOR EACH ROW WHEN NEW."{column}" = OLD."{column}" BEGIN UPDATE "{table_name}" SET "{column}" = CURRENT_TIMESTAMP WHERE rowid = NEW.rowid; END"#,
+        table_name = table_name,
+        column = update_time_column
+    )))
+}
+
+/// Length in characters, SQLite has no ``CHAR_LENGTH``
+pub fn char_length<T: Into<SimpleExpr>>(expr: T, db_kind: DbBackend) -> SimpleExpr {
+    match db_kind {
+        DbBackend::Sqlite => Func::cust(Length).args(vec![expr.into()]),
+        DbBackend::MySql | DbBackend::Postgres => Func::char_length(expr),
+    }
+}
+
+/// Text value of a top-level field of the JSON text column, ``NULL`` if the field does not exist
+///
+/// The column and field names are written into the SQL, so they must not come from the request.
+pub fn json_text_field(column: &str, field: &str, db_kind: DbBackend) -> SimpleExpr {
+    match db_kind {
+        DbBackend::MySql => Expr::cust(&format!("JSON_UNQUOTE(JSON_EXTRACT(`{}`, '$.{}'))", column, field)),
+        DbBackend::Postgres => Expr::cust(&format!(r#"CAST("{}" AS JSON) ->> '{}'"#, column, field)),
+        DbBackend::Sqlite => Expr::cust(&format!(r#"json_extract("{}", '$.{}')"#, column, field)),
+    }
+}
+
+/// Numeric value of the text column, empty text is treated as ``NULL``, e.g. to compare the values of the number attributes by range
+///
+/// The table and column names are written into the SQL, so they must not come from the request.
+pub fn text_to_number(table_name: &str, column: &str, db_kind: DbBackend) -> SimpleExpr {
+    match db_kind {
+        DbBackend::MySql => Expr::cust(&format!("CAST(NULLIF(`{}`.`{}`, '') AS DECIMAL(65, 10))", table_name, column)),
+        DbBackend::Postgres => Expr::cust(&format!(r#"CAST(NULLIF("{}"."{}", '') AS NUMERIC)"#, table_name, column)),
+        DbBackend::Sqlite => Expr::cust(&format!(r#"CAST(NULLIF("{}"."{}", '') AS REAL)"#, table_name, column)),
+    }
+}
+
+/// Integer of the ``BIGINT`` type, e.g. for the non-recursive part of a recursive common table expression, which decides the column types
+pub fn bigint(value: i64, db_kind: DbBackend) -> SimpleExpr {
+    match db_kind {
+        DbBackend::MySql => Expr::cust(&format!("CAST({} AS SIGNED)", value)),
+        DbBackend::Postgres | DbBackend::Sqlite => Expr::cust(&format!("CAST({} AS BIGINT)", value)),
+    }
+}
+
+/// Lock the selected rows until the end of the transaction, SQLite locks the whole database for writing instead
+pub fn lock_for_update(query: &mut SelectStatement, db_kind: DbBackend) -> &mut SelectStatement {
+    if db_kind != DbBackend::Sqlite {
+        query.lock(LockType::Update);
+    }
+    query
+}
+
+/// Lock the selected rows until the end of the transaction and skip the rows that are locked by others (MySQL 8.0+ and Postgres),
+/// SQLite locks the whole database for writing instead
+pub fn lock_for_update_skip_locked(query: &mut SelectStatement, db_kind: DbBackend) -> &mut SelectStatement {
+    if db_kind != DbBackend::Sqlite {
+        query.lock_with_behavior(LockType::Update, LockBehavior::SkipLocked);
+    }
+    query
+}
+
+/// Whether the own paths column is ``own_paths`` or one of its descendants, all own paths are the descendants of the empty own paths
+///
+/// The prefix is compared exactly by ``SUBSTR`` (supported by all the backends) rather than ``LIKE``, whose ``_`` wildcard may appear in the ids,
+/// e.g. to change the records of a subtree.
+pub fn sub_own_paths_cond(table_name: &str, column: &str, own_paths: &str) -> Cond {
+    if own_paths.is_empty() {
+        // An empty condition is ignored when it is nested, e.g. ``Cond::any().add(Cond::all())``
+        return Cond::all().add(Expr::val(1).eq(1));
+    }
+    let prefix = format!("{}/", own_paths);
+    Cond::any().add(Expr::tbl(Alias::new(table_name), Alias::new(column)).eq(own_paths)).add(
+        Expr::expr(Func::cust(Substr).args(vec![
+            Expr::tbl(Alias::new(table_name), Alias::new(column)).into(),
+            Expr::val(1).into(),
+            Expr::val(prefix.chars().count() as i64).into(),
+        ]))
+        .eq(prefix),
+    )
+}
+
+/// Statement that is not supported by the query builder
+pub struct RawSql(pub String);
+
+impl StatementBuilder for RawSql {
+    fn build(&self, db_backend: &DbBackend) -> Statement {
+        Statement::from_string(*db_backend, self.0.to_string())
+    }
+}
+
+/// ``LENGTH`` function of SQLite
+struct Length;
+
+impl Iden for Length {
+    fn unquoted(&self, s: &mut dyn std::fmt::Write) {
+        write!(s, "LENGTH").unwrap();
+    }
+}
+
+/// ``SUBSTR`` function
+struct Substr;
+
+impl Iden for Substr {
+    fn unquoted(&self, s: &mut dyn std::fmt::Write) {
+        write!(s, "SUBSTR").unwrap();
+    }
+}
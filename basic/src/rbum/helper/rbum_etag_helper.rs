@@ -0,0 +1,29 @@
+//! ETag helper for optimistic concurrency control
+//!
+//! Every rbum table has a `version` column that is increased on each change of the record, including changes of its attributes and relationships,
+//! the version is formatted as a strong ETag, e.g. `"3"`.
+//! The value of the `If-Match` header is parsed back to the expected version and passed to `modify_rbum_if_match` / `modify_item_if_match` / `check_rbum_version`.
+use tardis::basic::error::TardisError;
+use tardis::basic::result::TardisResult;
+
+pub const HEADER_ETAG: &str = "ETag";
+pub const HEADER_IF_MATCH: &str = "If-Match";
+
+pub fn to_etag(version: i64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Parse the `If-Match` header, `None` / empty / `*` means no precondition
+pub fn parse_if_match(if_match: Option<&str>) -> TardisResult<Option<i64>> {
+    let if_match = match if_match.map(|if_match| if_match.trim()) {
+        None | Some("") | Some("*") => return Ok(None),
+        Some(if_match) => if_match,
+    };
+    let version = if_match
+        .strip_prefix("W/")
+        .unwrap_or(if_match)
+        .trim_matches('"')
+        .parse::<i64>()
+        .map_err(|_| TardisError::BadRequest(format!("invalid {} header: {}", HEADER_IF_MATCH, if_match)))?;
+    Ok(Some(version))
+}
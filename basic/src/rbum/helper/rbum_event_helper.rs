@@ -1,32 +1,148 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
 use tardis::basic::dto::TardisContext;
+use tardis::basic::error::TardisError;
 use tardis::basic::result::TardisResult;
 use tardis::chrono::Utc;
-use tardis::TardisFunsInst;
+use tardis::db::sea_orm::*;
+use tardis::db::sea_query::{Alias, Cond, Expr, LockType, Order, Query};
+use tardis::serde_json::Value;
+use tardis::{TardisFuns, TardisFunsInst};
 
+use crate::rbum::domain::rbum_event_outbox;
+use crate::rbum::helper::{rbum_dialect_helper, rbum_scope_helper};
 use crate::rbum::rbum_config::RbumConfigApi;
 
 pub async fn try_notify<'a>(table_name: &str, operate: &str, record_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<bool> {
+    try_notify_with_snapshot(table_name, operate, record_id, None, None, funs, ctx).await
+}
+
+/// Notify event with the record snapshots.
+///
+/// The snapshots are only attached when the table is configured with the ``s`` flag in ``event_domains``,
+/// and a field-level diff is calculated for ``u`` events.
+///
+/// The ``own_paths`` of the message is the one of the record, taken from the snapshots or read from the table,
+/// so it is ``ctx.own_paths`` only when the record cannot be found.
+pub async fn try_notify_with_snapshot<'a>(
+    table_name: &str,
+    operate: &str,
+    record_id: &str,
+    before: Option<Value>,
+    after: Option<Value>,
+    funs: &TardisFunsInst<'a>,
+    ctx: &TardisContext,
+) -> TardisResult<bool> {
     #[cfg(feature = "with-mq")]
     {
         if funs.rbum_conf_match_event(table_name, operate) {
-            funs.mq()
-                .request(
-                    &funs.rbum_conf_mq_topic_event(),
-                    tardis::TardisFuns::json.obj_to_string(&RbumEventMessage {
-                        table_name: table_name.to_string(),
-                        operate: operate.to_string(),
-                        operator: ctx.owner.clone(),
-                        record_id: record_id.to_string(),
-                        ts: Utc::now().timestamp_millis(),
-                    })?,
-                    &HashMap::new(),
-                )
-                .await?;
+            let snapshot_own_paths =
+                after.as_ref().or(before.as_ref()).and_then(|snapshot| snapshot.get("own_paths")).and_then(|own_paths| own_paths.as_str()).map(|own_paths| own_paths.to_string());
+            let own_paths = match snapshot_own_paths {
+                Some(own_paths) => own_paths,
+                None => try_get_own_paths(table_name, operate, &[record_id.to_string()], funs).await?.remove(record_id).unwrap_or_else(|| ctx.own_paths.clone()),
+            };
+            let (before, after, diff) = if funs.rbum_conf_match_event_snapshot(table_name) {
+                let diff = if operate == "u" { diff_snapshot(&before, &after) } else { None };
+                (before, after, diff)
+            } else {
+                (None, None, None)
+            };
+            add_outbox(
+                &funs.rbum_conf_mq_topic_event(),
+                &HashMap::new(),
+                TardisFuns::json.obj_to_string(&RbumEventMessage {
+                    id: TardisFuns::field.nanoid(),
+                    table_name: table_name.to_string(),
+                    operate: operate.to_string(),
+                    operator: ctx.owner.clone(),
+                    own_paths,
+                    record_id: record_id.to_string(),
+                    ts: Utc::now().timestamp_millis(),
+                    before,
+                    after,
+                    diff,
+                })?,
+                record_id,
+                funs,
+                ctx,
+            )
+            .await?;
+        }
+        Ok(true)
+    }
+    #[cfg(not(feature = "with-mq"))]
+    {
+        Ok(false)
+    }
+}
+
+/// Notify the same operation of multiple records, the messages are written to the outbox in one statement.
+///
+/// Snapshots are not attached to batch events.
+/// The ``own_paths`` of the messages are read from the table, so the records must still exist,
+/// use [try_notifies_with_own_paths] for the deleted records.
+pub async fn try_notifies<'a>(table_name: &str, operate: &str, record_ids: &[String], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<bool> {
+    let own_paths = try_get_own_paths(table_name, operate, record_ids, funs).await?;
+    try_notifies_with_own_paths(table_name, operate, record_ids, &own_paths, funs, ctx).await
+}
+
+/// Notify the same operation of multiple records with their ``own_paths``,
+/// which are read by [try_get_own_paths] (e.g. before the records are deleted).
+///
+/// ``ctx.own_paths`` is used for the records without ``own_paths``.
+pub async fn try_notifies_with_own_paths<'a>(
+    table_name: &str,
+    operate: &str,
+    record_ids: &[String],
+    own_paths: &HashMap<String, String>,
+    funs: &TardisFunsInst<'a>,
+    ctx: &TardisContext,
+) -> TardisResult<bool> {
+    #[cfg(feature = "with-mq")]
+    {
+        if record_ids.is_empty() || !funs.rbum_conf_match_event(table_name, operate) {
+            return Ok(true);
         }
+        let topic = funs.rbum_conf_mq_topic_event();
+        let header = TardisFuns::json.obj_to_string(&HashMap::<String, String>::new())?;
+        let ts = Utc::now();
+        let mut seqs = get_next_seqs(&record_ids.iter().map(|record_id| record_id.as_str()).collect::<Vec<&str>>(), funs).await?;
+        let mut outboxes = Vec::with_capacity(record_ids.len());
+        for record_id in record_ids {
+            // A record may appear more than once in the batch
+            let seq = seqs.entry(record_id.to_string()).or_insert(1);
+            outboxes.push(rbum_event_outbox::ActiveModel {
+                id: Set(TardisFuns::field.nanoid()),
+                topic: Set(topic.clone()),
+                header: Set(header.clone()),
+                message: Set(TardisFuns::json.obj_to_string(&RbumEventMessage {
+                    id: TardisFuns::field.nanoid(),
+                    table_name: table_name.to_string(),
+                    operate: operate.to_string(),
+                    operator: ctx.owner.clone(),
+                    own_paths: own_paths.get(record_id).unwrap_or(&ctx.own_paths).to_string(),
+                    record_id: record_id.to_string(),
+                    ts: ts.timestamp_millis(),
+                    before: None,
+                    after: None,
+                    diff: None,
+                })?),
+                record_id: Set(record_id.to_string()),
+                seq: Set(*seq),
+                published: Set(false),
+                retry_times: Set(0),
+                last_error: Set("".to_string()),
+                ..Default::default()
+            });
+            *seq += 1;
+        }
+        funs.db().insert_many(outboxes, ctx).await?;
         Ok(true)
     }
     #[cfg(not(feature = "with-mq"))]
@@ -35,6 +151,343 @@ pub async fn try_notify<'a>(table_name: &str, operate: &str, record_id: &str, fu
     }
 }
 
+/// Load the ``own_paths`` of the records when the event of the table is enabled, used as the ``own_paths`` of the messages.
+///
+/// The records that are not found are missing from the result.
+pub async fn try_get_own_paths<'a>(table_name: &str, operate: &str, record_ids: &[String], funs: &TardisFunsInst<'a>) -> TardisResult<HashMap<String, String>> {
+    #[cfg(feature = "with-mq")]
+    {
+        #[derive(Deserialize, FromQueryResult, Serialize, Clone, Debug)]
+        struct OwnPathsResp {
+            pub id: String,
+            pub own_paths: String,
+        }
+
+        if record_ids.is_empty() || !funs.rbum_conf_match_event(table_name, operate) {
+            return Ok(HashMap::new());
+        }
+        let mut query = Query::select();
+        query
+            .columns(vec![Alias::new("id"), Alias::new("own_paths")])
+            .from(Alias::new(table_name))
+            .and_where(Expr::col(Alias::new("id")).is_in(record_ids.iter().map(|record_id| record_id.as_str()).collect::<Vec<&str>>()));
+        Ok(funs.db().find_dtos::<OwnPathsResp>(&query).await?.into_iter().map(|resp| (resp.id, resp.own_paths)).collect())
+    }
+    #[cfg(not(feature = "with-mq"))]
+    {
+        Ok(HashMap::new())
+    }
+}
+
+/// Load the snapshot of a record when the table is configured with the ``s`` flag in ``event_domains``.
+///
+/// All columns of the record are included.
+pub async fn try_get_snapshot<'a>(table_name: &str, operate: &str, record_id: &str, funs: &TardisFunsInst<'a>) -> TardisResult<Option<Value>> {
+    #[cfg(feature = "with-mq")]
+    {
+        if !funs.rbum_conf_match_event(table_name, operate) || !funs.rbum_conf_match_event_snapshot(table_name) {
+            return Ok(None);
+        }
+        let mut query = Query::select();
+        query.expr(Expr::cust("*")).from(Alias::new(table_name)).and_where(Expr::col(Alias::new("id")).eq(record_id));
+        funs.db().get_dto::<Value>(&query).await
+    }
+    #[cfg(not(feature = "with-mq"))]
+    {
+        Ok(None)
+    }
+}
+
+fn diff_snapshot(before: &Option<Value>, after: &Option<Value>) -> Option<Vec<RbumEventFieldDiff>> {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let mut fields = before.keys().chain(after.keys()).cloned().collect::<Vec<String>>();
+            fields.sort();
+            fields.dedup();
+            Some(
+                fields
+                    .into_iter()
+                    .filter_map(|field| {
+                        let before = before.get(&field).cloned().unwrap_or(Value::Null);
+                        let after = after.get(&field).cloned().unwrap_or(Value::Null);
+                        if before == after {
+                            None
+                        } else {
+                            Some(RbumEventFieldDiff { field, before, after })
+                        }
+                    })
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Write a message to the outbox.
+///
+/// The message is written with the connection (and transaction) of ``funs``,
+/// so it is only visible to the relay after the transaction is committed
+/// and is discarded if the transaction is rolled back.
+pub async fn add_outbox<'a>(topic: &str, header: &HashMap<String, String>, message: String, record_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+    let seq = get_next_seqs(&[record_id], funs).await?.remove(record_id).unwrap_or(1);
+    funs.db()
+        .insert_one(
+            rbum_event_outbox::ActiveModel {
+                id: Set(TardisFuns::field.nanoid()),
+                topic: Set(topic.to_string()),
+                header: Set(TardisFuns::json.obj_to_string(header)?),
+                message: Set(message),
+                record_id: Set(record_id.to_string()),
+                seq: Set(seq),
+                published: Set(false),
+                retry_times: Set(0),
+                last_error: Set("".to_string()),
+                ..Default::default()
+            },
+            ctx,
+        )
+        .await?;
+    Ok(())
+}
+
+/// The next sequence numbers of the messages of the records, the last one of each record plus one.
+///
+/// The numbers are assigned by the database rather than the clock, which may go backwards or tie across the instances.
+/// The messages of a record are written by the transactions that change the record, which are serialized by the lock of the record,
+/// and the unique index of ``record_id`` and ``seq`` rejects the message if they are not.
+/// MySQL reads the last numbers by a locking read, because a plain read of a repeatable read transaction may see an earlier snapshot.
+async fn get_next_seqs<'a>(record_ids: &[&str], funs: &TardisFunsInst<'a>) -> TardisResult<HashMap<String, i64>> {
+    #[derive(Deserialize, FromQueryResult, Serialize, Clone, Debug)]
+    struct OutboxSeqResp {
+        pub record_id: String,
+        pub seq: i64,
+    }
+
+    let mut query = Query::select();
+    query
+        .column(rbum_event_outbox::Column::RecordId)
+        .expr_as(Expr::col(rbum_event_outbox::Column::Seq).max(), Alias::new("seq"))
+        .from(rbum_event_outbox::Entity)
+        .and_where(Expr::col(rbum_event_outbox::Column::RecordId).is_in(record_ids.to_vec()))
+        .group_by_col(rbum_event_outbox::Column::RecordId);
+    // Postgres does not lock the grouped rows, its read committed transactions read the latest committed numbers
+    if funs.db().raw_conn().get_database_backend() == DbBackend::MySql {
+        query.lock(LockType::Update);
+    }
+    let mut seqs = funs.db().find_dtos::<OutboxSeqResp>(&query).await?.into_iter().map(|resp| (resp.record_id, resp.seq + 1)).collect::<HashMap<String, i64>>();
+    for record_id in record_ids {
+        seqs.entry(record_id.to_string()).or_insert(1);
+    }
+    Ok(seqs)
+}
+
+/// Deliver the committed outbox messages to MQ.
+///
+/// Each round claims the records whose first pending message can be delivered by locking that message
+/// (``FOR UPDATE SKIP LOCKED``) and leasing the pending messages of the records for ``event_outbox_relay_lease_ms``,
+/// so concurrent relays of multiple instances never deliver the messages of the same record.
+/// The claim is committed before the messages are published, so MQ is never requested while the rows are locked.
+/// The messages of the claimed records are delivered in ``seq`` order. When a message fails to be delivered,
+/// the subsequent messages of the same record are held back, so that the order of each record is preserved.
+/// A message that exceeds the maximum number of retries blocks its record until it is handled manually
+/// (e.g. its ``retry_times`` is reset or it is deleted).
+/// The published messages are purged after ``event_outbox_retention_sec``.
+///
+/// Each message is marked as published right after it is delivered, it may be delivered again
+/// if the relay fails before that or the lease expires first, so subscribers should be idempotent.
+///
+/// Return the number of delivered messages.
+pub async fn relay<'a>(funs: &TardisFunsInst<'a>) -> TardisResult<u64> {
+    #[cfg(feature = "with-mq")]
+    {
+        #[derive(Deserialize, FromQueryResult, Serialize, Clone, Debug)]
+        struct OutboxRecordResp {
+            pub record_id: String,
+        }
+
+        #[derive(Deserialize, FromQueryResult, Serialize, Clone, Debug)]
+        struct OutboxResp {
+            pub id: String,
+            pub topic: String,
+            pub header: String,
+            pub message: String,
+            pub record_id: String,
+            pub retry_times: u32,
+        }
+
+        let topics = vec![funs.rbum_conf_mq_topic_event(), funs.rbum_conf_mq_topic_entity_deleted()];
+        let max_retry_times = funs.rbum_conf_event_outbox_max_retry_times();
+        let batch_size = funs.rbum_conf_event_outbox_relay_batch_size();
+        let now_ms = Utc::now().timestamp_millis();
+        let lease = now_ms.saturating_add(funs.rbum_conf_event_outbox_relay_lease_ms() as i64);
+        let mut conn = TardisFuns::reldb().conn();
+        let db_kind = conn.raw_conn().get_database_backend();
+        conn.begin().await?;
+
+        let earlier_alias = Alias::new("earlier");
+        let mut earlier_query = Query::select();
+        earlier_query
+            .column((earlier_alias.clone(), rbum_event_outbox::Column::RecordId))
+            .from_as(rbum_event_outbox::Entity, earlier_alias.clone())
+            .and_where(Expr::tbl(earlier_alias.clone(), rbum_event_outbox::Column::RecordId).equals(rbum_event_outbox::Entity, rbum_event_outbox::Column::RecordId))
+            .and_where(Expr::tbl(earlier_alias.clone(), rbum_event_outbox::Column::Published).eq(false))
+            .and_where(Expr::tbl(earlier_alias.clone(), rbum_event_outbox::Column::Topic).is_in(topics.clone()))
+            .cond_where(
+                Cond::any()
+                    .add(Expr::tbl(earlier_alias.clone(), rbum_event_outbox::Column::Seq).less_than(Expr::tbl(rbum_event_outbox::Entity, rbum_event_outbox::Column::Seq)))
+                    .add(
+                        Cond::all()
+                            .add(Expr::tbl(earlier_alias.clone(), rbum_event_outbox::Column::Seq).equals(rbum_event_outbox::Entity, rbum_event_outbox::Column::Seq))
+                            .add(Expr::tbl(earlier_alias.clone(), rbum_event_outbox::Column::Id).less_than(Expr::tbl(rbum_event_outbox::Entity, rbum_event_outbox::Column::Id))),
+                    ),
+            );
+        // The first pending message of each record that is not leased by another relay,
+        // the records with a message that exceeds the maximum number of retries are not claimed.
+        // ``seq`` is only ordered within a record, so the records are claimed by the creation time of their first pending messages
+        let mut claim_query = Query::select();
+        claim_query
+            .column((rbum_event_outbox::Entity, rbum_event_outbox::Column::RecordId))
+            .from(rbum_event_outbox::Entity)
+            .and_where(Expr::tbl(rbum_event_outbox::Entity, rbum_event_outbox::Column::Published).eq(false))
+            .and_where(Expr::tbl(rbum_event_outbox::Entity, rbum_event_outbox::Column::RetryTimes).lt(max_retry_times))
+            .and_where(Expr::tbl(rbum_event_outbox::Entity, rbum_event_outbox::Column::RelayExpireTime).lt(now_ms))
+            .and_where(Expr::tbl(rbum_event_outbox::Entity, rbum_event_outbox::Column::Topic).is_in(topics.clone()))
+            .and_where(Expr::tbl(rbum_event_outbox::Entity, rbum_event_outbox::Column::RecordId).not_in_subquery(earlier_query))
+            .order_by((rbum_event_outbox::Entity, rbum_event_outbox::Column::CreateTime), Order::Asc)
+            .order_by((rbum_event_outbox::Entity, rbum_event_outbox::Column::Id), Order::Asc)
+            .limit(batch_size);
+        rbum_dialect_helper::lock_for_update_skip_locked(&mut claim_query, db_kind);
+        let record_ids = conn.find_dtos::<OutboxRecordResp>(&claim_query).await?.into_iter().map(|record| record.record_id).collect::<Vec<String>>();
+
+        let mut outboxes = Vec::new();
+        if !record_ids.is_empty() {
+            let mut query = Query::select();
+            query
+                .columns(vec![
+                    rbum_event_outbox::Column::Id,
+                    rbum_event_outbox::Column::Topic,
+                    rbum_event_outbox::Column::Header,
+                    rbum_event_outbox::Column::Message,
+                    rbum_event_outbox::Column::RecordId,
+                    rbum_event_outbox::Column::RetryTimes,
+                ])
+                .from(rbum_event_outbox::Entity)
+                .and_where(Expr::col(rbum_event_outbox::Column::Published).eq(false))
+                .and_where(Expr::col(rbum_event_outbox::Column::Topic).is_in(topics))
+                .and_where(Expr::col(rbum_event_outbox::Column::RecordId).is_in(record_ids))
+                .order_by(rbum_event_outbox::Column::Seq, Order::Asc)
+                .order_by(rbum_event_outbox::Column::Id, Order::Asc)
+                .limit(batch_size);
+            outboxes = conn.find_dtos::<OutboxResp>(&query).await?;
+            if !outboxes.is_empty() {
+                conn.execute(
+                    Query::update()
+                        .table(rbum_event_outbox::Entity)
+                        .value(rbum_event_outbox::Column::RelayExpireTime, lease.into())
+                        .and_where(Expr::col(rbum_event_outbox::Column::Id).is_in(outboxes.iter().map(|outbox| outbox.id.as_str()).collect::<Vec<&str>>())),
+                )
+                .await?;
+            }
+        }
+
+        let retention_sec = funs.rbum_conf_event_outbox_retention_sec();
+        if retention_sec > 0 {
+            let expired_time = now_ms - (retention_sec as i64).saturating_mul(1000);
+            conn.execute(
+                Query::delete()
+                    .from_table(rbum_event_outbox::Entity)
+                    .and_where(Expr::col(rbum_event_outbox::Column::Published).eq(true))
+                    .and_where(Expr::col(rbum_event_outbox::Column::PublishedTime).lt(expired_time)),
+            )
+            .await?;
+        }
+        conn.commit().await?;
+
+        // The rows are not locked from here on, each update only applies while the lease of this round holds
+        let mut published = 0;
+        let mut blocked_record_ids = HashSet::new();
+        let mut skipped_ids = Vec::new();
+        for outbox in outboxes {
+            // Stop the line, the subsequent messages of the record wait for the failed one
+            if blocked_record_ids.contains(&outbox.record_id) || outbox.retry_times >= max_retry_times {
+                blocked_record_ids.insert(outbox.record_id);
+                skipped_ids.push(outbox.id);
+                continue;
+            }
+            let header = TardisFuns::json.str_to_obj::<HashMap<String, String>>(&outbox.header)?;
+            let mut update_statement = Query::update();
+            update_statement.table(rbum_event_outbox::Entity).value(rbum_event_outbox::Column::RelayExpireTime, 0.into());
+            match funs.mq().request(&outbox.topic, outbox.message, &header).await {
+                Ok(_) => {
+                    update_statement.value(rbum_event_outbox::Column::Published, true.into());
+                    update_statement.value(rbum_event_outbox::Column::PublishedTime, Utc::now().timestamp_millis().into());
+                    published += 1;
+                }
+                Err(e) => {
+                    if outbox.retry_times + 1 >= max_retry_times {
+                        tardis::log::error!(
+                            "outbox {} exceeds the maximum number of retries, the subsequent messages of record {} are blocked: {:?}",
+                            outbox.id,
+                            outbox.record_id,
+                            e
+                        );
+                    } else {
+                        tardis::log::warn!("failed to relay outbox {} of record {}: {:?}", outbox.id, outbox.record_id, e);
+                    }
+                    update_statement.value(rbum_event_outbox::Column::RetryTimes, (outbox.retry_times + 1).into());
+                    update_statement.value(rbum_event_outbox::Column::LastError, format!("{:?}", e).into());
+                    blocked_record_ids.insert(outbox.record_id);
+                }
+            }
+            update_statement.and_where(Expr::col(rbum_event_outbox::Column::Id).eq(outbox.id.as_str())).and_where(Expr::col(rbum_event_outbox::Column::RelayExpireTime).eq(lease));
+            conn.execute(&update_statement).await?;
+        }
+        // Release the held back messages, so that the next round can claim their records at once
+        if !skipped_ids.is_empty() {
+            conn.execute(
+                Query::update()
+                    .table(rbum_event_outbox::Entity)
+                    .value(rbum_event_outbox::Column::RelayExpireTime, 0.into())
+                    .and_where(Expr::col(rbum_event_outbox::Column::Id).is_in(skipped_ids))
+                    .and_where(Expr::col(rbum_event_outbox::Column::RelayExpireTime).eq(lease)),
+            )
+            .await?;
+        }
+        Ok(published)
+    }
+    #[cfg(not(feature = "with-mq"))]
+    {
+        Ok(0)
+    }
+}
+
+/// Start a background task that periodically calls [relay].
+///
+/// The task is only started when ``with-mq`` is enabled and ``event_outbox_relay_interval_ms`` is greater than 0.
+pub fn start_relay(code: &str) -> bool {
+    #[cfg(feature = "with-mq")]
+    {
+        let funs = TardisFuns::inst_with_db_conn(code.to_string());
+        let interval_ms = funs.rbum_conf_event_outbox_relay_interval_ms();
+        if interval_ms == 0 {
+            return false;
+        }
+        tardis::tokio::spawn(async move {
+            loop {
+                if let Err(e) = relay(&funs).await {
+                    tardis::log::warn!("failed to relay outbox: {:?}", e);
+                }
+                tardis::tokio::time::sleep(std::time::Duration::from_millis(interval_ms)).await;
+            }
+        });
+        true
+    }
+    #[cfg(not(feature = "with-mq"))]
+    {
+        false
+    }
+}
+
 pub async fn receive<'a, F, T>(fun: F, funs: &TardisFunsInst<'a>) -> TardisResult<bool>
 where
     F: Fn((HashMap<String, String>, String)) -> T + Send + Sync + 'static,
@@ -51,15 +504,204 @@ where
     }
 }
 
+/// Subscribe to events with typed and filtered subscribers.
+///
+/// All subscribers share one consumer of the event topic, each message is decoded once
+/// and dispatched to every matching subscriber in order.
+///
+/// The message is acknowledged unless a subscriber fails and its error handler returns [RbumEventAckKind::Retry],
+/// in this case the message is redelivered and only the subscribers that have not handled it successfully are called again.
+/// The deliveries are tracked in memory, so a message that is redelivered to another instance
+/// (or after a restart) is dispatched to all matching subscribers, handlers should be idempotent.
+/// Messages that cannot be decoded are discarded.
+pub async fn subscribe<'a>(subscribers: Vec<RbumEventSubscriber>, funs: &TardisFunsInst<'a>) -> TardisResult<bool> {
+    let subscribers = Arc::new(subscribers);
+    let deliveries = Arc::new(Mutex::new(HashMap::new()));
+    receive(
+        move |(_, message)| {
+            let subscribers = subscribers.clone();
+            let deliveries = deliveries.clone();
+            async move { dispatch(&subscribers, &deliveries, message).await }
+        },
+        funs,
+    )
+    .await
+}
+
+/// Message key -> (first delivery time, indexes of the subscribers that have handled the message)
+type RbumEventDeliveries = Mutex<HashMap<String, (Instant, HashSet<usize>)>>;
+
+/// The deliveries of the messages that are not redelivered within this period are discarded
+const DELIVERY_EXPIRE_SEC: u64 = 60 * 60;
+
+async fn dispatch(subscribers: &[RbumEventSubscriber], deliveries: &RbumEventDeliveries, message: String) -> TardisResult<()> {
+    let message = match parse_message(message) {
+        Ok(message) => message,
+        Err(e) => {
+            tardis::log::warn!("discard undecodable event message: {:?}", e);
+            return Ok(());
+        }
+    };
+    let key = message.get_key();
+    let (first_delivery_time, mut delivered_idxes) = {
+        let mut deliveries = deliveries.lock().map_err(|e| TardisError::InternalError(format!("{:?}", e)))?;
+        deliveries.remove(&key).unwrap_or_else(|| (Instant::now(), HashSet::new()))
+    };
+    let pending_subscribers = subscribers.iter().enumerate().filter(|(idx, subscriber)| !delivered_idxes.contains(idx) && subscriber.matches(&message)).collect::<Vec<_>>();
+    let mut need_retry = false;
+    for (idx, subscriber) in pending_subscribers {
+        if let Err(e) = (subscriber.handler)(message.clone()).await {
+            let ack = if let Some(error_handler) = &subscriber.error_handler {
+                error_handler(&message, &e)
+            } else {
+                tardis::log::warn!("failed to handle event {} {} of {}: {:?}", message.operate, message.record_id, message.table_name, e);
+                RbumEventAckKind::Ack
+            };
+            if ack == RbumEventAckKind::Retry {
+                need_retry = true;
+                continue;
+            }
+        }
+        delivered_idxes.insert(idx);
+    }
+    if need_retry {
+        let mut deliveries = deliveries.lock().map_err(|e| TardisError::InternalError(format!("{:?}", e)))?;
+        deliveries.retain(|_, (delivery_time, _)| delivery_time.elapsed().as_secs() < DELIVERY_EXPIRE_SEC);
+        deliveries.insert(key, (first_delivery_time, delivered_idxes));
+        Err(TardisError::InternalError(format!(
+            "event {} {} of {} needs to be retried",
+            message.operate, message.record_id, message.table_name
+        )))
+    } else {
+        Ok(())
+    }
+}
+
 pub fn parse_message(message: String) -> TardisResult<RbumEventMessage> {
-    tardis::TardisFuns::json.str_to_obj::<RbumEventMessage>(&message)
+    TardisFuns::json.str_to_obj::<RbumEventMessage>(&message)
+}
+
+type RbumEventHandler = Arc<dyn Fn(RbumEventMessage) -> Pin<Box<dyn Future<Output = TardisResult<()>> + Send>> + Send + Sync>;
+type RbumEventErrorHandler = Arc<dyn Fn(&RbumEventMessage, &TardisError) -> RbumEventAckKind + Send + Sync>;
+
+/// Acknowledgement of a failed event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RbumEventAckKind {
+    /// Acknowledge the message, it will not be delivered again
+    Ack,
+    /// Do not acknowledge the message, it will be redelivered
+    Retry,
+}
+
+/// Typed and filtered event subscriber
+///
+/// # Examples
+///
+/// ```ignore
+/// let subscriber = RbumEventSubscriber::new(|message| async move {
+///     println!("{} {}", message.operate, message.record_id);
+///     Ok(())
+/// })
+/// .table_prefix("iam_account")
+/// .operates(&["u", "d"])
+/// .own_paths_prefix(&ctx.own_paths)
+/// .on_error(|_, _| RbumEventAckKind::Retry);
+/// rbum_event_helper::subscribe(vec![subscriber], &funs).await?;
+/// ```
+#[derive(Clone)]
+pub struct RbumEventSubscriber {
+    table_prefixes: Vec<String>,
+    operates: Vec<String>,
+    own_paths_prefix: Option<String>,
+    handler: RbumEventHandler,
+    error_handler: Option<RbumEventErrorHandler>,
+}
+
+impl RbumEventSubscriber {
+    pub fn new<F, T>(handler: F) -> Self
+    where
+        F: Fn(RbumEventMessage) -> T + Send + Sync + 'static,
+        T: Future<Output = TardisResult<()>> + Send + 'static,
+    {
+        RbumEventSubscriber {
+            table_prefixes: vec![],
+            operates: vec![],
+            own_paths_prefix: None,
+            handler: Arc::new(move |message| Box::pin(handler(message)) as Pin<Box<dyn Future<Output = TardisResult<()>> + Send>>),
+            error_handler: None,
+        }
+    }
+
+    /// Only receive events of tables with the prefix, can be called multiple times.
+    pub fn table_prefix(mut self, table_prefix: &str) -> Self {
+        self.table_prefixes.push(table_prefix.to_string());
+        self
+    }
+
+    /// Only receive events of the operates (``c`` / ``u`` / ``d``).
+    pub fn operates(mut self, operates: &[&str]) -> Self {
+        self.operates = operates.iter().map(|operate| operate.to_string()).collect();
+        self
+    }
+
+    /// Only receive events that occurred in the own_paths and its sub own_paths, compared by whole nodes, see [rbum_scope_helper::is_sub_own_paths].
+    pub fn own_paths_prefix(mut self, own_paths_prefix: &str) -> Self {
+        self.own_paths_prefix = Some(own_paths_prefix.to_string());
+        self
+    }
+
+    /// Decide whether a failed event is acknowledged, the default is to log the error and acknowledge it.
+    pub fn on_error<F>(mut self, error_handler: F) -> Self
+    where
+        F: Fn(&RbumEventMessage, &TardisError) -> RbumEventAckKind + Send + Sync + 'static,
+    {
+        self.error_handler = Some(Arc::new(error_handler));
+        self
+    }
+
+    pub fn matches(&self, message: &RbumEventMessage) -> bool {
+        (self.table_prefixes.is_empty() || self.table_prefixes.iter().any(|table_prefix| message.table_name.starts_with(table_prefix)))
+            && (self.operates.is_empty() || self.operates.contains(&message.operate))
+            && self.own_paths_prefix.as_ref().map(|own_paths_prefix| rbum_scope_helper::is_sub_own_paths(&message.own_paths, own_paths_prefix)).unwrap_or(true)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RbumEventMessage {
+    /// Unique id of the message, empty for the messages written before it was introduced
+    #[serde(default)]
+    pub id: String,
     pub table_name: String,
     pub operate: String,
     pub operator: String,
+    #[serde(default)]
+    pub own_paths: String,
     pub record_id: String,
     pub ts: i64,
+    /// Record before the change, only for tables with snapshot enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    /// Record after the change, only for tables with snapshot enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+    /// Changed fields of ``u`` events, only for tables with snapshot enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<Vec<RbumEventFieldDiff>>,
+}
+
+impl RbumEventMessage {
+    fn get_key(&self) -> String {
+        if self.id.is_empty() {
+            format!("{}:{}:{}:{}", self.table_name, self.operate, self.record_id, self.ts)
+        } else {
+            self.id.to_string()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RbumEventFieldDiff {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
 }
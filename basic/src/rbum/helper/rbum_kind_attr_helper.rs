@@ -0,0 +1,402 @@
+//! Validation of extended attribute values against the [kind attribute](crate::rbum::domain::rbum_kind_attr::Model) definitions
+//! and rendering of the definitions as a JSON Schema document and a form layout,
+//! and evaluation of the [relationship attribute](crate::rbum::domain::rbum_rel_attr::Model) conditions by the data types of the definitions
+//!
+//! ``options`` is a JSON array, each element is either a value or an object whose keys are the values, e.g. `["a","b"]` or `[{"l1":"L1","l2":"L2"}]`.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use tardis::chrono::{DateTime, NaiveDate, NaiveDateTime};
+use tardis::regex::Regex;
+use tardis::serde_json::{json, Map as JsonMap, Value as JsonValue};
+use tardis::TardisFuns;
+
+use crate::rbum::dto::rbum_item_attr_dto::RbumItemAttrValidateErrorResp;
+use crate::rbum::dto::rbum_kind_attr_dto::{RbumKindAttrFormItemResp, RbumKindAttrFormRowResp, RbumKindAttrSchemaResp, RbumKindAttrSummaryResp};
+use crate::rbum::rbum_enumeration::{RbumDataTypeKind, RbumRelAttrOpKind};
+
+/// Max number of the cached regular expressions, the cache is cleared when it is full
+const CONDITION_REGEX_CACHE_SIZE: usize = 1000;
+
+lazy_static! {
+    // condition value -> regular expression of the Regex conditions,
+    // compiled when the conditions are validated (saved) or first checked by this process
+    static ref CONDITION_REGEXES: RwLock<HashMap<String, Regex>> = RwLock::new(HashMap::new());
+}
+
+/// Validate the values (name -> value), unknown names are ignored.
+///
+/// Empty and whitespace-only values count as missing.
+/// When ``is_add`` is true, missing values are filled with the default values and missing required values are reported,
+/// otherwise only the given values are checked, and a required value can not be cleared.
+pub fn validate_values(rbum_kind_attrs: &[RbumKindAttrSummaryResp], values: &mut HashMap<String, String>, is_add: bool) -> Vec<RbumItemAttrValidateErrorResp> {
+    let mut errors = Vec::new();
+    for rbum_kind_attr in rbum_kind_attrs {
+        let missing = values.get(&rbum_kind_attr.name).map(|value| value.trim().is_empty()).unwrap_or(true);
+        if is_add && missing && !rbum_kind_attr.default_value.trim().is_empty() {
+            values.insert(rbum_kind_attr.name.clone(), rbum_kind_attr.default_value.clone());
+        }
+        match values.get(&rbum_kind_attr.name) {
+            Some(value) if value.trim().is_empty() && rbum_kind_attr.required => errors.push(RbumItemAttrValidateErrorResp {
+                name: rbum_kind_attr.name.clone(),
+                message: "is required".to_string(),
+            }),
+            Some(value) => {
+                if let Err(message) = validate_value(rbum_kind_attr, value) {
+                    errors.push(RbumItemAttrValidateErrorResp {
+                        name: rbum_kind_attr.name.clone(),
+                        message,
+                    });
+                }
+            }
+            None if is_add && rbum_kind_attr.required => errors.push(RbumItemAttrValidateErrorResp {
+                name: rbum_kind_attr.name.clone(),
+                message: "is required".to_string(),
+            }),
+            None => {}
+        }
+    }
+    errors
+}
+
+/// Validate a value by the data type, length bounds and options of the attribute
+pub fn validate_value(rbum_kind_attr: &RbumKindAttrSummaryResp, value: &str) -> Result<(), String> {
+    // Empty value means clearing the attribute, which is rejected for the required attributes by ``validate_values``
+    if value.trim().is_empty() {
+        return Ok(());
+    }
+    let len = value.chars().count() as u32;
+    if rbum_kind_attr.min_length > 0 && len < rbum_kind_attr.min_length {
+        return Err(format!("length must be greater than or equal to {}", rbum_kind_attr.min_length));
+    }
+    if rbum_kind_attr.max_length > 0 && len > rbum_kind_attr.max_length {
+        return Err(format!("length must be less than or equal to {}", rbum_kind_attr.max_length));
+    }
+    let items = match rbum_kind_attr.data_type {
+        RbumDataTypeKind::Strings | RbumDataTypeKind::Numbers | RbumDataTypeKind::Booleans | RbumDataTypeKind::Dates | RbumDataTypeKind::DateTimes | RbumDataTypeKind::Array => {
+            match TardisFuns::json.str_to_json(value) {
+                Ok(JsonValue::Array(items)) => items.into_iter().map(|item| if let JsonValue::String(item) = item { item } else { item.to_string() }).collect::<Vec<String>>(),
+                _ => return Err(format!("must be a JSON array of {}", rbum_kind_attr.data_type)),
+            }
+        }
+        _ => vec![value.to_string()],
+    };
+    let valid = match rbum_kind_attr.data_type {
+        RbumDataTypeKind::String | RbumDataTypeKind::Strings | RbumDataTypeKind::Array => true,
+        RbumDataTypeKind::Number | RbumDataTypeKind::Numbers => items.iter().all(|item| item.parse::<f64>().is_ok()),
+        RbumDataTypeKind::Boolean | RbumDataTypeKind::Booleans => items.iter().all(|item| item.parse::<bool>().is_ok()),
+        RbumDataTypeKind::Date | RbumDataTypeKind::Dates => items.iter().all(|item| NaiveDate::parse_from_str(item, "%Y-%m-%d").is_ok()),
+        RbumDataTypeKind::DateTime | RbumDataTypeKind::DateTimes => {
+            items.iter().all(|item| DateTime::parse_from_rfc3339(item).is_ok() || NaiveDateTime::parse_from_str(item, "%Y-%m-%d %H:%M:%S").is_ok())
+        }
+        RbumDataTypeKind::Json => TardisFuns::json.str_to_json(value).is_ok(),
+    };
+    if !valid {
+        return Err(format!("must be of type {}", rbum_kind_attr.data_type));
+    }
+    if let Some(options) = parse_options(&rbum_kind_attr.options) {
+        if let Some(item) = items.iter().find(|item| !options.contains(*item)) {
+            return Err(format!("{} is not one of the options", item));
+        }
+    }
+    Ok(())
+}
+
+/// Validate the value of a relationship attribute condition, see [check_condition]
+pub fn validate_condition(data_type: &RbumDataTypeKind, op: &RbumRelAttrOpKind, condition_value: &str) -> Result<(), String> {
+    match op {
+        RbumRelAttrOpKind::Gt | RbumRelAttrOpKind::Gte | RbumRelAttrOpKind::Lt | RbumRelAttrOpKind::Lte
+            if !matches!(data_type, RbumDataTypeKind::Number | RbumDataTypeKind::Date | RbumDataTypeKind::DateTime) =>
+        {
+            Err(format!("operator {} is only supported by Number, Date and DateTime, not {}", op, data_type))
+        }
+        RbumRelAttrOpKind::Regex | RbumRelAttrOpKind::Prefix if data_type != &RbumDataTypeKind::String => {
+            Err(format!("operator {} is only supported by String, not {}", op, data_type))
+        }
+        RbumRelAttrOpKind::Regex => get_condition_regex(condition_value).map(|_| ()),
+        RbumRelAttrOpKind::Prefix => Ok(()),
+        RbumRelAttrOpKind::In | RbumRelAttrOpKind::NotIn => match parse_condition_values(data_type, condition_value) {
+            Some(_) => Ok(()),
+            None => Err(format!("must be a JSON array of {}", data_type)),
+        },
+        _ => match parse_comparable_value(data_type, condition_value) {
+            Some(_) => Ok(()),
+            None => Err(format!("must be of type {}", data_type)),
+        },
+    }
+}
+
+/// Check whether the attribute value satisfies the relationship attribute condition (``value op condition_value``)
+///
+/// Numbers, booleans, dates and datetimes are compared by their values (e.g. ``3`` equals ``3.0``),
+/// the other data types are compared as text. Values that cannot be parsed never satisfy the condition,
+/// an error is returned only if the condition itself is illegal, see [validate_condition].
+pub fn check_condition(data_type: &RbumDataTypeKind, op: &RbumRelAttrOpKind, condition_value: &str, value: &str) -> Result<bool, String> {
+    validate_condition(data_type, op, condition_value)?;
+    let value = match parse_comparable_value(data_type, value) {
+        Some(value) => value,
+        None => return Ok(false),
+    };
+    let satisfied = match op {
+        RbumRelAttrOpKind::Regex => get_condition_regex(condition_value)?.is_match(value.as_text()),
+        RbumRelAttrOpKind::Prefix => value.as_text().starts_with(condition_value),
+        RbumRelAttrOpKind::In | RbumRelAttrOpKind::NotIn => {
+            let condition_values = parse_condition_values(data_type, condition_value).unwrap_or_default();
+            condition_values.contains(&value) == (op == &RbumRelAttrOpKind::In)
+        }
+        _ => {
+            let ordering = parse_comparable_value(data_type, condition_value).and_then(|condition_value| value.partial_cmp(&condition_value));
+            match (op, ordering) {
+                (_, None) => false,
+                (RbumRelAttrOpKind::Eq, Some(ordering)) => ordering == Ordering::Equal,
+                (RbumRelAttrOpKind::Ne, Some(ordering)) => ordering != Ordering::Equal,
+                (RbumRelAttrOpKind::Gt, Some(ordering)) => ordering == Ordering::Greater,
+                (RbumRelAttrOpKind::Gte, Some(ordering)) => ordering != Ordering::Less,
+                (RbumRelAttrOpKind::Lt, Some(ordering)) => ordering == Ordering::Less,
+                (RbumRelAttrOpKind::Lte, Some(ordering)) => ordering != Ordering::Greater,
+                // Handled above
+                (_, Some(_)) => false,
+            }
+        }
+    };
+    Ok(satisfied)
+}
+
+#[derive(PartialEq, PartialOrd)]
+enum ComparableValue {
+    Number(f64),
+    Boolean(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    Text(String),
+}
+
+impl ComparableValue {
+    fn as_text(&self) -> &str {
+        match self {
+            ComparableValue::Text(value) => value,
+            _ => "",
+        }
+    }
+}
+
+fn get_condition_regex(condition_value: &str) -> Result<Regex, String> {
+    if let Some(regex) = CONDITION_REGEXES.read().ok().and_then(|regexes| regexes.get(condition_value).cloned()) {
+        return Ok(regex);
+    }
+    let regex = Regex::new(condition_value).map_err(|e| format!("must be a regular expression: {}", e))?;
+    if let Ok(mut regexes) = CONDITION_REGEXES.write() {
+        if regexes.len() >= CONDITION_REGEX_CACHE_SIZE {
+            regexes.clear();
+        }
+        regexes.insert(condition_value.to_string(), regex.clone());
+    }
+    Ok(regex)
+}
+
+fn parse_comparable_value(data_type: &RbumDataTypeKind, value: &str) -> Option<ComparableValue> {
+    match data_type {
+        RbumDataTypeKind::Number => value.trim().parse::<f64>().ok().filter(|value| !value.is_nan()).map(ComparableValue::Number),
+        RbumDataTypeKind::Boolean => value.trim().parse::<bool>().ok().map(ComparableValue::Boolean),
+        RbumDataTypeKind::Date => NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok().map(ComparableValue::Date),
+        // Datetimes without offsets are in UTC
+        RbumDataTypeKind::DateTime => DateTime::parse_from_rfc3339(value.trim())
+            .map(|value| value.naive_utc())
+            .or_else(|_| NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S"))
+            .ok()
+            .map(ComparableValue::DateTime),
+        _ => Some(ComparableValue::Text(value.to_string())),
+    }
+}
+
+fn parse_condition_values(data_type: &RbumDataTypeKind, condition_value: &str) -> Option<Vec<ComparableValue>> {
+    match TardisFuns::json.str_to_json(condition_value) {
+        Ok(JsonValue::Array(items)) => {
+            items.into_iter().map(|item| if let JsonValue::String(item) = item { item } else { item.to_string() }).map(|item| parse_comparable_value(data_type, &item)).collect()
+        }
+        _ => None,
+    }
+}
+
+fn parse_options(options: &str) -> Option<Vec<String>> {
+    if options.trim().is_empty() {
+        return None;
+    }
+    match TardisFuns::json.str_to_json(options) {
+        Ok(JsonValue::Array(options)) => Some(
+            options
+                .into_iter()
+                .flat_map(|option| match option {
+                    JsonValue::Object(option) => option.into_iter().map(|(value, _)| value).collect::<Vec<String>>(),
+                    JsonValue::String(option) => vec![option],
+                    option => vec![option.to_string()],
+                })
+                .collect(),
+        ),
+        // Options that are not a JSON array are for display only
+        _ => None,
+    }
+}
+
+/// Render the attributes as a JSON Schema document and a form layout, see ``to_json_schema`` and ``to_form``
+pub fn to_schema(rbum_kind_attrs: &[RbumKindAttrSummaryResp]) -> RbumKindAttrSchemaResp {
+    RbumKindAttrSchemaResp {
+        json_schema: to_json_schema(rbum_kind_attrs),
+        form: to_form(rbum_kind_attrs),
+    }
+}
+
+/// Render the attributes as a JSON Schema (draft 2020-12) object, each attribute is a property of the object.
+///
+/// Hidden attributes are included, because their values are still part of the item.
+pub fn to_json_schema(rbum_kind_attrs: &[RbumKindAttrSummaryResp]) -> JsonValue {
+    let mut properties = JsonMap::new();
+    let mut required = Vec::new();
+    for rbum_kind_attr in sort_attrs(rbum_kind_attrs) {
+        properties.insert(rbum_kind_attr.name.clone(), package_property(rbum_kind_attr));
+        if rbum_kind_attr.required {
+            required.push(JsonValue::String(rbum_kind_attr.name.clone()));
+        }
+    }
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// Render the attributes as a form layout.
+///
+/// Attributes are ordered by ``sort`` (ascending) and hidden attributes are left out.
+/// An attribute with ``position`` takes a whole row, the other attributes are put in the same row until the next ``position`` attribute.
+pub fn to_form(rbum_kind_attrs: &[RbumKindAttrSummaryResp]) -> Vec<RbumKindAttrFormRowResp> {
+    let mut rows = Vec::new();
+    let mut row_items = Vec::new();
+    for rbum_kind_attr in sort_attrs(rbum_kind_attrs).into_iter().filter(|rbum_kind_attr| !rbum_kind_attr.hide) {
+        let item = RbumKindAttrFormItemResp {
+            name: rbum_kind_attr.name.clone(),
+            label: rbum_kind_attr.label.clone(),
+            note: rbum_kind_attr.note.clone(),
+            data_type: rbum_kind_attr.data_type.clone(),
+            widget_type: rbum_kind_attr.widget_type.clone(),
+            default_value: rbum_kind_attr.default_value.clone(),
+            options: rbum_kind_attr.options.clone(),
+            required: rbum_kind_attr.required,
+            min_length: rbum_kind_attr.min_length,
+            max_length: rbum_kind_attr.max_length,
+            action: rbum_kind_attr.action.clone(),
+            ext: rbum_kind_attr.ext.clone(),
+        };
+        if rbum_kind_attr.position {
+            if !row_items.is_empty() {
+                rows.push(RbumKindAttrFormRowResp { items: row_items });
+                row_items = Vec::new();
+            }
+            rows.push(RbumKindAttrFormRowResp { items: vec![item] });
+        } else {
+            row_items.push(item);
+        }
+    }
+    if !row_items.is_empty() {
+        rows.push(RbumKindAttrFormRowResp { items: row_items });
+    }
+    rows
+}
+
+fn sort_attrs(rbum_kind_attrs: &[RbumKindAttrSummaryResp]) -> Vec<&RbumKindAttrSummaryResp> {
+    let mut rbum_kind_attrs = rbum_kind_attrs.iter().collect::<Vec<&RbumKindAttrSummaryResp>>();
+    rbum_kind_attrs.sort_by(|a, b| a.sort.cmp(&b.sort).then_with(|| a.name.cmp(&b.name)));
+    rbum_kind_attrs
+}
+
+fn package_property(rbum_kind_attr: &RbumKindAttrSummaryResp) -> JsonValue {
+    let scalar_data_type = match rbum_kind_attr.data_type {
+        RbumDataTypeKind::Strings => Some(RbumDataTypeKind::String),
+        RbumDataTypeKind::Numbers => Some(RbumDataTypeKind::Number),
+        RbumDataTypeKind::Booleans => Some(RbumDataTypeKind::Boolean),
+        RbumDataTypeKind::Dates => Some(RbumDataTypeKind::Date),
+        RbumDataTypeKind::DateTimes => Some(RbumDataTypeKind::DateTime),
+        _ => None,
+    };
+    let mut property = match &scalar_data_type {
+        Some(scalar_data_type) => {
+            let mut property = JsonMap::new();
+            property.insert("type".to_string(), json!("array"));
+            property.insert("items".to_string(), JsonValue::Object(package_scalar_schema(scalar_data_type, &rbum_kind_attr.options)));
+            property
+        }
+        None => package_scalar_schema(&rbum_kind_attr.data_type, &rbum_kind_attr.options),
+    };
+    property.insert("title".to_string(), json!(rbum_kind_attr.label));
+    if !rbum_kind_attr.note.is_empty() {
+        property.insert("description".to_string(), json!(rbum_kind_attr.note));
+    }
+    if rbum_kind_attr.data_type == RbumDataTypeKind::String {
+        if rbum_kind_attr.min_length > 0 {
+            property.insert("minLength".to_string(), json!(rbum_kind_attr.min_length));
+        }
+        if rbum_kind_attr.max_length > 0 {
+            property.insert("maxLength".to_string(), json!(rbum_kind_attr.max_length));
+        }
+    }
+    if !rbum_kind_attr.default_value.is_empty() {
+        let default_value = if scalar_data_type.is_some() || rbum_kind_attr.data_type == RbumDataTypeKind::Array || rbum_kind_attr.data_type == RbumDataTypeKind::Json {
+            TardisFuns::json.str_to_json(&rbum_kind_attr.default_value).unwrap_or_else(|_| JsonValue::String(rbum_kind_attr.default_value.clone()))
+        } else {
+            to_scalar_json_value(&rbum_kind_attr.data_type, &rbum_kind_attr.default_value)
+        };
+        property.insert("default".to_string(), default_value);
+    }
+    JsonValue::Object(property)
+}
+
+fn package_scalar_schema(data_type: &RbumDataTypeKind, options: &str) -> JsonMap<String, JsonValue> {
+    let mut schema = JsonMap::new();
+    match data_type {
+        RbumDataTypeKind::String => {
+            schema.insert("type".to_string(), json!("string"));
+        }
+        RbumDataTypeKind::Number => {
+            schema.insert("type".to_string(), json!("number"));
+        }
+        RbumDataTypeKind::Boolean => {
+            schema.insert("type".to_string(), json!("boolean"));
+        }
+        RbumDataTypeKind::Date => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("format".to_string(), json!("date"));
+        }
+        RbumDataTypeKind::DateTime => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("format".to_string(), json!("date-time"));
+        }
+        RbumDataTypeKind::Array => {
+            schema.insert("type".to_string(), json!("array"));
+        }
+        // Any JSON value
+        _ => {}
+    }
+    if let Some(options) = parse_options(options) {
+        let options = JsonValue::Array(options.iter().map(|option| to_scalar_json_value(data_type, option)).collect());
+        if data_type == &RbumDataTypeKind::Array {
+            // Each element of the array must be one of the options
+            schema.insert("items".to_string(), json!({ "enum": options }));
+        } else {
+            schema.insert("enum".to_string(), options);
+        }
+    }
+    schema
+}
+
+fn to_scalar_json_value(data_type: &RbumDataTypeKind, value: &str) -> JsonValue {
+    match data_type {
+        RbumDataTypeKind::Number | RbumDataTypeKind::Boolean => match TardisFuns::json.str_to_json(value) {
+            Ok(json_value) if json_value.is_number() || json_value.is_boolean() => json_value,
+            _ => JsonValue::String(value.to_string()),
+        },
+        _ => JsonValue::String(value.to_string()),
+    }
+}
@@ -0,0 +1,313 @@
+//! Versioned schema migrations
+//!
+//! Each module (e.g. ``rbum``, ``iam``) registers its ordered migration steps, the applied steps are recorded
+//! in the ``rbum_migration`` table with their checksums. Creating a table that already exists and adding a column
+//! that already exists are skipped, so the first step of a module can create all of its tables
+//! and also works with the databases created before the migrations were introduced.
+//!
+//! The tables are created by the current entity definitions, so only the table names of ``CreateTable`` are checksummed,
+//! the columns added to an entity later have to be added by ``AddColumn`` steps for the existing databases.
+use serde::{Deserialize, Serialize};
+use tardis::basic::error::TardisError;
+use tardis::basic::result::TardisResult;
+use tardis::chrono::{DateTime, Utc};
+use tardis::db::reldb_client::{TardisActiveModel, TardisRelDBlConnection};
+use tardis::db::sea_orm::*;
+use tardis::db::sea_query::{Alias, ColumnDef, Expr, IndexCreateStatement, IndexDropStatement, Order, Query, Table, TableAlterStatement, TableCreateStatement};
+use tardis::log::info;
+use tardis::TardisFuns;
+
+use crate::rbum::domain::rbum_migration;
+use crate::rbum::helper::rbum_dialect_helper::{self, RawSql};
+
+const UPDATE_TIME_COLUMN: &str = "update_time";
+
+/// Key of the Postgres advisory lock that serializes the migrations
+const MIGRATION_LOCK_KEY: i64 = 0x7262_756d_6d69_67;
+
+/// Name of the MySQL lock that serializes the migrations
+const MIGRATION_LOCK_NAME: &str = "rbum_migration";
+
+const MIGRATION_LOCK_TIMEOUT_SEC: u32 = 300;
+
+pub enum RbumMigrationStatement {
+    /// Table name, table, indexes and other statements (e.g. triggers) of the table, skipped if the table already exists
+    CreateTable(String, TableCreateStatement, Vec<IndexCreateStatement>, Vec<String>),
+    /// Table name, column name and column, skipped if the column already exists
+    AddColumn(String, String, ColumnDef),
+    AlterTable(TableAlterStatement),
+    CreateIndex(IndexCreateStatement),
+    DropIndex(IndexDropStatement),
+}
+
+impl RbumMigrationStatement {
+    pub fn create_table<T: TardisActiveModel + ActiveModelTrait>(db_kind: DbBackend) -> RbumMigrationStatement {
+        let (table, indexes) = T::create_table_and_index_statement(db_kind);
+        let table_name = T::Entity::default().table_name().to_string();
+        let others = <T::Entity as EntityTrait>::Column::iter()
+            .filter(|column| column.as_str() == UPDATE_TIME_COLUMN)
+            .filter_map(|column| rbum_dialect_helper::package_update_time_trigger(&table_name, column.as_str(), db_kind))
+            .map(|trigger| trigger.0)
+            .collect();
+        RbumMigrationStatement::CreateTable(table_name, table, indexes, others)
+    }
+
+    pub fn add_column<E: EntityName, C: ColumnTrait>(entity: E, column: C, column_def: ColumnDef) -> RbumMigrationStatement {
+        RbumMigrationStatement::AddColumn(entity.table_name().to_string(), column.as_str().to_string(), column_def)
+    }
+
+    fn to_sqls(&self, db_kind: DbBackend) -> Vec<String> {
+        match self {
+            RbumMigrationStatement::CreateTable(_, table, indexes, others) => {
+                let mut sqls = vec![db_kind.build(table).to_string()];
+                sqls.extend(indexes.iter().map(|index| db_kind.build(index).to_string()));
+                sqls.extend(others.iter().cloned());
+                sqls
+            }
+            RbumMigrationStatement::AddColumn(table_name, _, column) => vec![db_kind.build(&Self::package_add_column(table_name, column)).to_string()],
+            RbumMigrationStatement::AlterTable(table) => vec![db_kind.build(table).to_string()],
+            RbumMigrationStatement::CreateIndex(index) => vec![db_kind.build(index).to_string()],
+            RbumMigrationStatement::DropIndex(index) => vec![db_kind.build(index).to_string()],
+        }
+    }
+
+    async fn execute<'a>(&self, db: &TardisRelDBlConnection<'a>) -> TardisResult<()> {
+        match self {
+            RbumMigrationStatement::CreateTable(table_name, table, indexes, others) => {
+                if !exist_table(table_name, db).await? {
+                    db.create_table_and_index(&(table.clone(), indexes.clone())).await?;
+                    for other in others {
+                        db.execute(&RawSql(other.to_string())).await?;
+                    }
+                }
+            }
+            RbumMigrationStatement::AddColumn(table_name, column_name, column) => {
+                if !exist_column(table_name, column_name, db).await? {
+                    db.execute(&Self::package_add_column(table_name, column)).await?;
+                }
+            }
+            RbumMigrationStatement::AlterTable(table) => {
+                db.execute(table).await?;
+            }
+            RbumMigrationStatement::CreateIndex(index) => {
+                db.execute(index).await?;
+            }
+            RbumMigrationStatement::DropIndex(index) => {
+                db.execute(index).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The created tables change with the entities, only their names are checksummed
+    fn to_checksum_sqls(&self, db_kind: DbBackend) -> Vec<String> {
+        match self {
+            RbumMigrationStatement::CreateTable(table_name, _, _, _) => vec![format!("CREATE TABLE {}", table_name)],
+            _ => self.to_sqls(db_kind),
+        }
+    }
+
+    fn package_add_column(table_name: &str, column: &ColumnDef) -> TableAlterStatement {
+        Table::alter().table(Alias::new(table_name)).add_column(&mut column.clone()).to_owned()
+    }
+}
+
+/// Migration step, the version must be unique and increasing in a module, the statements must not be changed once the step is released
+pub struct RbumMigrationStep {
+    pub version: u32,
+    pub name: String,
+    pub statements: Vec<RbumMigrationStatement>,
+}
+
+impl RbumMigrationStep {
+    pub fn new(version: u32, name: &str, statements: Vec<RbumMigrationStatement>) -> RbumMigrationStep {
+        RbumMigrationStep {
+            version,
+            name: name.to_string(),
+            statements,
+        }
+    }
+
+    fn checksum(&self, db_kind: DbBackend) -> TardisResult<String> {
+        TardisFuns::crypto.digest.md5(&self.statements.iter().flat_map(|statement| statement.to_checksum_sqls(db_kind)).collect::<Vec<String>>().join(";\n"))
+    }
+
+    fn to_sqls(&self, db_kind: DbBackend) -> Vec<String> {
+        self.statements.iter().flat_map(|statement| statement.to_sqls(db_kind)).collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RbumMigrationPendingResp {
+    pub module: String,
+    pub version: u32,
+    pub name: String,
+    pub checksum: String,
+    pub sqls: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, FromQueryResult)]
+pub struct RbumMigrationHistoryResp {
+    pub module: String,
+    pub version: u32,
+    pub name: String,
+    pub checksum: String,
+    pub create_time: DateTime<Utc>,
+}
+
+/// Apply the steps in a transaction of the default database, see ``migrate_in_tx``
+///
+/// The migrations of the instances are serialized by a lock: ``pg_advisory_xact_lock`` in the transaction with Postgres,
+/// ``GET_LOCK`` on another pinned connection with MySQL (it survives the implicit commits of the DDL statements),
+/// and the database lock of the writing transaction with SQLite.
+pub async fn migrate(module: &str, steps: &[RbumMigrationStep], dry_run: bool) -> TardisResult<Vec<RbumMigrationPendingResp>> {
+    let db_kind = TardisFuns::reldb().backend();
+    let mut lock_conn = TardisFuns::reldb().conn();
+    if db_kind == DatabaseBackend::MySql {
+        lock_conn.begin().await?;
+        let locked = lock_conn
+            .query_one(
+                "SELECT GET_LOCK(?, ?) AS locked",
+                vec![Value::from(MIGRATION_LOCK_NAME), Value::from(MIGRATION_LOCK_TIMEOUT_SEC)],
+            )
+            .await?
+            .map(|row| row.try_get::<Option<i64>>("", "locked"))
+            .transpose()?
+            .flatten();
+        if locked != Some(1) {
+            return Err(TardisError::Conflict(format!("migration lock is not acquired in {} seconds", MIGRATION_LOCK_TIMEOUT_SEC)));
+        }
+    }
+    let mut conn = TardisFuns::reldb().conn();
+    conn.begin().await?;
+    if db_kind == DatabaseBackend::Postgres {
+        conn.execute_one("SELECT pg_advisory_xact_lock($1)", vec![Value::from(MIGRATION_LOCK_KEY)]).await?;
+    }
+    let result = match migrate_in_tx(module, steps, dry_run, &conn).await {
+        Ok(pending_steps) => conn.commit().await.map(|_| pending_steps),
+        // The lock is released below even if the rollback fails
+        Err(error) => conn.rollback().await.and(Err(error)),
+    };
+    if db_kind == DatabaseBackend::MySql {
+        lock_conn.execute_one("SELECT RELEASE_LOCK(?)", vec![Value::from(MIGRATION_LOCK_NAME)]).await?;
+        lock_conn.commit().await?;
+    }
+    result
+}
+
+/// Apply the steps that are not applied yet in order and return them
+///
+/// With ``dry_run`` the pending steps are returned without being applied.
+/// An error is returned if an applied step is missing from ``steps`` or its checksum has changed.
+///
+/// A failed step is rolled back with Postgres and SQLite. MySQL commits the DDL statements implicitly,
+/// so a failed step may be partially applied there: ``CreateTable`` and ``AddColumn`` are skipped when they exist and can be retried,
+/// the other statements have to be repaired manually before retrying.
+async fn migrate_in_tx<'a>(module: &str, steps: &[RbumMigrationStep], dry_run: bool, db: &TardisRelDBlConnection<'a>) -> TardisResult<Vec<RbumMigrationPendingResp>> {
+    let db_kind = db.raw_conn().get_database_backend();
+    if steps.windows(2).any(|pair| pair[0].version >= pair[1].version) {
+        return Err(TardisError::InternalError(format!("migration steps of {} must be ordered by unique versions", module)));
+    }
+    let histories = find_histories(module, db).await?;
+    for history in &histories {
+        let step = steps
+            .iter()
+            .find(|step| step.version == history.version)
+            .ok_or_else(|| TardisError::Conflict(format!("migration {}:{} is applied but not registered", module, history.version)))?;
+        if step.checksum(db_kind)? != history.checksum {
+            return Err(TardisError::Conflict(format!(
+                "checksum of migration {}:{} has changed after it was applied, please add a new step instead",
+                module, history.version
+            )));
+        }
+    }
+    let mut pending_steps = Vec::new();
+    for step in steps.iter().filter(|step| !histories.iter().any(|history| history.version == step.version)) {
+        let checksum = step.checksum(db_kind)?;
+        if !dry_run {
+            info!("apply migration {}:{} {}", module, step.version, step.name);
+            for statement in &step.statements {
+                statement.execute(db).await?;
+            }
+            if !exist_table(rbum_migration::Entity.table_name(), db).await? {
+                db.create_table_and_index(&rbum_migration::ActiveModel::create_table_and_index_statement(db_kind)).await?;
+            }
+            db.execute(
+                Query::insert()
+                    .into_table(rbum_migration::Entity)
+                    .columns(vec![
+                        rbum_migration::Column::Id,
+                        rbum_migration::Column::Module,
+                        rbum_migration::Column::Version,
+                        rbum_migration::Column::Name,
+                        rbum_migration::Column::Checksum,
+                    ])
+                    .values_panic(vec![
+                        format!("{}:{}", module, step.version).into(),
+                        module.into(),
+                        step.version.into(),
+                        step.name.as_str().into(),
+                        checksum.as_str().into(),
+                    ]),
+            )
+            .await?;
+        }
+        pending_steps.push(RbumMigrationPendingResp {
+            module: module.to_string(),
+            version: step.version,
+            name: step.name.to_string(),
+            checksum,
+            sqls: step.to_sqls(db_kind),
+        });
+    }
+    Ok(pending_steps)
+}
+
+/// Applied steps of the module ordered by version
+pub async fn find_histories<'a>(module: &str, db: &TardisRelDBlConnection<'a>) -> TardisResult<Vec<RbumMigrationHistoryResp>> {
+    if !exist_table(rbum_migration::Entity.table_name(), db).await? {
+        return Ok(vec![]);
+    }
+    db.find_dtos(
+        Query::select()
+            .columns(vec![
+                rbum_migration::Column::Module,
+                rbum_migration::Column::Version,
+                rbum_migration::Column::Name,
+                rbum_migration::Column::Checksum,
+                rbum_migration::Column::CreateTime,
+            ])
+            .from(rbum_migration::Entity)
+            .and_where(Expr::col(rbum_migration::Column::Module).eq(module))
+            .order_by(rbum_migration::Column::Version, Order::Asc),
+    )
+    .await
+}
+
+pub async fn exist_table<'a>(table_name: &str, db: &TardisRelDBlConnection<'a>) -> TardisResult<bool> {
+    let db_kind = db.raw_conn().get_database_backend();
+    let sql = match db_kind {
+        DatabaseBackend::MySql => "SELECT COUNT(1) AS count FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = ?",
+        DatabaseBackend::Postgres => "SELECT COUNT(1) AS count FROM information_schema.tables WHERE table_schema = current_schema() AND table_name = $1",
+        DatabaseBackend::Sqlite => "SELECT COUNT(1) AS count FROM sqlite_master WHERE type = 'table' AND name = ?",
+    };
+    query_count(sql, vec![Value::from(table_name)], db).await
+}
+
+pub async fn exist_column<'a>(table_name: &str, column_name: &str, db: &TardisRelDBlConnection<'a>) -> TardisResult<bool> {
+    let db_kind = db.raw_conn().get_database_backend();
+    let sql = match db_kind {
+        DatabaseBackend::MySql => "SELECT COUNT(1) AS count FROM information_schema.columns WHERE table_schema = DATABASE() AND table_name = ? AND column_name = ?",
+        DatabaseBackend::Postgres => "SELECT COUNT(1) AS count FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1 AND column_name = $2",
+        DatabaseBackend::Sqlite => "SELECT COUNT(1) AS count FROM pragma_table_info(?) WHERE name = ?",
+    };
+    query_count(sql, vec![Value::from(table_name), Value::from(column_name)], db).await
+}
+
+/// Query in the transaction of the connection, e.g. the tables created by the previous steps are visible
+async fn query_count<'a>(sql: &str, params: Vec<Value>, db: &TardisRelDBlConnection<'a>) -> TardisResult<bool> {
+    match db.query_one(sql, params).await? {
+        Some(row) => Ok(row.try_get::<i64>("", "count")? > 0),
+        None => Ok(false),
+    }
+}
@@ -25,6 +25,10 @@
 //! | 'AAAA/BBBB/CCCC' | 2           |                |                | AAAA/BBBB%     | AAAA/BBBB%      |
 //! | 'AAAA/BBBB/CCCC' | 3           |                |                |                | AAAA/BBBB/CCCC% |
 //!
+//! The rule is the same for any depth: a record with scope level ``n`` (n >= 1) is visible to the own paths ``v1/v2/.../vm`` (m >= n)
+//! when its own paths is `''`, `v1`, `v1/v2` ... `v1/.../v(n-1)` or starts with `v1/.../vn`.
+//! ``get_scope_conds`` builds these conditions once, both the SQL condition (``RbumCrudQueryPackage::with_scope``) and ``check_scope`` use them.
+//!
 use std::cmp::Ordering;
 
 use crate::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
@@ -33,7 +37,8 @@ use tardis::basic::dto::TardisContext;
 use tardis::basic::error::TardisError;
 use tardis::basic::result::TardisResult;
 
-use crate::rbum::rbum_enumeration::RbumScopeLevelKind;
+use crate::rbum::dto::rbum_scope_dto::{RbumScopeExplainResp, RbumScopeExplainRuleResp};
+use crate::rbum::rbum_enumeration::{RbumScopeLevelKind, RbumScopeRuleKind};
 
 pub fn get_pre_paths(scope_level: i8, own_paths: &str) -> Option<String> {
     let own_paths = own_paths.trim();
@@ -79,6 +84,22 @@ pub fn get_max_level_id_by_context(ctx: &TardisContext) -> Option<String> {
     own_paths.split('/').collect::<Vec<&str>>().last().map(|s| s.to_string())
 }
 
+/// Whether ``own_paths`` is ``parent_own_paths`` or one of its descendants, compared by whole nodes (``t1`` is not the parent of ``t10``)
+pub fn is_sub_own_paths(own_paths: &str, parent_own_paths: &str) -> bool {
+    parent_own_paths.is_empty() || own_paths == parent_own_paths || own_paths.starts_with(&format!("{}/", parent_own_paths))
+}
+
+/// Replace the ``source_own_paths`` prefix of ``own_paths`` with ``target_own_paths``, ``own_paths`` outside ``source_own_paths`` is returned as is
+pub fn rebase_own_paths(own_paths: &str, source_own_paths: &str, target_own_paths: &str) -> String {
+    if own_paths == source_own_paths {
+        target_own_paths.to_string()
+    } else if let Some(sub_paths) = own_paths.strip_prefix(&format!("{}/", source_own_paths)) {
+        format!("{}/{}", target_own_paths, sub_paths)
+    } else {
+        own_paths.to_string()
+    }
+}
+
 pub fn degrade_own_paths(mut ctx: TardisContext, new_own_paths: &str) -> TardisResult<TardisContext> {
     if !new_own_paths.contains(&ctx.own_paths) {
         return Err(TardisError::Conflict("not qualified for downgrade".to_string()));
@@ -88,33 +109,108 @@ pub fn degrade_own_paths(mut ctx: TardisContext, new_own_paths: &str) -> TardisR
 }
 
 pub fn check_scope(record_own_paths: &str, record_scope_level: Option<i8>, filter: &RbumBasicFilterReq, ctx: &TardisContext) -> bool {
+    explain_scope(record_own_paths, record_scope_level, filter, ctx).visible
+}
+
+/// Evaluate the scope rules of the record one by one and record why each rule matched or failed.
+///
+/// All rules compare the record with the own paths of the filter (or the context if the filter has none).
+pub fn explain_scope(record_own_paths: &str, record_scope_level: Option<i8>, filter: &RbumBasicFilterReq, ctx: &TardisContext) -> RbumScopeExplainResp {
     let filter_own_paths = if let Some(own_paths) = &filter.own_paths { own_paths.as_str() } else { &ctx.own_paths };
-    if record_own_paths == filter_own_paths || filter.with_sub_own_paths && record_own_paths.contains(filter_own_paths) {
-        return true;
+    let mut explain = RbumScopeExplainResp {
+        visible: false,
+        record_own_paths: record_own_paths.to_string(),
+        record_scope_level: record_scope_level.and_then(|scope_level| RbumScopeLevelKind::from_int(scope_level).ok()),
+        filter_own_paths: filter_own_paths.to_string(),
+        ctx_own_paths: ctx.own_paths.to_string(),
+        rules: Vec::new(),
+    };
+    let mut add_rule = |rule: RbumScopeRuleKind, matched: bool, message: String| {
+        explain.rules.push(RbumScopeExplainRuleResp { rule, matched, message });
+        matched
+    };
+
+    if add_rule(
+        RbumScopeRuleKind::OwnPaths,
+        record_own_paths == filter_own_paths,
+        format!("record own paths '{}' = '{}'", record_own_paths, filter_own_paths),
+    ) {
+        explain.visible = true;
+        return explain;
+    }
+    if filter.with_sub_own_paths
+        && add_rule(
+            RbumScopeRuleKind::SubOwnPaths,
+            record_own_paths.starts_with(filter_own_paths),
+            format!("record own paths '{}' starts with '{}'", record_own_paths, filter_own_paths),
+        )
+    {
+        explain.visible = true;
+        return explain;
     }
     if filter.ignore_scope {
-        return false;
+        add_rule(RbumScopeRuleKind::IgnoreScope, false, "scope level rules are ignored by the filter".to_string());
+        return explain;
     }
-    if let Some(record_scope_level) = record_scope_level {
-        if let Some(p1) = get_pre_paths(1, filter_own_paths) {
-            if record_scope_level == 1 {
-                return record_own_paths.is_empty() || record_own_paths.contains(&p1);
-            }
-            if let Some(p2) = get_pre_paths(2, filter_own_paths) {
-                let node_len = p2.len() - p1.len() - 1;
-                if record_scope_level == 2 {
-                    return record_own_paths.is_empty() || record_own_paths.contains(&p2) || (record_own_paths.len() == node_len && record_own_paths.contains(&p1));
-                }
-                if let Some(p3) = get_pre_paths(3, filter_own_paths) {
-                    if record_scope_level == 3 {
-                        return record_own_paths.is_empty()
-                            || record_own_paths.contains(&p3)
-                            || (record_own_paths.len() == node_len && record_own_paths.contains(&p1))
-                            || (record_own_paths.len() == node_len * 2 + 1 && record_own_paths.contains(&p2));
-                    }
-                }
-            }
+    explain.visible = match record_scope_level {
+        None => add_rule(RbumScopeRuleKind::Level, false, "record has no scope level".to_string()),
+        Some(-1) => add_rule(RbumScopeRuleKind::Private, false, "private record is only visible to its own paths".to_string()),
+        Some(0) => add_rule(RbumScopeRuleKind::Root, true, "root record is visible to all".to_string()),
+        Some(record_scope_level) => match get_scope_conds(filter_own_paths).into_iter().find(|(scope_level, _)| *scope_level == record_scope_level) {
+            Some((_, conds)) => match conds.iter().find(|cond| cond.is_match(record_own_paths)) {
+                Some(cond) => add_rule(
+                    RbumScopeRuleKind::Level,
+                    true,
+                    format!("record own paths '{}' of level {} matches {:?}", record_own_paths, record_scope_level, cond),
+                ),
+                None => add_rule(
+                    RbumScopeRuleKind::Level,
+                    false,
+                    format!("record own paths '{}' of level {} matches none of {:?}", record_own_paths, record_scope_level, conds),
+                ),
+            },
+            None => add_rule(
+                RbumScopeRuleKind::Level,
+                false,
+                format!("own paths '{}' is shallower than level {}", filter_own_paths, record_scope_level),
+            ),
+        },
+    };
+    explain
+}
+
+/// Condition on the own paths of a record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RbumScopePathCond {
+    Eq(String),
+    Prefix(String),
+}
+
+impl RbumScopePathCond {
+    pub fn is_match(&self, record_own_paths: &str) -> bool {
+        match self {
+            RbumScopePathCond::Eq(own_paths) => record_own_paths == own_paths,
+            RbumScopePathCond::Prefix(own_paths) => record_own_paths.starts_with(own_paths.as_str()),
         }
     }
-    false
+}
+
+/// Get the conditions on the own paths of the records visible to ``own_paths`` for each scope level (1..=depth of ``own_paths``).
+///
+/// A record matching any condition of its scope level is visible.
+/// Root records are visible to all and private records are only visible to the same own paths, so they have no conditions here.
+pub fn get_scope_conds(own_paths: &str) -> Vec<(i8, Vec<RbumScopePathCond>)> {
+    let mut scope_conds = Vec::new();
+    let mut conds = vec![RbumScopePathCond::Eq("".to_string())];
+    for scope_level in 1..=RbumScopeLevelKind::MAX_LEVEL {
+        let pre_paths = match get_pre_paths(scope_level, own_paths) {
+            Some(pre_paths) => pre_paths,
+            None => break,
+        };
+        let mut level_conds = conds.clone();
+        level_conds.push(RbumScopePathCond::Prefix(pre_paths.clone()));
+        scope_conds.push((scope_level, level_conds));
+        conds.push(RbumScopePathCond::Eq(pre_paths));
+    }
+    scope_conds
 }
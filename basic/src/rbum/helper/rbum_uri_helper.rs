@@ -0,0 +1,57 @@
+//! Resource URI
+//!
+//! Each [resource](crate::rbum::domain::rbum_item::Model) corresponds to a unique uri: `<resource kind code>://<resource domain code>/<resource code>`,
+//! e.g. `iam-account://iam/xxx`. The kind code and the domain code must match ``R_URL_PART_CODE``, the resource code is everything after the first `/`
+//! and may contain `/`.
+use std::fmt;
+
+use tardis::basic::error::TardisError;
+use tardis::basic::result::TardisResult;
+
+use crate::rbum::serv::rbum_crud_serv::R_URL_PART_CODE;
+
+pub const URI_SCHEME_SEPARATOR: &str = "://";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RbumUri {
+    pub kind_code: String,
+    pub domain_code: String,
+    pub item_code: String,
+}
+
+impl RbumUri {
+    pub fn new(kind_code: &str, domain_code: &str, item_code: &str) -> TardisResult<RbumUri> {
+        let uri = RbumUri {
+            kind_code: kind_code.to_string(),
+            domain_code: domain_code.to_string(),
+            item_code: item_code.to_string(),
+        };
+        uri.validate()?;
+        Ok(uri)
+    }
+
+    pub fn parse(uri: &str) -> TardisResult<RbumUri> {
+        let (kind_code, path) = uri.split_once(URI_SCHEME_SEPARATOR).ok_or_else(|| TardisError::BadRequest(format!("uri {} is invalid", uri)))?;
+        let (domain_code, item_code) = path.split_once('/').ok_or_else(|| TardisError::BadRequest(format!("uri {} is invalid", uri)))?;
+        RbumUri::new(kind_code, domain_code, item_code)
+    }
+
+    fn validate(&self) -> TardisResult<()> {
+        if !R_URL_PART_CODE.is_match(&self.kind_code) {
+            return Err(TardisError::BadRequest(format!("kind code {} of uri is invalid", self.kind_code)));
+        }
+        if !R_URL_PART_CODE.is_match(&self.domain_code) {
+            return Err(TardisError::BadRequest(format!("domain code {} of uri is invalid", self.domain_code)));
+        }
+        if self.item_code.trim().is_empty() {
+            return Err(TardisError::BadRequest("item code of uri is empty".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RbumUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}/{}", self.kind_code, URI_SCHEME_SEPARATOR, self.domain_code, self.item_code)
+    }
+}
@@ -8,10 +8,14 @@ use tardis::basic::error::TardisError;
 use tardis::basic::result::TardisResult;
 use tardis::TardisFunsInst;
 
+use crate::rbum::rbum_enumeration::RbumScopeLevelKind;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct RbumConfig {
     pub set_cate_sys_code_node_len: usize,
+    // the deepest scope level (1..=RbumScopeLevelKind::MAX_LEVEL) that can be assigned to records, e.g. 4 for tenant/app/sub-app/workspace
+    pub scope_max_level: i8,
     pub mq_topic_entity_deleted: String,
     pub mq_topic_event: String,
     pub mq_header_name_operator: String,
@@ -22,14 +26,39 @@ pub struct RbumConfig {
     pub cache_key_cert_code_expire_sec: usize,
     pub cache_key_set_code_: String,
     pub cache_key_set_code_expire_sec: usize,
-    // table name (support prefix matching) -> <c><u><d>
+    // results of RbumRelServ::check_rel, invalidated by the events of the relationships and sets,
+    // see RbumRelServ::check_rel_cache_subscriber, 0 means the results are not cached
+    pub cache_key_rel_check_: String,
+    pub cache_key_rel_check_expire_sec: usize,
+    // addresses of the trusted proxies in the format of the ips environments of the relationships (e.g. 10.0.0.0/8,fd00::/8),
+    // the forwarded addresses of the relationship checks are only followed through them
+    pub trusted_proxy_ips: String,
+    // table name (support prefix matching) -> <c><u><d><r><s>
+    // r: restore from the recycle bin
+    // s: attach the record snapshots (and the field diff of update) to the event
     pub event_domains: HashMap<String, String>,
+    // 0 means the outbox relay is not started automatically
+    pub event_outbox_relay_interval_ms: u64,
+    pub event_outbox_relay_batch_size: u64,
+    // the messages claimed by a relay are not claimed by the others within this period,
+    // it should be longer than publishing a batch, otherwise the messages may be delivered again (and out of order)
+    pub event_outbox_relay_lease_ms: u64,
+    // the message that fails more than this number of times blocks the subsequent messages of the same record
+    pub event_outbox_max_retry_times: u32,
+    // the published messages are purged after this period, 0 means they are kept
+    pub event_outbox_retention_sec: u64,
+    // apply the pending migrations at startup, otherwise they are applied by a command
+    pub migrate_on_startup: bool,
+    // the maximum wait for the metadata lock of the ext table when adding / altering / dropping its columns (MySQL only),
+    // the lock is held by any transaction that has accessed the ext table
+    pub ext_ddl_lock_wait_timeout_sec: u32,
 }
 
 impl Default for RbumConfig {
     fn default() -> Self {
         RbumConfig {
             set_cate_sys_code_node_len: 4,
+            scope_max_level: 3,
             mq_topic_entity_deleted: "rbum::entity_deleted".to_string(),
             mq_topic_event: "rbum::event".to_string(),
             mq_header_name_operator: "OP".to_string(),
@@ -39,7 +68,17 @@ impl Default for RbumConfig {
             cache_key_cert_code_expire_sec: 60 * 60 * 24,
             cache_key_set_code_: "rbum:cache:set:code:".to_string(),
             cache_key_set_code_expire_sec: 60 * 60 * 24,
-            event_domains: HashMap::from([("rbum_".to_string(), "cud".to_string())]),
+            cache_key_rel_check_: "rbum:cache:rel:check:".to_string(),
+            cache_key_rel_check_expire_sec: 60 * 60,
+            trusted_proxy_ips: "".to_string(),
+            event_domains: HashMap::from([("rbum_".to_string(), "cudr".to_string())]),
+            event_outbox_relay_interval_ms: 500,
+            event_outbox_relay_batch_size: 100,
+            event_outbox_relay_lease_ms: 60 * 1000,
+            event_outbox_max_retry_times: 10,
+            event_outbox_retention_sec: 60 * 60 * 24 * 7,
+            migrate_on_startup: true,
+            ext_ddl_lock_wait_timeout_sec: 10,
         }
     }
 }
@@ -52,6 +91,14 @@ pub struct RbumConfigManager;
 
 impl RbumConfigManager {
     pub fn add(code: &str, config: RbumConfig) -> TardisResult<()> {
+        if config.scope_max_level < 1 || config.scope_max_level > RbumScopeLevelKind::MAX_LEVEL {
+            return Err(TardisError::BadRequest(format!(
+                "scope_max_level {} of rbum config {} is out of range 1..={}",
+                config.scope_max_level,
+                code,
+                RbumScopeLevelKind::MAX_LEVEL
+            )));
+        }
         let mut conf = RBUM_CONFIG.lock().map_err(|e| TardisError::InternalError(format!("{:?}", e)))?;
         conf.insert(code.to_string(), config);
         Ok(())
@@ -61,6 +108,10 @@ impl RbumConfigManager {
         Self::get_config(code, |conf| conf.event_domains.iter().any(|(k, v)| table_name.contains(k) && v.contains(operate)))
     }
 
+    pub fn match_event_snapshot(code: &str, table_name: &str) -> bool {
+        Self::get_config(code, |conf| conf.event_domains.iter().any(|(k, v)| table_name.contains(k) && v.contains('s')))
+    }
+
     pub fn get_config<F, T>(code: &str, fun: F) -> T
     where
         F: Fn(&RbumConfig) -> T,
@@ -73,6 +124,7 @@ impl RbumConfigManager {
 
 pub trait RbumConfigApi {
     fn rbum_conf_set_cate_sys_code_node_len(&self) -> usize;
+    fn rbum_conf_scope_max_level(&self) -> i8;
     fn rbum_conf_mq_topic_entity_deleted(&self) -> String;
     fn rbum_conf_mq_topic_event(&self) -> String;
     fn rbum_conf_mq_header_name_operator(&self) -> String;
@@ -82,7 +134,17 @@ pub trait RbumConfigApi {
     fn rbum_conf_cache_key_cert_code_expire_sec(&self) -> usize;
     fn rbum_conf_cache_key_set_code_(&self) -> String;
     fn rbum_conf_cache_key_set_code_expire_sec(&self) -> usize;
+    fn rbum_conf_cache_key_rel_check_(&self) -> String;
+    fn rbum_conf_cache_key_rel_check_expire_sec(&self) -> usize;
+    fn rbum_conf_trusted_proxy_ips(&self) -> String;
     fn rbum_conf_match_event(&self, table_name: &str, operate: &str) -> bool;
+    fn rbum_conf_match_event_snapshot(&self, table_name: &str) -> bool;
+    fn rbum_conf_event_outbox_relay_interval_ms(&self) -> u64;
+    fn rbum_conf_event_outbox_relay_batch_size(&self) -> u64;
+    fn rbum_conf_event_outbox_relay_lease_ms(&self) -> u64;
+    fn rbum_conf_event_outbox_max_retry_times(&self) -> u32;
+    fn rbum_conf_event_outbox_retention_sec(&self) -> u64;
+    fn rbum_conf_ext_ddl_lock_wait_timeout_sec(&self) -> u32;
 }
 
 impl<'a> RbumConfigApi for TardisFunsInst<'a> {
@@ -90,6 +152,10 @@ impl<'a> RbumConfigApi for TardisFunsInst<'a> {
         RbumConfigManager::get_config(self.module_code(), |conf| conf.set_cate_sys_code_node_len)
     }
 
+    fn rbum_conf_scope_max_level(&self) -> i8 {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.scope_max_level)
+    }
+
     fn rbum_conf_mq_topic_entity_deleted(&self) -> String {
         RbumConfigManager::get_config(self.module_code(), |conf| conf.mq_topic_entity_deleted.to_string())
     }
@@ -126,7 +192,47 @@ impl<'a> RbumConfigApi for TardisFunsInst<'a> {
         RbumConfigManager::get_config(self.module_code(), |conf| conf.cache_key_set_code_expire_sec)
     }
 
+    fn rbum_conf_cache_key_rel_check_(&self) -> String {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.cache_key_rel_check_.to_string())
+    }
+
+    fn rbum_conf_cache_key_rel_check_expire_sec(&self) -> usize {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.cache_key_rel_check_expire_sec)
+    }
+
+    fn rbum_conf_trusted_proxy_ips(&self) -> String {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.trusted_proxy_ips.to_string())
+    }
+
     fn rbum_conf_match_event(&self, table_name: &str, operate: &str) -> bool {
         RbumConfigManager::match_event(self.module_code(), table_name, operate)
     }
+
+    fn rbum_conf_match_event_snapshot(&self, table_name: &str) -> bool {
+        RbumConfigManager::match_event_snapshot(self.module_code(), table_name)
+    }
+
+    fn rbum_conf_event_outbox_relay_interval_ms(&self) -> u64 {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.event_outbox_relay_interval_ms)
+    }
+
+    fn rbum_conf_event_outbox_relay_batch_size(&self) -> u64 {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.event_outbox_relay_batch_size)
+    }
+
+    fn rbum_conf_event_outbox_relay_lease_ms(&self) -> u64 {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.event_outbox_relay_lease_ms)
+    }
+
+    fn rbum_conf_event_outbox_max_retry_times(&self) -> u32 {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.event_outbox_max_retry_times)
+    }
+
+    fn rbum_conf_event_outbox_retention_sec(&self) -> u64 {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.event_outbox_retention_sec)
+    }
+
+    fn rbum_conf_ext_ddl_lock_wait_timeout_sec(&self) -> u32 {
+        RbumConfigManager::get_config(self.module_code(), |conf| conf.ext_ddl_lock_wait_timeout_sec)
+    }
 }
@@ -7,6 +7,7 @@ use tardis::basic::result::TardisResult;
 #[cfg(feature = "default")]
 use tardis::db::sea_orm::{DbErr, QueryResult, TryGetError, TryGetable};
 
+/// Scope level of a record, see ``rbum_scope_helper``, the deepest usable level is limited by ``RbumConfig::scope_max_level``
 #[derive(Display, Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Enum))]
 pub enum RbumScopeLevelKind {
@@ -15,9 +16,17 @@ pub enum RbumScopeLevelKind {
     L1,
     L2,
     L3,
+    L4,
+    L5,
+    L6,
+    L7,
+    L8,
+    L9,
 }
 
 impl RbumScopeLevelKind {
+    pub const MAX_LEVEL: i8 = 9;
+
     pub fn from_int(s: i8) -> TardisResult<RbumScopeLevelKind> {
         match s {
             -1 => Ok(RbumScopeLevelKind::Private),
@@ -25,6 +34,12 @@ impl RbumScopeLevelKind {
             1 => Ok(RbumScopeLevelKind::L1),
             2 => Ok(RbumScopeLevelKind::L2),
             3 => Ok(RbumScopeLevelKind::L3),
+            4 => Ok(RbumScopeLevelKind::L4),
+            5 => Ok(RbumScopeLevelKind::L5),
+            6 => Ok(RbumScopeLevelKind::L6),
+            7 => Ok(RbumScopeLevelKind::L7),
+            8 => Ok(RbumScopeLevelKind::L8),
+            9 => Ok(RbumScopeLevelKind::L9),
             _ => Err(TardisError::FormatError(format!("invalid RbumScopeLevelKind: {}", s))),
         }
     }
@@ -36,6 +51,12 @@ impl RbumScopeLevelKind {
             RbumScopeLevelKind::L1 => 1,
             RbumScopeLevelKind::L2 => 2,
             RbumScopeLevelKind::L3 => 3,
+            RbumScopeLevelKind::L4 => 4,
+            RbumScopeLevelKind::L5 => 5,
+            RbumScopeLevelKind::L6 => 6,
+            RbumScopeLevelKind::L7 => 7,
+            RbumScopeLevelKind::L8 => 8,
+            RbumScopeLevelKind::L9 => 9,
         }
     }
 }
@@ -156,8 +177,15 @@ impl TryGetable for RbumCertStatusKind {
 #[derive(Display, Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Enum))]
 pub enum RbumRelEnvKind {
+    /// ``value1`` and ``value2`` are the start and end timestamps in seconds
     DatetimeRange,
+    /// Daily time range, ``value1`` and ``value2`` are the start (inclusive) and end (exclusive) time in ``HH:MM:SS±HH:MM`` format,
+    /// e.g. ``22:00:00+08:00`` and ``06:00:00+08:00``. The range wraps midnight if the end is earlier than the start.
+    /// Only fixed UTC offsets are supported, daylight saving time is not taken into account.
     TimeRange,
+    /// Client addresses, ``value1`` is a comma separated list of IPv4/IPv6 addresses, CIDRs and ranges,
+    /// e.g. ``10.0.0.0/8,192.168.1.10-192.168.1.20,fd00::/8``, ``value2`` is not used.
+    /// It is checked against the request environment of [crate::rbum::dto::rbum_rel_dto::RbumRelCheckReq], requests without an address never match.
     Ips,
 }
 
@@ -188,6 +216,68 @@ impl TryGetable for RbumRelEnvKind {
     }
 }
 
+/// Operator of a relationship attribute condition, the attribute value of the check request is the left operand
+/// and the value of the condition is the right operand, both are compared by the data type of the kind attribute,
+/// see ``rbum_kind_attr_helper::check_condition``
+#[derive(Display, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Enum))]
+pub enum RbumRelAttrOpKind {
+    Eq,
+    Ne,
+    /// The value of the condition is a JSON array, e.g. ``["cn","sg"]``
+    In,
+    /// The value of the condition is a JSON array, e.g. ``["cn","sg"]``
+    NotIn,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// The value of the condition is a regular expression, which matches any part of the attribute value unless it is anchored
+    Regex,
+    Prefix,
+}
+
+impl RbumRelAttrOpKind {
+    pub fn from_int(s: u8) -> TardisResult<RbumRelAttrOpKind> {
+        match s {
+            0 => Ok(RbumRelAttrOpKind::Eq),
+            1 => Ok(RbumRelAttrOpKind::Ne),
+            2 => Ok(RbumRelAttrOpKind::In),
+            3 => Ok(RbumRelAttrOpKind::NotIn),
+            4 => Ok(RbumRelAttrOpKind::Gt),
+            5 => Ok(RbumRelAttrOpKind::Gte),
+            6 => Ok(RbumRelAttrOpKind::Lt),
+            7 => Ok(RbumRelAttrOpKind::Lte),
+            8 => Ok(RbumRelAttrOpKind::Regex),
+            9 => Ok(RbumRelAttrOpKind::Prefix),
+            _ => Err(TardisError::FormatError(format!("invalid RbumRelAttrOpKind: {}", s))),
+        }
+    }
+
+    pub fn to_int(&self) -> u8 {
+        match self {
+            RbumRelAttrOpKind::Eq => 0,
+            RbumRelAttrOpKind::Ne => 1,
+            RbumRelAttrOpKind::In => 2,
+            RbumRelAttrOpKind::NotIn => 3,
+            RbumRelAttrOpKind::Gt => 4,
+            RbumRelAttrOpKind::Gte => 5,
+            RbumRelAttrOpKind::Lt => 6,
+            RbumRelAttrOpKind::Lte => 7,
+            RbumRelAttrOpKind::Regex => 8,
+            RbumRelAttrOpKind::Prefix => 9,
+        }
+    }
+}
+
+#[cfg(feature = "default")]
+impl TryGetable for RbumRelAttrOpKind {
+    fn try_get(res: &QueryResult, pre: &str, col: &str) -> Result<Self, TryGetError> {
+        let s = u8::try_get(res, pre, col)?;
+        RbumRelAttrOpKind::from_int(s).map_err(|_| TryGetError::DbErr(DbErr::RecordNotFound(format!("{}:{}", pre, col))))
+    }
+}
+
 #[derive(Display, Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Enum, sea_orm::strum::EnumString))]
 pub enum RbumDataTypeKind {
@@ -245,3 +335,68 @@ pub enum RbumSetCateLevelQueryKind {
     CurrentAndParent,
     Parent,
 }
+
+#[derive(Display, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Enum))]
+pub enum RbumCursorSortKind {
+    CreateTime,
+    UpdateTime,
+    Sort,
+}
+
+impl RbumCursorSortKind {
+    pub fn to_field_name(&self) -> &'static str {
+        match self {
+            RbumCursorSortKind::CreateTime => "create_time",
+            RbumCursorSortKind::UpdateTime => "update_time",
+            RbumCursorSortKind::Sort => "sort",
+        }
+    }
+}
+
+#[derive(Display, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Enum))]
+pub enum RbumSortFieldKind {
+    Id,
+    Name,
+    Code,
+    Sort,
+    CreateTime,
+    UpdateTime,
+}
+
+impl RbumSortFieldKind {
+    pub fn to_field_name(&self) -> &'static str {
+        match self {
+            RbumSortFieldKind::Id => "id",
+            RbumSortFieldKind::Name => "name",
+            RbumSortFieldKind::Code => "code",
+            RbumSortFieldKind::Sort => "sort",
+            RbumSortFieldKind::CreateTime => "create_time",
+            RbumSortFieldKind::UpdateTime => "update_time",
+        }
+    }
+}
+
+#[derive(Display, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Enum))]
+pub enum RbumOperatorKind {
+    Eq,
+    In,
+    Like,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Display, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "default", derive(tardis::web::poem_openapi::Enum))]
+pub enum RbumScopeRuleKind {
+    OwnPaths,
+    SubOwnPaths,
+    IgnoreScope,
+    Private,
+    Root,
+    Level,
+}
@@ -1,43 +1,128 @@
 use serde::{Deserialize, Serialize};
 use tardis::basic::dto::TardisContext;
 use tardis::basic::result::TardisResult;
-use tardis::db::reldb_client::TardisActiveModel;
 use tardis::db::sea_orm::*;
 use tardis::db::sea_query::*;
+use tardis::log;
 use tardis::TardisFuns;
 use tardis::TardisFunsInst;
 
 use crate::rbum::domain::{
-    rbum_cert, rbum_cert_conf, rbum_domain, rbum_item, rbum_item_attr, rbum_kind, rbum_kind_attr, rbum_rel, rbum_rel_attr, rbum_rel_env, rbum_set, rbum_set_cate, rbum_set_item,
+    rbum_cert, rbum_cert_conf, rbum_domain, rbum_event_outbox, rbum_item, rbum_item_attr, rbum_kind, rbum_kind_attr, rbum_rel, rbum_rel_attr, rbum_rel_env, rbum_set,
+    rbum_set_cate, rbum_set_item,
 };
+use crate::rbum::helper::rbum_event_helper;
+use crate::rbum::helper::rbum_migration_helper::{self, RbumMigrationPendingResp, RbumMigrationStatement, RbumMigrationStep};
 use crate::rbum::rbum_config::{RbumConfig, RbumConfigManager};
+use crate::rbum::serv::rbum_crud_serv::VERSION_FIELD;
+
+pub const MIGRATION_MODULE: &str = "rbum";
 
 pub async fn init(code: &str, config: RbumConfig) -> TardisResult<()> {
+    let migrate_on_startup = config.migrate_on_startup;
     RbumConfigManager::add(code, config)?;
-    let db_kind = TardisFuns::reldb().backend();
-    let mut tx = TardisFuns::reldb().conn();
-    if TardisFuns::dict.get("__RBUM_INIT__", &tx).await?.is_some() {
-        return Ok(());
+    let pending_steps = migrate(!migrate_on_startup).await?;
+    if !migrate_on_startup && !pending_steps.is_empty() {
+        log::warn!("{} rbum migrations are pending, please apply them by the migrate command", pending_steps.len());
     }
-    tx.begin().await?;
-    TardisFuns::dict.add("__RBUM_INIT__", "", "", &tx).await?;
-    tx.create_table_and_index(&rbum_domain::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_kind::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_item::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_kind_attr::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_item_attr::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_rel::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_rel_attr::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_rel_env::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_cert_conf::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_cert::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_set::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_set_cate::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.create_table_and_index(&rbum_set_item::ActiveModel::create_table_and_index_statement(db_kind)).await?;
-    tx.commit().await?;
+    rbum_event_helper::start_relay(code);
     Ok(())
 }
 
+/// Apply the pending rbum migrations, see ``rbum_migration_helper::migrate``
+pub async fn migrate(dry_run: bool) -> TardisResult<Vec<RbumMigrationPendingResp>> {
+    rbum_migration_helper::migrate(MIGRATION_MODULE, &get_migration_steps(TardisFuns::reldb().backend()), dry_run).await
+}
+
+/// Migration steps of the rbum tables, append new steps instead of modifying the released ones
+pub fn get_migration_steps(db_kind: DbBackend) -> Vec<RbumMigrationStep> {
+    vec![
+        RbumMigrationStep::new(
+            1,
+            "create rbum tables",
+            vec![
+                RbumMigrationStatement::create_table::<rbum_domain::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_kind::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_item::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_kind_attr::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_item_attr::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_rel::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_rel_attr::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_rel_env::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_cert_conf::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_cert::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_set::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_set_cate::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_set_item::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<rbum_event_outbox::ActiveModel>(db_kind),
+            ],
+        ),
+        RbumMigrationStep::new(
+            2,
+            "add op to rbum_rel_attr",
+            vec![RbumMigrationStatement::add_column(
+                rbum_rel_attr::Entity,
+                rbum_rel_attr::Column::Op,
+                ColumnDef::new(rbum_rel_attr::Column::Op).not_null().tiny_unsigned().default(0).to_owned(),
+            )],
+        ),
+        RbumMigrationStep::new(
+            3,
+            "add version to rbum tables",
+            [
+                rbum_domain::Entity.table_name(),
+                rbum_kind::Entity.table_name(),
+                rbum_item::Entity.table_name(),
+                rbum_kind_attr::Entity.table_name(),
+                rbum_item_attr::Entity.table_name(),
+                rbum_rel::Entity.table_name(),
+                rbum_rel_attr::Entity.table_name(),
+                rbum_rel_env::Entity.table_name(),
+                rbum_cert_conf::Entity.table_name(),
+                rbum_cert::Entity.table_name(),
+                rbum_set::Entity.table_name(),
+                rbum_set_cate::Entity.table_name(),
+                rbum_set_item::Entity.table_name(),
+            ]
+            .into_iter()
+            .map(|table_name| {
+                RbumMigrationStatement::AddColumn(
+                    table_name.to_string(),
+                    VERSION_FIELD.to_string(),
+                    ColumnDef::new(VERSION_FIELD.clone()).not_null().big_integer().default(0).to_owned(),
+                )
+            })
+            .collect(),
+        ),
+        RbumMigrationStep::new(
+            4,
+            "order the outbox messages by record",
+            vec![
+                RbumMigrationStatement::add_column(
+                    rbum_event_outbox::Entity,
+                    rbum_event_outbox::Column::PublishedTime,
+                    ColumnDef::new(rbum_event_outbox::Column::PublishedTime).not_null().big_integer().default(0).to_owned(),
+                ),
+                RbumMigrationStatement::add_column(
+                    rbum_event_outbox::Entity,
+                    rbum_event_outbox::Column::RelayExpireTime,
+                    ColumnDef::new(rbum_event_outbox::Column::RelayExpireTime).not_null().big_integer().default(0).to_owned(),
+                ),
+                // Rejects the messages of a record written by concurrent transactions with the same sequence number
+                RbumMigrationStatement::CreateIndex(
+                    Index::create()
+                        .name(&format!("idx-{}-record_seq", rbum_event_outbox::Entity.table_name()))
+                        .table(rbum_event_outbox::Entity)
+                        .col(rbum_event_outbox::Column::RecordId)
+                        .col(rbum_event_outbox::Column::Seq)
+                        .unique()
+                        .to_owned(),
+                ),
+            ],
+        ),
+    ]
+}
+
 pub async fn get_first_account_context<'a>(rbum_kind_code: &str, rbum_domain_code: &str, funs: &TardisFunsInst<'a>) -> TardisResult<Option<TardisContext>> {
     #[derive(Deserialize, FromQueryResult, Serialize, Clone, Debug)]
     struct TmpContext {
@@ -78,23 +163,29 @@ pub async fn get_first_account_context<'a>(rbum_kind_code: &str, rbum_domain_cod
 }
 
 pub async fn truncate_data<'a>(funs: &TardisFunsInst<'a>) -> TardisResult<()> {
-    funs.db()
-        .execute(
-            Table::truncate()
-                .table(rbum_cert::Entity)
-                .table(rbum_cert_conf::Entity)
-                .table(rbum_domain::Entity)
-                .table(rbum_item::Entity)
-                .table(rbum_item_attr::Entity)
-                .table(rbum_kind::Entity)
-                .table(rbum_kind_attr::Entity)
-                .table(rbum_rel::Entity)
-                .table(rbum_rel_attr::Entity)
-                .table(rbum_rel_env::Entity)
-                .table(rbum_set::Entity)
-                .table(rbum_set_cate::Entity)
-                .table(rbum_set_item::Entity),
-        )
-        .await?;
+    let db_kind = funs.db().raw_conn().get_database_backend();
+    for table_name in [
+        rbum_cert::Entity.table_name(),
+        rbum_cert_conf::Entity.table_name(),
+        rbum_domain::Entity.table_name(),
+        rbum_event_outbox::Entity.table_name(),
+        rbum_item::Entity.table_name(),
+        rbum_item_attr::Entity.table_name(),
+        rbum_kind::Entity.table_name(),
+        rbum_kind_attr::Entity.table_name(),
+        rbum_rel::Entity.table_name(),
+        rbum_rel_attr::Entity.table_name(),
+        rbum_rel_env::Entity.table_name(),
+        rbum_set::Entity.table_name(),
+        rbum_set_cate::Entity.table_name(),
+        rbum_set_item::Entity.table_name(),
+    ] {
+        // SQLite does not support TRUNCATE
+        if db_kind == DatabaseBackend::Sqlite {
+            funs.db().execute(&Query::delete().from_table(Alias::new(table_name)).to_owned()).await?;
+        } else {
+            funs.db().execute(&Table::truncate().table(Alias::new(table_name)).to_owned()).await?;
+        }
+    }
     Ok(())
 }
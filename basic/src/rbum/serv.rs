@@ -1,3 +1,4 @@
+pub mod rbum_archive_serv;
 pub mod rbum_cert_serv;
 pub mod rbum_crud_serv;
 pub mod rbum_domain_serv;
@@ -0,0 +1,379 @@
+use std::collections::{HashMap, HashSet};
+
+use tardis::basic::dto::TardisContext;
+use tardis::basic::result::TardisResult;
+use tardis::chrono::Utc;
+use tardis::db::sea_orm::*;
+use tardis::db::sea_query::{Alias, Cond, Expr, Order, Query};
+use tardis::serde_json::Value as JsonValue;
+use tardis::{TardisFuns, TardisFunsInst};
+
+use crate::rbum::domain::{
+    rbum_cert, rbum_cert_conf, rbum_domain, rbum_item, rbum_item_attr, rbum_kind, rbum_kind_attr, rbum_rel, rbum_rel_attr, rbum_rel_env, rbum_set, rbum_set_cate, rbum_set_item,
+};
+use crate::rbum::dto::rbum_archive_dto::{RbumArchive, RbumArchiveConflictResp, RbumArchiveImportResp, RbumArchiveImportTableResp, RbumArchiveManifest, RbumArchiveManifestTable};
+use crate::rbum::helper::rbum_archive_helper::{self, RbumArchiveReader, RbumArchiveWriter};
+use crate::rbum::helper::{rbum_dialect_helper, rbum_event_helper, rbum_scope_helper};
+use crate::rbum::serv::rbum_crud_serv::{package_insert_from_json, to_db_value, ID_FIELD};
+
+/// Export all records under an own paths to a portable archive and import them to another own paths
+///
+/// The archive contains the domains, kinds, kind attributes, items with their ext records and attributes,
+/// sets with their categories and items, relationships with their attributes and environments, certificate configurations
+/// and optionally certificates. It is a JSON archive (``RbumArchive``) or a bundle (ZIP), see ``rbum_archive_helper``.
+///
+/// When importing, every record gets a new id and the own paths are moved to the target own paths,
+/// the reference columns (see ``REF_COLUMNS``) to the records of the archive are replaced with the new ids, references to other records are kept.
+/// Ids embedded in other columns (e.g. ``ext``) are not replaced.
+pub struct RbumArchiveServ;
+
+/// Unique keys of the tables, records that violate them are reported as conflicts
+const UNIQUE_KEYS: [(&str, &[&str]); 7] = [
+    ("rbum_domain", &["code"]),
+    ("rbum_kind", &["code"]),
+    ("rbum_set", &["code"]),
+    ("rbum_item", &["code", "rel_rbum_kind_id", "rel_rbum_domain_id"]),
+    ("rbum_set_cate", &["rel_rbum_set_id", "sys_code"]),
+    ("rbum_cert_conf", &["code", "rel_rbum_domain_id", "rel_rbum_item_id"]),
+    ("rbum_cert", &["own_paths", "rel_rbum_cert_conf_id", "ak"]),
+];
+
+/// Columns of all tables (including the ext tables) that reference the records
+const COMMON_REF_COLUMNS: [&str; 2] = ["id", "owner"];
+
+/// Columns of the tables that reference the records besides ``COMMON_REF_COLUMNS``
+const REF_COLUMNS: [(&str, &[&str]); 10] = [
+    ("rbum_kind_attr", &["rel_rbum_kind_id"]),
+    ("rbum_item", &["rel_rbum_kind_id", "rel_rbum_domain_id"]),
+    ("rbum_item_attr", &["rel_rbum_item_id", "rel_rbum_kind_attr_id"]),
+    ("rbum_set_cate", &["rel_rbum_set_id"]),
+    ("rbum_set_item", &["rel_rbum_set_id", "rel_rbum_item_id"]),
+    ("rbum_rel", &["from_rbum_id", "to_rbum_item_id"]),
+    ("rbum_rel_attr", &["rel_rbum_rel_id", "rel_rbum_kind_attr_id"]),
+    ("rbum_rel_env", &["rel_rbum_rel_id"]),
+    ("rbum_cert_conf", &["rel_rbum_domain_id", "rel_rbum_item_id"]),
+    ("rbum_cert", &["rel_rbum_cert_conf_id", "rel_rbum_id"]),
+];
+
+const OWN_PATHS_FIELD_NAME: &str = "own_paths";
+
+const OWN_PATHS_FIELDS: [&str; 2] = [OWN_PATHS_FIELD_NAME, "to_own_paths"];
+
+type JsonMap = tardis::serde_json::Map<String, JsonValue>;
+
+impl<'a> RbumArchiveServ {
+    pub const VERSION: u32 = 1;
+
+    /// Export the records whose own paths are ``own_paths`` or its sub paths to a JSON archive
+    pub async fn export(own_paths: &str, with_certs: bool, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumArchive> {
+        let mut archive = RbumArchive {
+            version: Self::VERSION,
+            own_paths: own_paths.to_string(),
+            export_time: Utc::now(),
+            tables: vec![],
+        };
+        Self::export_to(own_paths, with_certs, &mut archive, funs, ctx).await?;
+        Ok(archive)
+    }
+
+    /// Export the records whose own paths are ``own_paths`` or its sub paths to the writer chunk by chunk
+    pub async fn export_to<W: RbumArchiveWriter + Send>(
+        own_paths: &str,
+        with_certs: bool,
+        writer: &mut W,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumArchiveManifest> {
+        if !rbum_scope_helper::is_sub_own_paths(own_paths, &ctx.own_paths) {
+            return Err(funs.err().bad_request("archive", "export", &format!("own paths {} is not under {}", own_paths, ctx.own_paths)));
+        }
+        let mut table_names = vec![
+            rbum_domain::Entity.table_name().to_string(),
+            rbum_kind::Entity.table_name().to_string(),
+            rbum_kind_attr::Entity.table_name().to_string(),
+            rbum_item::Entity.table_name().to_string(),
+        ];
+        // The ext records have the same ids as the items
+        let ext_table_names = Self::find_ext_table_names(own_paths, funs).await?;
+        table_names.extend(ext_table_names.iter().cloned());
+        table_names.extend([
+            rbum_item_attr::Entity.table_name().to_string(),
+            rbum_set::Entity.table_name().to_string(),
+            rbum_set_cate::Entity.table_name().to_string(),
+            rbum_set_item::Entity.table_name().to_string(),
+            rbum_rel::Entity.table_name().to_string(),
+            rbum_rel_attr::Entity.table_name().to_string(),
+            rbum_rel_env::Entity.table_name().to_string(),
+            rbum_cert_conf::Entity.table_name().to_string(),
+        ]);
+        if with_certs {
+            table_names.push(rbum_cert::Entity.table_name().to_string());
+        }
+        let mut manifest = RbumArchiveManifest {
+            version: Self::VERSION,
+            own_paths: own_paths.to_string(),
+            export_time: Utc::now(),
+            tables: Vec::with_capacity(table_names.len()),
+        };
+        for table_name in table_names {
+            let mut count = 0;
+            let mut last_id = None;
+            for chunk in 0.. {
+                let mut query = Query::select();
+                query.expr(Expr::cust("*")).from(Alias::new(&table_name));
+                if ext_table_names.contains(&table_name) {
+                    query.and_where(
+                        Expr::col(ID_FIELD.clone())
+                            .in_subquery(Query::select().column(rbum_item::Column::Id).from(rbum_item::Entity).cond_where(Self::package_item_cond(own_paths)).to_owned()),
+                    );
+                } else if table_name == rbum_item::Entity.table_name() {
+                    query.cond_where(Self::package_item_cond(own_paths));
+                } else {
+                    query.cond_where(rbum_dialect_helper::sub_own_paths_cond(&table_name, OWN_PATHS_FIELD_NAME, own_paths));
+                }
+                // Paginate by the id rather than the offset, the later pages are as fast as the first
+                if let Some(last_id) = &last_id {
+                    query.and_where(Expr::col(ID_FIELD.clone()).gt(last_id.as_str()));
+                }
+                query.order_by(ID_FIELD.clone(), Order::Asc).limit(rbum_archive_helper::CHUNK_SIZE as u64);
+                let records = funs.db().find_dtos::<JsonValue>(&query).await?;
+                let is_last_chunk = records.len() < rbum_archive_helper::CHUNK_SIZE;
+                count += records.len() as u64;
+                last_id = records.last().and_then(|record| record.get("id")).and_then(|id| id.as_str()).map(|id| id.to_string());
+                // Every table has at least one chunk
+                if chunk == 0 || !records.is_empty() {
+                    writer.write_chunk(&table_name, chunk, records)?;
+                }
+                if is_last_chunk {
+                    break;
+                }
+            }
+            manifest.tables.push(RbumArchiveManifestTable { table_name, count });
+        }
+        writer.finish(&manifest)?;
+        Ok(manifest)
+    }
+
+    /// Import the records of the archive to ``target_own_paths``, see ``import_with_mapper``
+    pub async fn import<R: RbumArchiveReader + Send>(
+        reader: &mut R,
+        target_own_paths: &str,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumArchiveImportResp> {
+        Self::import_with_mapper(reader, target_own_paths, &|_, _| {}, funs, ctx).await
+    }
+
+    /// Import the records of the archive to ``target_own_paths``
+    ///
+    /// The archive is read chunk by chunk three times: to generate the new ids, to check the records against the unique keys
+    /// and to import them, so only the ids are held in memory. Nothing is imported if there are conflicts.
+    ///
+    /// ``record_mapper`` changes the original records (with the table name) before importing, e.g. to replace the codes that contain the own paths.
+    pub async fn import_with_mapper<R: RbumArchiveReader + Send>(
+        reader: &mut R,
+        target_own_paths: &str,
+        record_mapper: &(dyn Fn(&str, &mut JsonMap) + Send + Sync),
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumArchiveImportResp> {
+        let manifest = reader.manifest()?;
+        if manifest.version != Self::VERSION {
+            return Err(funs.err().bad_request("archive", "import", &format!("archive version {} is not supported", manifest.version)));
+        }
+        if !rbum_scope_helper::is_sub_own_paths(target_own_paths, &ctx.own_paths) {
+            return Err(funs.err().bad_request("archive", "import", &format!("target own paths {} is not under {}", target_own_paths, ctx.own_paths)));
+        }
+        let source_owner_id = Self::get_owner_id(&manifest.own_paths);
+        let target_owner_id = Self::get_owner_id(target_own_paths);
+        let mut id_mapping = HashMap::new();
+        if let (Some(source_owner_id), Some(target_owner_id)) = (source_owner_id, target_owner_id) {
+            id_mapping.insert(source_owner_id.to_string(), target_owner_id.to_string());
+        }
+        for table in &manifest.tables {
+            let mut chunk = 0;
+            while let Some(records) = reader.read_chunk(&table.table_name, chunk)? {
+                for record in records {
+                    let id = record
+                        .get("id")
+                        .and_then(|id| id.as_str())
+                        .ok_or_else(|| funs.err().bad_request("archive", "import", &format!("record of {} has no id", table.table_name)))?;
+                    id_mapping.entry(id.to_string()).or_insert_with(|| TardisFuns::field.nanoid_len(id.len()));
+                }
+                chunk += 1;
+            }
+        }
+
+        let mut resp = RbumArchiveImportResp::default();
+        // The owner is only imported when it does not exist, e.g. when importing to a new tenant
+        let mut owner_table_names = HashSet::new();
+        if let (Some(_), Some(target_owner_id)) = (source_owner_id, target_owner_id) {
+            for table in &manifest.tables {
+                if !Self::exist_record(&table.table_name, target_owner_id, funs).await? {
+                    owner_table_names.insert(table.table_name.as_str());
+                }
+            }
+        }
+        let package_record = |table_name: &str, mut record: JsonValue| -> TardisResult<Option<(String, JsonMap)>> {
+            let record = record.as_object_mut().ok_or_else(|| funs.err().bad_request("archive", "import", &format!("record of {} is illegal", table_name)))?;
+            let original_id = record.get("id").and_then(|id| id.as_str()).unwrap_or_default().to_string();
+            record_mapper(table_name, record);
+            let mut fields = Self::remap_record(table_name, record, &manifest.own_paths, target_own_paths, &id_mapping);
+            if source_owner_id == Some(original_id.as_str()) {
+                if !owner_table_names.contains(table_name) {
+                    return Ok(None);
+                }
+                if let Some(own_paths) = fields.get_mut(OWN_PATHS_FIELD_NAME) {
+                    *own_paths = JsonValue::String(Self::get_parent_own_paths(target_own_paths).to_string());
+                }
+            }
+            Ok(Some((original_id, fields)))
+        };
+        for table in &manifest.tables {
+            let mut chunk = 0;
+            while let Some(records) = reader.read_chunk(&table.table_name, chunk)? {
+                for record in records {
+                    if let Some((original_id, fields)) = package_record(&table.table_name, record)? {
+                        if let Some(message) = Self::check_unique_keys(&table.table_name, &fields, funs).await? {
+                            resp.conflicts.push(RbumArchiveConflictResp {
+                                table_name: table.table_name.to_string(),
+                                id: original_id,
+                                message,
+                            });
+                        }
+                    }
+                }
+                chunk += 1;
+            }
+        }
+        if !resp.conflicts.is_empty() {
+            return Ok(resp);
+        }
+
+        for table in &manifest.tables {
+            let mut count = 0;
+            let mut chunk = 0;
+            while let Some(records) = reader.read_chunk(&table.table_name, chunk)? {
+                let mut ids = Vec::with_capacity(records.len());
+                for record in records {
+                    if let Some((_, fields)) = package_record(&table.table_name, record)? {
+                        funs.db().execute(&package_insert_from_json(&table.table_name, &fields)).await?;
+                        ids.push(fields.get("id").and_then(|id| id.as_str()).unwrap_or_default().to_string());
+                    }
+                }
+                rbum_event_helper::try_notifies(&table.table_name, "c", &ids, funs, ctx).await?;
+                count += ids.len() as u64;
+                chunk += 1;
+            }
+            resp.imported.push(RbumArchiveImportTableResp {
+                table_name: table.table_name.to_string(),
+                count,
+            });
+        }
+        Ok(resp)
+    }
+
+    async fn find_ext_table_names(own_paths: &str, funs: &TardisFunsInst<'a>) -> TardisResult<Vec<String>> {
+        #[derive(Debug, FromQueryResult)]
+        struct ExtTableNameResp {
+            pub ext_table_name: String,
+        }
+
+        let mut query = Query::select();
+        query
+            .distinct()
+            .column((rbum_kind::Entity, rbum_kind::Column::ExtTableName))
+            .from(rbum_item::Entity)
+            .inner_join(
+                rbum_kind::Entity,
+                Expr::tbl(rbum_kind::Entity, rbum_kind::Column::Id).equals(rbum_item::Entity, rbum_item::Column::RelRbumKindId),
+            )
+            .cond_where(Self::package_item_cond(own_paths))
+            .and_where(Expr::tbl(rbum_kind::Entity, rbum_kind::Column::ExtTableName).ne(""))
+            .order_by((rbum_kind::Entity, rbum_kind::Column::ExtTableName), Order::Asc);
+        Ok(funs.db().find_dtos::<ExtTableNameResp>(&query).await?.into_iter().map(|resp| resp.ext_table_name).collect())
+    }
+
+    /// The items under the own paths and the item that owns the own paths (the last path item, e.g. the tenant)
+    fn package_item_cond(own_paths: &str) -> Cond {
+        let mut cond = Cond::any().add(rbum_dialect_helper::sub_own_paths_cond(rbum_item::Entity.table_name(), OWN_PATHS_FIELD_NAME, own_paths));
+        if let Some(owner_id) = Self::get_owner_id(own_paths) {
+            cond = cond.add(Expr::tbl(rbum_item::Entity, rbum_item::Column::Id).eq(owner_id));
+        }
+        cond
+    }
+
+    fn get_owner_id(own_paths: &str) -> Option<&str> {
+        own_paths.rsplit('/').next().filter(|owner_id| !owner_id.is_empty())
+    }
+
+    fn get_parent_own_paths(own_paths: &str) -> &str {
+        own_paths.rsplit_once('/').map(|(parent_own_paths, _)| parent_own_paths).unwrap_or("")
+    }
+
+    /// Move the own paths and replace the reference columns with the new ids
+    fn remap_record(table_name: &str, record: &JsonMap, source_own_paths: &str, target_own_paths: &str, id_mapping: &HashMap<String, String>) -> JsonMap {
+        let ref_columns = REF_COLUMNS.iter().find(|(ref_table_name, _)| *ref_table_name == table_name).map(|(_, ref_columns)| *ref_columns).unwrap_or_default();
+        record
+            .iter()
+            .map(|(field, value)| {
+                let field = field.as_str();
+                let value = match value {
+                    JsonValue::String(value) if OWN_PATHS_FIELDS.contains(&field) => {
+                        JsonValue::String(Self::remap_own_paths(value, source_own_paths, target_own_paths, id_mapping))
+                    }
+                    JsonValue::String(value) if COMMON_REF_COLUMNS.contains(&field) || ref_columns.contains(&field) => {
+                        JsonValue::String(id_mapping.get(value).cloned().unwrap_or_else(|| value.to_string()))
+                    }
+                    _ => value.clone(),
+                };
+                (field.to_string(), value)
+            })
+            .collect()
+    }
+
+    /// Move the own paths from ``source_own_paths`` to ``target_own_paths``, the ids in the sub paths are replaced too
+    fn remap_own_paths(own_paths: &str, source_own_paths: &str, target_own_paths: &str, id_mapping: &HashMap<String, String>) -> String {
+        if own_paths == source_own_paths {
+            return target_own_paths.to_string();
+        }
+        let sub_paths = if source_own_paths.is_empty() {
+            own_paths
+        } else if let Some(sub_paths) = own_paths.strip_prefix(&format!("{}/", source_own_paths)) {
+            sub_paths
+        } else {
+            return own_paths.to_string();
+        };
+        let sub_paths = sub_paths.split('/').map(|path_item| id_mapping.get(path_item).map(|id| id.as_str()).unwrap_or(path_item)).collect::<Vec<&str>>().join("/");
+        if target_own_paths.is_empty() {
+            sub_paths
+        } else {
+            format!("{}/{}", target_own_paths, sub_paths)
+        }
+    }
+
+    async fn exist_record(table_name: &str, id: &str, funs: &TardisFunsInst<'a>) -> TardisResult<bool> {
+        Ok(funs.db().count(Query::select().column(ID_FIELD.clone()).from(Alias::new(table_name)).and_where(Expr::col(ID_FIELD.clone()).eq(id))).await? > 0)
+    }
+
+    async fn check_unique_keys(table_name: &str, fields: &JsonMap, funs: &TardisFunsInst<'a>) -> TardisResult<Option<String>> {
+        let unique_keys = if let Some((_, unique_keys)) = UNIQUE_KEYS.iter().find(|(unique_table_name, _)| *unique_table_name == table_name) {
+            unique_keys
+        } else {
+            return Ok(None);
+        };
+        let mut query = Query::select();
+        query.column(ID_FIELD.clone()).from(Alias::new(table_name));
+        for unique_key in unique_keys.iter() {
+            match fields.get(*unique_key).and_then(to_db_value) {
+                Some(value) => query.and_where(Expr::col(Alias::new(unique_key)).eq(value)),
+                None => return Ok(None),
+            };
+        }
+        if funs.db().count(&query).await? > 0 {
+            let values = unique_keys.iter().map(|unique_key| fields.get(*unique_key).map(|value| value.to_string()).unwrap_or_default()).collect::<Vec<String>>();
+            return Ok(Some(format!("{} ({}) already exists", unique_keys.join(","), values.join(","))));
+        }
+        Ok(None)
+    }
+}
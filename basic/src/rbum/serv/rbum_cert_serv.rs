@@ -669,6 +669,53 @@ impl<'a> RbumCertServ {
         Ok(())
     }
 
+    /// Bind the certificate to another certificate configuration, e.g. when the owner of the certificate is moved to another tenant.
+    ///
+    /// The sk is kept, so both configurations must encrypt the sk in the same way.
+    pub async fn rebind_cert_conf(id: &str, rel_rbum_cert_conf_id: &str, filter: &RbumCertFilterReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        let rbum_cert = Self::peek_rbum(id, filter, funs, ctx).await?;
+        if rbum_cert.rel_rbum_cert_conf_id.as_deref() == Some(rel_rbum_cert_conf_id) {
+            return Ok(());
+        }
+        let conf_filter = RbumCertConfFilterReq {
+            basic: filter.basic.clone(),
+            ..Default::default()
+        };
+        let rbum_cert_conf = RbumCertConfServ::peek_rbum(rel_rbum_cert_conf_id, &conf_filter, funs, ctx).await?;
+        if let Some(original_rel_rbum_cert_conf_id) = &rbum_cert.rel_rbum_cert_conf_id {
+            let original_rbum_cert_conf = RbumCertConfServ::peek_rbum(original_rel_rbum_cert_conf_id, &conf_filter, funs, ctx).await?;
+            if original_rbum_cert_conf.sk_encrypted != rbum_cert_conf.sk_encrypted {
+                return Err(funs.err().conflict(&Self::get_obj_name(), "rebind", "the sk encryption of the cert conf is inconsistent"));
+            }
+        }
+        if funs
+            .db()
+            .count(
+                Query::select()
+                    .column(rbum_cert::Column::Id)
+                    .from(rbum_cert::Entity)
+                    .and_where(Expr::col(rbum_cert::Column::RelRbumCertConfId).eq(rel_rbum_cert_conf_id))
+                    .and_where(Expr::col(rbum_cert::Column::Ak).eq(rbum_cert.ak.as_str()))
+                    .and_where(Expr::col(rbum_cert::Column::Id).ne(id)),
+            )
+            .await?
+            > 0
+        {
+            return Err(funs.err().conflict(&Self::get_obj_name(), "rebind", "ak is used"));
+        }
+        funs.db()
+            .update_one(
+                rbum_cert::ActiveModel {
+                    id: Set(id.to_string()),
+                    rel_rbum_cert_conf_id: Set(rel_rbum_cert_conf_id.to_string()),
+                    ..Default::default()
+                },
+                ctx,
+            )
+            .await?;
+        Ok(())
+    }
+
     async fn check_cert_conf_constraint_by_add(
         add_req: &RbumCertAddReq,
         rbum_cert_conf: &RbumCertConfSummaryResp,
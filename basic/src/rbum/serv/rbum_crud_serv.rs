@@ -1,20 +1,35 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::str::FromStr;
+
 use async_trait::async_trait;
 use lazy_static::lazy_static;
 use serde::Serialize;
 use tardis::basic::dto::TardisContext;
+use tardis::basic::error::TardisError;
 use tardis::basic::result::TardisResult;
+use tardis::chrono::{DateTime, Utc};
+use tardis::db::domain::tardis_db_del_record;
+use tardis::db::reldb_client::DeleteEntity;
 use tardis::db::reldb_client::{IdResp, TardisActiveModel};
 use tardis::db::sea_orm::*;
-use tardis::db::sea_query::{Alias, Cond, Expr, Func, IntoValueTuple, JoinType, Order, Query, SelectStatement, Value, ValueTuple};
+use tardis::db::sea_query::{Alias, Cond, Expr, InsertStatement, IntoValueTuple, JoinType, Order, Query, SelectStatement, Value, ValueTuple};
 use tardis::regex::Regex;
+use tardis::serde_json::Value as JsonValue;
 use tardis::web::poem_openapi::types::{ParseFromJSON, ToJSON};
 use tardis::web::web_resp::TardisPage;
-use tardis::TardisFunsInst;
-
-use crate::rbum::domain::rbum_item;
-use crate::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
-use crate::rbum::helper::{rbum_event_helper, rbum_scope_helper};
+use tardis::{TardisFuns, TardisFunsInst};
+
+use crate::rbum::domain::{rbum_domain, rbum_item, rbum_kind, rbum_kind_attr, rbum_set, rbum_set_cate};
+use crate::rbum::dto::rbum_batch_dto::RbumBatchResp;
+use crate::rbum::dto::rbum_cursor_dto::{RbumCursorPage, RbumCursorReq};
+use crate::rbum::dto::rbum_filer_dto::{RbumBasicFilterFetcher, RbumBasicFilterReq};
+use crate::rbum::dto::rbum_recycle_dto::RbumDeletedResp;
+use crate::rbum::dto::rbum_scope_dto::RbumScopeExplainResp;
+use crate::rbum::helper::rbum_scope_helper::RbumScopePathCond;
+use crate::rbum::helper::{rbum_cursor_helper, rbum_dialect_helper, rbum_event_helper, rbum_scope_helper};
 use crate::rbum::rbum_config::RbumConfigApi;
+use crate::rbum::rbum_enumeration::RbumScopeLevelKind;
 
 lazy_static! {
     pub static ref OWNER_TABLE: Alias = Alias::new("t_owner");
@@ -23,6 +38,7 @@ lazy_static! {
     pub static ref OWN_PATHS_FIELD: Alias = Alias::new("own_paths");
     pub static ref CREATE_TIME_FIELD: Alias = Alias::new("create_time");
     pub static ref UPDATE_TIME_FIELD: Alias = Alias::new("update_time");
+    pub static ref VERSION_FIELD: Alias = Alias::new("version");
     pub static ref CODE_FIELD: Alias = Alias::new("code");
     pub static ref NAME_FIELD: Alias = Alias::new("name");
     pub static ref SORT_FIELD: Alias = Alias::new("sort");
@@ -33,6 +49,11 @@ lazy_static! {
     pub static ref R_URL_PART_CODE: Regex = Regex::new(r"^[a-z0-9-.]+$").expect("Regular parsing error");
 }
 
+tardis::tokio::task_local! {
+    // The records (``table_name:id``) whose ownership has been checked in batch by the current task, see ``with_checked_ownerships``
+    static CHECKED_OWNERSHIPS: HashSet<String>;
+}
+
 #[async_trait]
 pub trait RbumCrudOperation<'a, E, AddReq, ModifyReq, SummaryResp, DetailResp, FilterReq>
 where
@@ -41,7 +62,7 @@ where
     ModifyReq: Sync + Send,
     SummaryResp: FromQueryResult + ParseFromJSON + ToJSON + Serialize + Send + Sync,
     DetailResp: FromQueryResult + ParseFromJSON + ToJSON + Serialize + Send + Sync,
-    FilterReq: Sync + Send,
+    FilterReq: RbumBasicFilterFetcher + Sync + Send,
 {
     fn get_table_name() -> &'static str;
 
@@ -60,6 +81,9 @@ where
     }
 
     async fn check_ownership_with_table_name(id: &str, table_name: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        if is_ownership_checked(table_name, id) {
+            return Ok(());
+        }
         if funs.db().count(&Self::package_ownership_query_with_table_name(id, table_name, ctx)).await? == 0 {
             return Err(funs.err().not_found(
                 &Self::get_obj_name_from(table_name),
@@ -94,7 +118,7 @@ where
                     .column((Alias::new(table_name), ID_FIELD.clone()))
                     .from(Alias::new(table_name))
                     .and_where(Expr::tbl(Alias::new(table_name), ID_FIELD.clone()).eq(id))
-                    .with_scope(table_name, &ctx.own_paths, false, ctx),
+                    .with_scope(table_name, &ctx.own_paths, false),
             )
             .await?
             == 0
@@ -108,6 +132,47 @@ where
         Ok(())
     }
 
+    /// Explain why the record is visible or not to the context with the filter, see ``rbum_scope_helper::explain_scope``.
+    ///
+    /// The table must be one of the rbum tables with the ``own_paths`` and ``scope_level`` columns, other tables are rejected.
+    async fn explain_scope(id: &str, table_name: &str, filter: &RbumBasicFilterReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumScopeExplainResp> {
+        let scoped_table_names = [
+            rbum_domain::Entity.table_name(),
+            rbum_kind::Entity.table_name(),
+            rbum_kind_attr::Entity.table_name(),
+            rbum_item::Entity.table_name(),
+            rbum_set::Entity.table_name(),
+            rbum_set_cate::Entity.table_name(),
+        ];
+        if !scoped_table_names.contains(&table_name) {
+            return Err(funs.err().bad_request(&Self::get_obj_name(), "explain_scope", &format!("table {} is not a scoped rbum table", table_name)));
+        }
+        let record = funs
+            .db()
+            .get_dto::<ScopeResp>(
+                Query::select()
+                    .column((Alias::new(table_name), OWN_PATHS_FIELD.clone()))
+                    .column((Alias::new(table_name), SCOPE_LEVEL_FIELD.clone()))
+                    .from(Alias::new(table_name))
+                    .and_where(Expr::tbl(Alias::new(table_name), ID_FIELD.clone()).eq(id)),
+            )
+            .await?
+            .ok_or_else(|| funs.err().not_found(&Self::get_obj_name_from(table_name), "explain_scope", &format!("not found {}.{}", table_name, id)))?;
+        Ok(rbum_scope_helper::explain_scope(&record.own_paths, Some(record.scope_level), filter, ctx))
+    }
+
+    /// Check the scope level does not exceed ``RbumConfig::scope_max_level`` and convert it to the stored value
+    fn check_scope_level(scope_level: &RbumScopeLevelKind, funs: &TardisFunsInst<'a>) -> TardisResult<i8> {
+        if scope_level.to_int() > funs.rbum_conf_scope_max_level() {
+            return Err(funs.err().bad_request(
+                &Self::get_obj_name(),
+                "check",
+                &format!("scope level {} exceeds the max level {}", scope_level, funs.rbum_conf_scope_max_level()),
+            ));
+        }
+        Ok(scope_level.to_int())
+    }
+
     // ----------------------------- Exist -------------------------------
 
     async fn check_exist_before_delete(id: &str, rel_table_name: &str, rel_field_name: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
@@ -202,13 +267,85 @@ where
 
     async fn modify_rbum(id: &str, modify_req: &mut ModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         Self::before_modify_rbum(id, modify_req, funs, ctx).await?;
+        let before = rbum_event_helper::try_get_snapshot(Self::get_table_name(), "u", id, funs).await?;
         let domain = Self::package_modify(id, modify_req, funs, ctx).await?;
         funs.db().update_one(domain, ctx).await?;
+        Self::touch_rbums(&[id.to_string()], funs).await?;
         Self::after_modify_rbum(id, modify_req, funs, ctx).await?;
-        rbum_event_helper::try_notify(Self::get_table_name(), "u", id, funs, ctx).await?;
+        let after = rbum_event_helper::try_get_snapshot(Self::get_table_name(), "u", id, funs).await?;
+        rbum_event_helper::try_notify_with_snapshot(Self::get_table_name(), "u", id, before, after, funs, ctx).await?;
+        Ok(())
+    }
+
+    /// Modify only if the record has not been modified since `expected_version`
+    ///
+    /// Used for optimistic concurrency control, a stale write is rejected with a conflict error, see ``rbum_etag_helper``.
+    async fn modify_rbum_if_match(id: &str, expected_version: Option<i64>, modify_req: &mut ModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        if let Some(expected_version) = expected_version {
+            Self::check_rbum_version(id, expected_version, funs, ctx).await?;
+        }
+        Self::modify_rbum(id, modify_req, funs, ctx).await
+    }
+
+    /// Increase the versions and refresh the update times of the records
+    ///
+    /// Called by the modifications, and by the changes of the dependent records (e.g. the attributes and relationships of an item)
+    /// so that the version (ETag) of the whole resource is changed.
+    async fn touch_rbums(ids: &[String], funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut update_statement = Query::update();
+        update_statement
+            .table(Alias::new(Self::get_table_name()))
+            .value_expr(VERSION_FIELD.clone(), Expr::col(VERSION_FIELD.clone()).add(1))
+            .value_expr(UPDATE_TIME_FIELD.clone(), Expr::cust("CURRENT_TIMESTAMP"))
+            .and_where(Expr::col(ID_FIELD.clone()).is_in(ids.iter().map(|id| id.as_str()).collect::<Vec<&str>>()));
+        funs.db().execute(&update_statement).await?;
         Ok(())
     }
 
+    async fn get_rbum_version(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<i64> {
+        Self::do_get_rbum_version(id, false, funs, ctx).await
+    }
+
+    /// Check the version of the record, the record is locked until the end of the transaction
+    async fn check_rbum_version(id: &str, expected_version: i64, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        if Self::do_get_rbum_version(id, true, funs, ctx).await? != expected_version {
+            return Err(funs.err().conflict(
+                &Self::get_obj_name(),
+                "modify",
+                &format!("{}.{} has been modified by others, please refresh and try again", Self::get_obj_name(), id),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn do_get_rbum_version(id: &str, lock: bool, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<i64> {
+        #[derive(Debug, FromQueryResult)]
+        struct VersionResp {
+            pub version: i64,
+        }
+
+        let mut query = Query::select();
+        query
+            .column(VERSION_FIELD.clone())
+            .from(Alias::new(Self::get_table_name()))
+            .and_where(Expr::col(ID_FIELD.clone()).eq(id))
+            .and_where(Expr::col(OWN_PATHS_FIELD.clone()).like(format!("{}%", ctx.own_paths).as_str()));
+        if lock {
+            rbum_dialect_helper::lock_for_update(&mut query, funs.db().raw_conn().get_database_backend());
+        }
+        match funs.db().get_dto::<VersionResp>(&query).await? {
+            Some(resp) => Ok(resp.version),
+            None => Err(funs.err().not_found(
+                &Self::get_obj_name(),
+                "check",
+                &format!("ownership {}.{} is illegal by {}", Self::get_obj_name(), id, ctx.owner),
+            )),
+        }
+    }
+
     // ----------------------------- Delete -------------------------------
 
     async fn package_delete(id: &str, _funs: &TardisFunsInst<'a>, _ctx: &TardisContext) -> TardisResult<Select<E::Entity>> {
@@ -227,27 +364,355 @@ where
     async fn delete_rbum(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<u64> {
         let deleted_rbum = Self::before_delete_rbum(id, funs, ctx).await?;
         let select = Self::package_delete(id, funs, ctx).await?;
+        let delete_records = funs.db().soft_delete_custom(select, "id").await?;
+        Self::add_deleted_records(&delete_records, funs, ctx).await?;
         #[cfg(feature = "with-mq")]
         {
-            let delete_records = funs.db().soft_delete_custom(select, "id").await?;
             let mq_topic_entity_deleted = &funs.rbum_conf_mq_topic_entity_deleted();
             let mq_header = std::collections::HashMap::from([(funs.rbum_conf_mq_header_name_operator(), ctx.owner.clone())]);
             for delete_record in &delete_records {
-                funs.mq().request(mq_topic_entity_deleted, tardis::TardisFuns::json.obj_to_string(delete_record)?, &mq_header).await?;
+                rbum_event_helper::add_outbox(mq_topic_entity_deleted, &mq_header, TardisFuns::json.obj_to_string(delete_record)?, id, funs, ctx).await?;
+            }
+        }
+        Self::after_delete_rbum(id, &deleted_rbum, funs, ctx).await?;
+        let before = delete_records.first().map(|delete_record| TardisFuns::json.str_to_json(&delete_record.content)).transpose()?;
+        rbum_event_helper::try_notify_with_snapshot(Self::get_table_name(), "d", id, before, None, funs, ctx).await?;
+        Ok(delete_records.len() as u64)
+    }
+
+    // ----------------------------- Batch -------------------------------
+
+    /// Find the ids that belong to the current context, checked with one query
+    async fn find_owned_ids(ids: &[String], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<HashSet<String>> {
+        Self::find_owned_ids_with_table_name(ids, Self::get_table_name(), funs, ctx).await
+    }
+
+    async fn find_owned_ids_with_table_name(ids: &[String], table_name: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<HashSet<String>> {
+        if ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let mut query = Query::select();
+        query
+            .column(ID_FIELD.clone())
+            .from(Alias::new(table_name))
+            .and_where(Expr::col(ID_FIELD.clone()).is_in(ids.iter().map(|id| id.as_str()).collect::<Vec<&str>>()))
+            .and_where(Expr::col(OWN_PATHS_FIELD.clone()).like(format!("{}%", ctx.own_paths).as_str()));
+        Ok(funs.db().find_dtos::<IdResp>(&query).await?.into_iter().map(|resp| resp.id).collect())
+    }
+
+    fn get_id_from_domain(domain: &E, funs: &TardisFunsInst<'a>) -> TardisResult<String> {
+        let id_column = <<E as ActiveModelTrait>::Entity as EntityTrait>::Column::from_str("id")
+            .map_err(|_| funs.err().internal_error(&Self::get_obj_name(), "add", "id column is missing"))?;
+        match domain.get(id_column).into_value() {
+            Some(Value::String(Some(id))) => Ok(id.to_string()),
+            _ => Err(funs.err().internal_error(&Self::get_obj_name(), "add", "id data type is invalid, currently only the string is supported")),
+        }
+    }
+
+    /// Add records in batch
+    ///
+    /// Each record is validated by ``before_add_rbum`` independently and the invalid ones are reported in the result,
+    /// the valid records are inserted with one statement and notified in batch.
+    /// All records share the transaction of the caller, see ``RbumBatchResp``.
+    async fn add_rbums(add_reqs: &mut [AddReq], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumBatchResp> {
+        let mut resp = RbumBatchResp::default();
+        let mut domains = Vec::with_capacity(add_reqs.len());
+        let mut added = Vec::with_capacity(add_reqs.len());
+        for (idx, add_req) in add_reqs.iter_mut().enumerate() {
+            let domain = match Self::before_add_rbum(add_req, funs, ctx).await {
+                Ok(_) => Self::package_add(add_req, funs, ctx).await,
+                Err(e) => Err(e),
+            };
+            match domain.and_then(|domain| Ok((Self::get_id_from_domain(&domain, funs)?, domain))) {
+                Ok((id, domain)) => {
+                    added.push((idx, id));
+                    domains.push(domain);
+                }
+                Err(e) => resp.add_failed(idx, None, &e),
+            }
+        }
+        if !domains.is_empty() {
+            funs.db().insert_many(domains, ctx).await?;
+        }
+        for (idx, id) in &added {
+            Self::after_add_rbum(id, &add_reqs[*idx], funs, ctx).await?;
+        }
+        resp.succeed_ids = added.into_iter().map(|(_, id)| id).collect();
+        rbum_event_helper::try_notifies(Self::get_table_name(), "c", &resp.succeed_ids, funs, ctx).await?;
+        Ok(resp)
+    }
+
+    /// Modify records in batch
+    ///
+    /// The ownership of all records is checked with one query (and is not checked again by ``check_ownership`` in the hooks),
+    /// records that fail the check or ``before_modify_rbum`` are reported in the result.
+    /// All records share the transaction of the caller, see ``RbumBatchResp``.
+    async fn modify_rbums(modify_reqs: &mut [(String, ModifyReq)], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumBatchResp> {
+        let ids = modify_reqs.iter().map(|(id, _)| id.to_string()).collect::<Vec<String>>();
+        let owned_ids = Self::find_owned_ids(&ids, funs, ctx).await?;
+        let mut resp = RbumBatchResp::default();
+        with_checked_ownerships(Self::get_table_name(), &owned_ids, async {
+            for (idx, (id, modify_req)) in modify_reqs.iter_mut().enumerate() {
+                if !owned_ids.contains(id.as_str()) {
+                    resp.add_failed(
+                        idx,
+                        Some(id.as_str()),
+                        &funs.err().not_found(
+                            &Self::get_obj_name(),
+                            "modify",
+                            &format!("ownership {}.{} is illegal by {}", Self::get_obj_name(), id, ctx.owner),
+                        ),
+                    );
+                    continue;
+                }
+                let domain = match Self::before_modify_rbum(id, modify_req, funs, ctx).await {
+                    Ok(_) => Self::package_modify(id, modify_req, funs, ctx).await,
+                    Err(e) => Err(e),
+                };
+                match domain {
+                    Ok(domain) => {
+                        funs.db().update_one(domain, ctx).await?;
+                        Self::after_modify_rbum(id, modify_req, funs, ctx).await?;
+                        resp.succeed_ids.push(id.to_string());
+                    }
+                    Err(e) => resp.add_failed(idx, Some(id.as_str()), &e),
+                }
             }
-            Self::after_delete_rbum(id, &deleted_rbum, funs, ctx).await?;
-            rbum_event_helper::try_notify(Self::get_table_name(), "d", id, funs, ctx).await?;
-            Ok(delete_records.len() as u64)
+            Ok::<(), TardisError>(())
+        })
+        .await?;
+        Self::touch_rbums(&resp.succeed_ids, funs).await?;
+        rbum_event_helper::try_notifies(Self::get_table_name(), "u", &resp.succeed_ids, funs, ctx).await?;
+        Ok(resp)
+    }
+
+    async fn package_deletes(ids: &[String], _funs: &TardisFunsInst<'a>, _ctx: &TardisContext) -> TardisResult<Select<E::Entity>> {
+        Ok(E::Entity::find().filter(Expr::col(ID_FIELD.clone()).is_in(ids.iter().map(|id| id.as_str()).collect::<Vec<&str>>())))
+    }
+
+    /// Delete records in batch
+    ///
+    /// The ownership of all records is checked with one query (and is not checked again by ``check_ownership`` in the hooks),
+    /// records that fail the check or ``before_delete_rbum`` are reported in the result,
+    /// the others are deleted with one statement and notified in batch.
+    /// All records share the transaction of the caller, see ``RbumBatchResp``.
+    async fn delete_rbums(ids: &[String], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumBatchResp> {
+        let owned_ids = Self::find_owned_ids(ids, funs, ctx).await?;
+        let mut resp = RbumBatchResp::default();
+        let mut deleted_rbums = Vec::with_capacity(ids.len());
+        with_checked_ownerships(Self::get_table_name(), &owned_ids, async {
+            for (idx, id) in ids.iter().enumerate() {
+                if !owned_ids.contains(id.as_str()) {
+                    resp.add_failed(
+                        idx,
+                        Some(id.as_str()),
+                        &funs.err().not_found(
+                            &Self::get_obj_name(),
+                            "delete",
+                            &format!("ownership {}.{} is illegal by {}", Self::get_obj_name(), id, ctx.owner),
+                        ),
+                    );
+                    continue;
+                }
+                match Self::before_delete_rbum(id, funs, ctx).await {
+                    Ok(deleted_rbum) => {
+                        resp.succeed_ids.push(id.to_string());
+                        deleted_rbums.push(deleted_rbum);
+                    }
+                    Err(e) => resp.add_failed(idx, Some(id.as_str()), &e),
+                }
+            }
+        })
+        .await;
+        if resp.succeed_ids.is_empty() {
+            return Ok(resp);
         }
-        #[cfg(not(feature = "with-mq"))]
+        // The records are gone after deletion, so their own paths are read for the events in advance
+        let own_paths = rbum_event_helper::try_get_own_paths(Self::get_table_name(), "d", &resp.succeed_ids, funs).await?;
+        let select = Self::package_deletes(&resp.succeed_ids, funs, ctx).await?;
+        let delete_records = funs.db().soft_delete_custom(select, "id").await?;
+        Self::add_deleted_records(&delete_records, funs, ctx).await?;
+        #[cfg(feature = "with-mq")]
         {
-            let delete_records = funs.db().soft_delete(select, &ctx.owner).await?;
-            Self::after_delete_rbum(id, &deleted_rbum, funs, ctx).await?;
-            rbum_event_helper::try_notify(Self::get_table_name(), "d", &id, funs, ctx).await?;
-            Ok(delete_records)
+            let mq_topic_entity_deleted = &funs.rbum_conf_mq_topic_entity_deleted();
+            let mq_header = std::collections::HashMap::from([(funs.rbum_conf_mq_header_name_operator(), ctx.owner.clone())]);
+            for delete_record in &delete_records {
+                rbum_event_helper::add_outbox(
+                    mq_topic_entity_deleted,
+                    &mq_header,
+                    TardisFuns::json.obj_to_string(delete_record)?,
+                    &delete_record.record_id,
+                    funs,
+                    ctx,
+                )
+                .await?;
+            }
+        }
+        for (id, deleted_rbum) in resp.succeed_ids.iter().zip(deleted_rbums.iter()) {
+            Self::after_delete_rbum(id, deleted_rbum, funs, ctx).await?;
+        }
+        rbum_event_helper::try_notifies_with_own_paths(Self::get_table_name(), "d", &resp.succeed_ids, &own_paths, funs, ctx).await?;
+        Ok(resp)
+    }
+
+    // ----------------------------- Recycle -------------------------------
+
+    /// Keep the deleted records in the recycle bin, regardless of whether MQ is enabled
+    async fn add_deleted_records(delete_records: &[DeleteEntity], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        for delete_record in delete_records {
+            funs.db()
+                .insert_one(
+                    tardis_db_del_record::ActiveModel {
+                        entity_name: Set(delete_record.entity_name.to_string()),
+                        record_id: Set(delete_record.record_id.to_string()),
+                        content: Set(delete_record.content.to_string()),
+                        creator: Set(ctx.owner.to_string()),
+                        ..Default::default()
+                    },
+                    ctx,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn paginate_deleted_rbums(
+        with_sub_own_paths: bool,
+        page_number: u64,
+        page_size: u64,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<TardisPage<RbumDeletedResp>> {
+        Self::paginate_deleted_rbums_with_table_name(Self::get_table_name(), None, with_sub_own_paths, page_number, page_size, funs, ctx).await
+    }
+
+    /// Paginate the deleted records of the table, optionally only the records of the kind (``rel_rbum_kind_id``), the latest deleted first
+    async fn paginate_deleted_rbums_with_table_name(
+        table_name: &str,
+        rbum_kind_id: Option<&str>,
+        with_sub_own_paths: bool,
+        page_number: u64,
+        page_size: u64,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<TardisPage<RbumDeletedResp>> {
+        let mut query = Self::package_deleted_query(table_name, with_sub_own_paths, funs, ctx);
+        if let Some(rbum_kind_id) = rbum_kind_id {
+            query.and_where(
+                Expr::expr(rbum_dialect_helper::json_text_field(
+                    tardis_db_del_record::Column::Content.as_str(),
+                    REL_KIND_ID_FIELD.to_string().as_str(),
+                    funs.db().raw_conn().get_database_backend(),
+                ))
+                .eq(rbum_kind_id),
+            );
+        }
+        let (records, total_size) = funs.db().paginate_dtos::<DeletedRecordResp>(&query, page_number, page_size).await?;
+        Ok(TardisPage {
+            page_size,
+            page_number,
+            total_size,
+            records: records.into_iter().map(|record| record.to_resp()).collect::<TardisResult<Vec<RbumDeletedResp>>>()?,
+        })
+    }
+
+    async fn find_deleted_rbums_with_table_name(table_name: &str, ids: &[String], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<Vec<RbumDeletedResp>> {
+        let mut query = Self::package_deleted_query(table_name, true, funs, ctx);
+        query.and_where(Expr::col(tardis_db_del_record::Column::RecordId).is_in(ids.to_vec()));
+        funs.db().find_dtos::<DeletedRecordResp>(&query).await?.into_iter().map(|record| record.to_resp()).collect()
+    }
+
+    fn package_deleted_query(table_name: &str, with_sub_own_paths: bool, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> SelectStatement {
+        let own_paths = rbum_dialect_helper::json_text_field(
+            tardis_db_del_record::Column::Content.as_str(),
+            OWN_PATHS_FIELD.to_string().as_str(),
+            funs.db().raw_conn().get_database_backend(),
+        );
+        let mut own_paths_cond = Cond::any().add(Expr::expr(own_paths.clone()).eq(ctx.own_paths.as_str()));
+        if ctx.own_paths.is_empty() {
+            // The records of the tables without own_paths are not owned by any tenant, so only the root context can see them
+            own_paths_cond = own_paths_cond.add(Expr::expr(own_paths.clone()).is_null());
+        }
+        if with_sub_own_paths {
+            own_paths_cond = own_paths_cond.add(Expr::expr(own_paths).like(format!("{}%", ctx.own_paths).as_str()));
+        }
+        let mut query = Query::select();
+        query
+            .columns(vec![
+                tardis_db_del_record::Column::RecordId,
+                tardis_db_del_record::Column::Content,
+                tardis_db_del_record::Column::Creator,
+                tardis_db_del_record::Column::CreateTime,
+            ])
+            .from(tardis_db_del_record::Entity)
+            .and_where(Expr::col(tardis_db_del_record::Column::EntityName).eq(table_name))
+            .cond_where(own_paths_cond)
+            .order_by(tardis_db_del_record::Column::CreateTime, Order::Desc)
+            .order_by(tardis_db_del_record::Column::Id, Order::Desc);
+        query
+    }
+
+    async fn get_deleted_rbum_with_table_name(id: &str, table_name: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumDeletedResp> {
+        // The latest deleted record is used if the record has been deleted multiple times
+        if let Some(deleted_record) = Self::find_deleted_rbums_with_table_name(table_name, &[id.to_string()], funs, ctx).await?.into_iter().next() {
+            Ok(deleted_record)
+        } else {
+            Err(funs.err().not_found(
+                &Self::get_obj_name_from(table_name),
+                "restore",
+                &format!("deleted {}.{} not found by {}", Self::get_obj_name_from(table_name), id, ctx.owner),
+            ))
         }
     }
 
+    async fn do_restore_rbum_with_table_name(deleted_record: &RbumDeletedResp, table_name: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if funs.db().count(Query::select().column(ID_FIELD.clone()).from(Alias::new(table_name)).and_where(Expr::col(ID_FIELD.clone()).eq(deleted_record.id.as_str()))).await? > 0 {
+            return Err(funs.err().conflict(
+                &Self::get_obj_name_from(table_name),
+                "restore",
+                &format!("{}.{} already exists", Self::get_obj_name_from(table_name), deleted_record.id),
+            ));
+        }
+        let fields = if let Some(fields) = deleted_record.content.as_object() {
+            fields
+        } else {
+            return Err(funs.err().bad_request(
+                &Self::get_obj_name_from(table_name),
+                "restore",
+                &format!("deleted {}.{} content is illegal", Self::get_obj_name_from(table_name), deleted_record.id),
+            ));
+        };
+        let insert_statement = package_insert_from_json(table_name, fields);
+        funs.db().execute(&insert_statement).await?;
+        funs.db()
+            .execute(
+                Query::delete()
+                    .from_table(tardis_db_del_record::Entity)
+                    .and_where(Expr::col(tardis_db_del_record::Column::EntityName).eq(table_name))
+                    .and_where(Expr::col(tardis_db_del_record::Column::RecordId).eq(deleted_record.id.as_str())),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn before_restore_rbum(_: &str, _: &RbumDeletedResp, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        Ok(())
+    }
+
+    async fn after_restore_rbum(_: &str, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        Ok(())
+    }
+
+    async fn restore_rbum(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        // Implicit ownership check
+        let deleted_record = Self::get_deleted_rbum_with_table_name(id, Self::get_table_name(), funs, ctx).await?;
+        Self::before_restore_rbum(id, &deleted_record, funs, ctx).await?;
+        Self::do_restore_rbum_with_table_name(&deleted_record, Self::get_table_name(), funs).await?;
+        Self::after_restore_rbum(id, funs, ctx).await?;
+        rbum_event_helper::try_notify(Self::get_table_name(), "r", id, funs, ctx).await?;
+        Ok(())
+    }
+
     // ----------------------------- Query -------------------------------
 
     async fn package_query(is_detail: bool, filter: &FilterReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<SelectStatement>;
@@ -354,6 +819,24 @@ where
         })
     }
 
+    /// Paginate by cursor, see ``rbum_cursor_helper``
+    async fn paginate_rbums_by_cursor(filter: &FilterReq, cursor_req: &RbumCursorReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumCursorPage<SummaryResp>> {
+        Self::do_paginate_rbums_by_cursor(filter, cursor_req, funs, ctx).await
+    }
+
+    async fn do_paginate_rbums_by_cursor(
+        filter: &FilterReq,
+        cursor_req: &RbumCursorReq,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumCursorPage<SummaryResp>> {
+        rbum_cursor_helper::check_filter_sorts(filter.basic(), funs)?;
+        let mut query = Self::package_query(false, filter, funs, ctx).await?;
+        rbum_cursor_helper::package_cursor_query(&mut query, Self::get_table_name(), cursor_req, funs)?;
+        let records = funs.db().find_dtos(&query).await?;
+        rbum_cursor_helper::package_cursor_page(records, cursor_req, funs)
+    }
+
     async fn paginate_detail_rbums(
         filter: &FilterReq,
         page_number: u64,
@@ -515,7 +998,7 @@ where
 
 pub trait RbumCrudQueryPackage {
     fn with_filter(&mut self, table_name: &str, filter: &RbumBasicFilterReq, ignore_owner: bool, has_scope: bool, ctx: &TardisContext) -> &mut Self;
-    fn with_scope(&mut self, table_name: &str, filter_own_paths: &str, with_sub_own_paths: bool, ctx: &TardisContext) -> &mut Self;
+    fn with_scope(&mut self, table_name: &str, filter_own_paths: &str, with_sub_own_paths: bool) -> &mut Self;
 }
 
 impl RbumCrudQueryPackage for SelectStatement {
@@ -526,6 +1009,12 @@ impl RbumCrudQueryPackage for SelectStatement {
         if let Some(ids) = &filter.ids {
             self.and_where(Expr::tbl(Alias::new(table_name), ID_FIELD.clone()).is_in(ids.clone()));
         }
+        if let Some(exclude_ids) = &filter.exclude_ids {
+            self.and_where(Expr::tbl(Alias::new(table_name), ID_FIELD.clone()).is_not_in(exclude_ids.clone()));
+        }
+        if let Some(owners) = &filter.owners {
+            self.and_where(Expr::tbl(Alias::new(table_name), OWNER_FIELD.clone()).is_in(owners.clone()));
+        }
 
         if let Some(scope_level) = &filter.scope_level {
             self.and_where(Expr::tbl(Alias::new(table_name), SCOPE_LEVEL_FIELD.clone()).eq(scope_level.to_int()));
@@ -540,13 +1029,36 @@ impl RbumCrudQueryPackage for SelectStatement {
         if let Some(code) = &filter.code {
             self.and_where(Expr::tbl(Alias::new(table_name), CODE_FIELD.clone()).like(format!("{}%", code).as_str()));
         }
+        if let Some(exact_name) = &filter.exact_name {
+            self.and_where(Expr::tbl(Alias::new(table_name), NAME_FIELD.clone()).eq(exact_name.as_str()));
+        }
+        if let Some(exact_code) = &filter.exact_code {
+            self.and_where(Expr::tbl(Alias::new(table_name), CODE_FIELD.clone()).eq(exact_code.as_str()));
+        }
 
         if let Some(rbum_kind_id) = &filter.rbum_kind_id {
             self.and_where(Expr::tbl(Alias::new(table_name), REL_KIND_ID_FIELD.clone()).eq(rbum_kind_id.to_string()));
         }
+        if let Some(rbum_kind_ids) = &filter.rbum_kind_ids {
+            self.and_where(Expr::tbl(Alias::new(table_name), REL_KIND_ID_FIELD.clone()).is_in(rbum_kind_ids.clone()));
+        }
         if let Some(rbum_domain_id) = &filter.rbum_domain_id {
             self.and_where(Expr::tbl(Alias::new(table_name), REL_DOMAIN_ID_FIELD.clone()).eq(rbum_domain_id.to_string()));
         }
+
+        if let Some(create_time_start) = filter.create_time_start {
+            self.and_where(Expr::tbl(Alias::new(table_name), CREATE_TIME_FIELD.clone()).gte(create_time_start));
+        }
+        if let Some(create_time_end) = filter.create_time_end {
+            self.and_where(Expr::tbl(Alias::new(table_name), CREATE_TIME_FIELD.clone()).lte(create_time_end));
+        }
+        if let Some(update_time_start) = filter.update_time_start {
+            self.and_where(Expr::tbl(Alias::new(table_name), UPDATE_TIME_FIELD.clone()).gte(update_time_start));
+        }
+        if let Some(update_time_end) = filter.update_time_end {
+            self.and_where(Expr::tbl(Alias::new(table_name), UPDATE_TIME_FIELD.clone()).lte(update_time_end));
+        }
+
         if with_owner {
             self.expr_as(Expr::tbl(OWNER_TABLE.clone(), NAME_FIELD.clone()), Alias::new("owner_name")).join_as(
                 JoinType::InnerJoin,
@@ -557,7 +1069,7 @@ impl RbumCrudQueryPackage for SelectStatement {
         }
         let filter_own_paths = if let Some(own_paths) = &filter.own_paths { own_paths.as_str() } else { &ctx.own_paths };
         if has_scope && !filter.ignore_scope {
-            self.with_scope(table_name, filter_own_paths, filter.with_sub_own_paths, ctx);
+            self.with_scope(table_name, filter_own_paths, filter.with_sub_own_paths);
         } else if filter.with_sub_own_paths {
             self.and_where(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).like(format!("{}%", filter_own_paths).as_str()));
         } else {
@@ -566,10 +1078,18 @@ impl RbumCrudQueryPackage for SelectStatement {
         if let Some(desc_by_sort) = filter.desc_by_sort {
             self.order_by((Alias::new(table_name), SORT_FIELD.clone()), if desc_by_sort { Order::Desc } else { Order::Asc });
         }
+        if let Some(sorts) = &filter.sorts {
+            for sort in sorts {
+                self.order_by(
+                    (Alias::new(table_name), Alias::new(sort.field.to_field_name())),
+                    if sort.desc { Order::Desc } else { Order::Asc },
+                );
+            }
+        }
         self
     }
 
-    fn with_scope(&mut self, table_name: &str, filter_own_paths: &str, with_sub_own_paths: bool, ctx: &TardisContext) -> &mut Self {
+    fn with_scope(&mut self, table_name: &str, filter_own_paths: &str, with_sub_own_paths: bool) -> &mut Self {
         let mut cond = Cond::any().add(Expr::tbl(Alias::new(table_name), SCOPE_LEVEL_FIELD.clone()).eq(0));
 
         let own_cond = if with_sub_own_paths {
@@ -579,56 +1099,101 @@ impl RbumCrudQueryPackage for SelectStatement {
         };
         cond = cond.add(own_cond);
 
-        if let Some(p1) = rbum_scope_helper::get_pre_paths(1, &ctx.own_paths) {
-            cond = cond.add(
-                Cond::all().add(Expr::tbl(Alias::new(table_name), SCOPE_LEVEL_FIELD.clone()).eq(1)).add(
-                    Cond::any()
-                        .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).eq(""))
-                        .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).like(&format!("{}%", p1))),
-                ),
-            );
-            if let Some(p2) = rbum_scope_helper::get_pre_paths(2, &ctx.own_paths) {
-                let node_len = (p2.len() - p1.len() - 1) as u8;
-                cond = cond.add(
-                    Cond::all().add(Expr::tbl(Alias::new(table_name), SCOPE_LEVEL_FIELD.clone()).eq(2)).add(
-                        Cond::any()
-                            .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).eq(""))
-                            .add(
-                                Cond::all()
-                                    .add(Expr::expr(Func::char_length(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()))).eq(node_len))
-                                    .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).like(&format!("{}%", p1))),
-                            )
-                            .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).like(&format!("{}%", p2))),
-                    ),
-                );
-                if let Some(p3) = rbum_scope_helper::get_pre_paths(3, &ctx.own_paths) {
-                    cond = cond.add(
-                        Cond::all().add(Expr::tbl(Alias::new(table_name), SCOPE_LEVEL_FIELD.clone()).eq(3)).add(
-                            Cond::any()
-                                .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).eq(""))
-                                .add(
-                                    Cond::all()
-                                        .add(Expr::expr(Func::char_length(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()))).eq(node_len))
-                                        .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).like(&format!("{}%", p1))),
-                                )
-                                .add(
-                                    Cond::all()
-                                        .add(Expr::expr(Func::char_length(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()))).eq(node_len * 2 + 1))
-                                        .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).like(&format!("{}%", p2))),
-                                )
-                                .add(Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).like(&format!("{}%", p3))),
-                        ),
-                    );
-                };
-            };
-        };
+        for (scope_level, path_conds) in rbum_scope_helper::get_scope_conds(filter_own_paths) {
+            let mut own_paths_cond = Cond::any();
+            for path_cond in path_conds {
+                own_paths_cond = own_paths_cond.add(match path_cond {
+                    RbumScopePathCond::Eq(own_paths) => Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).eq(own_paths),
+                    RbumScopePathCond::Prefix(own_paths) => Expr::tbl(Alias::new(table_name), OWN_PATHS_FIELD.clone()).like(&format!("{}%", own_paths)),
+                });
+            }
+            cond = cond.add(Cond::all().add(Expr::tbl(Alias::new(table_name), SCOPE_LEVEL_FIELD.clone()).eq(scope_level)).add(own_paths_cond));
+        }
 
         self.cond_where(Cond::all().add(cond));
         self
     }
 }
 
+/// Run ``fut`` with the ownership of the records (``ids`` of ``table_name``) treated as checked,
+/// used by the batch operations which check the ownership of all records with one query,
+/// so that ``check_ownership`` in the hooks of each record does not query them again.
+///
+/// Only the current task is affected, the records checked by the enclosing batch operations are kept.
+pub async fn with_checked_ownerships<F: Future>(table_name: &str, ids: &HashSet<String>, fut: F) -> F::Output {
+    let mut checked = CHECKED_OWNERSHIPS.try_with(|checked| checked.clone()).unwrap_or_default();
+    checked.extend(ids.iter().map(|id| format!("{}:{}", table_name, id)));
+    CHECKED_OWNERSHIPS.scope(checked, fut).await
+}
+
+fn is_ownership_checked(table_name: &str, id: &str) -> bool {
+    CHECKED_OWNERSHIPS.try_with(|checked| checked.contains(&format!("{}:{}", table_name, id))).unwrap_or(false)
+}
+
+/// Convert a json value to a database value, ``None`` means the column should be skipped
+pub fn to_db_value(value: &JsonValue) -> Option<Value> {
+    let value = match value {
+        JsonValue::Null => return None,
+        JsonValue::Bool(value) => (*value).into(),
+        JsonValue::Number(value) => {
+            if let Some(value) = value.as_i64() {
+                value.into()
+            } else if let Some(value) = value.as_u64() {
+                value.into()
+            } else {
+                value.as_f64().unwrap_or_default().into()
+            }
+        }
+        JsonValue::String(value) => value.as_str().into(),
+        _ => value.to_string().into(),
+    };
+    Some(value)
+}
+
+/// Package the insert statement of a record with all columns, e.g. a deleted record or an archived record
+pub fn package_insert_from_json(table_name: &str, fields: &tardis::serde_json::Map<String, JsonValue>) -> InsertStatement {
+    let mut columns = Vec::new();
+    let mut values = Vec::new();
+    for (field, value) in fields {
+        if let Some(value) = to_db_value(value) {
+            columns.push(Alias::new(field));
+            values.push(value);
+        }
+    }
+    let mut insert_statement = Query::insert();
+    insert_statement.into_table(Alias::new(table_name)).columns(columns).values_panic(values);
+    insert_statement
+}
+
+#[derive(Debug, FromQueryResult)]
+pub struct ScopeResp {
+    pub own_paths: String,
+    pub scope_level: i8,
+}
+
 #[derive(Debug, FromQueryResult)]
 pub struct NameResp {
     pub name: String,
 }
+
+#[derive(Debug, FromQueryResult)]
+struct DeletedRecordResp {
+    pub record_id: String,
+    pub content: String,
+    pub creator: String,
+    pub create_time: DateTime<Utc>,
+}
+
+impl DeletedRecordResp {
+    fn to_resp(self) -> TardisResult<RbumDeletedResp> {
+        let content = TardisFuns::json.str_to_json(&self.content)?;
+        let own_paths = content.get(OWN_PATHS_FIELD.to_string()).and_then(|own_paths| own_paths.as_str()).unwrap_or_default().to_string();
+        Ok(RbumDeletedResp {
+            id: self.record_id,
+            own_paths,
+            deleter: self.creator,
+            delete_time: self.create_time,
+            content,
+        })
+    }
+}
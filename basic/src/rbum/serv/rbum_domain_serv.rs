@@ -25,7 +25,7 @@ impl<'a> RbumCrudOperation<'a, rbum_domain::ActiveModel, RbumDomainAddReq, RbumD
         rbum_domain::Entity.table_name()
     }
 
-    async fn package_add(add_req: &RbumDomainAddReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_domain::ActiveModel> {
+    async fn package_add(add_req: &RbumDomainAddReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_domain::ActiveModel> {
         Ok(rbum_domain::ActiveModel {
             id: Set(TardisFuns::field.nanoid()),
             code: Set(add_req.code.to_string()),
@@ -33,7 +33,7 @@ impl<'a> RbumCrudOperation<'a, rbum_domain::ActiveModel, RbumDomainAddReq, RbumD
             note: Set(add_req.note.as_ref().unwrap_or(&"".to_string()).to_string()),
             icon: Set(add_req.icon.as_ref().unwrap_or(&"".to_string()).to_string()),
             sort: Set(add_req.sort.unwrap_or(0)),
-            scope_level: Set(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private).to_int()),
+            scope_level: Set(Self::check_scope_level(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private), funs)?),
             ..Default::default()
         })
     }
@@ -53,7 +53,7 @@ impl<'a> RbumCrudOperation<'a, rbum_domain::ActiveModel, RbumDomainAddReq, RbumD
         Ok(())
     }
 
-    async fn package_modify(id: &str, modify_req: &RbumDomainModifyReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_domain::ActiveModel> {
+    async fn package_modify(id: &str, modify_req: &RbumDomainModifyReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_domain::ActiveModel> {
         let mut rbum_domain = rbum_domain::ActiveModel {
             id: Set(id.to_string()),
             ..Default::default()
@@ -71,7 +71,7 @@ impl<'a> RbumCrudOperation<'a, rbum_domain::ActiveModel, RbumDomainAddReq, RbumD
             rbum_domain.sort = Set(sort);
         }
         if let Some(scope_level) = &modify_req.scope_level {
-            rbum_domain.scope_level = Set(scope_level.to_int());
+            rbum_domain.scope_level = Set(Self::check_scope_level(scope_level, funs)?);
         }
         Ok(rbum_domain)
     }
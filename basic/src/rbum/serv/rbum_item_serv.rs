@@ -1,9 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use async_trait::async_trait;
 use serde::Serialize;
 use tardis::basic::dto::TardisContext;
+use tardis::basic::error::TardisError;
 use tardis::basic::result::TardisResult;
+use tardis::chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use tardis::db::reldb_client::{IdResp, TardisActiveModel};
 use tardis::db::sea_orm::*;
 use tardis::db::sea_query::*;
@@ -11,23 +13,30 @@ use tardis::web::poem_openapi::types::{ParseFromJSON, ToJSON};
 use tardis::web::web_resp::TardisPage;
 use tardis::{TardisFuns, TardisFunsInst};
 
-use crate::rbum::domain::{rbum_cert, rbum_cert_conf, rbum_domain, rbum_item, rbum_item_attr, rbum_kind, rbum_kind_attr, rbum_rel, rbum_set_item};
+use crate::rbum::domain::{rbum_cert, rbum_cert_conf, rbum_domain, rbum_item, rbum_item_attr, rbum_kind, rbum_kind_attr, rbum_rel, rbum_set, rbum_set_cate, rbum_set_item};
+use crate::rbum::dto::rbum_batch_dto::RbumBatchResp;
+use crate::rbum::dto::rbum_cursor_dto::{RbumCursorPage, RbumCursorReq};
 use crate::rbum::dto::rbum_filer_dto::{
-    RbumBasicFilterReq, RbumCertConfFilterReq, RbumCertFilterReq, RbumItemAttrFilterReq, RbumItemFilterFetcher, RbumItemRelFilterReq, RbumKindAttrFilterReq, RbumSetItemFilterReq,
+    RbumBasicFilterReq, RbumCertConfFilterReq, RbumCertFilterReq, RbumItemAttrFilterReq, RbumItemAttrPredicateReq, RbumItemFilterFetcher, RbumItemRelFilterReq,
+    RbumKindAttrFilterReq, RbumSetItemFilterReq,
 };
 use crate::rbum::dto::rbum_item_attr_dto::{RbumItemAttrAddReq, RbumItemAttrDetailResp, RbumItemAttrModifyReq, RbumItemAttrSummaryResp, RbumItemAttrsAddOrModifyReq};
 use crate::rbum::dto::rbum_item_dto::{RbumItemAddReq, RbumItemDetailResp, RbumItemKernelAddReq, RbumItemModifyReq, RbumItemSummaryResp};
 use crate::rbum::dto::rbum_kind_attr_dto::RbumKindAttrSummaryResp;
+use crate::rbum::dto::rbum_recycle_dto::RbumDeletedResp;
 use crate::rbum::dto::rbum_rel_dto::{RbumRelAddReq, RbumRelFindReq};
-use crate::rbum::helper::rbum_event_helper;
+use crate::rbum::helper::rbum_uri_helper::RbumUri;
+use crate::rbum::helper::{rbum_cursor_helper, rbum_dialect_helper, rbum_event_helper, rbum_kind_attr_helper, rbum_scope_helper};
 use crate::rbum::rbum_config::RbumConfigApi;
-use crate::rbum::rbum_enumeration::{RbumCertRelKind, RbumRelFromKind, RbumScopeLevelKind};
+use crate::rbum::rbum_enumeration::{RbumCertRelKind, RbumCursorSortKind, RbumDataTypeKind, RbumOperatorKind, RbumRelFromKind, RbumScopeLevelKind, RbumSortFieldKind};
 use crate::rbum::serv::rbum_cert_serv::{RbumCertConfServ, RbumCertServ};
-use crate::rbum::serv::rbum_crud_serv::{RbumCrudOperation, RbumCrudQueryPackage, CREATE_TIME_FIELD, ID_FIELD, UPDATE_TIME_FIELD};
+use crate::rbum::serv::rbum_crud_serv::{
+    with_checked_ownerships, RbumCrudOperation, RbumCrudQueryPackage, ScopeResp, CREATE_TIME_FIELD, ID_FIELD, OWN_PATHS_FIELD, SORT_FIELD, UPDATE_TIME_FIELD,
+};
 use crate::rbum::serv::rbum_domain_serv::RbumDomainServ;
 use crate::rbum::serv::rbum_kind_serv::{RbumKindAttrServ, RbumKindServ};
-use crate::rbum::serv::rbum_rel_serv::RbumRelServ;
-use crate::rbum::serv::rbum_set_serv::RbumSetItemServ;
+use crate::rbum::serv::rbum_rel_serv::{RbumRelAttrServ, RbumRelEnvServ, RbumRelServ};
+use crate::rbum::serv::rbum_set_serv::{RbumSetCateServ, RbumSetItemServ, RbumSetServ};
 
 pub struct RbumItemServ;
 
@@ -73,7 +82,7 @@ impl<'a> RbumCrudOperation<'a, rbum_item::ActiveModel, RbumItemAddReq, RbumItemM
             name: Set(add_req.name.to_string()),
             rel_rbum_kind_id: Set(add_req.rel_rbum_kind_id.to_string()),
             rel_rbum_domain_id: Set(add_req.rel_rbum_domain_id.to_string()),
-            scope_level: Set(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private).to_int()),
+            scope_level: Set(Self::check_scope_level(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private), funs)?),
             disabled: Set(add_req.disabled.unwrap_or(false)),
             ..Default::default()
         })
@@ -119,7 +128,7 @@ impl<'a> RbumCrudOperation<'a, rbum_item::ActiveModel, RbumItemAddReq, RbumItemM
             rbum_item.name = Set(name.to_string());
         }
         if let Some(scope_level) = &modify_req.scope_level {
-            rbum_item.scope_level = Set(scope_level.to_int());
+            rbum_item.scope_level = Set(Self::check_scope_level(scope_level, funs)?);
         }
         if let Some(disabled) = modify_req.disabled {
             rbum_item.disabled = Set(disabled);
@@ -149,6 +158,19 @@ impl<'a> RbumCrudOperation<'a, rbum_item::ActiveModel, RbumItemAddReq, RbumItemM
         Ok(None)
     }
 
+    async fn before_restore_rbum(_: &str, deleted_record: &RbumDeletedResp, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        let rel_rbum_kind_id = deleted_record.content.get(rbum_item::Column::RelRbumKindId.as_str()).and_then(|v| v.as_str()).unwrap_or_default();
+        let rel_rbum_domain_id = deleted_record.content.get(rbum_item::Column::RelRbumDomainId.as_str()).and_then(|v| v.as_str()).unwrap_or_default();
+        Self::check_scope(rel_rbum_kind_id, RbumKindServ::get_table_name(), funs, ctx).await?;
+        Self::check_scope(rel_rbum_domain_id, RbumDomainServ::get_table_name(), funs, ctx).await?;
+        if let Some(code) = deleted_record.content.get(rbum_item::Column::Code.as_str()).and_then(|v| v.as_str()) {
+            if funs.db().count(Query::select().column(rbum_item::Column::Id).from(rbum_item::Entity).and_where(Expr::col(rbum_item::Column::Code).eq(code))).await? > 0 {
+                return Err(funs.err().conflict(&Self::get_obj_name(), "restore", &format!("code {} already exists", code)));
+            }
+        }
+        Ok(())
+    }
+
     async fn package_query(is_detail: bool, filter: &RbumBasicFilterReq, _: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<SelectStatement> {
         let mut query = Query::select();
         query
@@ -185,6 +207,524 @@ impl<'a> RbumCrudOperation<'a, rbum_item::ActiveModel, RbumItemAddReq, RbumItemM
     }
 }
 
+impl<'a> RbumItemServ {
+    /// Find the item by its uri (`<kind code>://<domain code>/<item code>`), only the items visible to the context are found
+    pub async fn find_one_rbum_by_uri(uri: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<Option<RbumItemSummaryResp>> {
+        let uri = RbumUri::parse(uri)?;
+        let rbum_kind_id = match RbumKindServ::get_rbum_kind_id_by_code(&uri.kind_code, funs).await? {
+            Some(rbum_kind_id) => rbum_kind_id,
+            None => return Ok(None),
+        };
+        let rbum_domain_id = match RbumDomainServ::get_rbum_domain_id_by_code(&uri.domain_code, funs).await? {
+            Some(rbum_domain_id) => rbum_domain_id,
+            None => return Ok(None),
+        };
+        Self::find_one_rbum(
+            &RbumBasicFilterReq {
+                exact_code: Some(uri.item_code),
+                rbum_kind_id: Some(rbum_kind_id),
+                rbum_domain_id: Some(rbum_domain_id),
+                ..Default::default()
+            },
+            funs,
+            ctx,
+        )
+        .await
+    }
+
+    /// Get the canonical uri of the item
+    pub async fn get_rbum_uri(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumUri> {
+        let rbum_item = Self::get_rbum(id, &RbumBasicFilterReq::default(), funs, ctx).await?;
+        let rbum_kind_code = funs
+            .db()
+            .get_dto::<CodeResp>(
+                Query::select().column(rbum_kind::Column::Code).from(rbum_kind::Entity).and_where(Expr::col(rbum_kind::Column::Id).eq(rbum_item.rel_rbum_kind_id.as_str())),
+            )
+            .await?
+            .ok_or_else(|| funs.err().not_found(&Self::get_obj_name(), "get_uri", &format!("not found kind of item {}", id)))?
+            .code;
+        let rbum_domain_code = funs
+            .db()
+            .get_dto::<CodeResp>(
+                Query::select().column(rbum_domain::Column::Code).from(rbum_domain::Entity).and_where(Expr::col(rbum_domain::Column::Id).eq(rbum_item.rel_rbum_domain_id.as_str())),
+            )
+            .await?
+            .ok_or_else(|| funs.err().not_found(&Self::get_obj_name(), "get_uri", &format!("not found domain of item {}", id)))?
+            .code;
+        RbumUri::new(&rbum_kind_code, &rbum_domain_code, &rbum_item.code)
+    }
+
+    /// Move the item and its dependent records (attributes, certificates, set items and relationships) to ``target_own_paths``.
+    ///
+    /// Both the item and the target own paths must belong to the context.
+    /// The relationships must remain valid after moving: the target items of the outgoing relationships must be visible to ``target_own_paths``,
+    /// and the item must be visible to the owners of the incoming relationships, otherwise a conflict error is returned.
+    /// The sets and categories of the set items must also be visible to ``target_own_paths``.
+    ///
+    /// Return the ids of the moved relationships.
+    pub async fn move_rbum(id: &str, target_own_paths: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<Vec<String>> {
+        #[derive(Debug, FromQueryResult)]
+        struct RelScopeResp {
+            pub id: String,
+            pub own_paths: String,
+            pub scope_level: i8,
+        }
+        #[derive(Debug, FromQueryResult)]
+        struct RelOwnPathsResp {
+            pub id: String,
+            pub own_paths: String,
+        }
+
+        Self::check_ownership(id, funs, ctx).await?;
+        if !rbum_scope_helper::is_sub_own_paths(target_own_paths, &ctx.own_paths) {
+            return Err(funs.err().bad_request(
+                &Self::get_obj_name(),
+                "move",
+                &format!("target own paths {} is not under {}", target_own_paths, ctx.own_paths),
+            ));
+        }
+        let rbum_item = funs
+            .db()
+            .get_dto::<ScopeResp>(
+                Query::select()
+                    .column(rbum_item::Column::OwnPaths)
+                    .column(rbum_item::Column::ScopeLevel)
+                    .from(rbum_item::Entity)
+                    .and_where(Expr::col(rbum_item::Column::Id).eq(id)),
+            )
+            .await?
+            .ok_or_else(|| funs.err().not_found(&Self::get_obj_name(), "move", &format!("not found {}.{}", Self::get_obj_name(), id)))?;
+        if rbum_item.own_paths == target_own_paths {
+            return Ok(vec![]);
+        }
+
+        // Outgoing relationships: the target items must be visible to the target own paths
+        let target_ctx = TardisContext {
+            own_paths: target_own_paths.to_string(),
+            ..ctx.clone()
+        };
+        let from_rels = funs
+            .db()
+            .find_dtos::<RelScopeResp>(
+                Query::select()
+                    .column((rbum_rel::Entity, rbum_rel::Column::Id))
+                    .column((rbum_item::Entity, rbum_item::Column::OwnPaths))
+                    .column((rbum_item::Entity, rbum_item::Column::ScopeLevel))
+                    .from(rbum_rel::Entity)
+                    .inner_join(
+                        rbum_item::Entity,
+                        Expr::tbl(rbum_item::Entity, rbum_item::Column::Id).equals(rbum_rel::Entity, rbum_rel::Column::ToRbumItemId),
+                    )
+                    .and_where(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::FromRbumKind).eq(RbumRelFromKind::Item.to_int()))
+                    .and_where(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::FromRbumId).eq(id)),
+            )
+            .await?;
+        for from_rel in &from_rels {
+            if !rbum_scope_helper::check_scope(&from_rel.own_paths, Some(from_rel.scope_level), &RbumBasicFilterReq::default(), &target_ctx) {
+                return Err(funs.err().conflict(
+                    &Self::get_obj_name(),
+                    "move",
+                    &format!("the target item of relationship {} is invisible to {}", from_rel.id, target_own_paths),
+                ));
+            }
+        }
+        // Incoming relationships: the item must be visible to the owners of the relationships
+        let to_rels = funs
+            .db()
+            .find_dtos::<RelOwnPathsResp>(
+                Query::select().column(rbum_rel::Column::Id).column(rbum_rel::Column::OwnPaths).from(rbum_rel::Entity).and_where(Expr::col(rbum_rel::Column::ToRbumItemId).eq(id)),
+            )
+            .await?;
+        for to_rel in &to_rels {
+            let rel_ctx = TardisContext {
+                own_paths: to_rel.own_paths.clone(),
+                ..ctx.clone()
+            };
+            if !rbum_scope_helper::check_scope(target_own_paths, Some(rbum_item.scope_level), &RbumBasicFilterReq::default(), &rel_ctx) {
+                return Err(funs.err().conflict(
+                    &Self::get_obj_name(),
+                    "move",
+                    &format!("{}.{} is invisible to the owner of relationship {} after moving", Self::get_obj_name(), id, to_rel.id),
+                ));
+            }
+        }
+        // Set items: the sets and categories must be visible to the target own paths
+        Self::check_moved_set_items(
+            Cond::all().add(Expr::tbl(rbum_set_item::Entity, rbum_set_item::Column::RelRbumItemId).eq(id)),
+            |_| target_own_paths.to_string(),
+            |own_paths| own_paths.to_string(),
+            funs,
+            ctx,
+        )
+        .await?;
+
+        let update_own_paths = |table: Alias, field: Alias, cond: SimpleExpr| Query::update().table(table).value(field, target_own_paths.into()).and_where(cond).to_owned();
+        funs.db()
+            .execute(&update_own_paths(
+                Alias::new(Self::get_table_name()),
+                OWN_PATHS_FIELD.clone(),
+                Expr::col(ID_FIELD.clone()).eq(id),
+            ))
+            .await?;
+        funs.db()
+            .execute(&update_own_paths(
+                Alias::new(RbumItemAttrServ::get_table_name()),
+                OWN_PATHS_FIELD.clone(),
+                Expr::col(rbum_item_attr::Column::RelRbumItemId).eq(id),
+            ))
+            .await?;
+        funs.db()
+            .execute(&update_own_paths(
+                Alias::new(RbumCertServ::get_table_name()),
+                OWN_PATHS_FIELD.clone(),
+                Expr::col(rbum_cert::Column::RelRbumKind).eq(RbumCertRelKind::Item.to_int()).and(Expr::col(rbum_cert::Column::RelRbumId).eq(id)),
+            ))
+            .await?;
+        funs.db()
+            .execute(&update_own_paths(
+                Alias::new(RbumSetItemServ::get_table_name()),
+                OWN_PATHS_FIELD.clone(),
+                Expr::col(rbum_set_item::Column::RelRbumItemId).eq(id),
+            ))
+            .await?;
+        let from_rel_ids = from_rels.into_iter().map(|from_rel| from_rel.id).collect::<Vec<String>>();
+        if !from_rel_ids.is_empty() {
+            let from_rel_id_values = from_rel_ids.iter().map(|id| id.as_str()).collect::<Vec<&str>>();
+            funs.db()
+                .execute(&update_own_paths(
+                    Alias::new(RbumRelServ::get_table_name()),
+                    OWN_PATHS_FIELD.clone(),
+                    Expr::col(ID_FIELD.clone()).is_in(from_rel_id_values.clone()),
+                ))
+                .await?;
+            for rel_table_name in [RbumRelAttrServ::get_table_name(), RbumRelEnvServ::get_table_name()] {
+                funs.db()
+                    .execute(&update_own_paths(
+                        Alias::new(rel_table_name),
+                        OWN_PATHS_FIELD.clone(),
+                        Expr::col(Alias::new("rel_rbum_rel_id")).is_in(from_rel_id_values.clone()),
+                    ))
+                    .await?;
+            }
+        }
+        funs.db()
+            .execute(&update_own_paths(
+                Alias::new(RbumRelServ::get_table_name()),
+                Alias::new(rbum_rel::Column::ToOwnPaths.as_str()),
+                Expr::col(rbum_rel::Column::ToRbumItemId).eq(id),
+            ))
+            .await?;
+        let mut moved_rel_ids = from_rel_ids;
+        moved_rel_ids.extend(to_rels.into_iter().map(|to_rel| to_rel.id));
+        Ok(moved_rel_ids)
+    }
+
+    /// Move all the records under ``source_own_paths`` (including itself) to ``target_own_paths``, keeping their relative paths,
+    /// e.g. an app ``t1/a1`` with its roles, sets, relationships and sub-apps to ``t2/a1``.
+    ///
+    /// The rbum tables and ``ext_table_names`` (tables with the ``own_paths`` column) are changed.
+    /// Both own paths must belong to the context and ``target_own_paths`` must be empty.
+    /// The relationships and set items between the moved records and the others must remain valid:
+    /// the records that are visible before moving must still be visible after moving, otherwise a conflict error is returned,
+    /// e.g. relationships to the accounts of the source tenant have to be deleted before moving an app to another tenant.
+    ///
+    /// Return the ids of the moved items.
+    pub async fn move_own_paths(
+        source_own_paths: &str,
+        target_own_paths: &str,
+        ext_table_names: &[&str],
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<Vec<String>> {
+        #[derive(Debug, FromQueryResult)]
+        struct RelScopeResp {
+            pub id: String,
+            pub own_paths: String,
+            pub to_own_paths: String,
+            pub from_own_paths: Option<String>,
+            pub from_scope_level: Option<i8>,
+            pub to_item_own_paths: Option<String>,
+            pub to_scope_level: Option<i8>,
+        }
+
+        for own_paths in [source_own_paths, target_own_paths] {
+            if own_paths.is_empty() || !rbum_scope_helper::is_sub_own_paths(own_paths, &ctx.own_paths) {
+                return Err(funs.err().bad_request(&Self::get_obj_name(), "move", &format!("own paths {} is not under {}", own_paths, ctx.own_paths)));
+            }
+        }
+        if rbum_scope_helper::is_sub_own_paths(target_own_paths, source_own_paths) || rbum_scope_helper::is_sub_own_paths(source_own_paths, target_own_paths) {
+            return Err(funs.err().bad_request(
+                &Self::get_obj_name(),
+                "move",
+                &format!("own paths {} and {} are overlapped", source_own_paths, target_own_paths),
+            ));
+        }
+        if funs
+            .db()
+            .count(
+                Query::select().column(rbum_item::Column::Id).from(rbum_item::Entity).cond_where(rbum_dialect_helper::sub_own_paths_cond(
+                    Self::get_table_name(),
+                    OWN_PATHS_FIELD.to_string().as_str(),
+                    target_own_paths,
+                )),
+            )
+            .await?
+            > 0
+        {
+            return Err(funs.err().conflict(&Self::get_obj_name(), "move", &format!("own paths {} is not empty", target_own_paths)));
+        }
+        let rebase = |own_paths: &str| rbum_scope_helper::rebase_own_paths(own_paths, source_own_paths, target_own_paths);
+        let visible = |record_own_paths: &str, record_scope_level: i8, own_paths: &str| {
+            rbum_scope_helper::check_scope(
+                record_own_paths,
+                Some(record_scope_level),
+                &RbumBasicFilterReq::default(),
+                &TardisContext {
+                    own_paths: own_paths.to_string(),
+                    ..ctx.clone()
+                },
+            )
+        };
+
+        // Relationships between the moved records and the others
+        let from_item = Alias::new("from_item");
+        let to_item = Alias::new("to_item");
+        let rel_table_name = RbumRelServ::get_table_name();
+        let rels = funs
+            .db()
+            .find_dtos::<RelScopeResp>(
+                Query::select()
+                    .column((rbum_rel::Entity, rbum_rel::Column::Id))
+                    .column((rbum_rel::Entity, rbum_rel::Column::OwnPaths))
+                    .column((rbum_rel::Entity, rbum_rel::Column::ToOwnPaths))
+                    .expr_as(Expr::tbl(from_item.clone(), rbum_item::Column::OwnPaths), Alias::new("from_own_paths"))
+                    .expr_as(Expr::tbl(from_item.clone(), rbum_item::Column::ScopeLevel), Alias::new("from_scope_level"))
+                    .expr_as(Expr::tbl(to_item.clone(), rbum_item::Column::OwnPaths), Alias::new("to_item_own_paths"))
+                    .expr_as(Expr::tbl(to_item.clone(), rbum_item::Column::ScopeLevel), Alias::new("to_scope_level"))
+                    .from(rbum_rel::Entity)
+                    .join_as(
+                        JoinType::LeftJoin,
+                        rbum_item::Entity,
+                        from_item.clone(),
+                        Cond::all()
+                            .add(Expr::tbl(from_item.clone(), rbum_item::Column::Id).equals(rbum_rel::Entity, rbum_rel::Column::FromRbumId))
+                            .add(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::FromRbumKind).eq(RbumRelFromKind::Item.to_int())),
+                    )
+                    .join_as(
+                        JoinType::LeftJoin,
+                        rbum_item::Entity,
+                        to_item.clone(),
+                        Expr::tbl(to_item.clone(), rbum_item::Column::Id).equals(rbum_rel::Entity, rbum_rel::Column::ToRbumItemId),
+                    )
+                    .cond_where(
+                        Cond::any()
+                            .add(rbum_dialect_helper::sub_own_paths_cond(
+                                rel_table_name,
+                                rbum_rel::Column::OwnPaths.as_str(),
+                                source_own_paths,
+                            ))
+                            .add(rbum_dialect_helper::sub_own_paths_cond(
+                                rel_table_name,
+                                rbum_rel::Column::ToOwnPaths.as_str(),
+                                source_own_paths,
+                            ))
+                            .add(rbum_dialect_helper::sub_own_paths_cond("from_item", rbum_item::Column::OwnPaths.as_str(), source_own_paths)),
+                    ),
+            )
+            .await?;
+        for rel in &rels {
+            let rel_own_paths = rebase(&rel.own_paths);
+            for (item_own_paths, item_scope_level) in [(&rel.from_own_paths, rel.from_scope_level), (&rel.to_item_own_paths, rel.to_scope_level)] {
+                if let (Some(item_own_paths), Some(item_scope_level)) = (item_own_paths, item_scope_level) {
+                    if visible(item_own_paths, item_scope_level, &rel.own_paths) && !visible(&rebase(item_own_paths), item_scope_level, &rel_own_paths) {
+                        return Err(funs.err().conflict(
+                            &Self::get_obj_name(),
+                            "move",
+                            &format!("the item {} of relationship {} is invisible to {} after moving", item_own_paths, rel.id, rel_own_paths),
+                        ));
+                    }
+                }
+            }
+        }
+        // Set items between the moved records and the others
+        Self::check_moved_set_items(
+            Cond::any()
+                .add(rbum_dialect_helper::sub_own_paths_cond(
+                    RbumSetItemServ::get_table_name(),
+                    rbum_set_item::Column::OwnPaths.as_str(),
+                    source_own_paths,
+                ))
+                .add(rbum_dialect_helper::sub_own_paths_cond(
+                    RbumSetServ::get_table_name(),
+                    rbum_set::Column::OwnPaths.as_str(),
+                    source_own_paths,
+                ))
+                .add(rbum_dialect_helper::sub_own_paths_cond(
+                    RbumSetCateServ::get_table_name(),
+                    rbum_set_cate::Column::OwnPaths.as_str(),
+                    source_own_paths,
+                )),
+            rebase,
+            rebase,
+            funs,
+            ctx,
+        )
+        .await?;
+
+        let moved_item_ids = funs
+            .db()
+            .find_dtos::<IdResp>(
+                Query::select().column(rbum_item::Column::Id).from(rbum_item::Entity).cond_where(rbum_dialect_helper::sub_own_paths_cond(
+                    Self::get_table_name(),
+                    OWN_PATHS_FIELD.to_string().as_str(),
+                    source_own_paths,
+                )),
+            )
+            .await?
+            .into_iter()
+            .map(|item| item.id)
+            .collect::<Vec<String>>();
+        let table_names = [
+            RbumDomainServ::get_table_name(),
+            RbumKindServ::get_table_name(),
+            RbumKindAttrServ::get_table_name(),
+            Self::get_table_name(),
+            RbumItemAttrServ::get_table_name(),
+            RbumCertConfServ::get_table_name(),
+            RbumCertServ::get_table_name(),
+            RbumSetServ::get_table_name(),
+            RbumSetCateServ::get_table_name(),
+            RbumSetItemServ::get_table_name(),
+            rel_table_name,
+            RbumRelAttrServ::get_table_name(),
+            RbumRelEnvServ::get_table_name(),
+        ];
+        for table_name in table_names.iter().chain(ext_table_names.iter()) {
+            Self::move_own_paths_column(table_name, OWN_PATHS_FIELD.to_string().as_str(), source_own_paths, target_own_paths, funs).await?;
+        }
+        Self::move_own_paths_column(rel_table_name, rbum_rel::Column::ToOwnPaths.as_str(), source_own_paths, target_own_paths, funs).await?;
+
+        Self::touch_rbums(&moved_item_ids, funs).await?;
+        rbum_event_helper::try_notifies(Self::get_table_name(), "u", &moved_item_ids, funs, ctx).await?;
+        let moved_rel_ids = rels
+            .into_iter()
+            .filter(|rel| rbum_scope_helper::is_sub_own_paths(&rel.own_paths, source_own_paths) || rbum_scope_helper::is_sub_own_paths(&rel.to_own_paths, source_own_paths))
+            .map(|rel| rel.id)
+            .collect::<Vec<String>>();
+        rbum_event_helper::try_notifies(rel_table_name, "u", &moved_rel_ids, funs, ctx).await?;
+        Ok(moved_item_ids)
+    }
+
+    /// Replace the ``source_own_paths`` prefix of the own paths column, see ``move_own_paths``
+    async fn move_own_paths_column(table_name: &str, column: &str, source_own_paths: &str, target_own_paths: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        #[derive(Debug, FromQueryResult)]
+        struct OwnPathsResp {
+            pub own_paths: String,
+        }
+
+        // The distinct own paths of a subtree are few, so they are replaced one by one instead of by the string functions of the database
+        let own_paths_list = funs
+            .db()
+            .find_dtos::<OwnPathsResp>(
+                Query::select()
+                    .distinct()
+                    .expr_as(Expr::tbl(Alias::new(table_name), Alias::new(column)), Alias::new("own_paths"))
+                    .from(Alias::new(table_name))
+                    .cond_where(rbum_dialect_helper::sub_own_paths_cond(table_name, column, source_own_paths)),
+            )
+            .await?;
+        for own_paths in own_paths_list {
+            funs.db()
+                .execute(
+                    Query::update()
+                        .table(Alias::new(table_name))
+                        .value(
+                            Alias::new(column),
+                            rbum_scope_helper::rebase_own_paths(&own_paths.own_paths, source_own_paths, target_own_paths).into(),
+                        )
+                        .and_where(Expr::col(Alias::new(column)).eq(own_paths.own_paths.as_str())),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// The sets and categories of the set items must remain visible to them after moving, see ``move_rbum`` and ``move_own_paths``
+    ///
+    /// ``set_item_own_paths`` and ``set_own_paths`` return the own paths after moving of the set items and the sets (categories).
+    async fn check_moved_set_items<F1, F2>(cond: Cond, set_item_own_paths: F1, set_own_paths: F2, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()>
+    where
+        F1: Fn(&str) -> String + Send + Sync,
+        F2: Fn(&str) -> String + Send + Sync,
+    {
+        #[derive(Debug, FromQueryResult)]
+        struct SetItemScopeResp {
+            pub id: String,
+            pub own_paths: String,
+            pub set_own_paths: String,
+            pub set_scope_level: i8,
+            pub cate_own_paths: Option<String>,
+            pub cate_scope_level: Option<i8>,
+        }
+
+        let set_items = funs
+            .db()
+            .find_dtos::<SetItemScopeResp>(
+                Query::select()
+                    .column((rbum_set_item::Entity, rbum_set_item::Column::Id))
+                    .column((rbum_set_item::Entity, rbum_set_item::Column::OwnPaths))
+                    .expr_as(Expr::tbl(rbum_set::Entity, rbum_set::Column::OwnPaths), Alias::new("set_own_paths"))
+                    .expr_as(Expr::tbl(rbum_set::Entity, rbum_set::Column::ScopeLevel), Alias::new("set_scope_level"))
+                    .expr_as(Expr::tbl(rbum_set_cate::Entity, rbum_set_cate::Column::OwnPaths), Alias::new("cate_own_paths"))
+                    .expr_as(Expr::tbl(rbum_set_cate::Entity, rbum_set_cate::Column::ScopeLevel), Alias::new("cate_scope_level"))
+                    .from(rbum_set_item::Entity)
+                    .inner_join(
+                        rbum_set::Entity,
+                        Expr::tbl(rbum_set::Entity, rbum_set::Column::Id).equals(rbum_set_item::Entity, rbum_set_item::Column::RelRbumSetId),
+                    )
+                    .left_join(
+                        rbum_set_cate::Entity,
+                        Cond::all()
+                            .add(Expr::tbl(rbum_set_cate::Entity, rbum_set_cate::Column::RelRbumSetId).equals(rbum_set_item::Entity, rbum_set_item::Column::RelRbumSetId))
+                            .add(Expr::tbl(rbum_set_cate::Entity, rbum_set_cate::Column::SysCode).equals(rbum_set_item::Entity, rbum_set_item::Column::RelRbumSetCateCode)),
+                    )
+                    .cond_where(cond),
+            )
+            .await?;
+        let visible = |record_own_paths: &str, record_scope_level: i8, own_paths: &str| {
+            rbum_scope_helper::check_scope(
+                record_own_paths,
+                Some(record_scope_level),
+                &RbumBasicFilterReq::default(),
+                &TardisContext {
+                    own_paths: own_paths.to_string(),
+                    ..ctx.clone()
+                },
+            )
+        };
+        for set_item in set_items {
+            let moved_own_paths = set_item_own_paths(&set_item.own_paths);
+            let mut scopes = vec![(set_item.set_own_paths, set_item.set_scope_level)];
+            if let (Some(cate_own_paths), Some(cate_scope_level)) = (set_item.cate_own_paths, set_item.cate_scope_level) {
+                scopes.push((cate_own_paths, cate_scope_level));
+            }
+            for (own_paths, scope_level) in scopes {
+                if visible(&own_paths, scope_level, &set_item.own_paths) && !visible(&set_own_paths(&own_paths), scope_level, &moved_own_paths) {
+                    return Err(funs.err().conflict(
+                        &Self::get_obj_name(),
+                        "move",
+                        &format!(
+                            "the set or category {} of set item {} is invisible to {} after moving",
+                            own_paths, set_item.id, moved_own_paths
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[async_trait]
 pub trait RbumItemCrudOperation<'a, EXT, AddReq, ModifyReq, SummaryResp, DetailResp, ItemFilterReq>
 where
@@ -300,20 +840,37 @@ where
     async fn modify_item(id: &str, modify_req: &mut ModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         Self::before_modify_item(id, modify_req, funs, ctx).await?;
         let item_modify_req = Self::package_item_modify(id, modify_req, funs, ctx).await?;
+        let item_modified = item_modify_req.is_some();
         if let Some(mut item_modify_req) = item_modify_req {
             RbumItemServ::modify_rbum(id, &mut item_modify_req, funs, ctx).await?;
         } else {
             RbumItemServ::check_ownership(id, funs, ctx).await?;
         }
+        let before = rbum_event_helper::try_get_snapshot(Self::get_ext_table_name(), "u", id, funs).await?;
         let ext_domain = Self::package_ext_modify(id, modify_req, funs, ctx).await?;
         if let Some(ext_domain) = ext_domain {
             funs.db().update_one(ext_domain, ctx).await?;
+            // The version of the item is used as the version of the whole resource
+            if !item_modified {
+                RbumItemServ::touch_rbums(&[id.to_string()], funs).await?;
+            }
         }
         Self::after_modify_item(id, modify_req, funs, ctx).await?;
-        rbum_event_helper::try_notify(Self::get_ext_table_name(), "u", id, funs, ctx).await?;
+        let after = rbum_event_helper::try_get_snapshot(Self::get_ext_table_name(), "u", id, funs).await?;
+        rbum_event_helper::try_notify_with_snapshot(Self::get_ext_table_name(), "u", id, before, after, funs, ctx).await?;
         Ok(())
     }
 
+    /// Modify only if the item has not been modified since `expected_version`
+    ///
+    /// The version of the rbum item is checked, a stale write is rejected with a conflict error, see ``rbum_etag_helper``.
+    async fn modify_item_if_match(id: &str, expected_version: Option<i64>, modify_req: &mut ModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(id, expected_version, funs, ctx).await?;
+        }
+        Self::modify_item(id, modify_req, funs, ctx).await
+    }
+
     // ----------------------------- Delete -------------------------------
 
     async fn package_delete(id: &str, _funs: &TardisFunsInst<'a>, _ctx: &TardisContext) -> TardisResult<Select<EXT::Entity>> {
@@ -331,27 +888,21 @@ where
     async fn delete_item(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<u64> {
         let deleted_item = Self::before_delete_item(id, funs, ctx).await?;
         let select = Self::package_delete(id, funs, ctx).await?;
+        let delete_records = funs.db().soft_delete_custom(select, "id").await?;
+        RbumItemServ::add_deleted_records(&delete_records, funs, ctx).await?;
+        RbumItemServ::delete_rbum(id, funs, ctx).await?;
         #[cfg(feature = "with-mq")]
         {
-            let delete_records = funs.db().soft_delete_custom(select, "id").await?;
-            RbumItemServ::delete_rbum(id, funs, ctx).await?;
             let mq_topic_entity_deleted = &funs.rbum_conf_mq_topic_entity_deleted();
             let mq_header = std::collections::HashMap::from([(funs.rbum_conf_mq_header_name_operator(), ctx.owner.clone())]);
             for delete_record in &delete_records {
-                funs.mq().request(mq_topic_entity_deleted, TardisFuns::json.obj_to_string(delete_record)?, &mq_header).await?;
+                rbum_event_helper::add_outbox(mq_topic_entity_deleted, &mq_header, TardisFuns::json.obj_to_string(delete_record)?, id, funs, ctx).await?;
             }
-            Self::after_delete_item(id, &deleted_item, funs, ctx).await?;
-            rbum_event_helper::try_notify(Self::get_ext_table_name(), "d", id, funs, ctx).await?;
-            Ok(delete_records.len() as u64)
-        }
-        #[cfg(not(feature = "with-mq"))]
-        {
-            let delete_records = funs.db().soft_delete(select, &ctx.owner).await?;
-            RbumItemServ::delete_rbum(id, funs, ctx).await?;
-            Self::after_delete_item(id, &deleted_item, funs, ctx).await?;
-            rbum_event_helper::try_notify(Self::get_ext_table_name(), "d", &id, funs, ctx).await?;
-            Ok(delete_records.len() as u64)
         }
+        Self::after_delete_item(id, &deleted_item, funs, ctx).await?;
+        let before = delete_records.first().map(|delete_record| TardisFuns::json.str_to_json(&delete_record.content)).transpose()?;
+        rbum_event_helper::try_notify_with_snapshot(Self::get_ext_table_name(), "d", id, before, None, funs, ctx).await?;
+        Ok(delete_records.len() as u64)
     }
 
     async fn delete_item_with_all_rels(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<u64> {
@@ -449,37 +1000,334 @@ where
         Self::delete_item(id, funs, ctx).await
     }
 
+    // ----------------------------- Batch -------------------------------
+
+    /// Add items in batch, the same as ``RbumCrudOperation::add_rbums``
+    async fn add_items(add_reqs: &mut [AddReq], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumBatchResp> {
+        let mut resp = RbumBatchResp::default();
+        let mut item_add_reqs = Vec::with_capacity(add_reqs.len());
+        let mut indexes = Vec::with_capacity(add_reqs.len());
+        for (idx, add_req) in add_reqs.iter_mut().enumerate() {
+            let item_add_req = match Self::before_add_item(add_req, funs, ctx).await {
+                Ok(_) => Self::package_item_add(add_req, funs, ctx).await,
+                Err(e) => Err(e),
+            };
+            match item_add_req {
+                Ok(item_add_req) => {
+                    item_add_reqs.push(RbumItemAddReq {
+                        id: item_add_req.id.clone(),
+                        code: item_add_req.code.clone(),
+                        name: item_add_req.name.clone(),
+                        rel_rbum_kind_id: Self::get_rbum_kind_id(),
+                        rel_rbum_domain_id: Self::get_rbum_domain_id(),
+                        scope_level: item_add_req.scope_level.clone(),
+                        disabled: item_add_req.disabled,
+                    });
+                    indexes.push(idx);
+                }
+                Err(e) => resp.add_failed(idx, None, &e),
+            }
+        }
+        let item_resp = RbumItemServ::add_rbums(&mut item_add_reqs, funs, ctx).await?;
+        let added = resp.merge_failed(item_resp, &indexes);
+        let mut ext_domains = Vec::with_capacity(added.len());
+        for (idx, id) in &added {
+            ext_domains.push(Self::package_ext_add(id, &add_reqs[*idx], funs, ctx).await?);
+        }
+        if !ext_domains.is_empty() {
+            funs.db().insert_many(ext_domains, ctx).await?;
+        }
+        for (_, id) in &added {
+            Self::after_add_item(id, funs, ctx).await?;
+        }
+        resp.succeed_ids = added.into_iter().map(|(_, id)| id).collect();
+        rbum_event_helper::try_notifies(Self::get_ext_table_name(), "c", &resp.succeed_ids, funs, ctx).await?;
+        Ok(resp)
+    }
+
+    /// Modify items in batch, the same as ``RbumCrudOperation::modify_rbums``
+    async fn modify_items(modify_reqs: &mut [(String, ModifyReq)], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumBatchResp> {
+        let ids = modify_reqs.iter().map(|(id, _)| id.to_string()).collect::<Vec<String>>();
+        let owned_ids = RbumItemServ::find_owned_ids(&ids, funs, ctx).await?;
+        let mut resp = RbumBatchResp::default();
+        let mut item_modify_reqs = Vec::with_capacity(modify_reqs.len());
+        let mut item_indexes = Vec::with_capacity(modify_reqs.len());
+        let mut ext_domains = Vec::with_capacity(modify_reqs.len());
+        with_checked_ownerships(RbumItemServ::get_table_name(), &owned_ids, async {
+            for (idx, (id, modify_req)) in modify_reqs.iter_mut().enumerate() {
+                if !owned_ids.contains(id.as_str()) {
+                    resp.add_failed(
+                        idx,
+                        Some(id.as_str()),
+                        &funs.err().not_found(
+                            &Self::get_obj_name(),
+                            "modify",
+                            &format!("ownership {}.{} is illegal by {}", Self::get_obj_name(), id, ctx.owner),
+                        ),
+                    );
+                    continue;
+                }
+                let packaged: TardisResult<_> = async {
+                    Self::before_modify_item(id, modify_req, funs, ctx).await?;
+                    let item_modify_req = Self::package_item_modify(id, modify_req, funs, ctx).await?;
+                    let ext_domain = Self::package_ext_modify(id, modify_req, funs, ctx).await?;
+                    Ok((item_modify_req, ext_domain))
+                }
+                .await;
+                match packaged {
+                    Ok((item_modify_req, ext_domain)) => {
+                        if let Some(item_modify_req) = item_modify_req {
+                            item_modify_reqs.push((id.to_string(), item_modify_req));
+                            item_indexes.push(idx);
+                        }
+                        ext_domains.push((idx, id.to_string(), ext_domain));
+                    }
+                    Err(e) => resp.add_failed(idx, Some(id.as_str()), &e),
+                }
+            }
+        })
+        .await;
+        let item_resp = RbumItemServ::modify_rbums(&mut item_modify_reqs, funs, ctx).await?;
+        let item_modified_indexes = resp.merge_failed(item_resp, &item_indexes).into_iter().map(|(idx, _)| idx).collect::<HashSet<usize>>();
+        let failed_indexes = resp.failed.iter().map(|failed| failed.index as usize).collect::<HashSet<usize>>();
+        let mut touched_ids = Vec::new();
+        for (idx, id, ext_domain) in ext_domains {
+            if failed_indexes.contains(&idx) {
+                continue;
+            }
+            if let Some(ext_domain) = ext_domain {
+                funs.db().update_one(ext_domain, ctx).await?;
+                if !item_modified_indexes.contains(&idx) {
+                    touched_ids.push(id.clone());
+                }
+            }
+            Self::after_modify_item(&id, &mut modify_reqs[idx].1, funs, ctx).await?;
+            resp.succeed_ids.push(id);
+        }
+        RbumItemServ::touch_rbums(&touched_ids, funs).await?;
+        rbum_event_helper::try_notifies(Self::get_ext_table_name(), "u", &resp.succeed_ids, funs, ctx).await?;
+        Ok(resp)
+    }
+
+    async fn package_deletes(ids: &[String], _funs: &TardisFunsInst<'a>, _ctx: &TardisContext) -> TardisResult<Select<EXT::Entity>> {
+        Ok(EXT::Entity::find().filter(Expr::col(ID_FIELD.clone()).is_in(ids.iter().map(|id| id.as_str()).collect::<Vec<&str>>())))
+    }
+
+    /// Delete items in batch, the same as ``RbumCrudOperation::delete_rbums``
+    async fn delete_items(ids: &[String], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumBatchResp> {
+        let owned_ids = RbumItemServ::find_owned_ids(ids, funs, ctx).await?;
+        let mut resp = RbumBatchResp::default();
+        let mut item_ids = Vec::with_capacity(ids.len());
+        let mut indexes = Vec::with_capacity(ids.len());
+        let mut deleted_items = HashMap::with_capacity(ids.len());
+        with_checked_ownerships(RbumItemServ::get_table_name(), &owned_ids, async {
+            for (idx, id) in ids.iter().enumerate() {
+                if !owned_ids.contains(id.as_str()) {
+                    resp.add_failed(
+                        idx,
+                        Some(id.as_str()),
+                        &funs.err().not_found(
+                            &Self::get_obj_name(),
+                            "delete",
+                            &format!("ownership {}.{} is illegal by {}", Self::get_obj_name(), id, ctx.owner),
+                        ),
+                    );
+                    continue;
+                }
+                match Self::before_delete_item(id, funs, ctx).await {
+                    Ok(deleted_item) => {
+                        item_ids.push(id.to_string());
+                        indexes.push(idx);
+                        deleted_items.insert(idx, deleted_item);
+                    }
+                    Err(e) => resp.add_failed(idx, Some(id.as_str()), &e),
+                }
+            }
+        })
+        .await;
+        let item_resp = RbumItemServ::delete_rbums(&item_ids, funs, ctx).await?;
+        let deleted = resp.merge_failed(item_resp, &indexes);
+        resp.succeed_ids = deleted.iter().map(|(_, id)| id.to_string()).collect();
+        if resp.succeed_ids.is_empty() {
+            return Ok(resp);
+        }
+        // The records are gone after deletion, so their own paths are read for the events in advance
+        let own_paths = rbum_event_helper::try_get_own_paths(Self::get_ext_table_name(), "d", &resp.succeed_ids, funs).await?;
+        let select = Self::package_deletes(&resp.succeed_ids, funs, ctx).await?;
+        let delete_records = funs.db().soft_delete_custom(select, "id").await?;
+        RbumItemServ::add_deleted_records(&delete_records, funs, ctx).await?;
+        #[cfg(feature = "with-mq")]
+        {
+            let mq_topic_entity_deleted = &funs.rbum_conf_mq_topic_entity_deleted();
+            let mq_header = std::collections::HashMap::from([(funs.rbum_conf_mq_header_name_operator(), ctx.owner.clone())]);
+            for delete_record in &delete_records {
+                rbum_event_helper::add_outbox(
+                    mq_topic_entity_deleted,
+                    &mq_header,
+                    TardisFuns::json.obj_to_string(delete_record)?,
+                    &delete_record.record_id,
+                    funs,
+                    ctx,
+                )
+                .await?;
+            }
+        }
+        for (idx, id) in &deleted {
+            Self::after_delete_item(id, &deleted_items.remove(idx).unwrap_or(None), funs, ctx).await?;
+        }
+        rbum_event_helper::try_notifies_with_own_paths(Self::get_ext_table_name(), "d", &resp.succeed_ids, &own_paths, funs, ctx).await?;
+        Ok(resp)
+    }
+
+    // ----------------------------- Recycle -------------------------------
+
+    async fn paginate_deleted_items(
+        with_sub_own_paths: bool,
+        page_number: u64,
+        page_size: u64,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<TardisPage<RbumDeletedResp>> {
+        let mut deleted_records = RbumItemServ::paginate_deleted_rbums_with_table_name(
+            RbumItemServ::get_table_name(),
+            Some(Self::get_rbum_kind_id().as_str()),
+            with_sub_own_paths,
+            page_number,
+            page_size,
+            funs,
+            ctx,
+        )
+        .await?;
+        let ids = deleted_records.records.iter().map(|deleted_record| deleted_record.id.clone()).collect::<Vec<String>>();
+        let mut deleted_ext_records = HashMap::new();
+        // The latest deleted ext record is used if the record has been deleted multiple times
+        for deleted_ext_record in RbumItemServ::find_deleted_rbums_with_table_name(Self::get_ext_table_name(), &ids, funs, ctx).await? {
+            deleted_ext_records.entry(deleted_ext_record.id).or_insert(deleted_ext_record.content);
+        }
+        for deleted_record in deleted_records.records.iter_mut() {
+            // Merge the ext record into the item record
+            if let (Some(content), Some(tardis::serde_json::Value::Object(ext_content))) = (deleted_record.content.as_object_mut(), deleted_ext_records.remove(&deleted_record.id))
+            {
+                for (field, value) in ext_content {
+                    content.entry(field).or_insert(value);
+                }
+            }
+        }
+        Ok(deleted_records)
+    }
+
+    async fn before_restore_item(_: &str, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        Ok(())
+    }
+
+    async fn after_restore_item(_: &str, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        Ok(())
+    }
+
+    async fn restore_item(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::before_restore_item(id, funs, ctx).await?;
+        let deleted_ext_record = RbumItemServ::get_deleted_rbum_with_table_name(id, Self::get_ext_table_name(), funs, ctx).await?;
+        // Implicit ownership and scope check
+        RbumItemServ::restore_rbum(id, funs, ctx).await?;
+        RbumItemServ::do_restore_rbum_with_table_name(&deleted_ext_record, Self::get_ext_table_name(), funs).await?;
+        Self::after_restore_item(id, funs, ctx).await?;
+        rbum_event_helper::try_notify(Self::get_ext_table_name(), "r", id, funs, ctx).await?;
+        Ok(())
+    }
+
+    // ----------------------------- Move -------------------------------
+
+    async fn before_move_item(_: &str, _: &str, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        Ok(())
+    }
+
+    async fn after_move_item(_: &str, _: &str, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        Ok(())
+    }
+
+    /// Move the item, its ext record and its dependent records to ``target_own_paths``, see ``RbumItemServ::move_rbum``
+    async fn move_item(id: &str, target_own_paths: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::before_move_item(id, target_own_paths, funs, ctx).await?;
+        let before = rbum_event_helper::try_get_snapshot(Self::get_ext_table_name(), "u", id, funs).await?;
+        // Implicit ownership and scope check
+        let moved_rel_ids = RbumItemServ::move_rbum(id, target_own_paths, funs, ctx).await?;
+        funs.db()
+            .execute(
+                Query::update().table(Alias::new(Self::get_ext_table_name())).value(OWN_PATHS_FIELD.clone(), target_own_paths.into()).and_where(Expr::col(ID_FIELD.clone()).eq(id)),
+            )
+            .await?;
+        Self::after_move_item(id, target_own_paths, funs, ctx).await?;
+        let after = rbum_event_helper::try_get_snapshot(Self::get_ext_table_name(), "u", id, funs).await?;
+        rbum_event_helper::try_notify_with_snapshot(Self::get_ext_table_name(), "u", id, before, after, funs, ctx).await?;
+        rbum_event_helper::try_notifies(RbumRelServ::get_table_name(), "u", &moved_rel_ids, funs, ctx).await?;
+        Ok(())
+    }
+
     // ----------------------------- Query -------------------------------
 
     async fn package_item_query(is_detail: bool, filter: &ItemFilterReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<SelectStatement> {
+        let basic = filter.basic();
+        // The sorts are applied below, the sort field belongs to the ext table rather than the rbum item
         let mut query = RbumItemServ::package_query(
             is_detail,
             &RbumBasicFilterReq {
-                ignore_scope: filter.basic().ignore_scope,
-                rel_ctx_owner: filter.basic().rel_ctx_owner,
-                own_paths: filter.basic().own_paths.clone(),
-                with_sub_own_paths: filter.basic().with_sub_own_paths,
-                ids: filter.basic().ids.clone(),
-                scope_level: filter.basic().scope_level.clone(),
-                enabled: filter.basic().enabled,
-                name: filter.basic().name.clone(),
-                code: filter.basic().code.clone(),
-                rbum_kind_id: if filter.basic().rbum_kind_id.is_some() {
-                    filter.basic().rbum_kind_id.clone()
+                ignore_scope: basic.ignore_scope,
+                rel_ctx_owner: basic.rel_ctx_owner,
+                own_paths: basic.own_paths.clone(),
+                with_sub_own_paths: basic.with_sub_own_paths,
+                ids: basic.ids.clone(),
+                exclude_ids: basic.exclude_ids.clone(),
+                owners: basic.owners.clone(),
+                scope_level: basic.scope_level.clone(),
+                enabled: basic.enabled,
+                name: basic.name.clone(),
+                exact_name: basic.exact_name.clone(),
+                code: basic.code.clone(),
+                exact_code: basic.exact_code.clone(),
+                rbum_kind_id: if basic.rbum_kind_id.is_some() || basic.rbum_kind_ids.is_some() {
+                    basic.rbum_kind_id.clone()
                 } else {
                     Some(Self::get_rbum_kind_id())
                 },
-                rbum_domain_id: if filter.basic().rbum_domain_id.is_some() {
-                    filter.basic().rbum_domain_id.clone()
+                rbum_kind_ids: None,
+                rbum_domain_id: if basic.rbum_domain_id.is_some() {
+                    basic.rbum_domain_id.clone()
                 } else {
                     Some(Self::get_rbum_domain_id())
                 },
-                desc_by_sort: filter.basic().desc_by_sort,
+                create_time_start: basic.create_time_start,
+                create_time_end: basic.create_time_end,
+                update_time_start: basic.update_time_start,
+                update_time_end: basic.update_time_end,
+                desc_by_sort: None,
+                sorts: None,
             },
             funs,
             ctx,
         )
         .await?;
+        if let Some(rbum_kind_ids) = &basic.rbum_kind_ids {
+            query.and_where(Expr::tbl(rbum_item::Entity, rbum_item::Column::RelRbumKindId).is_in(rbum_kind_ids.clone()));
+        }
+        if let Some(desc_by_sort) = basic.desc_by_sort {
+            query.order_by(
+                (Alias::new(Self::get_ext_table_name()), SORT_FIELD.clone()),
+                if desc_by_sort { Order::Desc } else { Order::Asc },
+            );
+        }
+        if let Some(sorts) = &basic.sorts {
+            for sort in sorts {
+                let table_name = if sort.field == RbumSortFieldKind::Sort {
+                    Self::get_ext_table_name()
+                } else {
+                    RbumItemServ::get_table_name()
+                };
+                query.order_by(
+                    (Alias::new(table_name), Alias::new(sort.field.to_field_name())),
+                    if sort.desc { Order::Desc } else { Order::Asc },
+                );
+            }
+        }
         fn package_rel(query: &mut SelectStatement, rel_table: Alias, rbum_item_rel_filter_req: &RbumItemRelFilterReq) {
             if rbum_item_rel_filter_req.rel_by_from {
                 query.join_as(
@@ -515,6 +1363,10 @@ where
         if let Some(rbum_item_rel_filter_req) = &filter.rel2() {
             package_rel(&mut query, Alias::new("rbum_rel2"), rbum_item_rel_filter_req);
         }
+        if let Some(attrs) = filter.attrs() {
+            let rbum_kind_id = filter.basic().rbum_kind_id.clone().unwrap_or_else(Self::get_rbum_kind_id);
+            RbumItemAttrServ::package_item_attr_query(&mut query, &rbum_kind_id, Self::get_ext_table_name(), attrs, funs, ctx).await?;
+        }
         Ok(query)
     }
 
@@ -642,6 +1494,37 @@ where
         })
     }
 
+    /// Paginate by cursor, the sort key is of the rbum item, see ``rbum_cursor_helper``
+    async fn paginate_items_by_cursor(
+        filter: &ItemFilterReq,
+        cursor_req: &RbumCursorReq,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumCursorPage<SummaryResp>> {
+        Self::do_paginate_items_by_cursor(filter, cursor_req, funs, ctx).await
+    }
+
+    async fn do_paginate_items_by_cursor(
+        filter: &ItemFilterReq,
+        cursor_req: &RbumCursorReq,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumCursorPage<SummaryResp>> {
+        if cursor_req.sort_by == RbumCursorSortKind::Sort {
+            return Err(funs.err().bad_request(&Self::get_obj_name(), "paginate", "items can not be sorted by sort"));
+        }
+        rbum_cursor_helper::check_filter_sorts(filter.basic(), funs)?;
+        let mut query = Self::package_item_query(false, filter, funs, ctx).await?;
+        query.inner_join(
+            Alias::new(Self::get_ext_table_name()),
+            Expr::tbl(Alias::new(Self::get_ext_table_name()), ID_FIELD.clone()).equals(rbum_item::Entity, rbum_item::Column::Id),
+        );
+        Self::package_ext_query(&mut query, false, filter, funs, ctx).await?;
+        rbum_cursor_helper::package_cursor_query(&mut query, RbumItemServ::get_table_name(), cursor_req, funs)?;
+        let records = funs.db().find_dtos(&query).await?;
+        rbum_cursor_helper::package_cursor_page(records, cursor_req, funs)
+    }
+
     async fn paginate_detail_items(
         filter: &ItemFilterReq,
         page_number: u64,
@@ -863,7 +1746,41 @@ impl<'a> RbumCrudOperation<'a, rbum_item_attr::ActiveModel, RbumItemAttrAddReq,
         if rbum_kind_attr.idx {
             return Err(funs.err().bad_request(&Self::get_obj_name(), "add", "index extension fields cannot be added using this function"));
         }
-        Ok(())
+        Self::check_item_attr_values(
+            std::slice::from_ref(&rbum_kind_attr),
+            &mut HashMap::from([(rbum_kind_attr.name.clone(), add_req.value.clone())]),
+            false,
+            funs,
+        )
+    }
+
+    async fn before_modify_rbum(id: &str, modify_req: &mut RbumItemAttrModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::check_ownership(id, funs, ctx).await?;
+        let rel_rbum_kind_attr_id = Self::peek_rbum(id, &RbumItemAttrFilterReq::default(), funs, ctx).await?.rel_rbum_kind_attr_id;
+        let rbum_kind_attr = RbumKindAttrServ::peek_rbum(&rel_rbum_kind_attr_id, &RbumKindAttrFilterReq::default(), funs, ctx).await?;
+        Self::check_item_attr_values(
+            std::slice::from_ref(&rbum_kind_attr),
+            &mut HashMap::from([(rbum_kind_attr.name.clone(), modify_req.value.clone())]),
+            false,
+            funs,
+        )
+    }
+
+    async fn after_add_rbum(_: &str, add_req: &RbumItemAttrAddReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        // The attributes are part of the item, so the version of the item is changed
+        RbumItemServ::touch_rbums(&[add_req.rel_rbum_item_id.to_string()], funs).await
+    }
+
+    async fn after_modify_rbum(id: &str, _: &mut RbumItemAttrModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        let rel_rbum_item_id = Self::peek_rbum(id, &RbumItemAttrFilterReq::default(), funs, ctx).await?.rel_rbum_item_id;
+        RbumItemServ::touch_rbums(&[rel_rbum_item_id], funs).await
+    }
+
+    async fn before_delete_rbum(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<Option<RbumItemAttrDetailResp>> {
+        Self::check_ownership(id, funs, ctx).await?;
+        let rel_rbum_item_id = Self::peek_rbum(id, &RbumItemAttrFilterReq::default(), funs, ctx).await?.rel_rbum_item_id;
+        RbumItemServ::touch_rbums(&[rel_rbum_item_id], funs).await?;
+        Ok(None)
     }
 
     async fn package_modify(id: &str, modify_req: &RbumItemAttrModifyReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_item_attr::ActiveModel> {
@@ -940,42 +1857,64 @@ impl<'a> RbumItemAttrServ {
     pub async fn add_or_modify_item_attrs(add_req: &RbumItemAttrsAddOrModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         // Implicit rel_rbum_kind_attr scope check
         let rbum_kind_attrs = Self::find_item_attr_defs_by_item_id(&add_req.rel_rbum_item_id, funs, ctx).await?;
-        let in_main_table_attrs = rbum_kind_attrs.iter().filter(|i| add_req.values.contains_key(&i.name) && i.main_column).collect::<Vec<&RbumKindAttrSummaryResp>>();
-        let in_ext_table_attrs = rbum_kind_attrs.iter().filter(|i| add_req.values.contains_key(&i.name) && !i.main_column).collect::<Vec<&RbumKindAttrSummaryResp>>();
+        // kind attr id -> item attr id of the stored values in the extension table
+        let stored_item_attr_ids = Self::find_rbums(
+            &RbumItemAttrFilterReq {
+                basic: Default::default(),
+                rel_rbum_item_id: Some(add_req.rel_rbum_item_id.to_string()),
+                rel_rbum_kind_attr_id: None,
+            },
+            None,
+            None,
+            funs,
+            ctx,
+        )
+        .await?
+        .into_iter()
+        .map(|item_attr| (item_attr.rel_rbum_kind_attr_id, item_attr.id))
+        .collect::<HashMap<String, String>>();
+        // The values not stored yet are validated as added (filled with the default values and checked if required), the others as modified,
+        // the validated values (with the default values) are stored
+        let (new_attrs, stored_attrs): (Vec<RbumKindAttrSummaryResp>, Vec<RbumKindAttrSummaryResp>) =
+            rbum_kind_attrs.into_iter().partition(|i| !i.main_column && !stored_item_attr_ids.contains_key(&i.id));
+        let mut values = add_req.values.clone();
+        Self::check_item_attr_values(&new_attrs, &mut values, true, funs)?;
+        Self::check_item_attr_values(&stored_attrs, &mut values, false, funs)?;
+        let in_main_table_attrs = stored_attrs.iter().filter(|i| values.contains_key(&i.name) && i.main_column).collect::<Vec<&RbumKindAttrSummaryResp>>();
+        let in_ext_table_attrs = new_attrs.iter().chain(stored_attrs.iter()).filter(|i| values.contains_key(&i.name) && !i.main_column).collect::<Vec<&RbumKindAttrSummaryResp>>();
 
         if !in_main_table_attrs.is_empty() {
             // Implicit rel_rbum_item scope check
             let rel_rbum_kind_id = RbumItemServ::peek_rbum(&add_req.rel_rbum_item_id, &RbumBasicFilterReq::default(), funs, ctx).await?.rel_rbum_kind_id;
             let main_table_name = RbumKindServ::peek_rbum(&rel_rbum_kind_id, &RbumBasicFilterReq::default(), funs, ctx).await?.ext_table_name;
 
+            let managed_columns = RbumKindAttrServ::find_managed_ext_columns(&main_table_name, funs).await?;
+
             let mut update_statement = Query::update();
             update_statement.table(Alias::new(&main_table_name));
 
             for in_main_table_attr in in_main_table_attrs {
                 let column_name = Alias::new(&in_main_table_attr.name);
-                let column_val = add_req.values.get(&in_main_table_attr.name).unwrap().clone();
-                update_statement.value(column_name, column_val.into());
+                let column_val = values.get(&in_main_table_attr.name).unwrap().clone();
+                if !managed_columns.contains(&in_main_table_attr.name) {
+                    update_statement.value(column_name, column_val.into());
+                } else if column_val.is_empty() {
+                    update_statement.value_expr(column_name, Expr::cust("NULL"));
+                } else {
+                    update_statement.value(column_name, Self::to_ext_column_value(&column_val, in_main_table_attr, funs)?);
+                }
             }
             update_statement.and_where(Expr::col(ID_FIELD.clone()).eq(add_req.rel_rbum_item_id.as_str()));
             funs.db().execute(&update_statement).await?;
+            RbumItemServ::touch_rbums(&[add_req.rel_rbum_item_id.to_string()], funs).await?;
         }
 
         if !in_ext_table_attrs.is_empty() {
             for in_ext_table_attr in in_ext_table_attrs {
-                let column_val = add_req.values.get(&in_ext_table_attr.name).unwrap().clone();
-                let exist_item_attr_ids = Self::find_id_rbums(
-                    &RbumItemAttrFilterReq {
-                        basic: Default::default(),
-                        rel_rbum_item_id: Some(add_req.rel_rbum_item_id.to_string()),
-                        rel_rbum_kind_attr_id: Some(in_ext_table_attr.id.to_string()),
-                    },
-                    None,
-                    None,
-                    funs,
-                    ctx,
-                )
-                .await?;
-                if exist_item_attr_ids.is_empty() {
+                let column_val = values.get(&in_ext_table_attr.name).unwrap().clone();
+                if let Some(item_attr_id) = stored_item_attr_ids.get(&in_ext_table_attr.id) {
+                    Self::modify_rbum(item_attr_id, &mut RbumItemAttrModifyReq { value: column_val }, funs, ctx).await?;
+                } else {
                     Self::add_rbum(
                         &mut RbumItemAttrAddReq {
                             value: column_val,
@@ -986,8 +1925,6 @@ impl<'a> RbumItemAttrServ {
                         ctx,
                     )
                     .await?;
-                } else {
-                    Self::modify_rbum(exist_item_attr_ids.get(0).unwrap(), &mut RbumItemAttrModifyReq { value: column_val }, funs, ctx).await?;
                 }
             }
         }
@@ -995,6 +1932,40 @@ impl<'a> RbumItemAttrServ {
         Ok(())
     }
 
+    /// Validate the values (name -> value) against the attribute definitions of the kind, see ``rbum_kind_attr_helper``
+    ///
+    /// When ``is_add`` is true, default values are filled in and missing required values are reported.
+    pub async fn validate_item_attr_values(
+        rel_rbum_kind_id: &str,
+        values: &mut HashMap<String, String>,
+        is_add: bool,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<()> {
+        let rbum_kind_attrs = RbumKindAttrServ::find_rbums(
+            &RbumKindAttrFilterReq {
+                basic: RbumBasicFilterReq {
+                    rbum_kind_id: Some(rel_rbum_kind_id.to_string()),
+                    ..Default::default()
+                },
+            },
+            None,
+            None,
+            funs,
+            ctx,
+        )
+        .await?;
+        Self::check_item_attr_values(&rbum_kind_attrs, values, is_add, funs)
+    }
+
+    fn check_item_attr_values(rbum_kind_attrs: &[RbumKindAttrSummaryResp], values: &mut HashMap<String, String>, is_add: bool, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        let errors = rbum_kind_attr_helper::validate_values(rbum_kind_attrs, values, is_add);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(funs.err().bad_request(&Self::get_obj_name(), "validate", &TardisFuns::json.obj_to_string(&errors)?))
+    }
+
     pub async fn find_item_attr_values(rbum_item_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<HashMap<String, String>> {
         let rbum_kind_attrs = Self::find_item_attr_defs_by_item_id(rbum_item_id, funs, ctx).await?;
         let in_main_table_attrs = rbum_kind_attrs.iter().filter(|i| i.main_column).collect::<Vec<&RbumKindAttrSummaryResp>>();
@@ -1014,8 +1985,13 @@ impl<'a> RbumItemAttrServ {
             select_statement.and_where(Expr::col(ID_FIELD.clone()).eq(rbum_item_id));
             let select_statement = funs.db().raw_conn().get_database_backend().build(&select_statement);
             if let Some(row) = funs.db().raw_conn().query_one(select_statement).await? {
+                let managed_columns = RbumKindAttrServ::find_managed_ext_columns(&ext_table_name, funs).await?;
                 for in_main_table_attr in &in_main_table_attrs {
-                    let value: String = row.try_get("", &in_main_table_attr.name)?;
+                    let value = if managed_columns.contains(&in_main_table_attr.name) {
+                        Self::get_ext_column_value(&row, in_main_table_attr)?
+                    } else {
+                        row.try_get("", &in_main_table_attr.name)?
+                    };
                     values.insert(in_main_table_attr.name.clone(), value);
                 }
             }
@@ -1039,6 +2015,139 @@ impl<'a> RbumItemAttrServ {
         }
         Ok(values)
     }
+
+    /// Translate the attribute predicates of the item filter into joins
+    ///
+    /// ``main_column`` attributes are compared on the ext table, others on a ``rbum_item_attr`` join per predicate.
+    pub async fn package_item_attr_query(
+        query: &mut SelectStatement,
+        rbum_kind_id: &str,
+        ext_table_name: &str,
+        predicates: &[RbumItemAttrPredicateReq],
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<()> {
+        if predicates.is_empty() {
+            return Ok(());
+        }
+        let rbum_kind_attrs = RbumKindAttrServ::find_rbums(
+            &RbumKindAttrFilterReq {
+                basic: RbumBasicFilterReq {
+                    rbum_kind_id: Some(rbum_kind_id.to_string()),
+                    ..Default::default()
+                },
+            },
+            None,
+            None,
+            funs,
+            ctx,
+        )
+        .await?;
+        let managed_columns = if rbum_kind_attrs.iter().any(|i| i.main_column) {
+            RbumKindAttrServ::find_managed_ext_columns(ext_table_name, funs).await?
+        } else {
+            HashSet::new()
+        };
+        let ext_table = Alias::new("rbum_item_ext");
+        let mut ext_table_joined = false;
+        for (idx, predicate) in predicates.iter().enumerate() {
+            let rbum_kind_attr = rbum_kind_attrs
+                .iter()
+                .find(|i| i.name == predicate.attr_name)
+                .ok_or_else(|| funs.err().not_found(&Self::get_obj_name(), "find", &format!("attribute {} is not found", predicate.attr_name)))?;
+            let typed_column = rbum_kind_attr.main_column && managed_columns.contains(&rbum_kind_attr.name);
+            let column = if rbum_kind_attr.main_column {
+                if !ext_table_joined {
+                    query.join_as(
+                        JoinType::InnerJoin,
+                        Alias::new(ext_table_name),
+                        ext_table.clone(),
+                        Expr::tbl(ext_table.clone(), ID_FIELD.clone()).equals(rbum_item::Entity, rbum_item::Column::Id),
+                    );
+                    ext_table_joined = true;
+                }
+                Expr::tbl(ext_table.clone(), Alias::new(&rbum_kind_attr.name))
+            } else {
+                let attr_table_name = format!("rbum_item_attr{}", idx);
+                let attr_table = Alias::new(&attr_table_name);
+                query.join_as(
+                    JoinType::InnerJoin,
+                    rbum_item_attr::Entity,
+                    attr_table.clone(),
+                    Cond::all()
+                        .add(Expr::tbl(attr_table.clone(), rbum_item_attr::Column::RelRbumItemId).equals(rbum_item::Entity, rbum_item::Column::Id))
+                        .add(Expr::tbl(attr_table.clone(), rbum_item_attr::Column::RelRbumKindAttrId).eq(rbum_kind_attr.id.as_str())),
+                );
+                if rbum_kind_attr.data_type == RbumDataTypeKind::Number && predicate.op != RbumOperatorKind::Like {
+                    // The values are stored as text, compare them as numbers rather than as strings
+                    Expr::expr(rbum_dialect_helper::text_to_number(
+                        &attr_table_name,
+                        rbum_item_attr::Column::Value.as_str(),
+                        funs.db().raw_conn().get_database_backend(),
+                    ))
+                } else {
+                    Expr::tbl(attr_table, rbum_item_attr::Column::Value)
+                }
+            };
+            let values = predicate
+                .values
+                .iter()
+                .map(|value| {
+                    if typed_column {
+                        Self::to_ext_column_value(value, rbum_kind_attr, funs)
+                    } else if rbum_kind_attr.data_type == RbumDataTypeKind::Number {
+                        value.parse::<f64>().map(Value::from).map_err(|_| Self::invalid_value_err(rbum_kind_attr, funs))
+                    } else {
+                        Ok(Value::from(value.as_str()))
+                    }
+                })
+                .collect::<TardisResult<Vec<Value>>>()?;
+            let value = values
+                .first()
+                .cloned()
+                .ok_or_else(|| funs.err().bad_request(&Self::get_obj_name(), "find", &format!("predicate of attribute {} requires a value", predicate.attr_name)))?;
+            let cond = match predicate.op {
+                RbumOperatorKind::Eq => column.eq(value),
+                RbumOperatorKind::In => column.is_in(values),
+                RbumOperatorKind::Like => column.like(&format!("%{}%", predicate.values[0])),
+                RbumOperatorKind::Gt => column.gt(value),
+                RbumOperatorKind::Ge => column.gte(value),
+                RbumOperatorKind::Lt => column.lt(value),
+                RbumOperatorKind::Le => column.lte(value),
+            };
+            query.and_where(cond);
+        }
+        Ok(())
+    }
+
+    fn invalid_value_err(rbum_kind_attr: &RbumKindAttrSummaryResp, funs: &TardisFunsInst<'a>) -> TardisError {
+        funs.err().bad_request(&Self::get_obj_name(), "attr", &format!("value of attribute {} is invalid", rbum_kind_attr.name))
+    }
+
+    /// Convert the value to the type of the ext table column that is managed by the attribute, see ``RbumKindAttrServ::find_managed_ext_columns``
+    fn to_ext_column_value(value: &str, rbum_kind_attr: &RbumKindAttrSummaryResp, funs: &TardisFunsInst<'a>) -> TardisResult<Value> {
+        let value = match rbum_kind_attr.data_type {
+            RbumDataTypeKind::Number => value.parse::<f64>().map(Value::from).ok(),
+            RbumDataTypeKind::Boolean => value.parse::<bool>().map(Value::from).ok(),
+            RbumDataTypeKind::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").map(Value::from).ok(),
+            RbumDataTypeKind::DateTime => {
+                DateTime::parse_from_rfc3339(value).map(|value| value.naive_utc()).or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")).map(Value::from).ok()
+            }
+            _ => Some(Value::from(value)),
+        };
+        value.ok_or_else(|| Self::invalid_value_err(rbum_kind_attr, funs))
+    }
+
+    fn get_ext_column_value(row: &QueryResult, rbum_kind_attr: &RbumKindAttrSummaryResp) -> TardisResult<String> {
+        let value = match rbum_kind_attr.data_type {
+            RbumDataTypeKind::Number => row.try_get::<Option<f64>>("", &rbum_kind_attr.name)?.map(|value| value.to_string()),
+            RbumDataTypeKind::Boolean => row.try_get::<Option<bool>>("", &rbum_kind_attr.name)?.map(|value| value.to_string()),
+            RbumDataTypeKind::Date => row.try_get::<Option<NaiveDate>>("", &rbum_kind_attr.name)?.map(|value| value.format("%Y-%m-%d").to_string()),
+            RbumDataTypeKind::DateTime => row.try_get::<Option<NaiveDateTime>>("", &rbum_kind_attr.name)?.map(|value| value.format("%Y-%m-%d %H:%M:%S").to_string()),
+            _ => row.try_get::<Option<String>>("", &rbum_kind_attr.name)?,
+        };
+        Ok(value.unwrap_or_default())
+    }
 }
 
 #[derive(Debug, FromQueryResult)]
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use async_trait::async_trait;
 use tardis::basic::dto::TardisContext;
 use tardis::basic::field::TrimString;
@@ -5,15 +7,18 @@ use tardis::basic::result::TardisResult;
 use tardis::db::reldb_client::IdResp;
 use tardis::db::sea_orm::*;
 use tardis::db::sea_query::*;
+use tardis::db::sea_query::{ColumnDef, IndexCreateStatement};
 use tardis::TardisFuns;
 use tardis::TardisFunsInst;
 
 use crate::rbum::domain::{rbum_item, rbum_item_attr, rbum_kind, rbum_kind_attr, rbum_rel_attr};
 use crate::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumKindAttrFilterReq};
-use crate::rbum::dto::rbum_kind_attr_dto::{RbumKindAttrAddReq, RbumKindAttrDetailResp, RbumKindAttrModifyReq, RbumKindAttrSummaryResp};
+use crate::rbum::dto::rbum_kind_attr_dto::{RbumKindAttrAddReq, RbumKindAttrDetailResp, RbumKindAttrModifyReq, RbumKindAttrSchemaResp, RbumKindAttrSummaryResp};
 use crate::rbum::dto::rbum_kind_dto::{RbumKindAddReq, RbumKindDetailResp, RbumKindModifyReq, RbumKindSummaryResp};
-use crate::rbum::rbum_enumeration::RbumScopeLevelKind;
-use crate::rbum::serv::rbum_crud_serv::{RbumCrudOperation, RbumCrudQueryPackage, R_URL_PART_CODE};
+use crate::rbum::helper::{rbum_kind_attr_helper, rbum_migration_helper};
+use crate::rbum::rbum_config::RbumConfigApi;
+use crate::rbum::rbum_enumeration::{RbumDataTypeKind, RbumScopeLevelKind};
+use crate::rbum::serv::rbum_crud_serv::{RbumCrudOperation, RbumCrudQueryPackage, ID_FIELD, R_URL_PART_CODE};
 use crate::rbum::serv::rbum_item_serv::{RbumItemAttrServ, RbumItemServ};
 use crate::rbum::serv::rbum_rel_serv::RbumRelAttrServ;
 
@@ -26,7 +31,7 @@ impl<'a> RbumCrudOperation<'a, rbum_kind::ActiveModel, RbumKindAddReq, RbumKindM
         rbum_kind::Entity.table_name()
     }
 
-    async fn package_add(add_req: &RbumKindAddReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_kind::ActiveModel> {
+    async fn package_add(add_req: &RbumKindAddReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_kind::ActiveModel> {
         Ok(rbum_kind::ActiveModel {
             id: Set(TardisFuns::field.nanoid()),
             code: Set(add_req.code.to_string()),
@@ -35,7 +40,7 @@ impl<'a> RbumCrudOperation<'a, rbum_kind::ActiveModel, RbumKindAddReq, RbumKindM
             icon: Set(add_req.icon.as_ref().unwrap_or(&"".to_string()).to_string()),
             sort: Set(add_req.sort.unwrap_or(0)),
             ext_table_name: Set(add_req.ext_table_name.as_ref().unwrap_or(&"".to_string()).to_string()),
-            scope_level: Set(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private).to_int()),
+            scope_level: Set(Self::check_scope_level(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private), funs)?),
             ..Default::default()
         })
     }
@@ -52,7 +57,7 @@ impl<'a> RbumCrudOperation<'a, rbum_kind::ActiveModel, RbumKindAddReq, RbumKindM
         Ok(())
     }
 
-    async fn package_modify(id: &str, modify_req: &RbumKindModifyReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_kind::ActiveModel> {
+    async fn package_modify(id: &str, modify_req: &RbumKindModifyReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_kind::ActiveModel> {
         let mut rbum_kind = rbum_kind::ActiveModel {
             id: Set(id.to_string()),
             ..Default::default()
@@ -73,7 +78,7 @@ impl<'a> RbumCrudOperation<'a, rbum_kind::ActiveModel, RbumKindAddReq, RbumKindM
             rbum_kind.ext_table_name = Set(ext_table_name.to_string());
         }
         if let Some(scope_level) = &modify_req.scope_level {
-            rbum_kind.scope_level = Set(scope_level.to_int());
+            rbum_kind.scope_level = Set(Self::check_scope_level(scope_level, funs)?);
         }
         Ok(rbum_kind)
     }
@@ -125,7 +130,7 @@ impl<'a> RbumCrudOperation<'a, rbum_kind_attr::ActiveModel, RbumKindAttrAddReq,
         rbum_kind_attr::Entity.table_name()
     }
 
-    async fn package_add(add_req: &RbumKindAttrAddReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_kind_attr::ActiveModel> {
+    async fn package_add(add_req: &RbumKindAttrAddReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_kind_attr::ActiveModel> {
         Ok(rbum_kind_attr::ActiveModel {
             id: Set(TardisFuns::field.nanoid()),
             name: Set(add_req.name.to_string()),
@@ -149,7 +154,7 @@ impl<'a> RbumCrudOperation<'a, rbum_kind_attr::ActiveModel, RbumKindAttrAddReq,
             action: Set(add_req.action.as_ref().unwrap_or(&"".to_string()).to_string()),
             ext: Set(add_req.ext.as_ref().unwrap_or(&"".to_string()).to_string()),
             rel_rbum_kind_id: Set(add_req.rel_rbum_kind_id.to_string()),
-            scope_level: Set(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private).to_int()),
+            scope_level: Set(Self::check_scope_level(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private), funs)?),
             ..Default::default()
         })
     }
@@ -173,10 +178,113 @@ impl<'a> RbumCrudOperation<'a, rbum_kind_attr::ActiveModel, RbumKindAttrAddReq,
         {
             return Err(funs.err().conflict(&Self::get_obj_name(), "add", &format!("name {} already exists", add_req.name)));
         }
+        if add_req.main_column.unwrap_or(false) {
+            Self::get_ext_table_name(&add_req.rel_rbum_kind_id, funs, ctx).await?;
+            Self::check_ext_column_def(&add_req.data_type, add_req.idx.unwrap_or(false), funs)?;
+        }
+        Ok(())
+    }
+
+    async fn after_add_rbum(_: &str, add_req: &RbumKindAttrAddReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        if add_req.main_column.unwrap_or(false) {
+            let ext_table_name = Self::get_ext_table_name(&add_req.rel_rbum_kind_id, funs, ctx).await?;
+            Self::add_ext_column(&ext_table_name, &add_req.name.0, &add_req.data_type, add_req.idx.unwrap_or(false), funs).await?;
+        }
+        Ok(())
+    }
+
+    async fn before_modify_rbum(id: &str, modify_req: &mut RbumKindAttrModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::check_ownership(id, funs, ctx).await?;
+        let rbum_kind_attr = Self::peek_rbum(id, &RbumKindAttrFilterReq::default(), funs, ctx).await?;
+        // Unchanged values are ignored, so ``after_modify_rbum`` only applies the real changes to the ext table column
+        if modify_req.main_column == Some(rbum_kind_attr.main_column) {
+            modify_req.main_column = None;
+        }
+        if modify_req.data_type.as_ref() == Some(&rbum_kind_attr.data_type) {
+            modify_req.data_type = None;
+        }
+        if modify_req.idx == Some(rbum_kind_attr.idx) {
+            modify_req.idx = None;
+        }
+        let main_column = modify_req.main_column.unwrap_or(rbum_kind_attr.main_column);
+        if !rbum_kind_attr.main_column && !main_column {
+            return Ok(());
+        }
+        let data_type = modify_req.data_type.clone().unwrap_or_else(|| rbum_kind_attr.data_type.clone());
+        let idx = modify_req.idx.unwrap_or(rbum_kind_attr.idx);
+        let ext_table_name = Self::get_ext_table_name(&rbum_kind_attr.rel_rbum_kind_id, funs, ctx).await?;
+        if !rbum_kind_attr.main_column {
+            // Values stored in rbum_item_attr are not moved to the ext table
+            if funs
+                .db()
+                .count(Query::select().column(rbum_item_attr::Column::Id).from(rbum_item_attr::Entity).and_where(Expr::col(rbum_item_attr::Column::RelRbumKindAttrId).eq(id)))
+                .await?
+                > 0
+            {
+                return Err(funs.err().conflict(&Self::get_obj_name(), "modify", "can not move attribute values to the main column"));
+            }
+            return Self::check_ext_column_def(&data_type, idx, funs);
+        }
+        if !Self::is_managed_ext_column(&ext_table_name, &rbum_kind_attr.name, funs).await? {
+            return Ok(());
+        }
+        if !main_column {
+            if Self::is_shared_ext_column(&rbum_kind_attr.id, &rbum_kind_attr.name, &rbum_kind_attr.rel_rbum_kind_id, funs).await? {
+                return Ok(());
+            }
+            Self::check_ext_column_droppable(&rbum_kind_attr, &ext_table_name, funs).await?;
+            return Self::check_ext_ddl_supported("dropping columns", funs);
+        }
+        Self::check_ext_column_def(&data_type, idx, funs)?;
+        if modify_req.data_type.is_some() {
+            Self::check_ext_column_droppable(&rbum_kind_attr, &ext_table_name, funs).await?;
+            Self::check_ext_ddl_supported("changing column types", funs)?;
+        }
+        Ok(())
+    }
+
+    async fn after_modify_rbum(id: &str, modify_req: &mut RbumKindAttrModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        if modify_req.main_column.is_none() && modify_req.data_type.is_none() && modify_req.idx.is_none() {
+            return Ok(());
+        }
+        let rbum_kind_attr = Self::peek_rbum(id, &RbumKindAttrFilterReq::default(), funs, ctx).await?;
+        if !rbum_kind_attr.main_column && modify_req.main_column != Some(false) {
+            return Ok(());
+        }
+        let ext_table_name = Self::get_ext_table_name(&rbum_kind_attr.rel_rbum_kind_id, funs, ctx).await?;
+        if modify_req.main_column == Some(true) {
+            return Self::add_ext_column(&ext_table_name, &rbum_kind_attr.name, &rbum_kind_attr.data_type, rbum_kind_attr.idx, funs).await;
+        }
+        if !Self::is_managed_ext_column(&ext_table_name, &rbum_kind_attr.name, funs).await? {
+            return Ok(());
+        }
+        if modify_req.main_column == Some(false) {
+            if Self::is_shared_ext_column(&rbum_kind_attr.id, &rbum_kind_attr.name, &rbum_kind_attr.rel_rbum_kind_id, funs).await? {
+                return Ok(());
+            }
+            return Self::drop_ext_column(&ext_table_name, &rbum_kind_attr.name, funs).await;
+        }
+        if let Some(idx) = modify_req.idx {
+            Self::change_ext_column_index(&ext_table_name, &rbum_kind_attr.name, idx, funs).await?;
+        }
+        if let Some(data_type) = &modify_req.data_type {
+            if let Err(e) = Self::execute_ext_ddl(
+                &Table::alter().table(Alias::new(&ext_table_name)).modify_column(&mut Self::package_ext_column_def(&rbum_kind_attr.name, data_type)).to_owned(),
+                funs,
+            )
+            .await
+            {
+                // Undo the index change that has been committed by MySQL
+                if let (Some(idx), true) = (modify_req.idx, Self::is_ext_ddl_outside_tx(funs)) {
+                    Self::change_ext_column_index(&ext_table_name, &rbum_kind_attr.name, !idx, funs).await?;
+                }
+                return Err(e);
+            }
+        }
         Ok(())
     }
 
-    async fn package_modify(id: &str, modify_req: &RbumKindAttrModifyReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_kind_attr::ActiveModel> {
+    async fn package_modify(id: &str, modify_req: &RbumKindAttrModifyReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_kind_attr::ActiveModel> {
         let mut rbum_kind_attr = rbum_kind_attr::ActiveModel {
             id: Set(id.to_string()),
             ..Default::default()
@@ -236,7 +344,7 @@ impl<'a> RbumCrudOperation<'a, rbum_kind_attr::ActiveModel, RbumKindAttrAddReq,
             rbum_kind_attr.ext = Set(ext.to_string());
         }
         if let Some(scope_level) = &modify_req.scope_level {
-            rbum_kind_attr.scope_level = Set(scope_level.to_int());
+            rbum_kind_attr.scope_level = Set(Self::check_scope_level(scope_level, funs)?);
         }
         Ok(rbum_kind_attr)
     }
@@ -245,7 +353,28 @@ impl<'a> RbumCrudOperation<'a, rbum_kind_attr::ActiveModel, RbumKindAttrAddReq,
         Self::check_ownership(id, funs, ctx).await?;
         Self::check_exist_before_delete(id, RbumItemAttrServ::get_table_name(), rbum_item_attr::Column::RelRbumKindAttrId.as_str(), funs).await?;
         Self::check_exist_before_delete(id, RbumRelAttrServ::get_table_name(), rbum_rel_attr::Column::RelRbumKindAttrId.as_str(), funs).await?;
-        Ok(None)
+        let rbum_kind_attr = Self::peek_rbum(id, &RbumKindAttrFilterReq::default(), funs, ctx).await?;
+        if !rbum_kind_attr.main_column {
+            return Ok(None);
+        }
+        let ext_table_name = Self::get_ext_table_name(&rbum_kind_attr.rel_rbum_kind_id, funs, ctx).await?;
+        if !Self::is_managed_ext_column(&ext_table_name, &rbum_kind_attr.name, funs).await?
+            || Self::is_shared_ext_column(&rbum_kind_attr.id, &rbum_kind_attr.name, &rbum_kind_attr.rel_rbum_kind_id, funs).await?
+        {
+            return Ok(None);
+        }
+        Self::check_ext_column_droppable(&rbum_kind_attr, &ext_table_name, funs).await?;
+        Self::check_ext_ddl_supported("dropping columns", funs)?;
+        // The column is dropped by ``after_delete_rbum``
+        Ok(Some(Self::get_rbum(id, &RbumKindAttrFilterReq::default(), funs, ctx).await?))
+    }
+
+    async fn after_delete_rbum(_: &str, deleted_rbum: &Option<RbumKindAttrDetailResp>, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        if let Some(rbum_kind_attr) = deleted_rbum {
+            let ext_table_name = Self::get_ext_table_name(&rbum_kind_attr.rel_rbum_kind_id, funs, ctx).await?;
+            Self::drop_ext_column(&ext_table_name, &rbum_kind_attr.name, funs).await?;
+        }
+        Ok(())
     }
 
     async fn package_query(is_detail: bool, filter: &RbumKindAttrFilterReq, _: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<SelectStatement> {
@@ -292,3 +421,267 @@ impl<'a> RbumCrudOperation<'a, rbum_kind_attr::ActiveModel, RbumKindAttrAddReq,
         Ok(query)
     }
 }
+
+impl<'a> RbumKindAttrServ {
+    /// Render the attributes of the kind as a JSON Schema document and a form layout
+    pub async fn get_kind_attr_schema(rel_rbum_kind_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumKindAttrSchemaResp> {
+        let rbum_kind_attrs = Self::find_rbums(
+            &RbumKindAttrFilterReq {
+                basic: RbumBasicFilterReq {
+                    rbum_kind_id: Some(rel_rbum_kind_id.to_string()),
+                    ..Default::default()
+                },
+            },
+            None,
+            None,
+            funs,
+            ctx,
+        )
+        .await?;
+        Ok(rbum_kind_attr_helper::to_schema(&rbum_kind_attrs))
+    }
+}
+
+// ----------------------------- Ext Table Column -------------------------------
+//
+// The value of a ``main_column`` attribute is stored in the column with the same name of the kind's ext table.
+// The column is added when the attribute is added and it does not exist yet, such a column is managed by the attribute
+// (recorded in the dict) and will be altered / dropped along with the attribute.
+// Columns that already exist (e.g. declared by the ext table model) are never changed.
+//
+// The column is changed after the attribute has been written, see ``execute_ext_ddl`` for the transaction of the DDL statements.
+// Sqlite can not alter the type of a column or drop a column, such changes are rejected.
+impl<'a> RbumKindAttrServ {
+    /// Modify the attribute, the data of its ext table column is discarded when the column type is changed
+    pub async fn force_modify_rbum(id: &str, modify_req: &mut RbumKindAttrModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::clear_ext_column_data(id, funs, ctx).await?;
+        Self::modify_rbum(id, modify_req, funs, ctx).await
+    }
+
+    /// Delete the attribute, the data of its ext table column is discarded
+    pub async fn force_delete_rbum(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<u64> {
+        Self::clear_ext_column_data(id, funs, ctx).await?;
+        Self::delete_rbum(id, funs, ctx).await
+    }
+
+    async fn get_ext_table_name(rel_rbum_kind_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<String> {
+        let ext_table_name = RbumKindServ::peek_rbum(rel_rbum_kind_id, &RbumBasicFilterReq::default(), funs, ctx).await?.ext_table_name;
+        if ext_table_name.is_empty() {
+            return Err(funs.err().bad_request(&Self::get_obj_name(), "add", "main column attributes require the kind to have an ext table"));
+        }
+        Ok(ext_table_name)
+    }
+
+    fn check_ext_column_def(data_type: &RbumDataTypeKind, idx: bool, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if idx && Self::is_text_data_type(data_type) {
+            return Err(funs.err().bad_request(&Self::get_obj_name(), "add", &format!("attributes of type {} can not be indexed", data_type)));
+        }
+        Ok(())
+    }
+
+    fn check_ext_ddl_supported(change: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if funs.db().raw_conn().get_database_backend() == DatabaseBackend::Sqlite {
+            return Err(funs.err().bad_request(&Self::get_obj_name(), "modify", &format!("{} of the ext table is not supported by sqlite", change)));
+        }
+        Ok(())
+    }
+
+    fn is_text_data_type(data_type: &RbumDataTypeKind) -> bool {
+        !matches!(
+            data_type,
+            RbumDataTypeKind::String | RbumDataTypeKind::Number | RbumDataTypeKind::Boolean | RbumDataTypeKind::Date | RbumDataTypeKind::DateTime
+        )
+    }
+
+    fn package_ext_column_def(column_name: &str, data_type: &RbumDataTypeKind) -> ColumnDef {
+        let mut column_def = ColumnDef::new(Alias::new(column_name));
+        match data_type {
+            RbumDataTypeKind::String => column_def.string(),
+            RbumDataTypeKind::Number => column_def.double(),
+            RbumDataTypeKind::Boolean => column_def.boolean(),
+            RbumDataTypeKind::Date => column_def.date(),
+            RbumDataTypeKind::DateTime => column_def.date_time(),
+            _ => column_def.text(),
+        };
+        column_def.null();
+        column_def
+    }
+
+    fn get_ext_column_index_name(ext_table_name: &str, column_name: &str) -> String {
+        format!("idx-{}-{}", ext_table_name, column_name)
+    }
+
+    fn package_ext_column_index(ext_table_name: &str, column_name: &str) -> IndexCreateStatement {
+        Index::create().name(&Self::get_ext_column_index_name(ext_table_name, column_name)).table(Alias::new(ext_table_name)).col(Alias::new(column_name)).to_owned()
+    }
+
+    fn get_managed_ext_column_key_prefix(ext_table_name: &str) -> String {
+        format!("__RBUM_KIND_ATTR_COLUMN__:{}:", ext_table_name)
+    }
+
+    /// DDL statements cause an implicit commit in MySQL, there the statements of the ext table columns
+    /// (including the data checks and the managed records) are executed without the business transaction.
+    /// Other databases support transactional DDL, the statements are executed in the business transaction.
+    fn is_ext_ddl_outside_tx(funs: &TardisFunsInst<'a>) -> bool {
+        funs.db().raw_conn().get_database_backend() == DatabaseBackend::MySql
+    }
+
+    /// Execute a DDL statement of the ext table, see ``is_ext_ddl_outside_tx``
+    ///
+    /// In MySQL the statement has been committed when it returns, the callers undo it if their subsequent statements fail.
+    /// The DDL waits for the metadata lock of the ext table, which is held by every transaction that has accessed the table,
+    /// so the business transaction must not access the ext table before changing its columns, the wait is limited by ``ext_ddl_lock_wait_timeout_sec``.
+    async fn execute_ext_ddl<S: StatementBuilder + Sync>(statement: &S, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if !Self::is_ext_ddl_outside_tx(funs) {
+            funs.db().execute(statement).await?;
+            return Ok(());
+        }
+        // The transaction only pins the connection for the session variable, it is committed implicitly by the DDL
+        let mut conn = TardisFuns::reldb().conn();
+        conn.begin().await?;
+        conn.execute_one(&format!("SET SESSION lock_wait_timeout = {}", funs.rbum_conf_ext_ddl_lock_wait_timeout_sec()), vec![]).await?;
+        let result = conn.execute(statement).await;
+        conn.execute_one("SET SESSION lock_wait_timeout = DEFAULT", vec![]).await?;
+        conn.commit().await?;
+        result?;
+        Ok(())
+    }
+
+    /// Whether the ext table column is added (and managed) by a kind attribute
+    pub async fn is_managed_ext_column(ext_table_name: &str, column_name: &str, funs: &TardisFunsInst<'a>) -> TardisResult<bool> {
+        Ok(Self::find_managed_ext_columns(ext_table_name, funs).await?.contains(column_name))
+    }
+
+    /// Find the names of the ext table columns that are added (and managed) by kind attributes
+    pub async fn find_managed_ext_columns(ext_table_name: &str, funs: &TardisFunsInst<'a>) -> TardisResult<HashSet<String>> {
+        let key_prefix = Self::get_managed_ext_column_key_prefix(ext_table_name);
+        let dicts = if Self::is_ext_ddl_outside_tx(funs) {
+            TardisFuns::dict.find_like(&key_prefix, &TardisFuns::reldb().conn()).await?
+        } else {
+            TardisFuns::dict.find_like(&key_prefix, funs.db()).await?
+        };
+        // ``_`` of the table name is a wildcard of like
+        Ok(dicts.into_iter().filter_map(|dict| dict.k.strip_prefix(&key_prefix).map(|column_name| column_name.to_string())).collect())
+    }
+
+    async fn set_managed_ext_column(ext_table_name: &str, column_name: &str, managed: bool, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        let key = format!("{}{}", Self::get_managed_ext_column_key_prefix(ext_table_name), column_name);
+        let conn = TardisFuns::reldb().conn();
+        let conn = if Self::is_ext_ddl_outside_tx(funs) { &conn } else { funs.db() };
+        if managed {
+            TardisFuns::dict.add(&key, "", "", conn).await
+        } else {
+            TardisFuns::dict.delete(&key, conn).await
+        }
+    }
+
+    async fn add_ext_column(ext_table_name: &str, column_name: &str, data_type: &RbumDataTypeKind, idx: bool, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if rbum_migration_helper::exist_column(ext_table_name, column_name, funs.db()).await? {
+            return Ok(());
+        }
+        Self::execute_ext_ddl(
+            &Table::alter().table(Alias::new(ext_table_name)).add_column(&mut Self::package_ext_column_def(column_name, data_type)).to_owned(),
+            funs,
+        )
+        .await?;
+        let result = if idx {
+            Self::execute_ext_ddl(&Self::package_ext_column_index(ext_table_name, column_name), funs).await
+        } else {
+            Ok(())
+        };
+        let result = match result {
+            Ok(_) => Self::set_managed_ext_column(ext_table_name, column_name, true, funs).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            // Undo the column that has been committed by MySQL
+            if Self::is_ext_ddl_outside_tx(funs) {
+                Self::execute_ext_ddl(&Table::alter().table(Alias::new(ext_table_name)).drop_column(Alias::new(column_name)).to_owned(), funs).await?;
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn change_ext_column_index(ext_table_name: &str, column_name: &str, idx: bool, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if idx {
+            Self::execute_ext_ddl(&Self::package_ext_column_index(ext_table_name, column_name), funs).await
+        } else {
+            Self::execute_ext_ddl(
+                &Index::drop().name(&Self::get_ext_column_index_name(ext_table_name, column_name)).table(Alias::new(ext_table_name)).to_owned(),
+                funs,
+            )
+            .await
+        }
+    }
+
+    /// The column can only be altered or dropped when it is not shared with other attributes and it has no data
+    async fn check_ext_column_droppable(rbum_kind_attr: &RbumKindAttrSummaryResp, ext_table_name: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if Self::is_shared_ext_column(&rbum_kind_attr.id, &rbum_kind_attr.name, &rbum_kind_attr.rel_rbum_kind_id, funs).await? {
+            return Err(funs.err().conflict(
+                &Self::get_obj_name(),
+                "modify",
+                &format!("column {}.{} is shared with other attributes", ext_table_name, rbum_kind_attr.name),
+            ));
+        }
+        let mut query = Query::select();
+        query.column(ID_FIELD.clone()).from(Alias::new(ext_table_name)).and_where(Expr::col(Alias::new(&rbum_kind_attr.name)).is_not_null());
+        let count = if Self::is_ext_ddl_outside_tx(funs) {
+            TardisFuns::reldb().conn().count(&query).await?
+        } else {
+            funs.db().count(&query).await?
+        };
+        if count > 0 {
+            return Err(funs.err().conflict(
+                &Self::get_obj_name(),
+                "modify",
+                &format!("column {}.{} has data, please force the change to discard it", ext_table_name, rbum_kind_attr.name),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn is_shared_ext_column(id: &str, column_name: &str, rel_rbum_kind_id: &str, funs: &TardisFunsInst<'a>) -> TardisResult<bool> {
+        let count = funs
+            .db()
+            .count(
+                Query::select()
+                    .column(rbum_kind_attr::Column::Id)
+                    .from(rbum_kind_attr::Entity)
+                    .and_where(Expr::col(rbum_kind_attr::Column::Name).eq(column_name))
+                    .and_where(Expr::col(rbum_kind_attr::Column::RelRbumKindId).eq(rel_rbum_kind_id))
+                    .and_where(Expr::col(rbum_kind_attr::Column::MainColumn).eq(true))
+                    .and_where(Expr::col(rbum_kind_attr::Column::Id).ne(id)),
+            )
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// The column has been checked by ``check_ext_column_droppable``
+    async fn drop_ext_column(ext_table_name: &str, column_name: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        Self::execute_ext_ddl(&Table::alter().table(Alias::new(ext_table_name)).drop_column(Alias::new(column_name)).to_owned(), funs).await?;
+        Self::set_managed_ext_column(ext_table_name, column_name, false, funs).await
+    }
+
+    async fn clear_ext_column_data(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::check_ownership(id, funs, ctx).await?;
+        let rbum_kind_attr = Self::peek_rbum(id, &RbumKindAttrFilterReq::default(), funs, ctx).await?;
+        if !rbum_kind_attr.main_column {
+            return Ok(());
+        }
+        let ext_table_name = Self::get_ext_table_name(&rbum_kind_attr.rel_rbum_kind_id, funs, ctx).await?;
+        if !Self::is_managed_ext_column(&ext_table_name, &rbum_kind_attr.name, funs).await?
+            || Self::is_shared_ext_column(&rbum_kind_attr.id, &rbum_kind_attr.name, &rbum_kind_attr.rel_rbum_kind_id, funs).await?
+        {
+            return Ok(());
+        }
+        // The data is discarded along with the column, so it is cleared outside the business transaction as well, see ``is_ext_ddl_outside_tx``
+        let update = Query::update().table(Alias::new(&ext_table_name)).value_expr(Alias::new(&rbum_kind_attr.name), Expr::cust("NULL")).to_owned();
+        if Self::is_ext_ddl_outside_tx(funs) {
+            TardisFuns::reldb().conn().execute(&update).await?;
+        } else {
+            funs.db().execute(&update).await?;
+        }
+        Ok(())
+    }
+}
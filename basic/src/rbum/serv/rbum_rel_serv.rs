@@ -1,9 +1,13 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
 use std::str::FromStr;
+use std::sync::RwLock;
 
 use async_trait::async_trait;
+use lazy_static::lazy_static;
 use tardis::basic::dto::TardisContext;
 use tardis::basic::result::TardisResult;
-use tardis::chrono::Utc;
+use tardis::chrono::{DateTime, Timelike, Utc};
 use tardis::db::reldb_client::IdResp;
 use tardis::db::sea_orm::*;
 use tardis::db::sea_query::*;
@@ -11,14 +15,21 @@ use tardis::web::web_resp::TardisPage;
 use tardis::TardisFuns;
 use tardis::TardisFunsInst;
 
-use crate::rbum::domain::{rbum_item, rbum_kind_attr, rbum_rel, rbum_rel_attr, rbum_rel_env, rbum_set, rbum_set_cate};
+use crate::rbum::domain::{rbum_item, rbum_kind_attr, rbum_rel, rbum_rel_attr, rbum_rel_env, rbum_set, rbum_set_cate, rbum_set_item};
+use crate::rbum::dto::rbum_cursor_dto::{RbumCursorPage, RbumCursorReq};
 use crate::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumRelExtFilterReq, RbumRelFilterReq, RbumSetCateFilterReq, RbumSetItemFilterReq};
 use crate::rbum::dto::rbum_rel_agg_dto::{RbumRelAggAddReq, RbumRelAggResp};
 use crate::rbum::dto::rbum_rel_attr_dto::{RbumRelAttrAddReq, RbumRelAttrDetailResp, RbumRelAttrModifyReq};
-use crate::rbum::dto::rbum_rel_dto::{RbumRelAddReq, RbumRelBoneResp, RbumRelCheckReq, RbumRelDetailResp, RbumRelFindReq, RbumRelModifyReq};
+use crate::rbum::dto::rbum_rel_dto::{
+    RbumRelAddReq, RbumRelBoneResp, RbumRelCheckReq, RbumRelDetailResp, RbumRelFindReq, RbumRelModifyReq, RbumRelTraverseHopResp, RbumRelTraverseReq, RbumRelTraverseResp,
+};
 use crate::rbum::dto::rbum_rel_env_dto::{RbumRelEnvAddReq, RbumRelEnvDetailResp, RbumRelEnvModifyReq};
-use crate::rbum::rbum_enumeration::{RbumRelEnvKind, RbumRelFromKind, RbumSetCateLevelQueryKind};
-use crate::rbum::serv::rbum_crud_serv::{NameResp, RbumCrudOperation, RbumCrudQueryPackage};
+use crate::rbum::helper::rbum_dialect_helper;
+use crate::rbum::helper::rbum_event_helper::{RbumEventMessage, RbumEventSubscriber};
+use crate::rbum::helper::rbum_kind_attr_helper;
+use crate::rbum::rbum_config::RbumConfigApi;
+use crate::rbum::rbum_enumeration::{RbumDataTypeKind, RbumRelAttrOpKind, RbumRelEnvKind, RbumRelFromKind, RbumSetCateLevelQueryKind};
+use crate::rbum::serv::rbum_crud_serv::{NameResp, RbumCrudOperation, RbumCrudQueryPackage, ID_FIELD};
 use crate::rbum::serv::rbum_item_serv::RbumItemServ;
 use crate::rbum::serv::rbum_kind_serv::RbumKindAttrServ;
 use crate::rbum::serv::rbum_set_serv::{RbumSetCateServ, RbumSetItemServ, RbumSetServ};
@@ -69,6 +80,14 @@ impl<'a> RbumCrudOperation<'a, rbum_rel::ActiveModel, RbumRelAddReq, RbumRelModi
         Ok(())
     }
 
+    async fn after_add_rbum(id: &str, _: &RbumRelAddReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        Self::touch_rel_items(id, funs).await
+    }
+
+    async fn after_modify_rbum(id: &str, _: &mut RbumRelModifyReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        Self::touch_rel_items(id, funs).await
+    }
+
     async fn package_modify(id: &str, modify_req: &RbumRelModifyReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_rel::ActiveModel> {
         let mut rbum_rel = rbum_rel::ActiveModel {
             id: Set(id.to_string()),
@@ -104,6 +123,7 @@ impl<'a> RbumCrudOperation<'a, rbum_rel::ActiveModel, RbumRelAddReq, RbumRelModi
         }
         Self::check_exist_before_delete(id, RbumRelAttrServ::get_table_name(), rbum_rel_attr::Column::RelRbumRelId.as_str(), funs).await?;
         Self::check_exist_before_delete(id, RbumRelEnvServ::get_table_name(), rbum_rel_env::Column::RelRbumRelId.as_str(), funs).await?;
+        Self::touch_rel_items(id, funs).await?;
         Ok(None)
     }
 
@@ -185,6 +205,30 @@ impl<'a> RbumCrudOperation<'a, rbum_rel::ActiveModel, RbumRelAddReq, RbumRelModi
 }
 
 impl<'a> RbumRelServ {
+    /// The relationships are part of the related items, so the versions of the items are changed, see ``RbumCrudOperation::touch_rbums``
+    async fn touch_rel_items(id: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        #[derive(Debug, FromQueryResult)]
+        struct RelItemResp {
+            pub from_rbum_kind: u8,
+            pub from_rbum_id: String,
+            pub to_rbum_item_id: String,
+        }
+
+        let mut query = Query::select();
+        query
+            .columns(vec![rbum_rel::Column::FromRbumKind, rbum_rel::Column::FromRbumId, rbum_rel::Column::ToRbumItemId])
+            .from(rbum_rel::Entity)
+            .and_where(Expr::col(rbum_rel::Column::Id).eq(id));
+        if let Some(rel) = funs.db().get_dto::<RelItemResp>(&query).await? {
+            let mut rbum_item_ids = vec![rel.to_rbum_item_id];
+            if rel.from_rbum_kind == RbumRelFromKind::Item.to_int() {
+                rbum_item_ids.push(rel.from_rbum_id);
+            }
+            RbumItemServ::touch_rbums(&rbum_item_ids, funs).await?;
+        }
+        Ok(())
+    }
+
     pub async fn add_simple_rel(tag: &str, from_rbum_id: &str, to_rbum_item_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         RbumRelServ::add_rbum(
             &mut RbumRelAddReq {
@@ -210,6 +254,7 @@ impl<'a> RbumRelServ {
             RbumRelAttrServ::add_rbum(
                 &mut RbumRelAttrAddReq {
                     is_from: attr.is_from,
+                    op: attr.op.clone(),
                     value: attr.value.to_string(),
                     name: attr.name.to_string(),
                     rel_rbum_rel_id: rbum_rel_id.to_string(),
@@ -386,6 +431,35 @@ impl<'a> RbumRelServ {
         })
     }
 
+    pub async fn paginate_from_simple_rels_by_cursor(
+        tag: &str,
+        from_rbum_kind: &RbumRelFromKind,
+        with_sub: bool,
+        from_rbum_id: &str,
+        cursor_req: &RbumCursorReq,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumCursorPage<RbumRelBoneResp>> {
+        let result = Self::paginate_rbums_by_cursor(
+            &RbumRelFilterReq {
+                basic: RbumBasicFilterReq {
+                    with_sub_own_paths: with_sub,
+                    ..Default::default()
+                },
+                tag: Some(tag.to_string()),
+                from_rbum_kind: Some(from_rbum_kind.clone()),
+                from_rbum_id: Some(from_rbum_id.to_string()),
+                to_rbum_item_id: None,
+                to_own_paths: None,
+            },
+            cursor_req,
+            funs,
+            ctx,
+        )
+        .await?;
+        Ok(result.map(|item| RbumRelBoneResp::new(item, true)))
+    }
+
     pub async fn paginate_from_rels(
         tag: &str,
         from_rbum_kind: &RbumRelFromKind,
@@ -577,6 +651,35 @@ impl<'a> RbumRelServ {
         })
     }
 
+    pub async fn paginate_to_simple_rels_by_cursor(
+        tag: &str,
+        to_rbum_item_id: &str,
+        cursor_req: &RbumCursorReq,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumCursorPage<RbumRelBoneResp>> {
+        let result = Self::paginate_rbums_by_cursor(
+            &RbumRelFilterReq {
+                basic: RbumBasicFilterReq {
+                    own_paths: Some(ctx.own_paths.to_string()),
+                    with_sub_own_paths: true,
+                    ignore_scope: true,
+                    ..Default::default()
+                },
+                tag: Some(tag.to_string()),
+                from_rbum_kind: None,
+                from_rbum_id: None,
+                to_rbum_item_id: Some(to_rbum_item_id.to_string()),
+                to_own_paths: None,
+            },
+            cursor_req,
+            funs,
+            ctx,
+        )
+        .await?;
+        Ok(result.map(|item| RbumRelBoneResp::new(item, false)))
+    }
+
     pub async fn paginate_to_rels(
         tag: &str,
         to_rbum_item_id: &str,
@@ -734,9 +837,431 @@ impl<'a> RbumRelServ {
         query
     }
 
-    // TODO cache
+    /// Find the items reachable from the item through one or more hops of relationships
+    ///
+    /// Only the relationships from items ([RbumRelFromKind::Item]) are followed, their attribute and environment conditions are not evaluated.
+    /// Each reached item is returned once with the minimum number of hops, the start item is returned only if it is reached by the full tag sequence.
+    pub async fn find_traverse_rels(traverse_req: &RbumRelTraverseReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<Vec<RbumRelTraverseResp>> {
+        let depth = Self::get_traverse_depth(traverse_req, funs)?;
+        let mut rbum_item_depths: HashMap<String, u8> = HashMap::new();
+        for (rbum_item_id, item_depth) in Self::do_traverse_rels(traverse_req, depth, traverse_req.rbum_kind_id.as_deref(), funs, ctx).await? {
+            if traverse_req.tags.is_empty() {
+                if rbum_item_id != traverse_req.rbum_item_id {
+                    let min_depth = rbum_item_depths.entry(rbum_item_id).or_insert(item_depth);
+                    *min_depth = item_depth.min(*min_depth);
+                }
+            } else if item_depth == depth {
+                rbum_item_depths.insert(rbum_item_id, item_depth);
+            }
+        }
+        let mut result = rbum_item_depths.into_iter().map(|(rbum_item_id, depth)| RbumRelTraverseResp { rbum_item_id, depth }).collect::<Vec<RbumRelTraverseResp>>();
+        result.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.rbum_item_id.cmp(&b.rbum_item_id)));
+        Ok(result)
+    }
+
+    /// Find one of the shortest relationship paths from the item to the target item, see [RbumRelServ::find_traverse_rels]
+    ///
+    /// Return ``None`` if the target item is not reachable within the depth limit (or by the full tag sequence).
+    pub async fn find_shortest_rel_path(
+        traverse_req: &RbumRelTraverseReq,
+        to_rbum_item_id: &str,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<Option<Vec<RbumRelTraverseHopResp>>> {
+        let depth = Self::get_traverse_depth(traverse_req, funs)?;
+        if traverse_req.tags.is_empty() && to_rbum_item_id == traverse_req.rbum_item_id {
+            return Ok(Some(vec![]));
+        }
+        // Items reached by each number of hops
+        let mut layers = vec![HashSet::new(); depth as usize + 1];
+        layers[0].insert(traverse_req.rbum_item_id.clone());
+        for (rbum_item_id, item_depth) in Self::do_traverse_rels(traverse_req, depth, None, funs, ctx).await? {
+            layers[item_depth as usize].insert(rbum_item_id);
+        }
+        let to_depth = if traverse_req.tags.is_empty() {
+            layers.iter().skip(1).position(|layer| layer.contains(to_rbum_item_id)).map(|idx| idx + 1)
+        } else {
+            Some(depth as usize).filter(|depth| layers[*depth].contains(to_rbum_item_id))
+        };
+        let to_depth = if let Some(to_depth) = to_depth {
+            to_depth
+        } else {
+            return Ok(None);
+        };
+        // Walk back from the target item, each hop comes from an item reached by one hop less
+        let (near_column, far_column) = Self::get_traverse_columns(traverse_req.is_from);
+        let mut hops = Vec::with_capacity(to_depth);
+        let mut current_rbum_item_id = to_rbum_item_id.to_string();
+        for hop_depth in (1..=to_depth).rev() {
+            // The layer is bound in chunks, the hop with the smallest id is taken
+            let near_rbum_item_ids = layers[hop_depth - 1].iter().map(|id| id.as_str()).collect::<Vec<&str>>();
+            let mut hop: Option<RbumRelTraverseHopResp> = None;
+            for near_rbum_item_ids in near_rbum_item_ids.chunks(TRAVERSE_CHUNK_SIZE) {
+                let mut query = Query::select();
+                query
+                    .columns(vec![
+                        (rbum_rel::Entity, rbum_rel::Column::Id),
+                        (rbum_rel::Entity, rbum_rel::Column::Tag),
+                        (rbum_rel::Entity, rbum_rel::Column::FromRbumId),
+                        (rbum_rel::Entity, rbum_rel::Column::ToRbumItemId),
+                    ])
+                    .from(rbum_rel::Entity)
+                    .cond_where(Self::package_traverse_cond(ctx))
+                    .and_where(Expr::tbl(rbum_rel::Entity, near_column).is_in(near_rbum_item_ids.to_vec()))
+                    .and_where(Expr::tbl(rbum_rel::Entity, far_column).eq(current_rbum_item_id.as_str()))
+                    .order_by((rbum_rel::Entity, rbum_rel::Column::Id), Order::Asc)
+                    .limit(1);
+                if let Some(tag) = traverse_req.tags.get(hop_depth - 1) {
+                    query.and_where(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::Tag).eq(tag.as_str()));
+                }
+                if let Some(chunk_hop) = funs.db().get_dto::<RbumRelTraverseHopResp>(&query).await? {
+                    if hop.as_ref().map(|hop| chunk_hop.id < hop.id).unwrap_or(true) {
+                        hop = Some(chunk_hop);
+                    }
+                }
+            }
+            let hop = if let Some(hop) = hop {
+                hop
+            } else {
+                // The relationship has been deleted after the traversal
+                return Ok(None);
+            };
+            current_rbum_item_id = if traverse_req.is_from { hop.from_rbum_id.clone() } else { hop.to_rbum_item_id.clone() };
+            hops.push(hop);
+        }
+        hops.reverse();
+        Ok(Some(hops))
+    }
+
+    fn get_traverse_depth(traverse_req: &RbumRelTraverseReq, funs: &TardisFunsInst<'a>) -> TardisResult<u8> {
+        let depth = if traverse_req.tags.is_empty() {
+            traverse_req.max_depth as usize
+        } else {
+            traverse_req.tags.len()
+        };
+        if depth == 0 || depth > TRAVERSE_MAX_DEPTH as usize {
+            return Err(funs.err().bad_request(
+                &Self::get_obj_name(),
+                "traverse",
+                &format!("the number of hops must be between 1 and {}", TRAVERSE_MAX_DEPTH),
+            ));
+        }
+        Ok(depth as u8)
+    }
+
+    /// The column of the item that a hop starts from and the column of the item that it reaches
+    fn get_traverse_columns(is_from: bool) -> (rbum_rel::Column, rbum_rel::Column) {
+        if is_from {
+            (rbum_rel::Column::FromRbumId, rbum_rel::Column::ToRbumItemId)
+        } else {
+            (rbum_rel::Column::ToRbumItemId, rbum_rel::Column::FromRbumId)
+        }
+    }
+
+    fn package_traverse_cond(ctx: &TardisContext) -> Cond {
+        Cond::all().add(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::FromRbumKind).eq(RbumRelFromKind::Item.to_int())).add(
+            Cond::any()
+                .add(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::OwnPaths).like(format!("{}%", ctx.own_paths).as_str()))
+                .add(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::ToOwnPaths).like(format!("{}%", ctx.own_paths).as_str())),
+        )
+    }
+
+    /// Walk the relationships by a recursive common table expression and return the distinct (item, number of hops) pairs
+    ///
+    /// Distinct pairs keep the walk finite when there are cycles, it ends at the depth limit.
+    /// The databases without recursive common table expressions (MySQL before 8.0) are walked by a query per hop.
+    async fn do_traverse_rels(
+        traverse_req: &RbumRelTraverseReq,
+        depth: u8,
+        rbum_kind_id: Option<&str>,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<Vec<(String, u8)>> {
+        if !Self::is_recursive_cte_supported(funs).await? {
+            return Self::do_traverse_rels_by_hops(traverse_req, depth, rbum_kind_id, funs, ctx).await;
+        }
+        let db_kind = funs.db().raw_conn().get_database_backend();
+        let cte_name = Alias::new("rbum_rel_traverse");
+        let (near_column, far_column) = Self::get_traverse_columns(traverse_req.is_from);
+
+        let mut base_query = Query::select();
+        base_query
+            .column((rbum_rel::Entity, far_column))
+            .expr(rbum_dialect_helper::bigint(1, db_kind))
+            .from(rbum_rel::Entity)
+            .cond_where(Self::package_traverse_cond(ctx))
+            .and_where(Expr::tbl(rbum_rel::Entity, near_column).eq(traverse_req.rbum_item_id.as_str()));
+        if let Some(tag) = traverse_req.tags.first() {
+            base_query.and_where(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::Tag).eq(tag.as_str()));
+        }
+        let mut recursive_query = Query::select();
+        recursive_query
+            .column((rbum_rel::Entity, far_column))
+            .expr(Expr::tbl(cte_name.clone(), Alias::new("depth")).add(1))
+            .from(rbum_rel::Entity)
+            .inner_join(
+                cte_name.clone(),
+                Expr::tbl(cte_name.clone(), Alias::new("rbum_item_id")).equals(rbum_rel::Entity, near_column),
+            )
+            .cond_where(Self::package_traverse_cond(ctx))
+            .and_where(Expr::tbl(cte_name.clone(), Alias::new("depth")).lt(i64::from(depth)));
+        if traverse_req.tags.len() > 1 {
+            // The hop after the n-th hop uses the (n+1)-th tag
+            let mut tag_cond = Cond::any();
+            for (idx, tag) in traverse_req.tags.iter().enumerate().skip(1) {
+                tag_cond = tag_cond
+                    .add(Cond::all().add(Expr::tbl(cte_name.clone(), Alias::new("depth")).eq(idx as i64)).add(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::Tag).eq(tag.as_str())));
+            }
+            recursive_query.cond_where(tag_cond);
+        }
+        let cte = CommonTableExpression::new()
+            .query(base_query.union(UnionType::Distinct, recursive_query).to_owned())
+            .column(Alias::new("rbum_item_id"))
+            .column(Alias::new("depth"))
+            .table_name(cte_name.clone())
+            .to_owned();
+
+        let mut query = Query::select();
+        query.column((cte_name.clone(), Alias::new("rbum_item_id"))).column((cte_name.clone(), Alias::new("depth"))).from(cte_name.clone());
+        if let Some(rbum_kind_id) = rbum_kind_id {
+            query
+                .inner_join(
+                    rbum_item::Entity,
+                    Expr::tbl(rbum_item::Entity, rbum_item::Column::Id).equals(cte_name.clone(), Alias::new("rbum_item_id")),
+                )
+                .and_where(Expr::tbl(rbum_item::Entity, rbum_item::Column::RelRbumKindId).eq(rbum_kind_id));
+        }
+        let (sql, values) = query.with(WithClause::new().recursive(true).cte(cte).to_owned()).build_any(db_kind.get_query_builder().as_ref());
+        let rbum_items = funs.db().find_dtos_by_sql::<TraverseItemResp>(&sql, values.0).await?;
+        Ok(rbum_items.into_iter().map(|rbum_item| (rbum_item.rbum_item_id, rbum_item.depth as u8)).collect())
+    }
+
+    /// Walk the relationships by a query per hop, see [RbumRelServ::do_traverse_rels]
+    async fn do_traverse_rels_by_hops(
+        traverse_req: &RbumRelTraverseReq,
+        depth: u8,
+        rbum_kind_id: Option<&str>,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<Vec<(String, u8)>> {
+        let (near_column, far_column) = Self::get_traverse_columns(traverse_req.is_from);
+        let mut result = Vec::new();
+        let mut near_rbum_item_ids = vec![traverse_req.rbum_item_id.clone()];
+        for hop_depth in 1..=depth {
+            let mut far_rbum_item_ids = HashSet::new();
+            for near_rbum_item_ids in near_rbum_item_ids.chunks(TRAVERSE_CHUNK_SIZE) {
+                let mut query = Query::select();
+                query
+                    .distinct()
+                    .expr_as(Expr::tbl(rbum_rel::Entity, far_column), ID_FIELD.clone())
+                    .from(rbum_rel::Entity)
+                    .cond_where(Self::package_traverse_cond(ctx))
+                    .and_where(Expr::tbl(rbum_rel::Entity, near_column).is_in(near_rbum_item_ids.iter().map(|id| id.as_str()).collect::<Vec<&str>>()));
+                if let Some(tag) = traverse_req.tags.get(hop_depth as usize - 1) {
+                    query.and_where(Expr::tbl(rbum_rel::Entity, rbum_rel::Column::Tag).eq(tag.as_str()));
+                }
+                far_rbum_item_ids.extend(funs.db().find_dtos::<IdResp>(&query).await?.into_iter().map(|resp| resp.id));
+            }
+            near_rbum_item_ids = far_rbum_item_ids.into_iter().collect();
+            result.extend(near_rbum_item_ids.iter().map(|rbum_item_id| (rbum_item_id.clone(), hop_depth)));
+        }
+        if let Some(rbum_kind_id) = rbum_kind_id {
+            let rbum_item_ids = result.iter().map(|(rbum_item_id, _)| rbum_item_id.as_str()).collect::<HashSet<&str>>().into_iter().collect::<Vec<&str>>();
+            let mut kind_rbum_item_ids = HashSet::new();
+            for rbum_item_ids in rbum_item_ids.chunks(TRAVERSE_CHUNK_SIZE) {
+                let mut query = Query::select();
+                query
+                    .column(rbum_item::Column::Id)
+                    .from(rbum_item::Entity)
+                    .and_where(Expr::col(rbum_item::Column::Id).is_in(rbum_item_ids.to_vec()))
+                    .and_where(Expr::col(rbum_item::Column::RelRbumKindId).eq(rbum_kind_id));
+                kind_rbum_item_ids.extend(funs.db().find_dtos::<IdResp>(&query).await?.into_iter().map(|resp| resp.id));
+            }
+            result.retain(|(rbum_item_id, _)| kind_rbum_item_ids.contains(rbum_item_id));
+        }
+        Ok(result)
+    }
+
+    /// Whether the database supports recursive common table expressions, MySQL supports them since 8.0 and MariaDB since 10.2
+    async fn is_recursive_cte_supported(funs: &TardisFunsInst<'a>) -> TardisResult<bool> {
+        if funs.db().raw_conn().get_database_backend() != DbBackend::MySql {
+            return Ok(true);
+        }
+        if let Some(supported) = RECURSIVE_CTE_SUPPORTED.read().ok().and_then(|supported| supported.get(funs.module_code()).copied()) {
+            return Ok(supported);
+        }
+        let version = match funs.db().query_one("SELECT VERSION() AS version", vec![]).await? {
+            Some(row) => row.try_get::<String>("", "version")?,
+            None => "".to_string(),
+        };
+        let mut version_numbers = version.split(|c: char| !c.is_ascii_digit()).map(|number| number.parse::<u32>().unwrap_or(0));
+        let major_minor = (version_numbers.next().unwrap_or(0), version_numbers.next().unwrap_or(0));
+        let supported = if version.contains("MariaDB") { major_minor >= (10, 2) } else { major_minor >= (8, 0) };
+        if let Ok(mut recursive_cte_supported) = RECURSIVE_CTE_SUPPORTED.write() {
+            recursive_cte_supported.insert(funs.module_code().to_string(), supported);
+        }
+        Ok(supported)
+    }
+
+    /// Check whether the relationship exists, directly or through the set categories (and their parents) that the item belongs to
+    ///
+    /// The results are cached for ``cache_key_rel_check_expire_sec`` seconds or until a datetime range of the checked relationships starts or ends,
+    /// and are invalidated by the events of the records they depend on, see [RbumRelServ::check_rel_cache_subscriber].
+    /// The cache is not used without ``with-mq`` because no events are sent, and is bypassed (checked by the database) if it is unavailable.
     pub async fn check_rel(check_req: &mut RbumRelCheckReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<bool> {
-        if Self::do_check_rel(check_req, funs, ctx).await? {
+        let expire_sec = funs.rbum_conf_cache_key_rel_check_expire_sec();
+        if !cfg!(feature = "with-mq") || expire_sec == 0 {
+            return Self::do_check_rel_with_set(check_req, &mut RbumRelCheckTrace::new(Utc::now()), funs, ctx).await;
+        }
+        let cache_key = Self::package_check_rel_cache_key(check_req, funs, ctx)?;
+        let generation_key = format!("{}gen", funs.rbum_conf_cache_key_rel_check_());
+        // The generation is read before the check, a result that was checked across an invalidation is not kept
+        let generation = match funs.cache().get(&cache_key).await {
+            Ok(Some(cached_result)) => return Ok(cached_result == "true"),
+            Ok(None) => funs.cache().get(&generation_key).await,
+            Err(e) => Err(e),
+        };
+        let generation = match generation {
+            Ok(generation) => generation,
+            Err(e) => {
+                tardis::log::warn!("failed to get the cached result of check rel, check by the database: {:?}", e);
+                return Self::do_check_rel_with_set(check_req, &mut RbumRelCheckTrace::new(Utc::now()), funs, ctx).await;
+            }
+        };
+        let mut trace = RbumRelCheckTrace::new(Utc::now());
+        trace.depend_on(&check_req.from_rbum_id);
+        trace.depend_on(&check_req.to_rbum_item_id);
+        let result = Self::do_check_rel_with_set(check_req, &mut trace, funs, ctx).await?;
+        if let Err(e) = Self::cache_check_rel_result(&cache_key, result, &trace, &generation_key, generation, expire_sec, funs).await {
+            tardis::log::warn!("failed to cache the result of check rel: {:?}", e);
+        }
+        Ok(result)
+    }
+
+    /// Cache the result of [RbumRelServ::check_rel] and index it by the records it depends on
+    ///
+    /// The result is removed again if an invalidation happened since the generation was read:
+    /// the invalidation increases the generation before it removes the indexed results,
+    /// so it either finds the index written here or is seen by the generation read after it.
+    async fn cache_check_rel_result(
+        cache_key: &str,
+        result: bool,
+        trace: &RbumRelCheckTrace,
+        generation_key: &str,
+        generation: Option<String>,
+        expire_sec: usize,
+        funs: &TardisFunsInst<'a>,
+    ) -> TardisResult<()> {
+        let result_expire_sec = trace.expire_at.map(|expire_at| ((expire_at - trace.now.timestamp()).max(1) as usize).min(expire_sec)).unwrap_or(expire_sec);
+        funs.cache().set_ex(cache_key, &result.to_string(), result_expire_sec).await?;
+        let bucket = Self::get_check_rel_dependent_bucket(expire_sec);
+        for dependent_id in &trace.dependent_ids {
+            let dependent_key = Self::package_check_rel_dependent_key(dependent_id, bucket, funs);
+            funs.cache().hset(&dependent_key, cache_key, "").await?;
+            // The indexes are bucketed by the expire time, a bucket outlives the results written in it and is not extended
+            funs.cache().expire(&dependent_key, expire_sec * 2).await?;
+        }
+        if funs.cache().get(generation_key).await? != generation {
+            funs.cache().del(cache_key).await?;
+        }
+        Ok(())
+    }
+
+    /// Check whether the relationship exists at the specified time, the result is not cached
+    ///
+    /// Used to evaluate the time related environments (e.g. whether an account can access a resource at night).
+    pub async fn check_rel_at(check_req: &mut RbumRelCheckReq, now: DateTime<Utc>, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<bool> {
+        Self::do_check_rel_with_set(check_req, &mut RbumRelCheckTrace::new(now), funs, ctx).await
+    }
+
+    fn package_check_rel_cache_key(check_req: &RbumRelCheckReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<String> {
+        let from_attrs = check_req.from_attrs.iter().collect::<BTreeMap<&String, &String>>();
+        let to_attrs = check_req.to_attrs.iter().collect::<BTreeMap<&String, &String>>();
+        let check_req = TardisFuns::json.obj_to_string(&(
+            &ctx.own_paths,
+            &check_req.tag,
+            check_req.from_rbum_kind.to_int(),
+            &check_req.from_rbum_id,
+            &check_req.to_rbum_item_id,
+            from_attrs,
+            to_attrs,
+            check_req.get_request_ip(funs),
+        ))?;
+        Ok(format!("{}{}", funs.rbum_conf_cache_key_rel_check_(), TardisFuns::crypto.digest.md5(&check_req)?))
+    }
+
+    /// Subscriber that invalidates the cached results of [RbumRelServ::check_rel]
+    ///
+    /// It should be registered once by the application with [subscribe](crate::rbum::helper::rbum_event_helper::subscribe),
+    /// and the ``c``, ``u``, ``d`` and ``r`` events of the relationship and set tables must be enabled in ``event_domains``.
+    pub fn check_rel_cache_subscriber(code: &str) -> RbumEventSubscriber {
+        let code = code.to_string();
+        RbumEventSubscriber::new(move |message| {
+            let funs = TardisFuns::inst_with_db_conn(code.clone());
+            async move { RbumRelServ::invalidate_check_rel_cache(&message, &funs).await }
+        })
+        .table_prefix(rbum_rel::Entity.table_name())
+        .table_prefix(rbum_set_item::Entity.table_name())
+        .table_prefix(rbum_set_cate::Entity.table_name())
+    }
+
+    /// Invalidate the cached results of [RbumRelServ::check_rel] that depend on the changed record
+    ///
+    /// The results depend on the records they read, a new record affects the results
+    /// of the relationship target (for relationships) or of the item (for set items) or of the relationship (for attributes and environments).
+    pub async fn invalidate_check_rel_cache(message: &RbumEventMessage, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        let mut dependent_ids = vec![message.record_id.clone()];
+        if message.operate != "d" {
+            let dependent_id = if message.table_name == rbum_rel::Entity.table_name() {
+                Self::get_check_rel_dependent_id(rbum_rel::Entity, rbum_rel::Column::ToRbumItemId, &message.record_id, funs).await?
+            } else if message.table_name == rbum_rel_attr::Entity.table_name() {
+                Self::get_check_rel_dependent_id(rbum_rel_attr::Entity, rbum_rel_attr::Column::RelRbumRelId, &message.record_id, funs).await?
+            } else if message.table_name == rbum_rel_env::Entity.table_name() {
+                Self::get_check_rel_dependent_id(rbum_rel_env::Entity, rbum_rel_env::Column::RelRbumRelId, &message.record_id, funs).await?
+            } else if message.table_name == rbum_set_item::Entity.table_name() {
+                Self::get_check_rel_dependent_id(rbum_set_item::Entity, rbum_set_item::Column::RelRbumItemId, &message.record_id, funs).await?
+            } else {
+                None
+            };
+            dependent_ids.extend(dependent_id);
+        }
+        let expire_sec = funs.rbum_conf_cache_key_rel_check_expire_sec();
+        if expire_sec == 0 {
+            return Ok(());
+        }
+        funs.cache().incr(&format!("{}gen", funs.rbum_conf_cache_key_rel_check_()), 1).await?;
+        // The live results were written in the current or the previous bucket
+        let bucket = Self::get_check_rel_dependent_bucket(expire_sec);
+        for dependent_id in dependent_ids {
+            for dependent_key in [
+                Self::package_check_rel_dependent_key(&dependent_id, bucket, funs),
+                Self::package_check_rel_dependent_key(&dependent_id, bucket - 1, funs),
+            ] {
+                for cache_key in funs.cache().hgetall(&dependent_key).await?.keys() {
+                    funs.cache().del(cache_key).await?;
+                }
+                funs.cache().del(&dependent_key).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_check_rel_dependent_bucket(expire_sec: usize) -> i64 {
+        Utc::now().timestamp() / expire_sec as i64
+    }
+
+    fn package_check_rel_dependent_key(dependent_id: &str, bucket: i64, funs: &TardisFunsInst<'a>) -> String {
+        format!("{}dep:{}:{}", funs.rbum_conf_cache_key_rel_check_(), dependent_id, bucket)
+    }
+
+    async fn get_check_rel_dependent_id<E: EntityTrait>(entity: E, column: E::Column, id: &str, funs: &TardisFunsInst<'a>) -> TardisResult<Option<String>> {
+        let mut query = Query::select();
+        query.expr_as(Expr::col(column), ID_FIELD.clone()).from(entity).and_where(Expr::col(ID_FIELD.clone()).eq(id));
+        Ok(funs.db().get_dto::<IdResp>(&query).await?.map(|resp| resp.id))
+    }
+
+    async fn do_check_rel_with_set(check_req: &mut RbumRelCheckReq, trace: &mut RbumRelCheckTrace, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<bool> {
+        if Self::do_check_rel(check_req, trace, funs, ctx).await? {
             return Ok(true);
         }
         let rel_rbum_set_cate_ids = if check_req.from_rbum_kind == RbumRelFromKind::Item {
@@ -755,7 +1280,10 @@ impl<'a> RbumRelServ {
             )
             .await?
             .into_iter()
-            .map(|i| i.rel_rbum_set_cate_id)
+            .map(|i| {
+                trace.depend_on(&i.id);
+                i.rel_rbum_set_cate_id
+            })
             .collect::<Vec<String>>()
         } else if check_req.from_rbum_kind == RbumRelFromKind::SetCate {
             vec![check_req.from_rbum_id.clone()]
@@ -763,18 +1291,19 @@ impl<'a> RbumRelServ {
             return Ok(false);
         };
         for rel_rbum_set_cate_id in rel_rbum_set_cate_ids {
+            trace.depend_on(&rel_rbum_set_cate_id);
             let rbum_set_cate_base = RbumSetCateServ::peek_rbum(&rel_rbum_set_cate_id, &RbumSetCateFilterReq::default(), funs, ctx).await?;
             if check_req.from_rbum_kind != RbumRelFromKind::SetCate {
                 check_req.from_rbum_kind = RbumRelFromKind::SetCate;
                 check_req.from_rbum_id = rbum_set_cate_base.id.clone();
                 // Check directly related records
-                if Self::do_check_rel(check_req, funs, ctx).await? {
+                if Self::do_check_rel(check_req, trace, funs, ctx).await? {
                     return Ok(true);
                 }
             }
             check_req.from_rbum_kind = RbumRelFromKind::Set;
             check_req.from_rbum_id = rbum_set_cate_base.rel_rbum_set_id.clone();
-            if Self::do_check_rel(check_req, funs, ctx).await? {
+            if Self::do_check_rel(check_req, trace, funs, ctx).await? {
                 return Ok(true);
             }
             let rbum_set_cate_with_rel_ids = RbumSetCateServ::find_id_rbums(
@@ -792,10 +1321,11 @@ impl<'a> RbumRelServ {
             )
             .await?;
             for rbum_set_cate_with_rel_id in rbum_set_cate_with_rel_ids {
+                trace.depend_on(&rbum_set_cate_with_rel_id);
                 check_req.from_rbum_kind = RbumRelFromKind::SetCate;
                 check_req.from_rbum_id = rbum_set_cate_with_rel_id;
                 // Check indirectly related records
-                if Self::do_check_rel(check_req, funs, ctx).await? {
+                if Self::do_check_rel(check_req, trace, funs, ctx).await? {
                     return Ok(true);
                 }
             }
@@ -803,7 +1333,7 @@ impl<'a> RbumRelServ {
         Ok(false)
     }
 
-    async fn do_check_rel(check_req: &RbumRelCheckReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<bool> {
+    async fn do_check_rel(check_req: &RbumRelCheckReq, trace: &mut RbumRelCheckTrace, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<bool> {
         let rbum_rel_ids = Self::find_rel_ids(
             &RbumRelFindReq {
                 tag: Some(check_req.tag.clone()),
@@ -816,36 +1346,56 @@ impl<'a> RbumRelServ {
         )
         .await?;
         for rbum_rel_id in rbum_rel_ids {
+            trace.depend_on(&rbum_rel_id);
             let mut found = true;
             let rbum_rel_attrs = funs
                 .db()
                 .find_dtos::<NameAndValueResp>(
                     Query::select()
-                        .column(rbum_rel_attr::Column::IsFrom)
-                        .column(rbum_rel_attr::Column::Name)
-                        .column(rbum_rel_attr::Column::Value)
+                        .columns(vec![
+                            (rbum_rel_attr::Entity, rbum_rel_attr::Column::Id),
+                            (rbum_rel_attr::Entity, rbum_rel_attr::Column::IsFrom),
+                            (rbum_rel_attr::Entity, rbum_rel_attr::Column::Name),
+                            (rbum_rel_attr::Entity, rbum_rel_attr::Column::Op),
+                            (rbum_rel_attr::Entity, rbum_rel_attr::Column::Value),
+                        ])
+                        .column((rbum_kind_attr::Entity, rbum_kind_attr::Column::DataType))
                         .from(rbum_rel_attr::Entity)
-                        .and_where(Expr::col(rbum_rel_attr::Column::RelRbumRelId).eq(rbum_rel_id.clone()))
-                        .and_where(Expr::col(rbum_rel_attr::Column::RecordOnly).eq(false)),
+                        // The conditions of the deleted kind attributes are kept and never satisfied
+                        .left_join(
+                            rbum_kind_attr::Entity,
+                            Expr::tbl(rbum_kind_attr::Entity, rbum_kind_attr::Column::Id).equals(rbum_rel_attr::Entity, rbum_rel_attr::Column::RelRbumKindAttrId),
+                        )
+                        .and_where(Expr::tbl(rbum_rel_attr::Entity, rbum_rel_attr::Column::RelRbumRelId).eq(rbum_rel_id.clone()))
+                        .and_where(Expr::tbl(rbum_rel_attr::Entity, rbum_rel_attr::Column::RecordOnly).eq(false)),
                 )
                 .await?;
             for rbum_rel_attr in rbum_rel_attrs {
-                if rbum_rel_attr.is_from {
-                    if let Some(value) = check_req.from_attrs.get(&rbum_rel_attr.name) {
-                        if value != rbum_rel_attr.value.as_str() {
-                            found = false;
-                            break;
-                        }
-                    } else {
-                        found = false;
-                        break;
-                    }
-                } else if let Some(value) = check_req.to_attrs.get(&rbum_rel_attr.name) {
-                    if value != rbum_rel_attr.value.as_str() {
-                        found = false;
-                        break;
-                    }
+                trace.depend_on(&rbum_rel_attr.id);
+                let value = if rbum_rel_attr.is_from {
+                    check_req.from_attrs.get(&rbum_rel_attr.name)
                 } else {
+                    check_req.to_attrs.get(&rbum_rel_attr.name)
+                };
+                // Missing attributes (and the conditions of the deleted kind attributes) never satisfy the condition, whatever the operator is
+                let satisfied = match (value, &rbum_rel_attr.data_type) {
+                    (Some(value), Some(data_type)) => match rbum_kind_attr_helper::check_condition(data_type, &rbum_rel_attr.op, &rbum_rel_attr.value, value) {
+                        Ok(satisfied) => satisfied,
+                        Err(e) => {
+                            // Illegal conditions (e.g. added before they were validated) never match
+                            tardis::log::warn!(
+                                "illegal condition {} {} of rbum_rel_attr {}: {}",
+                                rbum_rel_attr.op,
+                                rbum_rel_attr.value,
+                                rbum_rel_attr.id,
+                                e
+                            );
+                            false
+                        }
+                    },
+                    _ => false,
+                };
+                if !satisfied {
                     found = false;
                     break;
                 }
@@ -854,6 +1404,7 @@ impl<'a> RbumRelServ {
                 .db()
                 .find_dtos::<KindAndValueResp>(
                     Query::select()
+                        .column(rbum_rel_env::Column::Id)
                         .column(rbum_rel_env::Column::Kind)
                         .column(rbum_rel_env::Column::Value1)
                         .column(rbum_rel_env::Column::Value2)
@@ -862,18 +1413,59 @@ impl<'a> RbumRelServ {
                 )
                 .await?;
             for rbum_rel_env in rbum_rel_envs {
+                trace.depend_on(&rbum_rel_env.id);
                 match rbum_rel_env.kind {
                     RbumRelEnvKind::DatetimeRange => {
-                        if i64::from_str(rbum_rel_env.value1.as_str())? > Utc::now().timestamp() || i64::from_str(rbum_rel_env.value2.as_str())? < Utc::now().timestamp() {
+                        let now = trace.now.timestamp();
+                        let start_time = i64::from_str(rbum_rel_env.value1.as_str())?;
+                        let end_time = i64::from_str(rbum_rel_env.value2.as_str())?;
+                        if start_time > now {
+                            trace.expire_no_later_than(start_time);
+                        } else if end_time >= now {
+                            trace.expire_no_later_than(end_time + 1);
+                        }
+                        if start_time > now || end_time < now {
                             found = false;
                             break;
                         }
                     }
                     RbumRelEnvKind::TimeRange => {
-                        // TODO
+                        let (start_time, end_time) = if let Some(time_range) = parse_time_range(&rbum_rel_env.value1, &rbum_rel_env.value2) {
+                            time_range
+                        } else {
+                            // Illegal values (e.g. added before they were validated) never match
+                            tardis::log::warn!("illegal time range {} - {} of rbum_rel_env {}", rbum_rel_env.value1, rbum_rel_env.value2, rbum_rel_env.id);
+                            found = false;
+                            break;
+                        };
+                        let now = trace.now.num_seconds_from_midnight();
+                        let in_range = if start_time < end_time {
+                            start_time <= now && now < end_time
+                        } else {
+                            start_time <= now || now < end_time
+                        };
+                        let next_time = if in_range { end_time } else { start_time };
+                        let seconds_to_next_time = (next_time + SECONDS_PER_DAY - now) % SECONDS_PER_DAY;
+                        trace.expire_no_later_than(trace.now.timestamp() + i64::from(seconds_to_next_time));
+                        if !in_range {
+                            found = false;
+                            break;
+                        }
                     }
                     RbumRelEnvKind::Ips => {
-                        // TODO
+                        let ip_ranges = if let Some(ip_ranges) = parse_ip_ranges(&rbum_rel_env.value1) {
+                            ip_ranges
+                        } else {
+                            tardis::log::warn!("illegal ips {} of rbum_rel_env {}", rbum_rel_env.value1, rbum_rel_env.id);
+                            found = false;
+                            break;
+                        };
+                        // Requests without a (legal) address never match
+                        let in_ranges = check_req.get_request_ip(funs).map(|ip| ip_ranges.iter().any(|(start_ip, end_ip)| start_ip <= &ip && &ip <= end_ip)).unwrap_or(false);
+                        if !in_ranges {
+                            found = false;
+                            break;
+                        }
                     }
                 }
             }
@@ -946,6 +1538,7 @@ impl<'a> RbumCrudOperation<'a, rbum_rel_attr::ActiveModel, RbumRelAttrAddReq, Rb
         Ok(rbum_rel_attr::ActiveModel {
             id: Set(TardisFuns::field.nanoid()),
             is_from: Set(add_req.is_from),
+            op: Set(add_req.op.as_ref().unwrap_or(&RbumRelAttrOpKind::Eq).to_int()),
             value: Set(add_req.value.to_string()),
             name: Set(rbum_rel_attr_name),
             record_only: Set(add_req.record_only),
@@ -958,6 +1551,30 @@ impl<'a> RbumCrudOperation<'a, rbum_rel_attr::ActiveModel, RbumRelAttrAddReq, Rb
     async fn before_add_rbum(add_req: &mut RbumRelAttrAddReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         Self::check_ownership_with_table_name(&add_req.rel_rbum_rel_id, RbumRelServ::get_table_name(), funs, ctx).await?;
         Self::check_scope(&add_req.rel_rbum_kind_attr_id, RbumKindAttrServ::get_table_name(), funs, ctx).await?;
+        Self::check_condition(
+            &add_req.rel_rbum_kind_attr_id,
+            add_req.op.as_ref().unwrap_or(&RbumRelAttrOpKind::Eq),
+            &add_req.value,
+            "add",
+            funs,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn before_modify_rbum(id: &str, modify_req: &mut RbumRelAttrModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::check_ownership(id, funs, ctx).await?;
+        if modify_req.op.is_some() || modify_req.value.is_some() {
+            let rbum_rel_attr = Self::peek_rbum(id, &RbumRelExtFilterReq::default(), funs, ctx).await?;
+            Self::check_condition(
+                &rbum_rel_attr.rel_rbum_kind_attr_id,
+                modify_req.op.as_ref().unwrap_or(&rbum_rel_attr.op),
+                modify_req.value.as_ref().unwrap_or(&rbum_rel_attr.value),
+                "modify",
+                funs,
+            )
+            .await?;
+        }
         Ok(())
     }
 
@@ -966,6 +1583,9 @@ impl<'a> RbumCrudOperation<'a, rbum_rel_attr::ActiveModel, RbumRelAttrAddReq, Rb
             id: Set(id.to_string()),
             ..Default::default()
         };
+        if let Some(op) = &modify_req.op {
+            rbum_rel_attr.op = Set(op.to_int());
+        }
         if let Some(value) = &modify_req.value {
             rbum_rel_attr.value = Set(value.to_string());
         }
@@ -981,6 +1601,7 @@ impl<'a> RbumCrudOperation<'a, rbum_rel_attr::ActiveModel, RbumRelAttrAddReq, Rb
             .columns(vec![
                 (rbum_rel_attr::Entity, rbum_rel_attr::Column::Id),
                 (rbum_rel_attr::Entity, rbum_rel_attr::Column::IsFrom),
+                (rbum_rel_attr::Entity, rbum_rel_attr::Column::Op),
                 (rbum_rel_attr::Entity, rbum_rel_attr::Column::Value),
                 (rbum_rel_attr::Entity, rbum_rel_attr::Column::Name),
                 (rbum_rel_attr::Entity, rbum_rel_attr::Column::RecordOnly),
@@ -1026,6 +1647,22 @@ impl<'a> RbumCrudOperation<'a, rbum_rel_env::ActiveModel, RbumRelEnvAddReq, Rbum
 
     async fn before_add_rbum(add_req: &mut RbumRelEnvAddReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         Self::check_ownership_with_table_name(&add_req.rel_rbum_rel_id, RbumRelServ::get_table_name(), funs, ctx).await?;
+        Self::check_values(&add_req.kind, &add_req.value1, add_req.value2.as_deref().unwrap_or(""), "add", funs)?;
+        Ok(())
+    }
+
+    async fn before_modify_rbum(id: &str, modify_req: &mut RbumRelEnvModifyReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::check_ownership(id, funs, ctx).await?;
+        if modify_req.value1.is_some() || modify_req.value2.is_some() {
+            let rbum_rel_env = Self::peek_rbum(id, &RbumRelExtFilterReq::default(), funs, ctx).await?;
+            Self::check_values(
+                &rbum_rel_env.kind,
+                modify_req.value1.as_ref().unwrap_or(&rbum_rel_env.value1),
+                modify_req.value2.as_ref().unwrap_or(&rbum_rel_env.value2),
+                "modify",
+                funs,
+            )?;
+        }
         Ok(())
     }
 
@@ -1067,8 +1704,185 @@ impl<'a> RbumCrudOperation<'a, rbum_rel_env::ActiveModel, RbumRelEnvAddReq, Rbum
     }
 }
 
+impl<'a> RbumRelAttrServ {
+    async fn check_condition(rel_rbum_kind_attr_id: &str, op: &RbumRelAttrOpKind, value: &str, op_name: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        #[derive(Debug, FromQueryResult)]
+        struct DataTypeResp {
+            pub data_type: RbumDataTypeKind,
+        }
+        let data_type = funs
+            .db()
+            .get_dto::<DataTypeResp>(
+                Query::select().column(rbum_kind_attr::Column::DataType).from(rbum_kind_attr::Entity).and_where(Expr::col(rbum_kind_attr::Column::Id).eq(rel_rbum_kind_attr_id)),
+            )
+            .await?
+            .ok_or_else(|| funs.err().not_found(&Self::get_obj_name(), op_name, &format!("not found rbum_kind_attr {}", rel_rbum_kind_attr_id)))?
+            .data_type;
+        rbum_kind_attr_helper::validate_condition(&data_type, op, value)
+            .map_err(|e| funs.err().bad_request(&Self::get_obj_name(), op_name, &format!("condition {} {} is illegal: {}", op, value, e)))
+    }
+}
+
+impl<'a> RbumRelEnvServ {
+    fn check_values(kind: &RbumRelEnvKind, value1: &str, value2: &str, op: &str, funs: &TardisFunsInst<'a>) -> TardisResult<()> {
+        if kind == &RbumRelEnvKind::TimeRange && parse_time_range(value1, value2).is_none() {
+            return Err(funs.err().bad_request(
+                &Self::get_obj_name(),
+                op,
+                &format!(
+                    "time range {} - {} is illegal, the format is HH:MM:SS±HH:MM and the start must differ from the end",
+                    value1, value2
+                ),
+            ));
+        }
+        if kind == &RbumRelEnvKind::Ips && parse_ip_ranges(value1).is_none() {
+            return Err(funs.err().bad_request(
+                &Self::get_obj_name(),
+                op,
+                &format!(
+                    "ips {} is illegal, the format is a comma separated list of addresses, CIDRs (e.g. 10.0.0.0/8) or ranges (e.g. 10.0.0.1-10.0.0.9)",
+                    value1
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Maximum number of hops of a relationship traversal
+const TRAVERSE_MAX_DEPTH: u8 = 10;
+
+/// Maximum number of items bound to a query of a relationship traversal
+const TRAVERSE_CHUNK_SIZE: usize = 500;
+
+lazy_static! {
+    // module code -> whether its database supports recursive common table expressions
+    static ref RECURSIVE_CTE_SUPPORTED: RwLock<HashMap<String, bool>> = RwLock::new(HashMap::new());
+}
+
+/// Parse the start and end of a [RbumRelEnvKind::TimeRange] to the seconds from midnight in UTC
+fn parse_time_range(value1: &str, value2: &str) -> Option<(u32, u32)> {
+    let parse_time =
+        |value: &str| DateTime::parse_from_str(&format!("1970-01-01 {}", value), "%Y-%m-%d %H:%M:%S%:z").ok().map(|time| time.with_timezone(&Utc).num_seconds_from_midnight());
+    match (parse_time(value1), parse_time(value2)) {
+        (Some(start_time), Some(end_time)) if start_time != end_time => Some((start_time, end_time)),
+        _ => None,
+    }
+}
+
+/// Parse the value of a [RbumRelEnvKind::Ips] to inclusive address ranges
+fn parse_ip_ranges(value: &str) -> Option<Vec<(IpAddr, IpAddr)>> {
+    let parse_ip = |ip: &str| IpAddr::from_str(ip.trim()).ok().map(normalize_ip);
+    value
+        .split(',')
+        .map(|item| {
+            if let Some((ip, prefix_len)) = item.split_once('/') {
+                let ip = parse_ip(ip)?;
+                let prefix_len = u32::from_str(prefix_len.trim()).ok()?;
+                match ip {
+                    IpAddr::V4(ip) if prefix_len <= 32 => {
+                        let mask = u32::MAX.checked_shl(32 - prefix_len).unwrap_or(0);
+                        let start_ip = u32::from(ip) & mask;
+                        Some((IpAddr::from(start_ip.to_be_bytes()), IpAddr::from((start_ip | !mask).to_be_bytes())))
+                    }
+                    IpAddr::V6(ip) if prefix_len <= 128 => {
+                        let mask = u128::MAX.checked_shl(128 - prefix_len).unwrap_or(0);
+                        let start_ip = u128::from(ip) & mask;
+                        Some((IpAddr::from(start_ip.to_be_bytes()), IpAddr::from((start_ip | !mask).to_be_bytes())))
+                    }
+                    _ => None,
+                }
+            } else if let Some((start_ip, end_ip)) = item.split_once('-') {
+                let (start_ip, end_ip) = (parse_ip(start_ip)?, parse_ip(end_ip)?);
+                if start_ip.is_ipv4() != end_ip.is_ipv4() || start_ip > end_ip {
+                    return None;
+                }
+                Some((start_ip, end_ip))
+            } else {
+                parse_ip(item).map(|ip| (ip, ip))
+            }
+        })
+        .collect()
+}
+
+/// IPv4-mapped IPv6 addresses (e.g. ``::ffff:10.0.0.1`` from dual-stack sockets) are compared as IPv4 addresses
+fn normalize_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => ipv6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+impl RbumRelCheckReq {
+    /// Address of the original client
+    ///
+    /// The hops (``client_ip`` and then the forwarded addresses from the right) are followed while they are trusted proxies,
+    /// the first untrusted hop is the client, ``client_ip`` is used if all hops are trusted.
+    /// An illegal forwarded address is not followed, so no address is matched.
+    fn get_request_ip(&self, funs: &TardisFunsInst) -> Option<IpAddr> {
+        let env = self.env.as_ref()?;
+        let client_ip = IpAddr::from_str(env.client_ip.as_ref()?.trim()).ok().map(normalize_ip)?;
+        let trusted_proxy_ips = funs.rbum_conf_trusted_proxy_ips();
+        let trusted_proxies = if trusted_proxy_ips.trim().is_empty() {
+            vec![]
+        } else {
+            parse_ip_ranges(&trusted_proxy_ips).unwrap_or_else(|| {
+                tardis::log::warn!("illegal trusted_proxy_ips {} of rbum config", trusted_proxy_ips);
+                vec![]
+            })
+        };
+        let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|(start_ip, end_ip)| start_ip <= ip && ip <= end_ip);
+        if !is_trusted(&client_ip) {
+            return Some(client_ip);
+        }
+        for forwarded_ip in env.forwarded_ips.iter().flatten().rev() {
+            let forwarded_ip = IpAddr::from_str(forwarded_ip.trim()).ok().map(normalize_ip)?;
+            if !is_trusted(&forwarded_ip) {
+                return Some(forwarded_ip);
+            }
+        }
+        Some(client_ip)
+    }
+}
+
+/// Records read by a relationship check and the time its result may change
+struct RbumRelCheckTrace {
+    /// The time to check at
+    pub now: DateTime<Utc>,
+    pub dependent_ids: HashSet<String>,
+    /// Timestamp in seconds
+    pub expire_at: Option<i64>,
+}
+
+impl RbumRelCheckTrace {
+    fn new(now: DateTime<Utc>) -> Self {
+        RbumRelCheckTrace {
+            now,
+            dependent_ids: HashSet::new(),
+            expire_at: None,
+        }
+    }
+
+    fn depend_on(&mut self, id: &str) {
+        self.dependent_ids.insert(id.to_string());
+    }
+
+    fn expire_no_later_than(&mut self, expire_at: i64) {
+        self.expire_at = Some(self.expire_at.map(|current| current.min(expire_at)).unwrap_or(expire_at));
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TraverseItemResp {
+    pub rbum_item_id: String,
+    pub depth: i64,
+}
+
 #[derive(Debug, FromQueryResult)]
 struct KindAndValueResp {
+    pub id: String,
     pub kind: RbumRelEnvKind,
     pub value1: String,
     pub value2: String,
@@ -1076,7 +1890,10 @@ struct KindAndValueResp {
 
 #[derive(Debug, FromQueryResult)]
 struct NameAndValueResp {
+    pub id: String,
     pub is_from: bool,
     pub name: String,
+    pub op: RbumRelAttrOpKind,
     pub value: String,
+    pub data_type: Option<RbumDataTypeKind>,
 }
@@ -12,6 +12,7 @@ use crate::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumSetCateFilterReq,
 use crate::rbum::dto::rbum_set_cate_dto::{RbumSetCateAddReq, RbumSetCateDetailResp, RbumSetCateModifyReq, RbumSetCateSummaryResp, RbumSetItemInfoResp, RbumSetTreeResp};
 use crate::rbum::dto::rbum_set_dto::{RbumSetAddReq, RbumSetDetailResp, RbumSetModifyReq, RbumSetPathResp, RbumSetSummaryResp};
 use crate::rbum::dto::rbum_set_item_dto::{RbumSetItemAddReq, RbumSetItemDetailResp, RbumSetItemModifyReq, RbumSetItemSummaryResp};
+use crate::rbum::helper::rbum_dialect_helper;
 use crate::rbum::rbum_config::RbumConfigApi;
 use crate::rbum::rbum_enumeration::{RbumCertRelKind, RbumRelFromKind, RbumScopeLevelKind, RbumSetCateLevelQueryKind};
 use crate::rbum::serv::rbum_cert_serv::RbumCertServ;
@@ -29,7 +30,7 @@ impl<'a> RbumCrudOperation<'a, rbum_set::ActiveModel, RbumSetAddReq, RbumSetModi
         rbum_set::Entity.table_name()
     }
 
-    async fn package_add(add_req: &RbumSetAddReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_set::ActiveModel> {
+    async fn package_add(add_req: &RbumSetAddReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_set::ActiveModel> {
         Ok(rbum_set::ActiveModel {
             id: Set(TardisFuns::field.nanoid()),
             code: Set(add_req.code.to_string()),
@@ -39,13 +40,13 @@ impl<'a> RbumCrudOperation<'a, rbum_set::ActiveModel, RbumSetAddReq, RbumSetModi
             icon: Set(add_req.icon.as_ref().unwrap_or(&"".to_string()).to_string()),
             sort: Set(add_req.sort.unwrap_or(0)),
             ext: Set(add_req.ext.as_ref().unwrap_or(&"".to_string()).to_string()),
-            scope_level: Set(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private).to_int()),
+            scope_level: Set(Self::check_scope_level(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private), funs)?),
             disabled: Set(add_req.disabled.unwrap_or(false)),
             ..Default::default()
         })
     }
 
-    async fn package_modify(id: &str, modify_req: &RbumSetModifyReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_set::ActiveModel> {
+    async fn package_modify(id: &str, modify_req: &RbumSetModifyReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_set::ActiveModel> {
         let mut rbum_set = rbum_set::ActiveModel {
             id: Set(id.to_string()),
             ..Default::default()
@@ -66,7 +67,7 @@ impl<'a> RbumCrudOperation<'a, rbum_set::ActiveModel, RbumSetAddReq, RbumSetModi
             rbum_set.ext = Set(ext.to_string());
         }
         if let Some(scope_level) = &modify_req.scope_level {
-            rbum_set.scope_level = Set(scope_level.to_int());
+            rbum_set.scope_level = Set(Self::check_scope_level(scope_level, funs)?);
         }
         if let Some(disabled) = modify_req.disabled {
             rbum_set.disabled = Set(disabled);
@@ -276,15 +277,14 @@ impl<'a> RbumSetServ {
             .and_where(Expr::col(rbum_set_cate::Column::RelRbumSetId).eq(rbum_set_id));
 
         if !fetch_all {
+            let sys_code_len = rbum_dialect_helper::char_length(Expr::col(rbum_set_cate::Column::SysCode), funs.db().raw_conn().get_database_backend());
             if let Some(parent_set_cate_id) = rbum_parent_set_cate_id {
                 Self::check_scope(parent_set_cate_id, RbumSetCateServ::get_table_name(), funs, ctx).await?;
                 let parent_sys_code = RbumSetCateServ::get_sys_code(parent_set_cate_id, funs, ctx).await?;
                 query.and_where(Expr::col(rbum_set_cate::Column::SysCode).like(format!("{}%", parent_sys_code).as_str()));
-                query.and_where(
-                    Expr::expr(Func::char_length(Expr::col(rbum_set_cate::Column::SysCode))).eq((parent_sys_code.len() + funs.rbum_conf_set_cate_sys_code_node_len()) as i32),
-                );
+                query.and_where(Expr::expr(sys_code_len).eq((parent_sys_code.len() + funs.rbum_conf_set_cate_sys_code_node_len()) as i32));
             } else {
-                query.and_where(Expr::expr(Func::char_length(Expr::col(rbum_set_cate::Column::SysCode))).eq(funs.rbum_conf_set_cate_sys_code_node_len() as i32));
+                query.and_where(Expr::expr(sys_code_len).eq(funs.rbum_conf_set_cate_sys_code_node_len() as i32));
             }
             query.order_by(rbum_set_cate::Column::Sort, Order::Asc);
         }
@@ -315,6 +315,32 @@ impl<'a> RbumSetServ {
             Ok(None)
         }
     }
+
+    /// Change the code of the set, e.g. the code contains the own paths that is changed by moving
+    pub async fn modify_rbum_set_code(id: &str, code: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        let rbum_set = Self::peek_rbum(
+            id,
+            &RbumSetFilterReq {
+                basic: RbumBasicFilterReq {
+                    with_sub_own_paths: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            funs,
+            ctx,
+        )
+        .await?;
+        if rbum_set.code == code {
+            return Ok(());
+        }
+        if funs.db().count(Query::select().column(rbum_set::Column::Id).from(rbum_set::Entity).and_where(Expr::col(rbum_set::Column::Code).eq(code))).await? > 0 {
+            return Err(funs.err().conflict(&Self::get_obj_name(), "modify", &format!("code {} already exists", code)));
+        }
+        funs.db().execute(Query::update().table(rbum_set::Entity).value(rbum_set::Column::Code, code.into()).and_where(Expr::col(rbum_set::Column::Id).eq(id))).await?;
+        funs.cache().del(&format!("{}{}", funs.rbum_conf_cache_key_set_code_(), rbum_set.code)).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -340,7 +366,7 @@ impl<'a> RbumCrudOperation<'a, rbum_set_cate::ActiveModel, RbumSetCateAddReq, Rb
             sort: Set(add_req.sort.unwrap_or(0)),
             ext: Set(add_req.ext.as_ref().unwrap_or(&"".to_string()).to_string()),
             rel_rbum_set_id: Set(add_req.rel_rbum_set_id.to_string()),
-            scope_level: Set(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private).to_int()),
+            scope_level: Set(Self::check_scope_level(add_req.scope_level.as_ref().unwrap_or(&RbumScopeLevelKind::Private), funs)?),
             ..Default::default()
         })
     }
@@ -353,7 +379,7 @@ impl<'a> RbumCrudOperation<'a, rbum_set_cate::ActiveModel, RbumSetCateAddReq, Rb
         Ok(())
     }
 
-    async fn package_modify(id: &str, modify_req: &RbumSetCateModifyReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_set_cate::ActiveModel> {
+    async fn package_modify(id: &str, modify_req: &RbumSetCateModifyReq, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<rbum_set_cate::ActiveModel> {
         let mut rbum_set_cate = rbum_set_cate::ActiveModel {
             id: Set(id.to_string()),
             ..Default::default()
@@ -374,7 +400,7 @@ impl<'a> RbumCrudOperation<'a, rbum_set_cate::ActiveModel, RbumSetCateAddReq, Rb
             rbum_set_cate.ext = Set(ext.to_string());
         }
         if let Some(scope_level) = &modify_req.scope_level {
-            rbum_set_cate.scope_level = Set(scope_level.to_int());
+            rbum_set_cate.scope_level = Set(Self::check_scope_level(scope_level, funs)?);
         }
         Ok(rbum_set_cate)
     }
@@ -538,12 +564,13 @@ impl<'a> RbumSetCateServ {
         let mut query = Query::select();
         query.columns(vec![(rbum_set_cate::Column::SysCode)]).from(rbum_set_cate::Entity).and_where(Expr::col(rbum_set_cate::Column::RelRbumSetId).eq(rbum_set_id));
 
+        let sys_code_len = rbum_dialect_helper::char_length(Expr::col(rbum_set_cate::Column::SysCode), funs.db().raw_conn().get_database_backend());
         if let Some(parent_sys_code) = parent_sys_code {
             query.and_where(Expr::col(rbum_set_cate::Column::SysCode).like(format!("{}%", parent_sys_code).as_str()));
-            query.and_where(Expr::expr(Func::char_length(Expr::col(rbum_set_cate::Column::SysCode))).eq((parent_sys_code.len() + set_cate_sys_code_node_len) as i32));
+            query.and_where(Expr::expr(sys_code_len).eq((parent_sys_code.len() + set_cate_sys_code_node_len) as i32));
         } else {
             // fetch max code in level 1
-            query.and_where(Expr::expr(Func::char_length(Expr::col(rbum_set_cate::Column::SysCode))).eq(set_cate_sys_code_node_len as i32));
+            query.and_where(Expr::expr(sys_code_len).eq(set_cate_sys_code_node_len as i32));
         }
         query.order_by(rbum_set_cate::Column::SysCode, Order::Desc);
         let max_sys_code = funs.db().get_dto::<SysCodeResp>(&query).await?.map(|r| r.sys_code);
@@ -2,11 +2,13 @@ use tardis::basic::result::TardisResult;
 use tardis::{testcontainers, tokio};
 
 mod test_basic;
+mod test_rbum_archive;
 mod test_rbum_cert;
 mod test_rbum_domain;
 mod test_rbum_event;
 mod test_rbum_item;
 mod test_rbum_kind;
+mod test_rbum_migration;
 mod test_rbum_rel;
 mod test_rbum_set;
 mod test_scope;
@@ -16,6 +18,7 @@ async fn test_rbum() -> TardisResult<()> {
     let docker = testcontainers::clients::Cli::default();
     let _x = test_basic::init(&docker).await?;
     let ctx = test_basic::init_test_data().await?;
+    test_rbum_migration::test().await?;
     test_scope::test().await?;
     test_rbum_domain::test(&ctx).await?;
     test_rbum_kind::test(&ctx).await?;
@@ -24,5 +27,6 @@ async fn test_rbum() -> TardisResult<()> {
     test_rbum_rel::test(&ctx).await?;
     test_rbum_set::test(&ctx).await?;
     test_rbum_event::test(&ctx).await?;
+    test_rbum_archive::test(&ctx).await?;
     Ok(())
 }
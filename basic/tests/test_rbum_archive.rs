@@ -0,0 +1,218 @@
+use std::io::Cursor;
+
+use tardis::basic::dto::TardisContext;
+use tardis::basic::field::TrimString;
+use tardis::basic::result::TardisResult;
+use tardis::log::info;
+use tardis::serde_json::Value;
+use tardis::TardisFuns;
+
+use bios_basic::rbum::dto::rbum_archive_dto::RbumArchive;
+use bios_basic::rbum::dto::rbum_domain_dto::RbumDomainAddReq;
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumRelFilterReq, RbumSetFilterReq};
+use bios_basic::rbum::dto::rbum_item_dto::RbumItemAddReq;
+use bios_basic::rbum::dto::rbum_kind_dto::RbumKindAddReq;
+use bios_basic::rbum::dto::rbum_rel_dto::RbumRelAddReq;
+use bios_basic::rbum::dto::rbum_set_dto::RbumSetAddReq;
+use bios_basic::rbum::helper::rbum_archive_helper::{RbumArchiveBundleReader, RbumArchiveBundleWriter, RbumArchiveReader};
+use bios_basic::rbum::rbum_enumeration::{RbumRelFromKind, RbumScopeLevelKind};
+use bios_basic::rbum::serv::rbum_archive_serv::RbumArchiveServ;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_domain_serv::RbumDomainServ;
+use bios_basic::rbum::serv::rbum_item_serv::RbumItemServ;
+use bios_basic::rbum::serv::rbum_kind_serv::RbumKindServ;
+use bios_basic::rbum::serv::rbum_rel_serv::RbumRelServ;
+use bios_basic::rbum::serv::rbum_set_serv::RbumSetServ;
+
+pub async fn test(context: &TardisContext) -> TardisResult<()> {
+    test_rbum_archive(context).await?;
+    Ok(())
+}
+
+fn count_records(archive: &RbumArchive, table_name: &str) -> Option<usize> {
+    archive.tables.iter().find(|table| table.table_name == table_name).map(|table| table.records.len())
+}
+
+async fn test_rbum_archive(context: &TardisContext) -> TardisResult<()> {
+    let mut funs = TardisFuns::inst_with_db_conn("".to_string());
+    funs.begin().await?;
+
+    info!("【test_rbum_archive】 : Prepare Kind : RbumKindServ::add_rbum");
+    let kind_id = RbumKindServ::add_rbum(
+        &mut RbumKindAddReq {
+            code: TrimString("account-archive".to_string()),
+            name: TrimString("Account Archive".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            ext_table_name: None,
+            scope_level: Some(RbumScopeLevelKind::Root),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_archive】 : Prepare Domain : RbumDomainServ::add_rbum");
+    let domain_id = RbumDomainServ::add_rbum(
+        &mut RbumDomainAddReq {
+            code: TrimString("iam-archive".to_string()),
+            name: TrimString("IAM Archive".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            scope_level: Some(RbumScopeLevelKind::Root),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    let source_ctx = TardisContext {
+        own_paths: "archive_src".to_string(),
+        ..context.clone()
+    };
+    let target_ctx = TardisContext {
+        own_paths: "archive_dst".to_string(),
+        ..context.clone()
+    };
+
+    info!("【test_rbum_archive】 : Prepare Item : RbumItemServ::add_rbum");
+    let mut item_ids = vec![];
+    for name in ["用户1", "用户2"] {
+        item_ids.push(
+            RbumItemServ::add_rbum(
+                &mut RbumItemAddReq {
+                    id: None,
+                    code: None,
+                    name: TrimString(name.to_string()),
+                    scope_level: Some(RbumScopeLevelKind::Private),
+                    disabled: None,
+                    rel_rbum_kind_id: kind_id.to_string(),
+                    rel_rbum_domain_id: domain_id.to_string(),
+                },
+                &funs,
+                &source_ctx,
+            )
+            .await?,
+        );
+    }
+
+    info!("【test_rbum_archive】 : Prepare Set : RbumSetServ::add_rbum");
+    RbumSetServ::add_rbum(
+        &mut RbumSetAddReq {
+            code: TrimString("archive_src:org".to_string()),
+            kind: TrimString("".to_string()),
+            name: TrimString("组织".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            scope_level: Some(RbumScopeLevelKind::Private),
+            ext: None,
+            disabled: None,
+        },
+        &funs,
+        &source_ctx,
+    )
+    .await?;
+
+    info!("【test_rbum_archive】 : Prepare Rel : RbumRelServ::add_rbum");
+    RbumRelServ::add_rbum(
+        &mut RbumRelAddReq {
+            tag: "bind".to_string(),
+            // Not a reference column, the id is kept
+            note: Some(item_ids[0].to_string()),
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: item_ids[0].to_string(),
+            to_rbum_item_id: item_ids[1].to_string(),
+            to_own_paths: source_ctx.own_paths.to_string(),
+            to_is_outside: false,
+            ext: None,
+        },
+        &funs,
+        &source_ctx,
+    )
+    .await?;
+
+    info!("【test_rbum_archive】 : Test Export : RbumArchiveServ::export");
+    assert!(RbumArchiveServ::export("archive_src", false, &funs, &target_ctx).await.is_err());
+    let mut archive = RbumArchiveServ::export("archive_src", false, &funs, &source_ctx).await?;
+    assert_eq!(archive.version, RbumArchiveServ::VERSION);
+    assert_eq!(archive.own_paths, "archive_src");
+    // The kind and the domain belong to the parent own paths
+    assert_eq!(count_records(&archive, "rbum_kind"), Some(0));
+    assert_eq!(count_records(&archive, "rbum_domain"), Some(0));
+    assert_eq!(count_records(&archive, "rbum_item"), Some(2));
+    assert_eq!(count_records(&archive, "rbum_set"), Some(1));
+    assert_eq!(count_records(&archive, "rbum_rel"), Some(1));
+    assert_eq!(count_records(&archive, "rbum_cert"), None);
+    assert!(RbumArchiveServ::export("archive_src", true, &funs, &source_ctx).await?.tables.iter().any(|table| table.table_name == "rbum_cert"));
+
+    info!("【test_rbum_archive】 : Test Import : RbumArchiveServ::import");
+    archive.version += 1;
+    assert!(RbumArchiveServ::import(&mut archive, "archive_dst", &funs, context).await.is_err());
+    archive.version -= 1;
+    assert!(RbumArchiveServ::import(&mut archive, "archive_dst", &funs, &source_ctx).await.is_err());
+    // The set code already exists
+    let resp = RbumArchiveServ::import(&mut archive, "archive_dst", &funs, context).await?;
+    assert!(resp.imported.is_empty());
+    assert_eq!(resp.conflicts.len(), 1);
+    assert_eq!(resp.conflicts[0].table_name, "rbum_set");
+    assert_eq!(RbumItemServ::count_rbums(&RbumBasicFilterReq::default(), &funs, &target_ctx).await?, 0);
+
+    for table in archive.tables.iter_mut().filter(|table| table.table_name == "rbum_set") {
+        for record in table.records.iter_mut() {
+            record["code"] = Value::String("archive_dst:org".to_string());
+        }
+    }
+    let resp = RbumArchiveServ::import(&mut archive, "archive_dst", &funs, context).await?;
+    assert!(resp.conflicts.is_empty());
+    assert_eq!(resp.imported.iter().find(|table| table.table_name == "rbum_item").unwrap().count, 2);
+
+    let items = RbumItemServ::find_rbums(&RbumBasicFilterReq::default(), None, None, &funs, &target_ctx).await?;
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|item| !item_ids.contains(&item.id) && item.own_paths == "archive_dst"));
+    assert!(items.iter().any(|item| item.name == "用户1"));
+    let sets = RbumSetServ::find_rbums(&RbumSetFilterReq::default(), None, None, &funs, &target_ctx).await?;
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].code, "archive_dst:org");
+    let rels = RbumRelServ::find_rbums(&RbumRelFilterReq::default(), None, None, &funs, &target_ctx).await?;
+    assert_eq!(rels.len(), 1);
+    assert_eq!(rels[0].to_own_paths, "archive_dst");
+    // The relationship references the imported items
+    assert!(items.iter().any(|item| item.id == rels[0].from_rbum_id));
+    assert!(items.iter().any(|item| item.id == rels[0].to_rbum_item_id));
+    assert_eq!(rels[0].note, item_ids[0]);
+    // The source records are kept
+    assert_eq!(RbumItemServ::count_rbums(&RbumBasicFilterReq::default(), &funs, &source_ctx).await?, 2);
+
+    info!("【test_rbum_archive】 : Test Bundle : RbumArchiveServ::export_to");
+    let mut writer = RbumArchiveBundleWriter::new(Cursor::new(vec![]));
+    let manifest = RbumArchiveServ::export_to("archive_src", false, &mut writer, &funs, &source_ctx).await?;
+    assert_eq!(manifest.tables.iter().find(|table| table.table_name == "rbum_item").unwrap().count, 2);
+    let mut reader = RbumArchiveBundleReader::new(writer.into_inner()?)?;
+    assert_eq!(reader.manifest()?.own_paths, "archive_src");
+    assert_eq!(reader.read_chunk("rbum_item", 0)?.unwrap().len(), 2);
+    assert!(reader.read_chunk("rbum_item", 1)?.is_none());
+    let bundle_target_ctx = TardisContext {
+        own_paths: "archive_bundle".to_string(),
+        ..context.clone()
+    };
+    let resp = RbumArchiveServ::import_with_mapper(
+        &mut reader,
+        "archive_bundle",
+        &|table_name, record| {
+            if table_name == "rbum_set" {
+                record.insert("code".to_string(), Value::String("archive_bundle:org".to_string()));
+            }
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(resp.conflicts.is_empty());
+    assert_eq!(RbumItemServ::count_rbums(&RbumBasicFilterReq::default(), &funs, &bundle_target_ctx).await?, 2);
+
+    funs.rollback().await?;
+    Ok(())
+}
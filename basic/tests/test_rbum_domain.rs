@@ -1,12 +1,13 @@
 use tardis::basic::dto::TardisContext;
 use tardis::basic::field::TrimString;
 use tardis::basic::result::TardisResult;
+use tardis::chrono::{Duration, Utc};
 use tardis::log::info;
 use tardis::TardisFuns;
 
 use bios_basic::rbum::dto::rbum_domain_dto::{RbumDomainAddReq, RbumDomainModifyReq};
-use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
-use bios_basic::rbum::rbum_enumeration::RbumScopeLevelKind;
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumSortReq};
+use bios_basic::rbum::rbum_enumeration::{RbumScopeLevelKind, RbumSortFieldKind};
 use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
 use bios_basic::rbum::serv::rbum_domain_serv::RbumDomainServ;
 
@@ -71,6 +72,99 @@ pub async fn test(context: &TardisContext) -> TardisResult<()> {
     assert_eq!(rbums.total_size, 1);
     assert_eq!(rbums.records.get(0).unwrap().icon, ".");
 
+    info!("【test_rbum_domin】 : Test Find By Advanced Filter : RbumDomainServ::find_rbums");
+    let id2 = RbumDomainServ::add_rbum(
+        &mut RbumDomainAddReq {
+            code: TrimString("mysql-dev2".to_string()),
+            name: TrimString("Mysql测试集群2".to_string()),
+            note: None,
+            icon: None,
+            sort: Some(1),
+            scope_level: Some(RbumScopeLevelKind::L2),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    let rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            code: Some("mysql-dev".to_string()),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(rbums.len(), 2);
+    let rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            exact_code: Some("mysql-dev".to_string()),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(rbums.len(), 1);
+    assert_eq!(rbums.get(0).unwrap().id, id);
+    let rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            code: Some("mysql-dev".to_string()),
+            exclude_ids: Some(vec![id.clone()]),
+            owners: Some(vec![context.owner.clone()]),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(rbums.len(), 1);
+    assert_eq!(rbums.get(0).unwrap().id, id2);
+    let rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            code: Some("mysql-dev".to_string()),
+            create_time_start: Some(Utc::now() + Duration::hours(1)),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(rbums.is_empty());
+    let rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            code: Some("mysql-dev".to_string()),
+            update_time_end: Some(Utc::now() + Duration::hours(1)),
+            sorts: Some(vec![
+                RbumSortReq {
+                    field: RbumSortFieldKind::Sort,
+                    desc: true,
+                },
+                RbumSortReq {
+                    field: RbumSortFieldKind::Code,
+                    desc: false,
+                },
+            ]),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(rbums.len(), 2);
+    assert_eq!(rbums.get(0).unwrap().id, id2);
+    assert_eq!(rbums.get(1).unwrap().id, id);
+
     info!("【test_rbum_domin】 : Test Delete : RbumDomainServ::delete_rbum");
     RbumDomainServ::delete_rbum(&id, &funs, context).await?;
     assert!(RbumDomainServ::get_rbum(&id, &RbumBasicFilterReq::default(), &funs, context).await.is_err());
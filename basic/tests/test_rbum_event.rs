@@ -1,35 +1,140 @@
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use tardis::basic::dto::TardisContext;
 use tardis::basic::field::TrimString;
 use tardis::basic::result::TardisResult;
+use tardis::db::sea_orm::FromQueryResult;
+use tardis::db::sea_query::{Alias, Expr, Order, Query};
 use tardis::log::info;
 use tardis::TardisFuns;
 
-use bios_basic::rbum::dto::rbum_set_dto::RbumSetAddReq;
+use bios_basic::rbum::dto::rbum_set_dto::{RbumSetAddReq, RbumSetModifyReq};
 use bios_basic::rbum::helper::rbum_event_helper;
+use bios_basic::rbum::helper::rbum_event_helper::{RbumEventAckKind, RbumEventMessage, RbumEventSubscriber};
 use bios_basic::rbum::rbum_enumeration::RbumScopeLevelKind;
 use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
 use bios_basic::rbum::serv::rbum_set_serv::RbumSetServ;
 
 static COUNTER: AtomicUsize = AtomicUsize::new(0);
+static MODIFY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+static OTHER_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 pub async fn test(context: &TardisContext) -> TardisResult<()> {
-    let funs = TardisFuns::inst_with_db_conn("".to_string());
-    info!("【test_rbum_event】 : receive events");
-    rbum_event_helper::receive(
-        |(_, msg)| async move {
-            let message = rbum_event_helper::parse_message(msg)?;
-            assert_eq!(message.table_name, "rbum_set");
-            assert_eq!(message.operate, "c");
-            COUNTER.fetch_add(1, Ordering::SeqCst);
-            Ok(())
-        },
+    let mut funs = TardisFuns::inst_with_db_conn("".to_string());
+    info!("【test_rbum_event】 : deliver pending events");
+    while rbum_event_helper::relay(&funs).await? > 0 {}
+
+    info!("【test_rbum_event】 : subscribers match the own paths by whole nodes");
+    let subscriber = RbumEventSubscriber::new(|_| async move { Ok(()) }).own_paths_prefix("t1");
+    let mut message = RbumEventMessage {
+        id: "".to_string(),
+        table_name: "rbum_set".to_string(),
+        operate: "c".to_string(),
+        operator: "".to_string(),
+        own_paths: "t1/a1".to_string(),
+        record_id: "".to_string(),
+        ts: 0,
+        before: None,
+        after: None,
+        diff: None,
+    };
+    assert!(subscriber.matches(&message));
+    message.own_paths = "t1".to_string();
+    assert!(subscriber.matches(&message));
+    message.own_paths = "t10".to_string();
+    assert!(!subscriber.matches(&message));
+    message.own_paths = "".to_string();
+    assert!(!subscriber.matches(&message));
+
+    info!("【test_rbum_event】 : subscribe events");
+    rbum_event_helper::subscribe(
+        vec![
+            RbumEventSubscriber::new(|message| async move {
+                assert_eq!(message.table_name, "rbum_set");
+                assert_eq!(message.operate, "c");
+                assert!(message.before.is_none());
+                assert!(message.diff.is_none());
+                COUNTER.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .table_prefix("rbum_set")
+            .operates(&["c"])
+            .own_paths_prefix(&context.own_paths),
+            RbumEventSubscriber::new(|message| async move {
+                assert_eq!(message.table_name, "rbum_set");
+                let before = message.before.unwrap();
+                let after = message.after.unwrap();
+                assert_eq!(before.get("name").unwrap().as_str().unwrap(), "测试集合");
+                assert_eq!(after.get("name").unwrap().as_str().unwrap(), "测试集合2");
+                assert_eq!(message.own_paths, after.get("own_paths").unwrap().as_str().unwrap());
+                let diff = message.diff.unwrap();
+                let name_diff = diff.iter().find(|d| d.field == "name").unwrap();
+                assert_eq!(name_diff.before.as_str().unwrap(), "测试集合");
+                assert_eq!(name_diff.after.as_str().unwrap(), "测试集合2");
+                assert!(diff.iter().all(|d| d.field != "code"));
+                MODIFY_COUNTER.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .table_prefix("rbum_set")
+            .operates(&["u"])
+            .on_error(|_, _| RbumEventAckKind::Retry),
+            RbumEventSubscriber::new(|_| async move {
+                OTHER_COUNTER.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .own_paths_prefix("not_exist"),
+        ],
         &funs,
     )
     .await?;
 
+    info!("【test_rbum_event】 : sequences are assigned per record");
+    funs.begin().await?;
+    rbum_event_helper::add_outbox("test_topic", &HashMap::new(), "1".to_string(), "test_seq_record", &funs, context).await?;
+    rbum_event_helper::add_outbox("test_topic", &HashMap::new(), "2".to_string(), "test_seq_record", &funs, context).await?;
+    rbum_event_helper::add_outbox("test_topic", &HashMap::new(), "1".to_string(), "test_seq_record_other", &funs, context).await?;
+    let seqs = funs
+        .db()
+        .find_dtos::<OutboxSeqResp>(
+            Query::select()
+                .columns(vec![Alias::new("message"), Alias::new("seq")])
+                .from(Alias::new("rbum_event_outbox"))
+                .and_where(Expr::col(Alias::new("record_id")).eq("test_seq_record"))
+                .order_by(Alias::new("seq"), Order::Asc),
+        )
+        .await?;
+    assert_eq!(seqs.len(), 2);
+    assert_eq!(seqs[0].message, "1");
+    assert_eq!(seqs[0].seq, 1);
+    assert_eq!(seqs[1].message, "2");
+    assert_eq!(seqs[1].seq, 2);
+    funs.rollback().await?;
+
+    info!("【test_rbum_event】 : rolled back events are not delivered");
+    funs.begin().await?;
     RbumSetServ::add_rbum(
+        &mut RbumSetAddReq {
+            code: TrimString("test_rbum_set_code_rollback".to_string()),
+            kind: TrimString("".to_string()),
+            name: TrimString(" 测试集合 ".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            scope_level: Some(RbumScopeLevelKind::L2),
+            ext: None,
+            disabled: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    funs.rollback().await?;
+    assert_eq!(rbum_event_helper::relay(&funs).await?, 0);
+
+    info!("【test_rbum_event】 : committed events are delivered by relay");
+    funs.begin().await?;
+    let set_id = RbumSetServ::add_rbum(
         &mut RbumSetAddReq {
             code: TrimString("test_rbum_set_code".to_string()),
             kind: TrimString("".to_string()),
@@ -45,11 +150,45 @@ pub async fn test(context: &TardisContext) -> TardisResult<()> {
         context,
     )
     .await?;
+    funs.commit().await?;
+    assert_eq!(rbum_event_helper::relay(&funs).await?, 1);
 
     loop {
         if COUNTER.load(Ordering::SeqCst) > 0 {
             break;
         }
     }
+    tardis::tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+    assert_eq!(COUNTER.load(Ordering::SeqCst), 1);
+
+    info!("【test_rbum_event】 : modify events carry snapshots and diff");
+    RbumSetServ::modify_rbum(
+        &set_id,
+        &mut RbumSetModifyReq {
+            name: Some(TrimString("测试集合2".to_string())),
+            note: None,
+            icon: None,
+            sort: None,
+            ext: None,
+            scope_level: None,
+            disabled: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(rbum_event_helper::relay(&funs).await?, 1);
+    loop {
+        if MODIFY_COUNTER.load(Ordering::SeqCst) > 0 {
+            break;
+        }
+    }
+    assert_eq!(OTHER_COUNTER.load(Ordering::SeqCst), 0);
     Ok(())
 }
+
+#[derive(Debug, FromQueryResult)]
+pub struct OutboxSeqResp {
+    pub message: String,
+    pub seq: i64,
+}
@@ -8,15 +8,17 @@ use tardis::db::reldb_client::TardisActiveModel;
 use tardis::db::sea_orm::*;
 use tardis::db::sea_query::Expr;
 use tardis::log::info;
+use tardis::serde_json::json;
 use tardis::TardisFuns;
 
+use bios_basic::rbum::dto::rbum_cursor_dto::RbumCursorReq;
 use bios_basic::rbum::dto::rbum_domain_dto::RbumDomainAddReq;
 use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumItemAttrFilterReq, RbumKindAttrFilterReq};
 use bios_basic::rbum::dto::rbum_item_attr_dto::{RbumItemAttrAddReq, RbumItemAttrModifyReq, RbumItemAttrsAddOrModifyReq};
 use bios_basic::rbum::dto::rbum_item_dto::{RbumItemAddReq, RbumItemModifyReq};
 use bios_basic::rbum::dto::rbum_kind_attr_dto::RbumKindAttrAddReq;
 use bios_basic::rbum::dto::rbum_kind_dto::RbumKindAddReq;
-use bios_basic::rbum::rbum_enumeration::{RbumDataTypeKind, RbumScopeLevelKind, RbumWidgetTypeKind};
+use bios_basic::rbum::rbum_enumeration::{RbumCursorSortKind, RbumDataTypeKind, RbumScopeLevelKind, RbumWidgetTypeKind};
 use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
 use bios_basic::rbum::serv::rbum_domain_serv::RbumDomainServ;
 use bios_basic::rbum::serv::rbum_item_serv::{RbumItemAttrServ, RbumItemServ};
@@ -26,6 +28,7 @@ pub async fn test(context: &TardisContext) -> TardisResult<()> {
     test_rbum_item(context).await?;
     test_rbum_item_attr(context).await?;
     test_rbum_item_attr_has_main_table(context).await?;
+    test_rbum_item_attr_main_column_ddl(context).await?;
     Ok(())
 }
 
@@ -135,6 +138,16 @@ async fn test_rbum_item(context: &TardisContext) -> TardisResult<()> {
     assert_eq!(rbum.id, id);
     assert_eq!(rbum.name, "实例1");
 
+    info!("【test_rbum_item】 : Test Get Uri : RbumItemServ::get_rbum_uri");
+    let uri = RbumItemServ::get_rbum_uri(&id, &funs, context).await?;
+    assert_eq!(uri.to_string(), format!("reldb://mysql-dev/{}", id));
+
+    info!("【test_rbum_item】 : Test Find By Uri : RbumItemServ::find_one_rbum_by_uri");
+    assert!(RbumItemServ::find_one_rbum_by_uri("reldb/mysql-dev", &funs, context).await.is_err());
+    assert!(RbumItemServ::find_one_rbum_by_uri("reldb://mysql-dev/xxx", &funs, context).await?.is_none());
+    assert!(RbumItemServ::find_one_rbum_by_uri(&format!("reldb://mysql-prod/{}", id), &funs, context).await?.is_none());
+    assert_eq!(RbumItemServ::find_one_rbum_by_uri(&uri.to_string(), &funs, context).await?.unwrap().id, id);
+
     info!("【test_rbum_item】 : Test Modify : RbumItemServ::modify_rbum");
     RbumItemServ::modify_rbum(
         &id,
@@ -149,6 +162,51 @@ async fn test_rbum_item(context: &TardisContext) -> TardisResult<()> {
     )
     .await?;
 
+    info!("【test_rbum_item】 : Test Modify : RbumItemServ::modify_rbum_if_match");
+    let version = RbumItemServ::get_rbum_version(&id, &funs, context).await?;
+    assert!(RbumItemServ::modify_rbum_if_match(
+        &id,
+        Some(version - 1),
+        &mut RbumItemModifyReq {
+            code: None,
+            name: Some(TrimString("数据库实例2".to_string())),
+            disabled: None,
+            scope_level: None,
+        },
+        &funs,
+        context,
+    )
+    .await
+    .is_err());
+    RbumItemServ::modify_rbum_if_match(
+        &id,
+        Some(version),
+        &mut RbumItemModifyReq {
+            code: None,
+            name: Some(TrimString("数据库实例1".to_string())),
+            disabled: None,
+            scope_level: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(RbumItemServ::get_rbum_version(&id, &funs, context).await?, version + 1);
+    assert!(RbumItemServ::modify_rbum_if_match(
+        &id,
+        Some(version),
+        &mut RbumItemModifyReq {
+            code: None,
+            name: Some(TrimString("数据库实例2".to_string())),
+            disabled: None,
+            scope_level: None,
+        },
+        &funs,
+        context,
+    )
+    .await
+    .is_err());
+
     info!("【test_rbum_item】 : Test Find : RbumItemServ::paginate_rbums");
     let rbums = RbumItemServ::paginate_rbums(
         &RbumBasicFilterReq {
@@ -173,6 +231,125 @@ async fn test_rbum_item(context: &TardisContext) -> TardisResult<()> {
     RbumItemServ::delete_rbum(&id, &funs, context).await?;
     assert!(RbumItemServ::get_rbum(&id, &RbumBasicFilterReq::default(), &funs, context).await.is_err());
 
+    info!("【test_rbum_item】 : Test Recycle : RbumItemServ::paginate_deleted_rbums");
+    let deleted_rbums = RbumItemServ::paginate_deleted_rbums(true, 1, 10, &funs, context).await?;
+    assert_eq!(deleted_rbums.page_number, 1);
+    assert_eq!(deleted_rbums.page_size, 10);
+    let deleted_rbum = deleted_rbums.records.iter().find(|i| i.id == id).unwrap();
+    assert_eq!(deleted_rbum.own_paths, context.own_paths);
+    assert_eq!(deleted_rbum.content.get("name").unwrap().as_str().unwrap(), "数据库实例1");
+    let other_ctx = TardisContext {
+        own_paths: "other".to_string(),
+        ..context.clone()
+    };
+    assert!(RbumItemServ::paginate_deleted_rbums(true, 1, 10, &funs, &other_ctx).await?.records.iter().all(|i| i.id != id));
+    assert!(RbumItemServ::restore_rbum(&id, &funs, &other_ctx).await.is_err());
+
+    info!("【test_rbum_item】 : Test Recycle : RbumItemServ::restore_rbum");
+    RbumItemServ::restore_rbum(&id, &funs, context).await?;
+    let rbum = RbumItemServ::get_rbum(&id, &RbumBasicFilterReq::default(), &funs, context).await?;
+    assert_eq!(rbum.name, "数据库实例1");
+    assert!(RbumItemServ::paginate_deleted_rbums(true, 1, 10, &funs, context).await?.records.iter().all(|i| i.id != id));
+    assert!(RbumItemServ::restore_rbum(&id, &funs, context).await.is_err());
+
+    info!("【test_rbum_item】 : Test Batch : RbumItemServ::add_rbums");
+    let add_req = |name: &str, rel_rbum_kind_id: &str| RbumItemAddReq {
+        id: None,
+        code: None,
+        name: TrimString(name.to_string()),
+        disabled: None,
+        rel_rbum_kind_id: rel_rbum_kind_id.to_string(),
+        rel_rbum_domain_id: domain_id.to_string(),
+        scope_level: Some(RbumScopeLevelKind::L2),
+    };
+    let batch_resp = RbumItemServ::add_rbums(
+        &mut [add_req("批量实例1", &kind_id), add_req("批量实例2", "123"), add_req("批量实例3", &kind_id)],
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(batch_resp.succeed_ids.len(), 2);
+    assert_eq!(batch_resp.failed.len(), 1);
+    assert_eq!(batch_resp.failed.get(0).unwrap().index, 1);
+    let batch_ids = batch_resp.succeed_ids;
+    assert_eq!(
+        RbumItemServ::get_rbum(batch_ids.get(1).unwrap(), &RbumBasicFilterReq::default(), &funs, context).await?.name,
+        "批量实例3"
+    );
+
+    info!("【test_rbum_item】 : Test Find : RbumItemServ::paginate_rbums_by_cursor");
+    let filter = RbumBasicFilterReq {
+        name: Some("批量实例%".to_string()),
+        ..Default::default()
+    };
+    let mut cursor_req = RbumCursorReq {
+        sort_by: RbumCursorSortKind::CreateTime,
+        desc: true,
+        page_size: 1,
+        cursor: None,
+    };
+    let first_page = RbumItemServ::paginate_rbums_by_cursor(&filter, &cursor_req, &funs, context).await?;
+    assert_eq!(first_page.records.len(), 1);
+    assert!(first_page.next_cursor.is_some());
+    cursor_req.cursor = first_page.next_cursor;
+    let second_page = RbumItemServ::paginate_rbums_by_cursor(&filter, &cursor_req, &funs, context).await?;
+    assert_eq!(second_page.records.len(), 1);
+    assert!(second_page.next_cursor.is_none());
+    assert_ne!(first_page.records.get(0).unwrap().id, second_page.records.get(0).unwrap().id);
+    cursor_req.desc = false;
+    assert!(RbumItemServ::paginate_rbums_by_cursor(&filter, &cursor_req, &funs, context).await.is_err());
+    cursor_req.cursor = None;
+    assert!(RbumItemServ::paginate_rbums_by_cursor(
+        &RbumBasicFilterReq {
+            desc_by_sort: Some(true),
+            ..filter.clone()
+        },
+        &cursor_req,
+        &funs,
+        context
+    )
+    .await
+    .is_err());
+
+    info!("【test_rbum_item】 : Test Batch : RbumItemServ::modify_rbums");
+    let modify_req = |name: &str| RbumItemModifyReq {
+        code: None,
+        name: Some(TrimString(name.to_string())),
+        disabled: None,
+        scope_level: None,
+    };
+    let batch_resp = RbumItemServ::modify_rbums(
+        &mut [
+            (batch_ids.get(0).unwrap().to_string(), modify_req("批量实例11")),
+            ("111".to_string(), modify_req("批量实例22")),
+            (batch_ids.get(1).unwrap().to_string(), modify_req("批量实例33")),
+        ],
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(batch_resp.succeed_ids, batch_ids);
+    assert_eq!(batch_resp.failed.get(0).unwrap().id, Some("111".to_string()));
+    assert_eq!(
+        RbumItemServ::get_rbum(batch_ids.get(0).unwrap(), &RbumBasicFilterReq::default(), &funs, context).await?.name,
+        "批量实例11"
+    );
+
+    info!("【test_rbum_item】 : Test Batch : RbumItemServ::delete_rbums");
+    let batch_resp = RbumItemServ::delete_rbums(&[batch_ids.get(0).unwrap().to_string(), batch_ids.get(1).unwrap().to_string()], &funs, &other_ctx).await?;
+    assert!(batch_resp.succeed_ids.is_empty());
+    assert_eq!(batch_resp.failed.len(), 2);
+    let batch_resp = RbumItemServ::delete_rbums(&batch_ids, &funs, context).await?;
+    assert_eq!(batch_resp.succeed_ids, batch_ids);
+    assert!(RbumItemServ::get_rbum(batch_ids.get(0).unwrap(), &RbumBasicFilterReq::default(), &funs, context).await.is_err());
+    assert_eq!(
+        RbumItemServ::paginate_deleted_rbums(true, 1, 10, &funs, context).await?.records.iter().filter(|i| batch_ids.contains(&i.id)).count(),
+        2
+    );
+    let deleted_rbums = RbumItemServ::paginate_deleted_rbums(true, 1, 1, &funs, context).await?;
+    assert_eq!(deleted_rbums.records.len(), 1);
+    assert!(deleted_rbums.total_size >= 2);
+
     funs.rollback().await?;
 
     Ok(())
@@ -298,6 +475,7 @@ async fn test_rbum_item_attr(context: &TardisContext) -> TardisResult<()> {
     .await
     .is_err());
 
+    let version = RbumItemServ::get_rbum_version(&item_id, &funs, context).await?;
     let item_attr_id = RbumItemAttrServ::add_rbum(
         &mut RbumItemAttrAddReq {
             value: "数据1".to_string(),
@@ -308,6 +486,7 @@ async fn test_rbum_item_attr(context: &TardisContext) -> TardisResult<()> {
         context,
     )
     .await?;
+    assert_eq!(RbumItemServ::get_rbum_version(&item_id, &funs, context).await?, version + 1);
 
     info!("【test_rbum_item_attr】 : Test Get : RbumItemAttrServ::get_rbum");
     let rbum = RbumItemAttrServ::get_rbum(&item_attr_id, &RbumItemAttrFilterReq::default(), &funs, context).await?;
@@ -321,6 +500,7 @@ async fn test_rbum_item_attr(context: &TardisContext) -> TardisResult<()> {
     info!("【test_rbum_item_attr】 : Test Modify : RbumItemAttrServ::modify_rbum");
     assert!(RbumItemAttrServ::modify_rbum("111", &mut RbumItemAttrModifyReq { value: "数据2".to_string() }, &funs, context).await.is_err());
     RbumItemAttrServ::modify_rbum(&item_attr_id, &mut RbumItemAttrModifyReq { value: "数据3".to_string() }, &funs, context).await?;
+    assert_eq!(RbumItemServ::get_rbum_version(&item_id, &funs, context).await?, version + 2);
 
     info!("【test_rbum_item_attr】 : Test Find : RbumItemAttrServ::paginate_rbums");
     let rbums = RbumItemAttrServ::paginate_rbums(&RbumItemAttrFilterReq::default(), 1, 10, None, None, &funs, context).await?;
@@ -333,6 +513,187 @@ async fn test_rbum_item_attr(context: &TardisContext) -> TardisResult<()> {
     RbumItemAttrServ::delete_rbum(&item_attr_id, &funs, context).await?;
     assert!(RbumItemAttrServ::get_rbum(&item_attr_id, &RbumItemAttrFilterReq::default(), &funs, context).await.is_err());
 
+    info!("【test_rbum_item_attr】 : Test Validate : RbumItemAttrServ::validate_item_attr_values");
+    let port_kind_attr_id = RbumKindAttrServ::add_rbum(
+        &mut RbumKindAttrAddReq {
+            name: TrimString("port".to_string()),
+            module: None,
+            label: "端口".to_string(),
+            data_type: RbumDataTypeKind::Number,
+            widget_type: RbumWidgetTypeKind::Select,
+            note: None,
+            sort: None,
+            main_column: None,
+            position: None,
+            capacity: None,
+            overload: None,
+            default_value: Some("3306".to_string()),
+            options: Some(r#"["3306","3307","abc"]"#.to_string()),
+            required: Some(true),
+            min_length: None,
+            max_length: Some(4),
+            action: None,
+            ext: None,
+            rel_rbum_kind_id: kind_id.to_string(),
+            scope_level: Some(RbumScopeLevelKind::L2),
+            idx: None,
+            hide: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(RbumItemAttrServ::add_rbum(
+        &mut RbumItemAttrAddReq {
+            value: "abc".to_string(),
+            rel_rbum_item_id: item_id.to_string(),
+            rel_rbum_kind_attr_id: port_kind_attr_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await
+    .is_err());
+    let mut values = HashMap::new();
+    RbumItemAttrServ::validate_item_attr_values(&kind_id, &mut values, true, &funs, context).await?;
+    assert_eq!(values.get("port").unwrap(), "3306");
+    let mut values = HashMap::from([("port".to_string(), "3308".to_string())]);
+    assert!(RbumItemAttrServ::validate_item_attr_values(&kind_id, &mut values, true, &funs, context).await.is_err());
+    let mut values = HashMap::from([("port".to_string(), "abc".to_string())]);
+    assert!(RbumItemAttrServ::validate_item_attr_values(&kind_id, &mut values, false, &funs, context).await.is_err());
+    RbumItemAttrServ::add_or_modify_item_attrs(
+        &RbumItemAttrsAddOrModifyReq {
+            values: HashMap::from([("port".to_string(), "3307".to_string())]),
+            rel_rbum_item_id: item_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(RbumItemAttrServ::add_or_modify_item_attrs(
+        &RbumItemAttrsAddOrModifyReq {
+            values: HashMap::from([("port".to_string(), "33070".to_string())]),
+            rel_rbum_item_id: item_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await
+    .is_err());
+
+    info!("【test_rbum_item_attr】 : Test Schema : RbumKindAttrServ::get_kind_attr_schema");
+    let schema = RbumKindAttrServ::get_kind_attr_schema(&kind_id, &funs, context).await?;
+    assert_eq!(schema.json_schema["type"], "object");
+    assert_eq!(schema.json_schema["required"], json!(["port"]));
+    assert_eq!(schema.json_schema["properties"]["db_type"]["type"], "string");
+    assert_eq!(schema.json_schema["properties"]["port"]["type"], "number");
+    assert_eq!(schema.json_schema["properties"]["port"]["title"], "端口");
+    assert_eq!(schema.json_schema["properties"]["port"]["default"], json!(3306));
+    assert_eq!(schema.json_schema["properties"]["port"]["enum"], json!([3306, 3307, "abc"]));
+    assert_eq!(schema.form.len(), 1);
+    assert_eq!(schema.form[0].items.len(), 2);
+    assert_eq!(schema.form[0].items[0].name, "db_type");
+    assert_eq!(schema.form[0].items[1].name, "port");
+    assert_eq!(schema.form[0].items[1].widget_type, RbumWidgetTypeKind::Select);
+
+    info!("【test_rbum_item_attr】 : Test Validate Blank Values : RbumItemAttrServ::validate_item_attr_values");
+    let mut values = HashMap::from([("port".to_string(), " ".to_string())]);
+    RbumItemAttrServ::validate_item_attr_values(&kind_id, &mut values, true, &funs, context).await?;
+    assert_eq!(values.get("port").unwrap(), "3306");
+    RbumKindAttrServ::add_rbum(
+        &mut RbumKindAttrAddReq {
+            name: TrimString("host".to_string()),
+            module: None,
+            label: "主机".to_string(),
+            data_type: RbumDataTypeKind::String,
+            widget_type: RbumWidgetTypeKind::Input,
+            note: None,
+            sort: None,
+            main_column: None,
+            position: None,
+            capacity: None,
+            overload: None,
+            default_value: None,
+            options: None,
+            required: Some(true),
+            min_length: None,
+            max_length: None,
+            action: None,
+            ext: None,
+            rel_rbum_kind_id: kind_id.to_string(),
+            scope_level: Some(RbumScopeLevelKind::L2),
+            idx: None,
+            hide: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    let mut values = HashMap::from([("host".to_string(), "".to_string())]);
+    assert!(RbumItemAttrServ::validate_item_attr_values(&kind_id, &mut values, true, &funs, context).await.is_err());
+    let mut values = HashMap::from([("host".to_string(), "  ".to_string())]);
+    assert!(RbumItemAttrServ::validate_item_attr_values(&kind_id, &mut values, true, &funs, context).await.is_err());
+    let mut values = HashMap::from([("host".to_string(), "  ".to_string())]);
+    assert!(RbumItemAttrServ::validate_item_attr_values(&kind_id, &mut values, false, &funs, context).await.is_err());
+    let mut values = HashMap::from([("host".to_string(), "127.0.0.1".to_string())]);
+    RbumItemAttrServ::validate_item_attr_values(&kind_id, &mut values, true, &funs, context).await?;
+    assert_eq!(values.get("port").unwrap(), "3306");
+
+    info!("【test_rbum_item_attr】 : Test Validate Not Stored Values : RbumItemAttrServ::add_or_modify_item_attrs");
+    RbumKindAttrServ::add_rbum(
+        &mut RbumKindAttrAddReq {
+            name: TrimString("charset".to_string()),
+            module: None,
+            label: "字符集".to_string(),
+            data_type: RbumDataTypeKind::String,
+            widget_type: RbumWidgetTypeKind::Input,
+            note: None,
+            sort: None,
+            main_column: None,
+            position: None,
+            capacity: None,
+            overload: None,
+            default_value: Some("utf8".to_string()),
+            options: None,
+            required: None,
+            min_length: None,
+            max_length: None,
+            action: None,
+            ext: None,
+            rel_rbum_kind_id: kind_id.to_string(),
+            scope_level: Some(RbumScopeLevelKind::L2),
+            idx: None,
+            hide: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    // host is required and not stored yet
+    assert!(RbumItemAttrServ::add_or_modify_item_attrs(
+        &RbumItemAttrsAddOrModifyReq {
+            values: HashMap::from([("port".to_string(), "3306".to_string())]),
+            rel_rbum_item_id: item_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await
+    .is_err());
+    RbumItemAttrServ::add_or_modify_item_attrs(
+        &RbumItemAttrsAddOrModifyReq {
+            values: HashMap::from([("host".to_string(), "127.0.0.1".to_string())]),
+            rel_rbum_item_id: item_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    let values = RbumItemAttrServ::find_item_attr_values(&item_id, &funs, context).await?;
+    assert_eq!(values.get("host").unwrap(), "127.0.0.1");
+    assert_eq!(values.get("port").unwrap(), "3307");
+    assert_eq!(values.get("charset").unwrap(), "utf8");
+
     funs.rollback().await?;
 
     Ok(())
@@ -605,6 +966,123 @@ async fn test_rbum_item_attr_has_main_table(context: &TardisContext) -> TardisRe
     Ok(())
 }
 
+// DDL statements cause an implicit commit in MySQL, so this test runs without a transaction and cleans up by itself
+async fn test_rbum_item_attr_main_column_ddl(context: &TardisContext) -> TardisResult<()> {
+    let funs = TardisFuns::inst_with_db_conn("".to_string());
+
+    info!("【test_rbum_item_attr】 : Prepare : RbumKindServ::add_rbum");
+    let kind_id = RbumKindServ::add_rbum(
+        &mut RbumKindAddReq {
+            code: TrimString("account-ddl".to_string()),
+            name: TrimString("账号".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            ext_table_name: Some("iam_account".to_string()),
+            scope_level: Some(RbumScopeLevelKind::L2),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    let domain_id = RbumDomainServ::add_rbum(
+        &mut RbumDomainAddReq {
+            code: TrimString("test-iam-ddl".to_string()),
+            name: TrimString("IAM".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            scope_level: Some(RbumScopeLevelKind::L2),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    let item_id = RbumItemServ::add_rbum(
+        &mut RbumItemAddReq {
+            id: None,
+            code: None,
+            name: TrimString("用户1".to_string()),
+            disabled: None,
+            rel_rbum_kind_id: kind_id.to_string(),
+            rel_rbum_domain_id: domain_id.to_string(),
+            scope_level: Some(RbumScopeLevelKind::L2),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    funs.db()
+        .execute(
+            Query::insert()
+                .into_table(test_iam_account::Entity)
+                .columns(vec![test_iam_account::Column::Id, test_iam_account::Column::Ext1Idx, test_iam_account::Column::Ext2])
+                .values_panic(vec![item_id.clone().into(), "".into(), "".into()]),
+        )
+        .await?;
+
+    info!("【test_rbum_item_attr】 : Test Add Main Column : RbumKindAttrServ::add_rbum");
+    let kind_attr_id = RbumKindAttrServ::add_rbum(
+        &mut RbumKindAttrAddReq {
+            name: TrimString("level".to_string()),
+            module: None,
+            label: "级别".to_string(),
+            data_type: RbumDataTypeKind::Number,
+            widget_type: RbumWidgetTypeKind::InputNum,
+            note: None,
+            sort: None,
+            main_column: Some(true),
+            position: None,
+            capacity: None,
+            overload: None,
+            default_value: None,
+            options: None,
+            required: None,
+            min_length: None,
+            max_length: None,
+            action: None,
+            ext: None,
+            rel_rbum_kind_id: kind_id.to_string(),
+            scope_level: Some(RbumScopeLevelKind::L2),
+            idx: Some(true),
+            hide: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(RbumKindAttrServ::is_managed_ext_column("iam_account", "level", &funs).await?);
+    assert!(!RbumKindAttrServ::is_managed_ext_column("iam_account", "ext2", &funs).await?);
+
+    RbumItemAttrServ::add_or_modify_item_attrs(
+        &RbumItemAttrsAddOrModifyReq {
+            values: HashMap::from([("level".to_string(), "3".to_string())]),
+            rel_rbum_item_id: item_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(RbumItemAttrServ::find_item_attr_values(&item_id, &funs, context).await?.get("level").unwrap(), "3");
+
+    info!("【test_rbum_item_attr】 : Test Delete Main Column : RbumKindAttrServ::delete_rbum");
+    assert!(RbumKindAttrServ::delete_rbum(&kind_attr_id, &funs, context).await.is_err());
+    if funs.db().raw_conn().get_database_backend() == DatabaseBackend::Sqlite {
+        // Dropping columns is not supported by Sqlite, the records are kept
+        assert!(RbumKindAttrServ::force_delete_rbum(&kind_attr_id, &funs, context).await.is_err());
+        return Ok(());
+    }
+    RbumKindAttrServ::force_delete_rbum(&kind_attr_id, &funs, context).await?;
+    assert!(!RbumKindAttrServ::is_managed_ext_column("iam_account", "level", &funs).await?);
+
+    funs.db().execute(Query::delete().from_table(test_iam_account::Entity).and_where(Expr::col(test_iam_account::Column::Id).eq(item_id.as_str()))).await?;
+    RbumItemServ::delete_rbum(&item_id, &funs, context).await?;
+    RbumDomainServ::delete_rbum(&domain_id, &funs, context).await?;
+    RbumKindServ::delete_rbum(&kind_id, &funs, context).await?;
+
+    Ok(())
+}
+
 #[derive(Debug, FromQueryResult)]
 pub struct IamAccountResp {
     pub id: String,
@@ -314,6 +314,44 @@ async fn test_rbum_kind_attr(context: &TardisContext) -> TardisResult<()> {
     )
     .await?;
 
+    let other_ctx = TardisContext {
+        own_paths: "other".to_string(),
+        owner: "other".to_string(),
+        ..context.clone()
+    };
+    assert!(RbumKindAttrServ::modify_rbum(
+        &kind_attr_id,
+        &mut RbumKindAttrModifyReq {
+            label: Some("其他".to_string()),
+            data_type: None,
+            widget_type: None,
+            note: None,
+            sort: None,
+            main_column: None,
+            position: None,
+            capacity: None,
+            overload: None,
+            default_value: None,
+            options: None,
+            required: None,
+            min_length: None,
+            max_length: None,
+            action: None,
+            ext: None,
+            scope_level: None,
+            idx: None,
+            hide: None
+        },
+        &funs,
+        &other_ctx
+    )
+    .await
+    .is_err());
+    assert_eq!(
+        RbumKindAttrServ::get_rbum(&kind_attr_id, &RbumKindAttrFilterReq::default(), &funs, context).await?.label,
+        "数据库类型"
+    );
+
     info!("【test_rbum_kind_attr】 : Test Find : RbumKindAttrServ::paginate_rbums");
     let rbums = RbumKindAttrServ::paginate_rbums(&RbumKindAttrFilterReq::default(), 1, 10, None, None, &funs, context).await?;
     assert_eq!(rbums.page_number, 1);
@@ -0,0 +1,96 @@
+use tardis::basic::result::TardisResult;
+use tardis::db::sea_query::{Alias, ColumnDef, Expr, Query, Table};
+use tardis::log::info;
+use tardis::TardisFuns;
+
+use bios_basic::rbum::helper::rbum_migration_helper::{self, RbumMigrationStatement, RbumMigrationStep};
+use bios_basic::rbum::rbum_initializer;
+
+const MODULE: &str = "test";
+const TABLE_NAME: &str = "test_migration";
+
+fn get_steps(name_len: u32) -> Vec<RbumMigrationStep> {
+    vec![
+        RbumMigrationStep::new(
+            1,
+            "create test table",
+            vec![RbumMigrationStatement::CreateTable(
+                TABLE_NAME.to_string(),
+                Table::create().table(Alias::new(TABLE_NAME)).if_not_exists().col(ColumnDef::new(Alias::new("id")).not_null().string().primary_key()).to_owned(),
+                vec![],
+                vec![],
+            )],
+        ),
+        RbumMigrationStep::new(
+            2,
+            "add name to test table",
+            vec![RbumMigrationStatement::AddColumn(
+                TABLE_NAME.to_string(),
+                "name".to_string(),
+                ColumnDef::new(Alias::new("name")).string_len(name_len).to_owned(),
+            )],
+        ),
+    ]
+}
+
+pub async fn test() -> TardisResult<()> {
+    // The migrations are applied in their own transactions
+    let funs = TardisFuns::inst_with_db_conn("".to_string());
+
+    info!("【test_rbum_migration】 : Test Init : rbum_initializer::init");
+    let histories = rbum_migration_helper::find_histories(rbum_initializer::MIGRATION_MODULE, funs.db()).await?;
+    assert_eq!(histories.len(), rbum_initializer::get_migration_steps(funs.db().raw_conn().get_database_backend()).len());
+    assert_eq!(histories[0].version, 1);
+    assert!(rbum_initializer::migrate(false).await?.is_empty());
+
+    info!("【test_rbum_migration】 : Test Dry Run : rbum_migration_helper::migrate");
+    let pending_steps = rbum_migration_helper::migrate(MODULE, &get_steps(255), true).await?;
+    assert_eq!(pending_steps.len(), 2);
+    assert_eq!(pending_steps[1].name, "add name to test table");
+    assert!(pending_steps[1].sqls[0].contains("name"));
+    assert!(rbum_migration_helper::find_histories(MODULE, funs.db()).await?.is_empty());
+    assert!(!rbum_migration_helper::exist_table(TABLE_NAME, funs.db()).await?);
+
+    info!("【test_rbum_migration】 : Test Migrate : rbum_migration_helper::migrate");
+    let applied_steps = rbum_migration_helper::migrate(MODULE, &get_steps(255), false).await?;
+    assert_eq!(applied_steps.len(), 2);
+    assert_eq!(applied_steps[0].checksum, pending_steps[0].checksum);
+    assert!(rbum_migration_helper::exist_column(TABLE_NAME, "name", funs.db()).await?);
+    let histories = rbum_migration_helper::find_histories(MODULE, funs.db()).await?;
+    assert_eq!(histories.iter().map(|history| history.version).collect::<Vec<u32>>(), vec![1, 2]);
+    assert!(rbum_migration_helper::migrate(MODULE, &get_steps(255), false).await?.is_empty());
+
+    info!("【test_rbum_migration】 : Test Changed Table : rbum_migration_helper::migrate");
+    // Only the name of the created table is checksummed, the table changes with the entity
+    let mut steps = get_steps(255);
+    steps[0] = RbumMigrationStep::new(
+        1,
+        "create test table",
+        vec![RbumMigrationStatement::CreateTable(
+            TABLE_NAME.to_string(),
+            Table::create()
+                .table(Alias::new(TABLE_NAME))
+                .if_not_exists()
+                .col(ColumnDef::new(Alias::new("id")).not_null().string().primary_key())
+                .col(ColumnDef::new(Alias::new("name")).string_len(255))
+                .to_owned(),
+            vec![],
+            vec![],
+        )],
+    );
+    assert!(rbum_migration_helper::migrate(MODULE, &steps, true).await?.is_empty());
+
+    info!("【test_rbum_migration】 : Test Illegal Steps : rbum_migration_helper::migrate");
+    // The applied step is changed
+    assert!(rbum_migration_helper::migrate(MODULE, &get_steps(100), true).await.is_err());
+    // The applied step is removed
+    assert!(rbum_migration_helper::migrate(MODULE, &get_steps(255)[..1], true).await.is_err());
+    // The steps are not ordered
+    let mut steps = get_steps(255);
+    steps.reverse();
+    assert!(rbum_migration_helper::migrate(MODULE, &steps, true).await.is_err());
+
+    funs.db().execute(&Table::drop().table(Alias::new(TABLE_NAME)).to_owned()).await?;
+    funs.db().execute(&Query::delete().from_table(Alias::new("rbum_migration")).and_where(Expr::col(Alias::new("module")).eq(MODULE)).to_owned()).await?;
+    Ok(())
+}
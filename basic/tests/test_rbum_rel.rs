@@ -4,23 +4,26 @@ use std::time::Duration;
 use tardis::basic::dto::TardisContext;
 use tardis::basic::field::TrimString;
 use tardis::basic::result::TardisResult;
-use tardis::chrono::Utc;
+use tardis::chrono::{DateTime, Utc};
+use tardis::db::sea_query::{Alias, Expr, Query};
 use tardis::log::info;
 use tardis::TardisFuns;
 
 use bios_basic::rbum::dto::rbum_domain_dto::RbumDomainAddReq;
-use bios_basic::rbum::dto::rbum_filer_dto::{RbumRelExtFilterReq, RbumRelFilterReq};
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumRelExtFilterReq, RbumRelFilterReq};
 use bios_basic::rbum::dto::rbum_item_dto::RbumItemAddReq;
 use bios_basic::rbum::dto::rbum_kind_attr_dto::RbumKindAttrAddReq;
 use bios_basic::rbum::dto::rbum_kind_dto::RbumKindAddReq;
 use bios_basic::rbum::dto::rbum_rel_agg_dto::{RbumRelAggAddReq, RbumRelAttrAggAddReq, RbumRelEnvAggAddReq};
 use bios_basic::rbum::dto::rbum_rel_attr_dto::{RbumRelAttrAddReq, RbumRelAttrModifyReq};
-use bios_basic::rbum::dto::rbum_rel_dto::{RbumRelAddReq, RbumRelCheckReq, RbumRelModifyReq};
+use bios_basic::rbum::dto::rbum_rel_dto::{RbumRelAddReq, RbumRelCheckEnvReq, RbumRelCheckReq, RbumRelModifyReq, RbumRelTraverseReq};
 use bios_basic::rbum::dto::rbum_rel_env_dto::{RbumRelEnvAddReq, RbumRelEnvModifyReq};
 use bios_basic::rbum::dto::rbum_set_cate_dto::RbumSetCateAddReq;
 use bios_basic::rbum::dto::rbum_set_dto::RbumSetAddReq;
 use bios_basic::rbum::dto::rbum_set_item_dto::RbumSetItemAddReq;
-use bios_basic::rbum::rbum_enumeration::{RbumDataTypeKind, RbumRelEnvKind, RbumRelFromKind, RbumScopeLevelKind, RbumWidgetTypeKind};
+use bios_basic::rbum::helper::rbum_event_helper::RbumEventMessage;
+use bios_basic::rbum::rbum_config::{RbumConfig, RbumConfigManager};
+use bios_basic::rbum::rbum_enumeration::{RbumDataTypeKind, RbumRelAttrOpKind, RbumRelEnvKind, RbumRelFromKind, RbumScopeLevelKind, RbumWidgetTypeKind};
 use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
 use bios_basic::rbum::serv::rbum_domain_serv::RbumDomainServ;
 use bios_basic::rbum::serv::rbum_item_serv::RbumItemServ;
@@ -32,8 +35,14 @@ pub async fn test(context: &TardisContext) -> TardisResult<()> {
     test_rbum_rel(context).await?;
     test_rbum_rel_with_set(context).await?;
     test_rbum_rel_attr(context).await?;
+    test_rbum_rel_attr_op(context).await?;
     test_rbum_rel_env(context).await?;
+    test_rbum_rel_env_time_range(context).await?;
+    test_rbum_rel_env_ips(context).await?;
     test_rbum_rel_use(context).await?;
+    test_rbum_rel_move_item(context).await?;
+    test_rbum_rel_check_cache(context).await?;
+    test_rbum_rel_traverse(context).await?;
     Ok(())
 }
 
@@ -329,7 +338,8 @@ async fn test_rbum_rel_with_set(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: context.owner.to_string(),
                 to_rbum_item_id: "xxxx".to_string(),
                 from_attrs: Default::default(),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -363,7 +373,8 @@ async fn test_rbum_rel_with_set(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: context.owner.to_string(),
                 to_rbum_item_id: "xxxx".to_string(),
                 from_attrs: Default::default(),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -383,7 +394,8 @@ async fn test_rbum_rel_with_set(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: context.owner.to_string(),
                 to_rbum_item_id: "xxxx".to_string(),
                 from_attrs: Default::default(),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -415,7 +427,8 @@ async fn test_rbum_rel_with_set(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: context.owner.to_string(),
                 to_rbum_item_id: "xxxx".to_string(),
                 from_attrs: Default::default(),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -577,6 +590,7 @@ async fn test_rbum_rel_attr(context: &TardisContext) -> TardisResult<()> {
     assert!(RbumRelAttrServ::add_rbum(
         &mut RbumRelAttrAddReq {
             is_from: true,
+            op: None,
             value: "mysql".to_string(),
             name: "".to_string(),
             rel_rbum_rel_id: "".to_string(),
@@ -592,6 +606,7 @@ async fn test_rbum_rel_attr(context: &TardisContext) -> TardisResult<()> {
     assert!(RbumRelAttrServ::add_rbum(
         &mut RbumRelAttrAddReq {
             is_from: true,
+            op: None,
             value: "mysql".to_string(),
             name: "".to_string(),
             rel_rbum_rel_id: rel_id.to_string(),
@@ -607,6 +622,7 @@ async fn test_rbum_rel_attr(context: &TardisContext) -> TardisResult<()> {
     let id = RbumRelAttrServ::add_rbum(
         &mut RbumRelAttrAddReq {
             is_from: true,
+            op: None,
             value: "mysql".to_string(),
             name: "".to_string(),
             rel_rbum_rel_id: rel_id.to_string(),
@@ -628,6 +644,7 @@ async fn test_rbum_rel_attr(context: &TardisContext) -> TardisResult<()> {
     RbumRelAttrServ::modify_rbum(
         &id,
         &mut RbumRelAttrModifyReq {
+            op: None,
             value: Some("tidb".to_string()),
             name: None,
         },
@@ -654,6 +671,185 @@ async fn test_rbum_rel_attr(context: &TardisContext) -> TardisResult<()> {
     Ok(())
 }
 
+async fn add_kind_attr(name: &str, data_type: RbumDataTypeKind, rel_rbum_kind_id: &str, funs: &tardis::TardisFunsInst<'_>, context: &TardisContext) -> TardisResult<String> {
+    RbumKindAttrServ::add_rbum(
+        &mut RbumKindAttrAddReq {
+            name: TrimString(name.to_string()),
+            module: None,
+            label: name.to_string(),
+            data_type,
+            widget_type: RbumWidgetTypeKind::InputTxt,
+            note: None,
+            sort: None,
+            main_column: None,
+            position: None,
+            capacity: None,
+            overload: None,
+            default_value: None,
+            options: None,
+            required: None,
+            min_length: None,
+            max_length: None,
+            action: None,
+            scope_level: Some(RbumScopeLevelKind::L2),
+            rel_rbum_kind_id: rel_rbum_kind_id.to_string(),
+            idx: None,
+            ext: None,
+            hide: None,
+        },
+        funs,
+        context,
+    )
+    .await
+}
+
+async fn add_rel_attr(
+    is_from: bool,
+    op: RbumRelAttrOpKind,
+    value: &str,
+    rel_rbum_kind_attr_id: &str,
+    rel_rbum_rel_id: &str,
+    funs: &tardis::TardisFunsInst<'_>,
+    context: &TardisContext,
+) -> TardisResult<String> {
+    RbumRelAttrServ::add_rbum(
+        &mut RbumRelAttrAddReq {
+            is_from,
+            op: Some(op),
+            value: value.to_string(),
+            name: "".to_string(),
+            record_only: false,
+            rel_rbum_kind_attr_id: rel_rbum_kind_attr_id.to_string(),
+            rel_rbum_rel_id: rel_rbum_rel_id.to_string(),
+        },
+        funs,
+        context,
+    )
+    .await
+}
+
+async fn check_rel_with_attrs(from_attrs: Vec<(&str, &str)>, to_attrs: Vec<(&str, &str)>, funs: &tardis::TardisFunsInst<'_>, context: &TardisContext) -> TardisResult<bool> {
+    RbumRelServ::check_rel(
+        &mut RbumRelCheckReq {
+            tag: "op".to_string(),
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "op-to".to_string(),
+            from_attrs: from_attrs.into_iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+            to_attrs: to_attrs.into_iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+            env: None,
+        },
+        funs,
+        context,
+    )
+    .await
+}
+
+async fn test_rbum_rel_attr_op(context: &TardisContext) -> TardisResult<()> {
+    let mut funs = TardisFuns::inst_with_db_conn("".to_string());
+    funs.begin().await?;
+
+    info!("【test_rbum_rel_attr_op】 : Prepare : RbumKindAttrServ::add_rbum");
+    let kind_id = RbumKindServ::add_rbum(
+        &mut RbumKindAddReq {
+            code: TrimString("contract".to_string()),
+            name: TrimString("Contract".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            ext_table_name: None,
+            scope_level: Some(RbumScopeLevelKind::L2),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    let kind_attr_amount_id = add_kind_attr("amount", RbumDataTypeKind::Number, &kind_id, &funs, context).await?;
+    let kind_attr_region_id = add_kind_attr("region", RbumDataTypeKind::String, &kind_id, &funs, context).await?;
+    let kind_attr_project_id = add_kind_attr("project_code", RbumDataTypeKind::String, &kind_id, &funs, context).await?;
+    let kind_attr_level_id = add_kind_attr("level", RbumDataTypeKind::Number, &kind_id, &funs, context).await?;
+
+    info!("【test_rbum_rel_attr_op】 : Prepare : RbumRelServ::add_rbum");
+    let rel_id = RbumRelServ::add_rbum(
+        &mut RbumRelAddReq {
+            tag: "op".to_string(),
+            note: None,
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "op-to".to_string(),
+            to_own_paths: context.own_paths.to_string(),
+            to_is_outside: true,
+            ext: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_rel_attr_op】 : Test Add : RbumRelAttrServ::add_rbum");
+    assert!(add_rel_attr(true, RbumRelAttrOpKind::Gt, "cn", &kind_attr_region_id, &rel_id, &funs, context).await.is_err());
+    assert!(add_rel_attr(true, RbumRelAttrOpKind::Regex, "^1", &kind_attr_level_id, &rel_id, &funs, context).await.is_err());
+    assert!(add_rel_attr(true, RbumRelAttrOpKind::Regex, "prj-(", &kind_attr_project_id, &rel_id, &funs, context).await.is_err());
+    assert!(add_rel_attr(true, RbumRelAttrOpKind::In, "cn,sg", &kind_attr_region_id, &rel_id, &funs, context).await.is_err());
+    assert!(add_rel_attr(true, RbumRelAttrOpKind::In, r#"[3,"high"]"#, &kind_attr_level_id, &rel_id, &funs, context).await.is_err());
+    assert!(add_rel_attr(false, RbumRelAttrOpKind::Lte, "10k", &kind_attr_amount_id, &rel_id, &funs, context).await.is_err());
+    add_rel_attr(false, RbumRelAttrOpKind::Lte, "10000", &kind_attr_amount_id, &rel_id, &funs, context).await?;
+    let region_id = add_rel_attr(true, RbumRelAttrOpKind::In, r#"["cn","sg"]"#, &kind_attr_region_id, &rel_id, &funs, context).await?;
+    add_rel_attr(true, RbumRelAttrOpKind::Regex, "^prj-[0-9]+$", &kind_attr_project_id, &rel_id, &funs, context).await?;
+    add_rel_attr(true, RbumRelAttrOpKind::Gte, "3", &kind_attr_level_id, &rel_id, &funs, context).await?;
+    let rbum = RbumRelAttrServ::get_rbum(&region_id, &RbumRelExtFilterReq::default(), &funs, context).await?;
+    assert_eq!(rbum.op, RbumRelAttrOpKind::In);
+    assert_eq!(rbum.name, "region");
+
+    info!("【test_rbum_rel_attr_op】 : Test Check : RbumRelServ::check_rel");
+    let from_attrs = vec![("region", "cn"), ("project_code", "prj-001"), ("level", "3")];
+    assert!(check_rel_with_attrs(from_attrs.clone(), vec![("amount", "10000")], &funs, context).await?);
+    assert!(check_rel_with_attrs(from_attrs.clone(), vec![("amount", "9999.5")], &funs, context).await?);
+    assert!(!check_rel_with_attrs(from_attrs.clone(), vec![("amount", "10000.5")], &funs, context).await?);
+    assert!(!check_rel_with_attrs(from_attrs.clone(), vec![("amount", "much")], &funs, context).await?);
+    assert!(!check_rel_with_attrs(from_attrs.clone(), vec![], &funs, context).await?);
+    // Numbers are not compared as text
+    assert!(check_rel_with_attrs(vec![("region", "sg"), ("project_code", "prj-001"), ("level", "10")], vec![("amount", "1")], &funs, context).await?);
+    assert!(!check_rel_with_attrs(vec![("region", "sg"), ("project_code", "prj-001"), ("level", "2.5")], vec![("amount", "1")], &funs, context).await?);
+    assert!(!check_rel_with_attrs(vec![("region", "us"), ("project_code", "prj-001"), ("level", "3")], vec![("amount", "1")], &funs, context).await?);
+    assert!(!check_rel_with_attrs(vec![("region", "cn"), ("project_code", "prj-x"), ("level", "3")], vec![("amount", "1")], &funs, context).await?);
+
+    info!("【test_rbum_rel_attr_op】 : Test Modify : RbumRelAttrServ::modify_rbum");
+    assert!(RbumRelAttrServ::modify_rbum(
+        &region_id,
+        &mut RbumRelAttrModifyReq {
+            op: Some(RbumRelAttrOpKind::Lt),
+            value: None,
+            name: None,
+        },
+        &funs,
+        context,
+    )
+    .await
+    .is_err());
+    RbumRelAttrServ::modify_rbum(
+        &region_id,
+        &mut RbumRelAttrModifyReq {
+            op: Some(RbumRelAttrOpKind::NotIn),
+            value: None,
+            name: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(!check_rel_with_attrs(vec![("region", "cn"), ("project_code", "prj-001"), ("level", "3")], vec![("amount", "1")], &funs, context).await?);
+    assert!(check_rel_with_attrs(vec![("region", "us"), ("project_code", "prj-001"), ("level", "3")], vec![("amount", "1")], &funs, context).await?);
+
+    info!("【test_rbum_rel_attr_op】 : Test Missing Kind Attr : RbumRelServ::check_rel");
+    // The kind attributes with conditions can not be deleted, but may be missing from inconsistent (e.g. partially imported) data
+    funs.db().execute(&Query::delete().from_table(Alias::new("rbum_kind_attr")).and_where(Expr::col(Alias::new("id")).eq(kind_attr_project_id.as_str())).to_owned()).await?;
+    assert!(!check_rel_with_attrs(vec![("region", "us"), ("project_code", "prj-001"), ("level", "3")], vec![("amount", "1")], &funs, context).await?);
+
+    funs.rollback().await?;
+    Ok(())
+}
+
 async fn test_rbum_rel_env(context: &TardisContext) -> TardisResult<()> {
     let mut funs = TardisFuns::inst_with_db_conn("".to_string());
     funs.begin().await?;
@@ -834,6 +1030,261 @@ async fn test_rbum_rel_env(context: &TardisContext) -> TardisResult<()> {
     Ok(())
 }
 
+async fn check_rel_at(time: &str, funs: &tardis::TardisFunsInst<'_>, context: &TardisContext) -> TardisResult<bool> {
+    RbumRelServ::check_rel_at(
+        &mut RbumRelCheckReq {
+            tag: "time".to_string(),
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "time-to".to_string(),
+            from_attrs: Default::default(),
+            to_attrs: Default::default(),
+            env: None,
+        },
+        DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Utc),
+        funs,
+        context,
+    )
+    .await
+}
+
+async fn test_rbum_rel_env_time_range(context: &TardisContext) -> TardisResult<()> {
+    let mut funs = TardisFuns::inst_with_db_conn("".to_string());
+    funs.begin().await?;
+
+    info!("【test_rbum_rel_env_time_range】 : Prepare : RbumRelServ::add_rbum");
+    let rel_id = RbumRelServ::add_rbum(
+        &mut RbumRelAddReq {
+            tag: "time".to_string(),
+            note: None,
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "time-to".to_string(),
+            to_own_paths: context.own_paths.to_string(),
+            to_is_outside: true,
+            ext: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_rel_env_time_range】 : Test Add : RbumRelEnvServ::add_rbum");
+    for (value1, value2) in [
+        ("22:00:00+08:00", None),
+        ("22:00+08:00", Some("06:00:00+08:00")),
+        ("25:00:00+08:00", Some("06:00:00+08:00")),
+        ("22:00:00", Some("06:00:00+08:00")),
+        ("22:00:00+08:00", Some("22:00:00+08:00")),
+    ] {
+        assert!(RbumRelEnvServ::add_rbum(
+            &mut RbumRelEnvAddReq {
+                kind: RbumRelEnvKind::TimeRange,
+                value1: value1.to_string(),
+                value2: value2.map(|value2| value2.to_string()),
+                rel_rbum_rel_id: rel_id.to_string(),
+            },
+            &funs,
+            context,
+        )
+        .await
+        .is_err());
+    }
+    // 14:00 - 22:00 in UTC
+    let env_id = RbumRelEnvServ::add_rbum(
+        &mut RbumRelEnvAddReq {
+            kind: RbumRelEnvKind::TimeRange,
+            value1: "22:00:00+08:00".to_string(),
+            value2: Some("06:00:00+08:00".to_string()),
+            rel_rbum_rel_id: rel_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_rel_env_time_range】 : Test Check Overnight : RbumRelServ::check_rel_at");
+    assert!(!check_rel_at("2022-01-01T13:59:59Z", &funs, context).await?);
+    assert!(check_rel_at("2022-01-01T14:00:00Z", &funs, context).await?);
+    assert!(check_rel_at("2022-01-01T16:30:00Z", &funs, context).await?);
+    assert!(check_rel_at("2022-01-01T21:59:59Z", &funs, context).await?);
+    assert!(!check_rel_at("2022-01-01T22:00:00Z", &funs, context).await?);
+    assert!(!check_rel_at("2022-01-01T02:00:00Z", &funs, context).await?);
+
+    info!("【test_rbum_rel_env_time_range】 : Test Modify : RbumRelEnvServ::modify_rbum");
+    assert!(RbumRelEnvServ::modify_rbum(
+        &env_id,
+        &mut RbumRelEnvModifyReq {
+            value1: Some("9:00".to_string()),
+            value2: None,
+        },
+        &funs,
+        context,
+    )
+    .await
+    .is_err());
+    // 01:00 - 10:00 in UTC
+    RbumRelEnvServ::modify_rbum(
+        &env_id,
+        &mut RbumRelEnvModifyReq {
+            value1: Some("09:00:00+08:00".to_string()),
+            value2: Some("18:00:00+08:00".to_string()),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_rel_env_time_range】 : Test Check : RbumRelServ::check_rel_at");
+    assert!(check_rel_at("2022-01-01T02:00:00Z", &funs, context).await?);
+    assert!(!check_rel_at("2022-01-01T16:30:00Z", &funs, context).await?);
+    assert!(!check_rel_at("2022-01-01T00:59:59Z", &funs, context).await?);
+
+    funs.rollback().await?;
+    Ok(())
+}
+
+async fn check_rel_from_ips(client_ip: Option<&str>, forwarded_ips: Option<Vec<&str>>, funs: &tardis::TardisFunsInst<'_>, context: &TardisContext) -> TardisResult<bool> {
+    RbumRelServ::check_rel(
+        &mut RbumRelCheckReq {
+            tag: "ips".to_string(),
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "ips-to".to_string(),
+            from_attrs: Default::default(),
+            to_attrs: Default::default(),
+            env: Some(RbumRelCheckEnvReq {
+                client_ip: client_ip.map(|ip| ip.to_string()),
+                forwarded_ips: forwarded_ips.map(|ips| ips.iter().map(|ip| ip.to_string()).collect()),
+            }),
+        },
+        funs,
+        context,
+    )
+    .await
+}
+
+fn add_rel_ips_config(trusted_proxy_ips: &str) -> TardisResult<()> {
+    RbumConfigManager::add(
+        "rel_ips",
+        RbumConfig {
+            trusted_proxy_ips: trusted_proxy_ips.to_string(),
+            event_outbox_relay_interval_ms: 0,
+            cache_key_rel_check_expire_sec: 0,
+            scope_max_level: 4,
+            ..Default::default()
+        },
+    )
+}
+
+async fn test_rbum_rel_env_ips(context: &TardisContext) -> TardisResult<()> {
+    add_rel_ips_config("")?;
+    let mut funs = TardisFuns::inst_with_db_conn("rel_ips".to_string());
+    funs.begin().await?;
+
+    info!("【test_rbum_rel_env_ips】 : Prepare : RbumRelServ::add_rbum");
+    let rel_id = RbumRelServ::add_rbum(
+        &mut RbumRelAddReq {
+            tag: "ips".to_string(),
+            note: None,
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "ips-to".to_string(),
+            to_own_paths: context.own_paths.to_string(),
+            to_is_outside: true,
+            ext: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_rel_env_ips】 : Test Add : RbumRelEnvServ::add_rbum");
+    for value1 in ["", "10.0.0", "10.0.0.0/33", "fd00::/129", "10.0.0.9-10.0.0.1", "10.0.0.1-fd00::1", "10.0.0.1,"] {
+        assert!(RbumRelEnvServ::add_rbum(
+            &mut RbumRelEnvAddReq {
+                kind: RbumRelEnvKind::Ips,
+                value1: value1.to_string(),
+                value2: None,
+                rel_rbum_rel_id: rel_id.to_string(),
+            },
+            &funs,
+            context,
+        )
+        .await
+        .is_err());
+    }
+    let env_id = RbumRelEnvServ::add_rbum(
+        &mut RbumRelEnvAddReq {
+            kind: RbumRelEnvKind::Ips,
+            value1: "10.1.0.0/16, 192.168.1.10-192.168.1.20,fd00::/8".to_string(),
+            value2: None,
+            rel_rbum_rel_id: rel_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_rel_env_ips】 : Test Check : RbumRelServ::check_rel");
+    assert!(check_rel_from_ips(Some("10.1.255.255"), None, &funs, context).await?);
+    assert!(!check_rel_from_ips(Some("10.2.0.1"), None, &funs, context).await?);
+    assert!(check_rel_from_ips(Some("192.168.1.10"), None, &funs, context).await?);
+    assert!(check_rel_from_ips(Some("192.168.1.20"), None, &funs, context).await?);
+    assert!(!check_rel_from_ips(Some("192.168.1.21"), None, &funs, context).await?);
+    assert!(check_rel_from_ips(Some("fd12:3456::1"), None, &funs, context).await?);
+    assert!(!check_rel_from_ips(Some("fe80::1"), None, &funs, context).await?);
+    // IPv4-mapped IPv6 address
+    assert!(check_rel_from_ips(Some("::ffff:10.1.0.1"), None, &funs, context).await?);
+    // The forwarded addresses are not followed without trusted proxies
+    assert!(!check_rel_from_ips(Some("172.16.0.1"), Some(vec!["10.1.0.1"]), &funs, context).await?);
+    assert!(check_rel_from_ips(Some("10.1.0.1"), Some(vec!["172.16.0.1"]), &funs, context).await?);
+    assert!(!check_rel_from_ips(Some("unknown"), None, &funs, context).await?);
+    assert!(!check_rel_from_ips(None, None, &funs, context).await?);
+
+    info!("【test_rbum_rel_env_ips】 : Test Trusted Proxies : RbumRelServ::check_rel");
+    add_rel_ips_config("172.16.0.0/12,fd99::1")?;
+    // The right-most forwarded address that is not a trusted proxy is the client
+    assert!(check_rel_from_ips(Some("172.16.0.1"), Some(vec!["10.1.0.1", "172.16.0.2"]), &funs, context).await?);
+    assert!(check_rel_from_ips(Some("fd99::1"), Some(vec!["10.1.0.1"]), &funs, context).await?);
+    // The address prepended by the client is ignored
+    assert!(!check_rel_from_ips(Some("172.16.0.1"), Some(vec!["10.1.0.1", "10.2.0.1"]), &funs, context).await?);
+    // The forwarded addresses are not followed from an untrusted peer
+    assert!(!check_rel_from_ips(Some("10.2.0.1"), Some(vec!["10.1.0.1"]), &funs, context).await?);
+    // The client address is used if all hops are trusted
+    assert!(!check_rel_from_ips(Some("172.16.0.1"), Some(vec!["172.16.0.2"]), &funs, context).await?);
+    assert!(!check_rel_from_ips(Some("172.16.0.1"), Some(vec!["10.1.0.1", "unknown"]), &funs, context).await?);
+    add_rel_ips_config("")?;
+
+    info!("【test_rbum_rel_env_ips】 : Test Modify : RbumRelEnvServ::modify_rbum");
+    assert!(RbumRelEnvServ::modify_rbum(
+        &env_id,
+        &mut RbumRelEnvModifyReq {
+            value1: Some("10.1.0.0/".to_string()),
+            value2: None,
+        },
+        &funs,
+        context,
+    )
+    .await
+    .is_err());
+    RbumRelEnvServ::modify_rbum(
+        &env_id,
+        &mut RbumRelEnvModifyReq {
+            value1: Some("0.0.0.0/0".to_string()),
+            value2: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(check_rel_from_ips(Some("10.2.0.1"), None, &funs, context).await?);
+    assert!(!check_rel_from_ips(Some("fd12:3456::1"), None, &funs, context).await?);
+
+    funs.rollback().await?;
+    Ok(())
+}
+
 async fn test_rbum_rel_use(context: &TardisContext) -> TardisResult<()> {
     let mut funs = TardisFuns::inst_with_db_conn("".to_string());
     funs.begin().await?;
@@ -979,6 +1430,7 @@ async fn test_rbum_rel_use(context: &TardisContext) -> TardisResult<()> {
             },
             attrs: vec![RbumRelAttrAggAddReq {
                 is_from: true,
+                op: None,
                 value: "mysql".to_string(),
                 name: "".to_string(),
                 record_only: false,
@@ -1036,7 +1488,8 @@ async fn test_rbum_rel_use(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: "".to_string(),
                 to_rbum_item_id: "".to_string(),
                 from_attrs: Default::default(),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -1052,7 +1505,8 @@ async fn test_rbum_rel_use(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: item_reldb_inst1_id.to_string(),
                 to_rbum_item_id: item_account_a1_id.to_string(),
                 from_attrs: Default::default(),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -1068,7 +1522,8 @@ async fn test_rbum_rel_use(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: item_reldb_inst1_id.to_string(),
                 to_rbum_item_id: item_account_a1_id.to_string(),
                 from_attrs: HashMap::from([("db_type".to_string(), "tidb".to_string()),]),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -1084,7 +1539,8 @@ async fn test_rbum_rel_use(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: item_reldb_inst1_id.to_string(),
                 to_rbum_item_id: item_account_a1_id.to_string(),
                 from_attrs: HashMap::from([("db_type".to_string(), "mysql".to_string()),]),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -1102,7 +1558,8 @@ async fn test_rbum_rel_use(context: &TardisContext) -> TardisResult<()> {
                 from_rbum_id: item_reldb_inst1_id.to_string(),
                 to_rbum_item_id: item_account_a1_id.to_string(),
                 from_attrs: HashMap::from([("db_type".to_string(), "mysql".to_string()),]),
-                to_attrs: Default::default()
+                to_attrs: Default::default(),
+                env: None
             },
             &funs,
             context
@@ -1114,3 +1571,418 @@ async fn test_rbum_rel_use(context: &TardisContext) -> TardisResult<()> {
 
     Ok(())
 }
+
+async fn test_rbum_rel_move_item(context: &TardisContext) -> TardisResult<()> {
+    let mut funs = TardisFuns::inst_with_db_conn("".to_string());
+    funs.begin().await?;
+
+    info!("【test_rbum_rel_move_item】 : Prepare : RbumKindServ::add_rbum");
+    let kind_id = RbumKindServ::add_rbum(
+        &mut RbumKindAddReq {
+            code: TrimString("account-move".to_string()),
+            name: TrimString("Account Move".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            ext_table_name: None,
+            scope_level: Some(RbumScopeLevelKind::Root),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_rel_move_item】 : Prepare Domain : RbumDomainServ::add_rbum");
+    let domain_id = RbumDomainServ::add_rbum(
+        &mut RbumDomainAddReq {
+            code: TrimString("iam-move".to_string()),
+            name: TrimString("IAM Move".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            scope_level: Some(RbumScopeLevelKind::Root),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+
+    info!("【test_rbum_rel_move_item】 : Prepare Item : RbumItemServ::add_rbum");
+    let mut item_ids = HashMap::new();
+    for (name, scope_level) in [
+        ("moved", RbumScopeLevelKind::Root),
+        ("private_moved", RbumScopeLevelKind::Private),
+        ("global", RbumScopeLevelKind::Root),
+        ("private", RbumScopeLevelKind::Private),
+    ] {
+        let item_id = RbumItemServ::add_rbum(
+            &mut RbumItemAddReq {
+                id: None,
+                code: None,
+                name: TrimString(name.to_string()),
+                scope_level: Some(scope_level),
+                disabled: None,
+                rel_rbum_kind_id: kind_id.to_string(),
+                rel_rbum_domain_id: domain_id.to_string(),
+            },
+            &funs,
+            context,
+        )
+        .await?;
+        item_ids.insert(name, item_id);
+    }
+
+    info!("【test_rbum_rel_move_item】 : Prepare Rel : RbumRelServ::add_rbum");
+    let add_rel = |from_name: &'static str, to_name: &'static str| RbumRelAddReq {
+        tag: "move".to_string(),
+        note: None,
+        from_rbum_kind: RbumRelFromKind::Item,
+        from_rbum_id: item_ids.get(from_name).unwrap().to_string(),
+        to_rbum_item_id: item_ids.get(to_name).unwrap().to_string(),
+        to_own_paths: context.own_paths.to_string(),
+        to_is_outside: false,
+        ext: None,
+    };
+    let rel_to_private_id = RbumRelServ::add_rbum(&mut add_rel("moved", "private"), &funs, context).await?;
+    let rel_to_global_id = RbumRelServ::add_rbum(&mut add_rel("moved", "global"), &funs, context).await?;
+    let rel_from_global_id = RbumRelServ::add_rbum(&mut add_rel("global", "moved"), &funs, context).await?;
+    RbumRelServ::add_rbum(&mut add_rel("global", "private_moved"), &funs, context).await?;
+
+    info!("【test_rbum_rel_move_item】 : Test Move : RbumItemServ::move_rbum");
+    // The private target item is invisible to the target own paths
+    assert!(RbumItemServ::move_rbum(item_ids.get("moved").unwrap(), "t1", &funs, context).await.is_err());
+    // The moved private item is invisible to the owner of the incoming relationship
+    assert!(RbumItemServ::move_rbum(item_ids.get("private_moved").unwrap(), "t1", &funs, context).await.is_err());
+
+    RbumRelServ::delete_rbum(&rel_to_private_id, &funs, context).await?;
+    let moved_rel_ids = RbumItemServ::move_rbum(item_ids.get("moved").unwrap(), "t1", &funs, context).await?;
+    assert_eq!(moved_rel_ids.len(), 2);
+    assert!(moved_rel_ids.contains(&rel_to_global_id));
+    assert!(moved_rel_ids.contains(&rel_from_global_id));
+
+    let moved_item = RbumItemServ::get_rbum(
+        item_ids.get("moved").unwrap(),
+        &RbumBasicFilterReq {
+            with_sub_own_paths: true,
+            ..Default::default()
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(moved_item.own_paths, "t1");
+    let rel_filter = RbumRelFilterReq {
+        basic: RbumBasicFilterReq {
+            with_sub_own_paths: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let rel_to_global = RbumRelServ::get_rbum(&rel_to_global_id, &rel_filter, &funs, context).await?;
+    assert_eq!(rel_to_global.own_paths, "t1");
+    assert_eq!(rel_to_global.to_own_paths, "");
+    let rel_from_global = RbumRelServ::get_rbum(&rel_from_global_id, &rel_filter, &funs, context).await?;
+    assert_eq!(rel_from_global.own_paths, "");
+    assert_eq!(rel_from_global.to_own_paths, "t1");
+    // Moving to the current own paths does nothing
+    assert!(RbumItemServ::move_rbum(item_ids.get("moved").unwrap(), "t1", &funs, context).await?.is_empty());
+    // The target own paths must belong to the context
+    let tenant_ctx = TardisContext {
+        own_paths: "t1".to_string(),
+        ..context.clone()
+    };
+    assert!(RbumItemServ::move_rbum(item_ids.get("moved").unwrap(), "t2", &funs, &tenant_ctx).await.is_err());
+
+    funs.rollback().await?;
+
+    Ok(())
+}
+
+fn package_event(table_name: &str, operate: &str, record_id: &str) -> RbumEventMessage {
+    RbumEventMessage {
+        id: TardisFuns::field.nanoid(),
+        table_name: table_name.to_string(),
+        operate: operate.to_string(),
+        operator: "".to_string(),
+        own_paths: "".to_string(),
+        record_id: record_id.to_string(),
+        ts: Utc::now().timestamp_millis(),
+        before: None,
+        after: None,
+        diff: None,
+    }
+}
+
+async fn check_cached_rel(funs: &tardis::TardisFunsInst<'_>, context: &TardisContext) -> TardisResult<bool> {
+    RbumRelServ::check_rel(
+        &mut RbumRelCheckReq {
+            tag: "cache".to_string(),
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "cache-to".to_string(),
+            from_attrs: Default::default(),
+            to_attrs: Default::default(),
+            env: None,
+        },
+        funs,
+        context,
+    )
+    .await
+}
+
+async fn test_rbum_rel_check_cache(context: &TardisContext) -> TardisResult<()> {
+    // The cache is disabled in the other tests because the events are not relayed
+    let cache_key_rel_check_ = format!("rbum:cache:rel:check:{}:", TardisFuns::field.nanoid());
+    RbumConfigManager::add(
+        "rel_cache",
+        RbumConfig {
+            cache_key_rel_check_: cache_key_rel_check_.clone(),
+            event_outbox_relay_interval_ms: 0,
+            scope_max_level: 4,
+            ..Default::default()
+        },
+    )?;
+    let mut funs = TardisFuns::inst_with_db_conn("rel_cache".to_string());
+    funs.begin().await?;
+
+    info!("【test_rbum_rel_check_cache】 : Test Cached Result : RbumRelServ::check_rel");
+    let rel_id = RbumRelServ::add_rbum(
+        &mut RbumRelAddReq {
+            tag: "cache".to_string(),
+            note: None,
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "cache-to".to_string(),
+            to_own_paths: context.own_paths.to_string(),
+            to_is_outside: true,
+            ext: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(check_cached_rel(&funs, context).await?);
+    // The index of the dependent records is bucketed by the expire time and is not kept longer than two buckets
+    let dependent_key = format!("{}dep:{}:{}", cache_key_rel_check_, context.owner, Utc::now().timestamp() / 3600);
+    let dependent_ttl = TardisFuns::cache().ttl(&dependent_key).await?;
+    assert!(dependent_ttl > 0 && dependent_ttl <= 3600 * 2);
+    RbumRelServ::delete_rbum(&rel_id, &funs, context).await?;
+    assert!(check_cached_rel(&funs, context).await?);
+
+    info!("【test_rbum_rel_check_cache】 : Test Invalidate : RbumRelServ::invalidate_check_rel_cache");
+    RbumRelServ::invalidate_check_rel_cache(&package_event("rbum_rel", "d", &rel_id), &funs).await?;
+    assert!(!check_cached_rel(&funs, context).await?);
+
+    let rel_id = RbumRelServ::add_rbum(
+        &mut RbumRelAddReq {
+            tag: "cache".to_string(),
+            note: None,
+            from_rbum_kind: RbumRelFromKind::Item,
+            from_rbum_id: context.owner.to_string(),
+            to_rbum_item_id: "cache-to".to_string(),
+            to_own_paths: context.own_paths.to_string(),
+            to_is_outside: true,
+            ext: None,
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(!check_cached_rel(&funs, context).await?);
+    // Events of other records do not affect the result
+    RbumRelServ::invalidate_check_rel_cache(&package_event("rbum_rel", "d", "other"), &funs).await?;
+    assert!(!check_cached_rel(&funs, context).await?);
+    // The new relationship is found by its target
+    RbumRelServ::invalidate_check_rel_cache(&package_event("rbum_rel", "c", &rel_id), &funs).await?;
+    assert!(check_cached_rel(&funs, context).await?);
+
+    info!("【test_rbum_rel_check_cache】 : Test Datetime Range : RbumRelServ::check_rel");
+    let env_id = RbumRelEnvServ::add_rbum(
+        &mut RbumRelEnvAddReq {
+            kind: RbumRelEnvKind::DatetimeRange,
+            value1: (Utc::now().timestamp() + 2).to_string(),
+            value2: Some((Utc::now().timestamp() + 2000).to_string()),
+            rel_rbum_rel_id: rel_id.to_string(),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    RbumRelServ::invalidate_check_rel_cache(&package_event("rbum_rel_env", "c", &env_id), &funs).await?;
+    assert!(!check_cached_rel(&funs, context).await?);
+    // The cached result expires when the datetime range starts
+    tardis::tokio::time::sleep(Duration::from_secs(3)).await;
+    assert!(check_cached_rel(&funs, context).await?);
+
+    funs.rollback().await?;
+    Ok(())
+}
+
+fn package_traverse_req(rbum_item_id: &str, is_from: bool, tags: Vec<&str>, max_depth: u8, rbum_kind_id: Option<&str>) -> RbumRelTraverseReq {
+    RbumRelTraverseReq {
+        rbum_item_id: rbum_item_id.to_string(),
+        is_from,
+        tags: tags.into_iter().map(|tag| tag.to_string()).collect(),
+        max_depth,
+        rbum_kind_id: rbum_kind_id.map(|rbum_kind_id| rbum_kind_id.to_string()),
+    }
+}
+
+async fn find_traverse_rels(traverse_req: RbumRelTraverseReq, funs: &tardis::TardisFunsInst<'_>, context: &TardisContext) -> TardisResult<Vec<(String, u8)>> {
+    Ok(RbumRelServ::find_traverse_rels(&traverse_req, funs, context).await?.into_iter().map(|resp| (resp.rbum_item_id, resp.depth)).collect())
+}
+
+async fn find_shortest_rel_path(
+    traverse_req: RbumRelTraverseReq,
+    to_rbum_item_id: &str,
+    funs: &tardis::TardisFunsInst<'_>,
+    context: &TardisContext,
+) -> TardisResult<Option<Vec<(String, String)>>> {
+    Ok(RbumRelServ::find_shortest_rel_path(&traverse_req, to_rbum_item_id, funs, context)
+        .await?
+        .map(|hops| hops.into_iter().map(|hop| (hop.from_rbum_id, hop.to_rbum_item_id)).collect()))
+}
+
+async fn test_rbum_rel_traverse(context: &TardisContext) -> TardisResult<()> {
+    let mut funs = TardisFuns::inst_with_db_conn("".to_string());
+    funs.begin().await?;
+
+    info!("【test_rbum_rel_traverse】 : Prepare : RbumItemServ::add_rbum");
+    let domain_id = RbumDomainServ::add_rbum(
+        &mut RbumDomainAddReq {
+            code: TrimString("traverse".to_string()),
+            name: TrimString("Traverse".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            scope_level: Some(RbumScopeLevelKind::L2),
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    let mut kind_ids = Vec::new();
+    for code in ["traverse-account", "traverse-node"] {
+        kind_ids.push(
+            RbumKindServ::add_rbum(
+                &mut RbumKindAddReq {
+                    code: TrimString(code.to_string()),
+                    name: TrimString(code.to_string()),
+                    note: None,
+                    icon: None,
+                    sort: None,
+                    ext_table_name: None,
+                    scope_level: Some(RbumScopeLevelKind::L2),
+                },
+                &funs,
+                context,
+            )
+            .await?,
+        );
+    }
+    let mut item_ids = Vec::new();
+    for (name, kind_id) in [("a", &kind_ids[0]), ("b", &kind_ids[1]), ("c", &kind_ids[1]), ("d", &kind_ids[1])] {
+        item_ids.push(
+            RbumItemServ::add_rbum(
+                &mut RbumItemAddReq {
+                    id: None,
+                    code: None,
+                    name: TrimString(name.to_string()),
+                    scope_level: Some(RbumScopeLevelKind::L2),
+                    disabled: None,
+                    rel_rbum_kind_id: kind_id.to_string(),
+                    rel_rbum_domain_id: domain_id.to_string(),
+                },
+                &funs,
+                context,
+            )
+            .await?,
+        );
+    }
+    let (a, b, c, d, e) = (item_ids[0].as_str(), item_ids[1].as_str(), item_ids[2].as_str(), item_ids[3].as_str(), "traverse-e");
+
+    info!("【test_rbum_rel_traverse】 : Prepare : RbumRelServ::add_rbum");
+    // a -x-> b -y-> c -y-> a, a -y-> d -x-> e
+    for (from_rbum_id, tag, to_rbum_item_id) in [(a, "x", b), (b, "y", c), (c, "y", a), (a, "y", d), (d, "x", e)] {
+        RbumRelServ::add_rbum(
+            &mut RbumRelAddReq {
+                tag: tag.to_string(),
+                note: None,
+                from_rbum_kind: RbumRelFromKind::Item,
+                from_rbum_id: from_rbum_id.to_string(),
+                to_rbum_item_id: to_rbum_item_id.to_string(),
+                to_own_paths: context.own_paths.to_string(),
+                to_is_outside: true,
+                ext: None,
+            },
+            &funs,
+            context,
+        )
+        .await?;
+    }
+
+    info!("【test_rbum_rel_traverse】 : Test Illegal Depth : RbumRelServ::find_traverse_rels");
+    assert!(RbumRelServ::find_traverse_rels(&package_traverse_req(a, true, vec![], 0, None), &funs, context).await.is_err());
+    assert!(RbumRelServ::find_traverse_rels(&package_traverse_req(a, true, vec!["x"; 11], 1, None), &funs, context).await.is_err());
+
+    info!("【test_rbum_rel_traverse】 : Test Tag Sequence : RbumRelServ::find_traverse_rels");
+    assert_eq!(
+        find_traverse_rels(package_traverse_req(a, true, vec!["x", "y"], 0, None), &funs, context).await?,
+        vec![(c.to_string(), 2)]
+    );
+    assert_eq!(
+        find_traverse_rels(package_traverse_req(a, true, vec!["x", "y", "y"], 0, None), &funs, context).await?,
+        vec![(a.to_string(), 3)]
+    );
+    assert!(find_traverse_rels(package_traverse_req(a, true, vec!["y", "y"], 0, None), &funs, context).await?.is_empty());
+
+    info!("【test_rbum_rel_traverse】 : Test Any Tag : RbumRelServ::find_traverse_rels");
+    let mut expected = vec![(b.to_string(), 1), (d.to_string(), 1)];
+    expected.sort();
+    assert_eq!(find_traverse_rels(package_traverse_req(a, true, vec![], 1, None), &funs, context).await?, expected);
+    // The cycle back to a ends at the depth limit and a is not returned
+    let rbum_items = find_traverse_rels(package_traverse_req(a, true, vec![], 10, None), &funs, context).await?;
+    assert_eq!(rbum_items.len(), 4);
+    assert!(rbum_items.contains(&(c.to_string(), 2)));
+    assert!(rbum_items.contains(&(e.to_string(), 2)));
+
+    info!("【test_rbum_rel_traverse】 : Test Reverse : RbumRelServ::find_traverse_rels");
+    assert_eq!(
+        find_traverse_rels(package_traverse_req(c, false, vec![], 5, None), &funs, context).await?,
+        vec![(b.to_string(), 1), (a.to_string(), 2)]
+    );
+    assert_eq!(
+        find_traverse_rels(package_traverse_req(c, false, vec![], 5, Some(kind_ids[0].as_str())), &funs, context).await?,
+        vec![(a.to_string(), 2)]
+    );
+    assert!(find_traverse_rels(package_traverse_req(e, true, vec![], 5, None), &funs, context).await?.is_empty());
+
+    info!("【test_rbum_rel_traverse】 : Test Shortest Path : RbumRelServ::find_shortest_rel_path");
+    assert_eq!(
+        find_shortest_rel_path(package_traverse_req(a, true, vec![], 5, None), c, &funs, context).await?,
+        Some(vec![(a.to_string(), b.to_string()), (b.to_string(), c.to_string())])
+    );
+    assert_eq!(
+        find_shortest_rel_path(package_traverse_req(e, false, vec![], 5, None), b, &funs, context).await?,
+        Some(vec![
+            (d.to_string(), e.to_string()),
+            (a.to_string(), d.to_string()),
+            (c.to_string(), a.to_string()),
+            (b.to_string(), c.to_string())
+        ])
+    );
+    assert_eq!(
+        find_shortest_rel_path(package_traverse_req(a, true, vec!["y", "x"], 0, None), e, &funs, context).await?,
+        Some(vec![(a.to_string(), d.to_string()), (d.to_string(), e.to_string())])
+    );
+    assert_eq!(
+        find_shortest_rel_path(package_traverse_req(a, true, vec![], 5, None), a, &funs, context).await?,
+        Some(vec![])
+    );
+    assert!(find_shortest_rel_path(package_traverse_req(a, true, vec!["x", "y"], 0, None), e, &funs, context).await?.is_none());
+    assert!(find_shortest_rel_path(package_traverse_req(a, true, vec![], 1, None), c, &funs, context).await?.is_none());
+    assert!(find_shortest_rel_path(package_traverse_req(a, true, vec![], 5, None), "traverse-z", &funs, context).await?.is_none());
+
+    funs.rollback().await?;
+    Ok(())
+}
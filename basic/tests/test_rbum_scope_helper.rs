@@ -1,6 +1,9 @@
+use tardis::basic::dto::TardisContext;
 use tardis::tokio;
 
-use bios_basic::rbum::helper::rbum_scope_helper::get_pre_paths;
+use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
+use bios_basic::rbum::helper::rbum_scope_helper::{check_scope, explain_scope, get_pre_paths, get_scope_conds, is_sub_own_paths, rebase_own_paths, RbumScopePathCond};
+use bios_basic::rbum::rbum_enumeration::RbumScopeRuleKind;
 
 #[tokio::test]
 pub async fn test_get_pre_paths() {
@@ -21,3 +24,134 @@ pub async fn test_get_pre_paths() {
     assert_eq!(get_pre_paths(3, "aaaa/bbbb"), None);
     assert_eq!(get_pre_paths(3, "aaaa/bbbb/cccc"), Some("aaaa/bbbb/cccc".to_string()));
 }
+
+#[tokio::test]
+pub async fn test_get_scope_conds() {
+    assert!(get_scope_conds("").is_empty());
+    assert_eq!(
+        get_scope_conds("aaaa/bbbb"),
+        vec![
+            (1, vec![RbumScopePathCond::Eq("".to_string()), RbumScopePathCond::Prefix("aaaa".to_string())]),
+            (
+                2,
+                vec![
+                    RbumScopePathCond::Eq("".to_string()),
+                    RbumScopePathCond::Eq("aaaa".to_string()),
+                    RbumScopePathCond::Prefix("aaaa/bbbb".to_string())
+                ]
+            ),
+        ]
+    );
+    let conds = get_scope_conds("aaaa/bbbb/cccc/dddd");
+    assert_eq!(conds.len(), 4);
+    assert_eq!(
+        conds[3],
+        (
+            4,
+            vec![
+                RbumScopePathCond::Eq("".to_string()),
+                RbumScopePathCond::Eq("aaaa".to_string()),
+                RbumScopePathCond::Eq("aaaa/bbbb".to_string()),
+                RbumScopePathCond::Eq("aaaa/bbbb/cccc".to_string()),
+                RbumScopePathCond::Prefix("aaaa/bbbb/cccc/dddd".to_string())
+            ]
+        )
+    );
+}
+
+#[tokio::test]
+pub async fn test_check_scope() {
+    let ctx = TardisContext {
+        own_paths: "aaaa/bbbb/cccc/dddd".to_string(),
+        ak: "".to_string(),
+        owner: "".to_string(),
+        roles: vec![],
+        groups: vec![],
+    };
+    let filter = RbumBasicFilterReq::default();
+    assert!(check_scope("aaaa/bbbb/cccc/dddd", Some(-1), &filter, &ctx));
+    assert!(!check_scope("aaaa/bbbb/cccc", Some(-1), &filter, &ctx));
+    assert!(check_scope("xxxx", Some(0), &filter, &ctx));
+    assert!(check_scope("aaaa/xxxx", Some(1), &filter, &ctx));
+    assert!(!check_scope("xxxx", Some(1), &filter, &ctx));
+    assert!(check_scope("aaaa", Some(3), &filter, &ctx));
+    assert!(!check_scope("aaaa/xxxx", Some(3), &filter, &ctx));
+    assert!(check_scope("aaaa/bbbb/cccc", Some(4), &filter, &ctx));
+    assert!(check_scope("aaaa/bbbb/cccc/dddd/eeee", Some(4), &filter, &ctx));
+    assert!(!check_scope("aaaa/bbbb/cccc/xxxx", Some(4), &filter, &ctx));
+    assert!(!check_scope("aaaa/bbbb/cccc/dddd", Some(5), &filter, &ctx));
+    // The scope level rules use the own paths of the filter
+    let filter = RbumBasicFilterReq {
+        own_paths: Some("xxxx/yyyy".to_string()),
+        ..Default::default()
+    };
+    assert!(check_scope("xxxx/zzzz", Some(1), &filter, &ctx));
+    assert!(!check_scope("aaaa/xxxx", Some(1), &filter, &ctx));
+    assert!(!check_scope(
+        "aaaa/xxxx",
+        Some(1),
+        &RbumBasicFilterReq {
+            ignore_scope: true,
+            ..Default::default()
+        },
+        &ctx
+    ));
+}
+
+#[tokio::test]
+pub async fn test_explain_scope() {
+    let ctx = TardisContext {
+        own_paths: "aaaa/bbbb".to_string(),
+        ak: "".to_string(),
+        owner: "".to_string(),
+        roles: vec![],
+        groups: vec![],
+    };
+    let explain = explain_scope("aaaa/bbbb", Some(-1), &RbumBasicFilterReq::default(), &ctx);
+    assert!(explain.visible);
+    assert_eq!(explain.rules.len(), 1);
+    assert_eq!(explain.rules[0].rule, RbumScopeRuleKind::OwnPaths);
+
+    let explain = explain_scope("aaaa/cccc", Some(-1), &RbumBasicFilterReq::default(), &ctx);
+    assert!(!explain.visible);
+    assert_eq!(
+        explain.rules.iter().map(|rule| rule.rule.clone()).collect::<Vec<_>>(),
+        vec![RbumScopeRuleKind::OwnPaths, RbumScopeRuleKind::Private]
+    );
+
+    let explain = explain_scope("aaaa", Some(2), &RbumBasicFilterReq::default(), &ctx);
+    assert!(explain.visible);
+    assert_eq!(explain.rules.last().unwrap().rule, RbumScopeRuleKind::Level);
+    assert!(explain.rules.last().unwrap().matched);
+
+    let explain = explain_scope("aaaa/bbbb/cccc", Some(3), &RbumBasicFilterReq::default(), &ctx);
+    assert!(!explain.visible);
+    assert!(explain.rules.last().unwrap().message.contains("shallower"));
+
+    let explain = explain_scope(
+        "aaaa/bbbb/cccc",
+        Some(-1),
+        &RbumBasicFilterReq {
+            with_sub_own_paths: true,
+            ..Default::default()
+        },
+        &ctx,
+    );
+    assert!(explain.visible);
+    assert_eq!(explain.rules.last().unwrap().rule, RbumScopeRuleKind::SubOwnPaths);
+}
+
+#[tokio::test]
+pub async fn test_sub_own_paths() {
+    assert!(is_sub_own_paths("t1", ""));
+    assert!(is_sub_own_paths("t1", "t1"));
+    assert!(is_sub_own_paths("t1/a1", "t1"));
+    assert!(!is_sub_own_paths("t10", "t1"));
+    assert!(!is_sub_own_paths("t10/a1", "t1"));
+    assert!(!is_sub_own_paths("t1", "t1/a1"));
+
+    assert_eq!(rebase_own_paths("t1/a1", "t1/a1", "t2/a1"), "t2/a1");
+    assert_eq!(rebase_own_paths("t1/a1/s1", "t1/a1", "t2/a1"), "t2/a1/s1");
+    assert_eq!(rebase_own_paths("t1/a10", "t1/a1", "t2/a1"), "t1/a10");
+    assert_eq!(rebase_own_paths("t1", "t1/a1", "t2/a1"), "t1");
+}
@@ -0,0 +1,20 @@
+use tardis::tokio;
+
+use bios_basic::rbum::helper::rbum_uri_helper::RbumUri;
+
+#[tokio::test]
+pub async fn test_parse_uri() {
+    let uri = RbumUri::parse("iam-res://iam/cs/**").unwrap();
+    assert_eq!(uri.kind_code, "iam-res");
+    assert_eq!(uri.domain_code, "iam");
+    assert_eq!(uri.item_code, "cs/**");
+    assert_eq!(uri.to_string(), "iam-res://iam/cs/**");
+
+    assert!(RbumUri::parse("iam-res://iam").is_err());
+    assert!(RbumUri::parse("iam-res://iam/").is_err());
+    assert!(RbumUri::parse("iam-res:/iam/xx").is_err());
+    assert!(RbumUri::parse("IAM-RES://iam/xx").is_err());
+    assert!(RbumUri::parse("iam-res://i_am/xx").is_err());
+    assert!(RbumUri::parse("://iam/xx").is_err());
+    assert!(RbumUri::new("iam-res", "iam", "").is_err());
+}
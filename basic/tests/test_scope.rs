@@ -6,7 +6,9 @@ use tardis::TardisFuns;
 
 use bios_basic::rbum::dto::rbum_domain_dto::RbumDomainAddReq;
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
-use bios_basic::rbum::rbum_enumeration::RbumScopeLevelKind;
+use bios_basic::rbum::helper::rbum_scope_helper::check_scope;
+use bios_basic::rbum::rbum_config::{RbumConfig, RbumConfigManager};
+use bios_basic::rbum::rbum_enumeration::{RbumScopeLevelKind, RbumScopeRuleKind};
 use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
 use bios_basic::rbum::serv::rbum_domain_serv::RbumDomainServ;
 
@@ -388,6 +390,193 @@ pub async fn test() -> TardisResult<()> {
     );
     assert_eq!(rbums.len(), 16);
 
+    info!("【test_scope】 : Test Level 4");
+    let s4 = TardisContext {
+        own_paths: format!("{}/{}", s3.own_paths, TardisFuns::field.nanoid_len(4)),
+        ak: "".to_string(),
+        owner: "".to_string(),
+        roles: vec![],
+        groups: vec![],
+    };
+    let s4_sibling = TardisContext {
+        own_paths: format!("{}/{}", s3.own_paths, TardisFuns::field.nanoid_len(4)),
+        ..s4.clone()
+    };
+    let s4_l4_id = RbumDomainServ::add_rbum(
+        &mut RbumDomainAddReq {
+            code: TrimString("s4-l4".to_string()),
+            name: TrimString("scope_test_s4-l4".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            scope_level: Some(RbumScopeLevelKind::L4),
+        },
+        &funs,
+        &s4,
+    )
+    .await?;
+    assert!(RbumDomainServ::add_rbum(
+        &mut RbumDomainAddReq {
+            code: TrimString("s4-l5".to_string()),
+            name: TrimString("scope_test_s4-l5".to_string()),
+            note: None,
+            icon: None,
+            sort: None,
+            scope_level: Some(RbumScopeLevelKind::L5),
+        },
+        &funs,
+        &s4,
+    )
+    .await
+    .is_err());
+    assert!(RbumConfigManager::add(
+        "scope_max_level",
+        RbumConfig {
+            scope_max_level: RbumScopeLevelKind::MAX_LEVEL + 1,
+            ..Default::default()
+        }
+    )
+    .is_err());
+    let rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            name: Some("scope_test_".to_string()),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        &TardisContext {
+            own_paths: format!("{}/x", s4.own_paths),
+            ..s4.clone()
+        },
+    )
+    .await?;
+    assert_eq!(rbums.len(), 17);
+    let rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            name: Some("scope_test_".to_string()),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        &s4_sibling,
+    )
+    .await?;
+    assert_eq!(rbums.len(), 16);
+    let rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            name: Some("scope_test_".to_string()),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        &s3,
+    )
+    .await?;
+    assert_eq!(rbums.len(), 16);
+
+    info!("【test_scope】 : Test Filter Own Paths");
+    let all_rbums = RbumDomainServ::find_rbums(
+        &RbumBasicFilterReq {
+            name: Some("scope_test_".to_string()),
+            own_paths: Some("".to_string()),
+            with_sub_own_paths: true,
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        &s0,
+    )
+    .await?;
+    for (filter, ctx) in [
+        (
+            RbumBasicFilterReq {
+                name: Some("scope_test_".to_string()),
+                own_paths: Some(s4.own_paths.clone()),
+                ..Default::default()
+            },
+            &s1,
+        ),
+        (
+            RbumBasicFilterReq {
+                name: Some("scope_test_".to_string()),
+                own_paths: Some(s1.own_paths.clone()),
+                ..Default::default()
+            },
+            &s4_sibling,
+        ),
+        (
+            RbumBasicFilterReq {
+                name: Some("scope_test_".to_string()),
+                own_paths: Some(s2.own_paths.clone()),
+                with_sub_own_paths: true,
+                ..Default::default()
+            },
+            &s4_sibling,
+        ),
+    ] {
+        let rbum_ids = RbumDomainServ::find_id_rbums(&filter, None, None, &funs, ctx).await?;
+        for rbum in &all_rbums {
+            assert_eq!(
+                rbum_ids.contains(&rbum.id),
+                check_scope(&rbum.own_paths, Some(rbum.scope_level.to_int()), &filter, ctx),
+                "{} of {}",
+                rbum.code,
+                rbum.own_paths
+            );
+        }
+    }
+
+    info!("【test_scope】 : Test Explain");
+    let mut explains = Vec::new();
+    for (filter, ctx) in [
+        (RbumBasicFilterReq::default(), &s4_sibling),
+        (
+            RbumBasicFilterReq {
+                ignore_scope: true,
+                ..Default::default()
+            },
+            &s4_sibling,
+        ),
+        (
+            RbumBasicFilterReq {
+                own_paths: Some(s3.own_paths.clone()),
+                with_sub_own_paths: true,
+                ..Default::default()
+            },
+            &s4_sibling,
+        ),
+        (
+            RbumBasicFilterReq {
+                own_paths: Some(s4.own_paths.clone()),
+                ..Default::default()
+            },
+            &s1,
+        ),
+    ] {
+        let explain = RbumDomainServ::explain_scope(&s4_l4_id, RbumDomainServ::get_table_name(), &filter, &funs, ctx).await?;
+        // The explanation must agree with the real query
+        assert_eq!(explain.visible, RbumDomainServ::find_id_rbums(&filter, None, None, &funs, ctx).await?.contains(&s4_l4_id));
+        explains.push(explain);
+    }
+    assert!(!explains[0].visible);
+    assert_eq!(explains[0].record_scope_level, Some(RbumScopeLevelKind::L4));
+    assert_eq!(explains[0].rules.last().unwrap().rule, RbumScopeRuleKind::Level);
+    assert!(!explains[0].rules.last().unwrap().matched);
+    assert!(!explains[1].visible);
+    assert_eq!(explains[1].rules.last().unwrap().rule, RbumScopeRuleKind::IgnoreScope);
+    assert!(explains[2].visible);
+    assert_eq!(explains[2].rules.last().unwrap().rule, RbumScopeRuleKind::SubOwnPaths);
+    assert!(explains[3].visible);
+    assert_eq!(explains[3].filter_own_paths, s4.own_paths);
+    assert_eq!(explains[3].ctx_own_paths, s1.own_paths);
+    assert_eq!(explains[3].rules.last().unwrap().rule, RbumScopeRuleKind::OwnPaths);
+    assert!(RbumDomainServ::explain_scope("xxx", RbumDomainServ::get_table_name(), &RbumBasicFilterReq::default(), &funs, &s4).await.is_err());
+    assert!(RbumDomainServ::explain_scope(&s4_l4_id, "rbum_cert", &RbumBasicFilterReq::default(), &funs, &s4).await.is_err());
+
     funs.rollback().await?;
 
     Ok(())
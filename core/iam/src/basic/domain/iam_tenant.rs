@@ -12,6 +12,8 @@ pub struct Model {
     pub sort: u32,
     pub contact_phone: String,
     pub note: String,
+    /// New tenants can be created from the template tenant, see ``IamCsTenantServ::add_tenant``
+    pub template: bool,
 
     pub own_paths: String,
 }
@@ -32,6 +34,7 @@ impl TardisActiveModel for ActiveModel {
             .col(ColumnDef::new(Column::Sort).not_null().unsigned())
             .col(ColumnDef::new(Column::ContactPhone).not_null().string())
             .col(ColumnDef::new(Column::Note).not_null().string())
+            .col(ColumnDef::new(Column::Template).not_null().boolean())
             .col(ColumnDef::new(Column::OwnPaths).not_null().string())
             .to_owned()
     }
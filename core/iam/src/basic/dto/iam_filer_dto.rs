@@ -3,7 +3,7 @@ use std::default::Default;
 use serde::{Deserialize, Serialize};
 use tardis::web::poem_openapi::Object;
 
-use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumItemFilterFetcher, RbumItemRelFilterReq};
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumItemAttrPredicateReq, RbumItemFilterFetcher, RbumItemRelFilterReq};
 
 use crate::iam_enumeration::IamResKind;
 
@@ -13,6 +13,7 @@ pub struct IamAccountFilterReq {
     pub basic: RbumBasicFilterReq,
     pub rel: Option<RbumItemRelFilterReq>,
     pub rel2: Option<RbumItemRelFilterReq>,
+    pub attrs: Option<Vec<RbumItemAttrPredicateReq>>,
     pub icon: Option<String>,
 }
 
@@ -26,6 +27,9 @@ impl RbumItemFilterFetcher for IamAccountFilterReq {
     fn rel2(&self) -> &Option<RbumItemRelFilterReq> {
         &self.rel2
     }
+    fn attrs(&self) -> &Option<Vec<RbumItemAttrPredicateReq>> {
+        &self.attrs
+    }
 }
 
 #[derive(Object, Serialize, Deserialize, Debug, Clone, Default)]
@@ -34,6 +38,7 @@ pub struct IamAppFilterReq {
     pub basic: RbumBasicFilterReq,
     pub rel: Option<RbumItemRelFilterReq>,
     pub rel2: Option<RbumItemRelFilterReq>,
+    pub attrs: Option<Vec<RbumItemAttrPredicateReq>>,
     pub icon: Option<String>,
     pub sort: Option<u32>,
     pub contact_phone: Option<String>,
@@ -49,6 +54,9 @@ impl RbumItemFilterFetcher for IamAppFilterReq {
     fn rel2(&self) -> &Option<RbumItemRelFilterReq> {
         &self.rel2
     }
+    fn attrs(&self) -> &Option<Vec<RbumItemAttrPredicateReq>> {
+        &self.attrs
+    }
 }
 
 #[derive(Object, Serialize, Deserialize, Debug, Clone, Default)]
@@ -57,9 +65,11 @@ pub struct IamTenantFilterReq {
     pub basic: RbumBasicFilterReq,
     pub rel: Option<RbumItemRelFilterReq>,
     pub rel2: Option<RbumItemRelFilterReq>,
+    pub attrs: Option<Vec<RbumItemAttrPredicateReq>>,
     pub icon: Option<String>,
     pub sort: Option<u32>,
     pub contact_phone: Option<String>,
+    pub template: Option<bool>,
 }
 
 impl RbumItemFilterFetcher for IamTenantFilterReq {
@@ -72,6 +82,9 @@ impl RbumItemFilterFetcher for IamTenantFilterReq {
     fn rel2(&self) -> &Option<RbumItemRelFilterReq> {
         &self.rel2
     }
+    fn attrs(&self) -> &Option<Vec<RbumItemAttrPredicateReq>> {
+        &self.attrs
+    }
 }
 
 #[derive(Object, Serialize, Deserialize, Debug, Clone, Default)]
@@ -80,6 +93,7 @@ pub struct IamResFilterReq {
     pub basic: RbumBasicFilterReq,
     pub rel: Option<RbumItemRelFilterReq>,
     pub rel2: Option<RbumItemRelFilterReq>,
+    pub attrs: Option<Vec<RbumItemAttrPredicateReq>>,
     pub kind: Option<IamResKind>,
     pub icon: Option<String>,
     pub sort: Option<u32>,
@@ -96,6 +110,9 @@ impl RbumItemFilterFetcher for IamResFilterReq {
     fn rel2(&self) -> &Option<RbumItemRelFilterReq> {
         &self.rel2
     }
+    fn attrs(&self) -> &Option<Vec<RbumItemAttrPredicateReq>> {
+        &self.attrs
+    }
 }
 
 #[derive(Object, Serialize, Deserialize, Debug, Clone, Default)]
@@ -104,6 +121,7 @@ pub struct IamRoleFilterReq {
     pub basic: RbumBasicFilterReq,
     pub rel: Option<RbumItemRelFilterReq>,
     pub rel2: Option<RbumItemRelFilterReq>,
+    pub attrs: Option<Vec<RbumItemAttrPredicateReq>>,
     pub icon: Option<String>,
     pub sort: Option<u32>,
 }
@@ -118,4 +136,7 @@ impl RbumItemFilterFetcher for IamRoleFilterReq {
     fn rel2(&self) -> &Option<RbumItemRelFilterReq> {
         &self.rel2
     }
+    fn attrs(&self) -> &Option<Vec<RbumItemAttrPredicateReq>> {
+        &self.attrs
+    }
 }
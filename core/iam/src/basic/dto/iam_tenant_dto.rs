@@ -18,6 +18,7 @@ pub struct IamTenantAddReq {
     pub sort: Option<u32>,
     pub contact_phone: Option<String>,
     pub note: Option<String>,
+    pub template: Option<bool>,
 }
 
 #[derive(Object, Serialize, Deserialize, Debug)]
@@ -57,6 +58,7 @@ pub struct IamTenantSummaryResp {
     pub sort: u32,
     pub contact_phone: String,
     pub note: String,
+    pub template: bool,
 }
 
 #[derive(Object, FromQueryResult, Serialize, Deserialize, Debug)]
@@ -77,4 +79,5 @@ pub struct IamTenantDetailResp {
     pub sort: u32,
     pub contact_phone: String,
     pub note: String,
+    pub template: bool,
 }
@@ -6,17 +6,20 @@ use tardis::db::sea_orm::*;
 use tardis::db::sea_query::{Expr, SelectStatement};
 use tardis::TardisFunsInst;
 
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumCertFilterReq};
 use bios_basic::rbum::dto::rbum_item_dto::{RbumItemKernelAddReq, RbumItemModifyReq};
 use bios_basic::rbum::dto::rbum_rel_dto::RbumRelBoneResp;
+use bios_basic::rbum::rbum_enumeration::RbumCertRelKind;
+use bios_basic::rbum::serv::rbum_cert_serv::RbumCertServ;
 use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
-use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemAttrServ, RbumItemCrudOperation, RbumItemServ};
 
 use crate::basic::domain::iam_account;
 use crate::basic::dto::iam_account_dto::{
     IamAccountAddReq, IamAccountAggAddReq, IamAccountAggModifyReq, IamAccountDetailResp, IamAccountModifyReq, IamAccountSelfModifyReq, IamAccountSummaryResp,
 };
 use crate::basic::dto::iam_cert_dto::{IamMailVCodeCertAddReq, IamPhoneVCodeCertAddReq, IamUserPwdCertAddReq};
-use crate::basic::dto::iam_filer_dto::IamAccountFilterReq;
+use crate::basic::dto::iam_filer_dto::{IamAccountFilterReq, IamTenantFilterReq};
 use crate::basic::serv::iam_attr_serv::IamAttrServ;
 use crate::basic::serv::iam_cert_mail_vcode_serv::IamCertMailVCodeServ;
 use crate::basic::serv::iam_cert_phone_vcode_serv::IamCertPhoneVCodeServ;
@@ -25,6 +28,7 @@ use crate::basic::serv::iam_cert_user_pwd_serv::IamCertUserPwdServ;
 use crate::basic::serv::iam_key_cache_serv::IamIdentCacheServ;
 use crate::basic::serv::iam_rel_serv::IamRelServ;
 use crate::basic::serv::iam_role_serv::IamRoleServ;
+use crate::basic::serv::iam_tenant_serv::IamTenantServ;
 use crate::iam_config::IamBasicInfoManager;
 use crate::iam_enumeration::{IamCertKind, IamRelKind};
 
@@ -109,6 +113,40 @@ impl<'a> RbumItemCrudOperation<'a, iam_account::ActiveModel, IamAccountAddReq, I
         Ok(())
     }
 
+    async fn before_move_item(_: &str, target_own_paths: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        // Accounts can only be moved to a tenant, the accounts join or leave the apps by the account app relationships instead of moving
+        if target_own_paths.contains('/') {
+            return Err(funs.err().bad_request(
+                &Self::get_obj_name(),
+                "move",
+                "account can only be moved between tenants, use the account app relationships to change the apps",
+            ));
+        }
+        IamTenantServ::peek_item(target_own_paths, &IamTenantFilterReq::default(), funs, ctx).await?;
+        Ok(())
+    }
+
+    async fn after_move_item(id: &str, target_own_paths: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        // Bind the certs to the cert confs of the target tenant with the same code
+        let filter = RbumCertFilterReq {
+            basic: RbumBasicFilterReq {
+                with_sub_own_paths: true,
+                ..Default::default()
+            },
+            rel_rbum_kind: Some(RbumCertRelKind::Item),
+            rel_rbum_id: Some(id.to_string()),
+            ..Default::default()
+        };
+        for cert in RbumCertServ::find_rbums(&filter, None, None, funs, ctx).await? {
+            if let Some(cert_conf_code) = &cert.rel_rbum_cert_conf_code {
+                let cert_conf_id = IamCertServ::get_cert_conf_id_by_code(cert_conf_code, Some(target_own_paths.to_string()), funs).await?;
+                RbumCertServ::rebind_cert_conf(&cert.id, &cert_conf_id, &filter, funs, ctx).await?;
+            }
+        }
+        IamIdentCacheServ::delete_tokens_and_contexts_by_account_id(id, funs).await?;
+        Ok(())
+    }
+
     async fn package_ext_query(query: &mut SelectStatement, _: bool, filter: &IamAccountFilterReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
         query.column((iam_account::Entity, iam_account::Column::Icon));
         query.column((iam_account::Entity, iam_account::Column::Ext1Idx));
@@ -129,10 +167,8 @@ impl<'a> RbumItemCrudOperation<'a, iam_account::ActiveModel, IamAccountAddReq, I
 
 impl<'a> IamAccountServ {
     pub async fn add_account_agg(add_req: &IamAccountAggAddReq, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<String> {
-        let attrs = IamAttrServ::find_account_attrs(funs, ctx).await?;
-        if attrs.iter().any(|i| i.required && !add_req.exts.contains_key(&i.name)) {
-            return Err(funs.err().bad_request(&Self::get_obj_name(), "add", "missing required field"));
-        }
+        let mut exts = add_req.exts.clone();
+        RbumItemAttrServ::validate_item_attr_values(&Self::get_rbum_kind_id(), &mut exts, true, funs, ctx).await?;
         let account_id = IamAccountServ::add_item(
             &mut IamAccountAddReq {
                 id: add_req.id.clone(),
@@ -182,7 +218,7 @@ impl<'a> IamAccountServ {
                 IamRoleServ::add_rel_account(role_id, &account_id, funs, ctx).await?;
             }
         }
-        IamAttrServ::add_or_modify_account_attr_values(&account_id, add_req.exts.clone(), funs, ctx).await?;
+        IamAttrServ::add_or_modify_account_attr_values(&account_id, exts, funs, ctx).await?;
         Ok(account_id)
     }
 
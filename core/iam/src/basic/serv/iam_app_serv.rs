@@ -5,17 +5,21 @@ use tardis::db::sea_orm::*;
 use tardis::db::sea_query::{Expr, SelectStatement};
 use tardis::{TardisFuns, TardisFunsInst};
 
-use bios_basic::rbum::dto::rbum_filer_dto::RbumItemRelFilterReq;
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumItemRelFilterReq};
 use bios_basic::rbum::dto::rbum_item_dto::{RbumItemKernelAddReq, RbumItemModifyReq};
 use bios_basic::rbum::helper::rbum_scope_helper;
 use bios_basic::rbum::rbum_enumeration::RbumRelFromKind;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
+use bios_basic::rbum::serv::rbum_set_serv::RbumSetServ;
 
-use crate::basic::domain::iam_app;
+use crate::basic::domain::{iam_account, iam_app, iam_res, iam_role, iam_tenant};
 use crate::basic::dto::iam_app_dto::{IamAppAddReq, IamAppDetailResp, IamAppModifyReq, IamAppSummaryResp};
-use crate::basic::dto::iam_filer_dto::IamAppFilterReq;
+use crate::basic::dto::iam_filer_dto::{IamAppFilterReq, IamTenantFilterReq};
 use crate::basic::serv::iam_key_cache_serv::IamIdentCacheServ;
 use crate::basic::serv::iam_rel_serv::IamRelServ;
+use crate::basic::serv::iam_set_serv::IamSetServ;
+use crate::basic::serv::iam_tenant_serv::IamTenantServ;
 use crate::iam_config::IamBasicInfoManager;
 use crate::iam_constants::{RBUM_ITEM_ID_APP_LEN, RBUM_SCOPE_LEVEL_APP};
 use crate::iam_enumeration::IamRelKind;
@@ -99,6 +103,11 @@ impl<'a> RbumItemCrudOperation<'a, iam_app::ActiveModel, IamAppAddReq, IamAppMod
         Err(funs.err().conflict(&Self::get_obj_name(), "delete", "app can only be disabled but not deleted"))
     }
 
+    async fn before_move_item(_: &str, _: &str, funs: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
+        // The resources of the app are under the own paths of the app, they have to be moved together, see ``move_app``
+        Err(funs.err().bad_request(&Self::get_obj_name(), "move", "app can only be moved by move_app"))
+    }
+
     async fn package_ext_query(query: &mut SelectStatement, _: bool, filter: &IamAppFilterReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
         query.column((iam_app::Entity, iam_app::Column::ContactPhone));
         query.column((iam_app::Entity, iam_app::Column::Icon));
@@ -115,6 +124,63 @@ impl<'a> IamAppServ {
         TardisFuns::field.nanoid_len(RBUM_ITEM_ID_APP_LEN as usize)
     }
 
+    /// Move the app with all its resources (roles, sets, relationships, etc.) from ``old_tenant_id/app_id`` to ``tenant_id/app_id``.
+    ///
+    /// The relationships to the resources of the old tenant (e.g. the accounts of the app) have to be deleted before moving,
+    /// otherwise a conflict error is returned.
+    pub async fn move_app(id: &str, tenant_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        IamTenantServ::peek_item(tenant_id, &IamTenantFilterReq::default(), funs, ctx).await?;
+        let app = Self::peek_item(
+            id,
+            &IamAppFilterReq {
+                basic: RbumBasicFilterReq {
+                    with_sub_own_paths: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            funs,
+            ctx,
+        )
+        .await?;
+        let target_own_paths = format!("{}/{}", tenant_id, id);
+        if app.own_paths == target_own_paths {
+            return Ok(());
+        }
+        RbumItemServ::move_own_paths(
+            &app.own_paths,
+            &target_own_paths,
+            &[
+                iam_account::Entity.table_name(),
+                iam_app::Entity.table_name(),
+                iam_res::Entity.table_name(),
+                iam_role::Entity.table_name(),
+                iam_tenant::Entity.table_name(),
+            ],
+            funs,
+            ctx,
+        )
+        .await?;
+        // The codes of the default sets contain the own paths
+        for is_org in [true, false] {
+            let (old_code, new_code) = if is_org {
+                (
+                    IamSetServ::get_default_org_code_by_own_paths(&app.own_paths),
+                    IamSetServ::get_default_org_code_by_own_paths(&target_own_paths),
+                )
+            } else {
+                (
+                    IamSetServ::get_default_res_code_by_own_paths(&app.own_paths),
+                    IamSetServ::get_default_res_code_by_own_paths(&target_own_paths),
+                )
+            };
+            if let Some(set_id) = RbumSetServ::get_rbum_set_id_by_code(&old_code, true, funs, ctx).await? {
+                RbumSetServ::modify_rbum_set_code(&set_id, &new_code, funs, ctx).await?;
+            }
+        }
+        IamIdentCacheServ::delete_tokens_and_contexts_by_tenant_or_app(id, true, funs, ctx).await
+    }
+
     pub fn get_id_by_ctx(ctx: &TardisContext, funs: &TardisFunsInst<'a>) -> TardisResult<String> {
         if let Some(id) = rbum_scope_helper::get_path_item(RBUM_SCOPE_LEVEL_APP.to_int(), &ctx.own_paths) {
             Ok(id)
@@ -6,7 +6,7 @@ use tardis::TardisFunsInst;
 
 use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumItemAttrFilterReq, RbumKindAttrFilterReq};
 use bios_basic::rbum::dto::rbum_item_attr_dto::{RbumItemAttrAddReq, RbumItemAttrDetailResp, RbumItemAttrModifyReq, RbumItemAttrsAddOrModifyReq};
-use bios_basic::rbum::dto::rbum_kind_attr_dto::{RbumKindAttrAddReq, RbumKindAttrDetailResp, RbumKindAttrModifyReq, RbumKindAttrSummaryResp};
+use bios_basic::rbum::dto::rbum_kind_attr_dto::{RbumKindAttrAddReq, RbumKindAttrDetailResp, RbumKindAttrModifyReq, RbumKindAttrSchemaResp, RbumKindAttrSummaryResp};
 use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
 use bios_basic::rbum::serv::rbum_item_serv::RbumItemAttrServ;
 use bios_basic::rbum::serv::rbum_kind_serv::RbumKindAttrServ;
@@ -101,6 +101,10 @@ impl<'a> IamAttrServ {
         .await
     }
 
+    pub async fn get_account_attr_schema(funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumKindAttrSchemaResp> {
+        RbumKindAttrServ::get_kind_attr_schema(&funs.iam_basic_kind_account_id(), funs, ctx).await
+    }
+
     pub async fn delete_account_attr(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<u64> {
         RbumKindAttrServ::delete_rbum(id, funs, ctx).await
     }
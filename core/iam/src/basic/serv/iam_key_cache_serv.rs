@@ -2,6 +2,7 @@ use std::default::Default;
 use std::str::FromStr;
 
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
+use bios_basic::rbum::helper::rbum_uri_helper::RbumUri;
 use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -295,13 +296,12 @@ impl<'a> IamResCacheServ {
     }
 
     fn package_uri_mixed(item_code: &str, action: &str) -> String {
-        format!(
-            "{}://{}/{}##{}",
-            iam_constants::RBUM_KIND_CODE_IAM_RES.to_lowercase(),
-            iam_constants::COMPONENT_CODE.to_lowercase(),
-            item_code,
-            action
-        )
+        let uri = RbumUri {
+            kind_code: iam_constants::RBUM_KIND_CODE_IAM_RES.to_lowercase(),
+            domain_code: iam_constants::COMPONENT_CODE.to_lowercase(),
+            item_code: item_code.to_string(),
+        };
+        format!("{}##{}", uri, action)
     }
 }
 
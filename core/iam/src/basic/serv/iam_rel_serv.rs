@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use tardis::basic::dto::TardisContext;
 use tardis::basic::result::TardisResult;
@@ -6,6 +6,8 @@ use tardis::chrono::{Duration, Utc};
 use tardis::web::web_resp::TardisPage;
 use tardis::TardisFunsInst;
 
+use bios_basic::rbum::dto::rbum_batch_dto::RbumBatchResp;
+use bios_basic::rbum::dto::rbum_cursor_dto::{RbumCursorPage, RbumCursorReq};
 use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumRelFilterReq};
 use bios_basic::rbum::dto::rbum_rel_agg_dto::{RbumRelAggAddReq, RbumRelEnvAggAddReq};
 use bios_basic::rbum::dto::rbum_rel_dto::{RbumRelAddReq, RbumRelBoneResp, RbumRelFindReq};
@@ -31,6 +33,19 @@ impl<'a> IamRelServ {
         funs: &TardisFunsInst<'a>,
         ctx: &TardisContext,
     ) -> TardisResult<()> {
+        Self::do_add_simple_rel(rel_kind, from_iam_item_id, to_iam_item_id, start_timestamp, end_timestamp, funs, ctx).await?;
+        Ok(())
+    }
+
+    async fn do_add_simple_rel(
+        rel_kind: &IamRelKind,
+        from_iam_item_id: &str,
+        to_iam_item_id: &str,
+        start_timestamp: Option<i64>,
+        end_timestamp: Option<i64>,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<String> {
         let value1 = start_timestamp.unwrap_or_else(|| Utc::now().timestamp());
         let value2 = end_timestamp.unwrap_or_else(|| (Utc::now() + Duration::days(365 * 100)).timestamp());
         let req = &mut RbumRelAggAddReq {
@@ -55,7 +70,7 @@ impl<'a> IamRelServ {
                 vec![]
             },
         };
-        RbumRelServ::add_rel(req, funs, ctx).await?;
+        let rel_id = RbumRelServ::add_rel(req, funs, ctx).await?;
         if rel_kind == &IamRelKind::IamResRole {
             let iam_res = IamResServ::peek_item(
                 from_iam_item_id,
@@ -71,7 +86,7 @@ impl<'a> IamRelServ {
             )
             .await?;
             if iam_res.kind != IamResKind::Api {
-                return Ok(());
+                return Ok(rel_id);
             }
             IamResCacheServ::add_or_modify_res_rel(
                 &iam_res.code,
@@ -140,7 +155,71 @@ impl<'a> IamRelServ {
                 .await?;
             }
         }
-        Ok(())
+        Ok(rel_id)
+    }
+
+    /// Add rels from multiple items to one item in batch
+    ///
+    /// The rels are checked one by one and inserted with one statement (see ``RbumCrudOperation::add_rbums``),
+    /// the failed ones are reported in the result.
+    pub async fn add_simple_rels(
+        rel_kind: &IamRelKind,
+        from_iam_item_ids: &[String],
+        to_iam_item_id: &str,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumBatchResp> {
+        let mut add_reqs = from_iam_item_ids
+            .iter()
+            .map(|from_iam_item_id| RbumRelAddReq {
+                tag: rel_kind.to_string(),
+                note: None,
+                from_rbum_kind: RbumRelFromKind::Item,
+                from_rbum_id: from_iam_item_id.to_string(),
+                to_rbum_item_id: to_iam_item_id.to_string(),
+                to_own_paths: ctx.own_paths.to_string(),
+                to_is_outside: false,
+                ext: None,
+            })
+            .collect::<Vec<RbumRelAddReq>>();
+        let resp = RbumRelServ::add_rbums(&mut add_reqs, funs, ctx).await?;
+        if rel_kind == &IamRelKind::IamResRole && !resp.succeed_ids.is_empty() {
+            let failed_indexes = resp.failed.iter().map(|failed| failed.index as usize).collect::<HashSet<usize>>();
+            let res_ids = from_iam_item_ids.iter().enumerate().filter(|(idx, _)| !failed_indexes.contains(idx)).map(|(_, id)| id.to_string()).collect::<Vec<String>>();
+            let rel_res = IamResServ::find_items(
+                &IamResFilterReq {
+                    basic: RbumBasicFilterReq {
+                        ids: Some(res_ids),
+                        with_sub_own_paths: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                None,
+                None,
+                funs,
+                ctx,
+            )
+            .await?;
+            for res in rel_res.into_iter().filter(|res| res.kind == IamResKind::Api) {
+                IamResCacheServ::add_or_modify_res_rel(
+                    &res.code,
+                    &res.method,
+                    &IamCacheResRelAddOrModifyReq {
+                        st: None,
+                        et: None,
+                        accounts: vec![],
+                        roles: vec![to_iam_item_id.to_string()],
+                        groups: vec![],
+                        apps: vec![],
+                        tenants: vec![],
+                    },
+                    funs,
+                )
+                .await?;
+            }
+        }
+        Ok(resp)
     }
 
     pub async fn delete_simple_rel(rel_kind: &IamRelKind, from_iam_item_id: &str, to_iam_item_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
@@ -426,6 +505,16 @@ impl<'a> IamRelServ {
         .await
     }
 
+    pub async fn paginate_to_simple_rels_by_cursor(
+        rel_kind: &IamRelKind,
+        to_iam_item_id: &str,
+        cursor_req: &RbumCursorReq,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumCursorPage<RbumRelBoneResp>> {
+        RbumRelServ::paginate_to_simple_rels_by_cursor(&rel_kind.to_string(), to_iam_item_id, cursor_req, funs, ctx).await
+    }
+
     pub async fn exist_rels(rel_kind: &IamRelKind, from_iam_item_id: &str, to_iam_item_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<bool> {
         RbumRelServ::exist_simple_rel(
             &RbumRelFindReq {
@@ -16,7 +16,7 @@ use crate::basic::domain::iam_res;
 use crate::basic::dto::iam_filer_dto::IamResFilterReq;
 use crate::basic::dto::iam_res_dto::{IamResAddReq, IamResAggAddReq, IamResDetailResp, IamResModifyReq, IamResSummaryResp};
 use crate::basic::dto::iam_set_dto::IamSetItemAddReq;
-use crate::basic::serv::iam_key_cache_serv::IamResCacheServ;
+use crate::basic::serv::iam_key_cache_serv::{IamCacheResRelAddOrModifyReq, IamResCacheServ};
 use crate::basic::serv::iam_rel_serv::IamRelServ;
 use crate::basic::serv::iam_set_serv::IamSetServ;
 use crate::iam_config::IamBasicInfoManager;
@@ -176,6 +176,46 @@ impl<'a> RbumItemCrudOperation<'a, iam_res::ActiveModel, IamResAddReq, IamResMod
         }
     }
 
+    async fn after_restore_item(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        let res = Self::peek_item(
+            id,
+            &IamResFilterReq {
+                basic: RbumBasicFilterReq {
+                    with_sub_own_paths: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            funs,
+            ctx,
+        )
+        .await?;
+        if res.kind != IamResKind::Api || res.disabled {
+            return Ok(());
+        }
+        IamResCacheServ::add_res(&res.code, &res.method, funs).await?;
+        // The relationships that are kept (or restored) with the resource take effect again
+        let role_ids = IamRelServ::find_from_id_rels(&IamRelKind::IamResRole, true, id, None, None, funs, ctx).await?;
+        if !role_ids.is_empty() {
+            IamResCacheServ::add_or_modify_res_rel(
+                &res.code,
+                &res.method,
+                &IamCacheResRelAddOrModifyReq {
+                    st: None,
+                    et: None,
+                    accounts: vec![],
+                    roles: role_ids,
+                    groups: vec![],
+                    apps: vec![],
+                    tenants: vec![],
+                },
+                funs,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     async fn package_ext_query(query: &mut SelectStatement, _: bool, filter: &IamResFilterReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
         query.column((iam_res::Entity, iam_res::Column::Kind));
         query.column((iam_res::Entity, iam_res::Column::Icon));
@@ -7,9 +7,11 @@ use tardis::db::sea_query::SelectStatement;
 use tardis::web::web_resp::TardisPage;
 use tardis::{TardisFuns, TardisFunsInst};
 
+use bios_basic::rbum::dto::rbum_batch_dto::RbumBatchResp;
+use bios_basic::rbum::dto::rbum_cursor_dto::{RbumCursorPage, RbumCursorReq};
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
 use bios_basic::rbum::dto::rbum_item_dto::{RbumItemKernelAddReq, RbumItemModifyReq};
-use bios_basic::rbum::dto::rbum_rel_dto::{RbumRelBoneResp, RbumRelCheckReq};
+use bios_basic::rbum::dto::rbum_rel_dto::{RbumRelBoneResp, RbumRelCheckEnvReq, RbumRelCheckReq};
 use bios_basic::rbum::helper::rbum_scope_helper;
 use bios_basic::rbum::helper::rbum_scope_helper::get_scope_level_by_context;
 use bios_basic::rbum::rbum_enumeration::RbumRelFromKind;
@@ -173,6 +175,29 @@ impl<'a> RbumItemCrudOperation<'a, iam_role::ActiveModel, IamRoleAddReq, IamRole
         Ok(())
     }
 
+    async fn after_restore_item(id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        let role = Self::do_get_item(
+            id,
+            &IamRoleFilterReq {
+                basic: RbumBasicFilterReq {
+                    with_sub_own_paths: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            funs,
+            ctx,
+        )
+        .await?;
+        funs.cache()
+            .set(
+                &format!("{}{}", funs.conf::<IamConfig>().cache_key_role_info_, id),
+                TardisFuns::json.obj_to_string(&role)?.as_str(),
+            )
+            .await?;
+        Ok(())
+    }
+
     async fn package_ext_query(query: &mut SelectStatement, _: bool, _: &IamRoleFilterReq, _: &TardisFunsInst<'a>, _: &TardisContext) -> TardisResult<()> {
         query.column((iam_role::Entity, iam_role::Column::Icon));
         query.column((iam_role::Entity, iam_role::Column::Sort));
@@ -227,18 +252,28 @@ impl<'a> IamRoleServ {
         Ok(())
     }
 
-    pub async fn add_rel_account(role_id: &str, account_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+    fn check_rel_account_role(role_id: &str, op: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         let scope_level = get_scope_level_by_context(ctx)?;
         if scope_level == RBUM_SCOPE_LEVEL_APP && (role_id == funs.iam_basic_role_sys_admin_id() || role_id == funs.iam_basic_role_tenant_admin_id())
             || scope_level == RBUM_SCOPE_LEVEL_TENANT && role_id == funs.iam_basic_role_sys_admin_id()
         {
-            return Err(funs.err().conflict(&Self::get_obj_name(), "add_rel_account", "associated role is invalid"));
+            return Err(funs.err().conflict(&Self::get_obj_name(), op, "associated role is invalid"));
         }
+        Ok(())
+    }
+
+    pub async fn add_rel_account(role_id: &str, account_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::check_rel_account_role(role_id, "add_rel_account", funs, ctx)?;
         // TODO only bind the same own_paths roles
         // E.g. sys admin can't bind tenant admin
         IamRelServ::add_simple_rel(&IamRelKind::IamAccountRole, account_id, role_id, None, None, funs, ctx).await
     }
 
+    pub async fn add_rel_accounts(role_id: &str, account_ids: &[String], funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumBatchResp> {
+        Self::check_rel_account_role(role_id, "add_rel_accounts", funs, ctx)?;
+        IamRelServ::add_simple_rels(&IamRelKind::IamAccountRole, account_ids, role_id, funs, ctx).await
+    }
+
     pub async fn delete_rel_account(role_id: &str, account_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         IamRelServ::delete_simple_rel(&IamRelKind::IamAccountRole, account_id, role_id, funs, ctx).await
     }
@@ -291,6 +326,15 @@ impl<'a> IamRoleServ {
         IamRelServ::paginate_to_simple_rels(&IamRelKind::IamAccountRole, role_id, page_number, page_size, desc_by_create, desc_by_update, funs, ctx).await
     }
 
+    pub async fn paginate_simple_rel_accounts_by_cursor(
+        role_id: &str,
+        cursor_req: &RbumCursorReq,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<RbumCursorPage<RbumRelBoneResp>> {
+        IamRelServ::paginate_to_simple_rels_by_cursor(&IamRelKind::IamAccountRole, role_id, cursor_req, funs, ctx).await
+    }
+
     pub async fn add_rel_res(role_id: &str, res_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         IamRelServ::add_simple_rel(&IamRelKind::IamResRole, res_id, role_id, None, None, funs, ctx).await
     }
@@ -347,19 +391,22 @@ impl<'a> IamRoleServ {
         IamRelServ::paginate_to_simple_rels(&IamRelKind::IamResRole, role_id, page_number, page_size, desc_by_create, desc_by_update, funs, ctx).await
     }
 
-    pub async fn need_sys_admin(funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
-        Self::need_role(&funs.iam_basic_role_sys_admin_id(), funs, ctx).await
+    pub async fn need_sys_admin(env: Option<RbumRelCheckEnvReq>, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::need_role(&funs.iam_basic_role_sys_admin_id(), env, funs, ctx).await
     }
 
-    pub async fn need_tenant_admin(funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
-        Self::need_role(&funs.iam_basic_role_tenant_admin_id(), funs, ctx).await
+    pub async fn need_tenant_admin(env: Option<RbumRelCheckEnvReq>, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::need_role(&funs.iam_basic_role_tenant_admin_id(), env, funs, ctx).await
     }
 
-    pub async fn need_app_admin(funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
-        Self::need_role(&funs.iam_basic_role_app_admin_id(), funs, ctx).await
+    pub async fn need_app_admin(env: Option<RbumRelCheckEnvReq>, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::need_role(&funs.iam_basic_role_app_admin_id(), env, funs, ctx).await
     }
 
-    pub async fn need_role(role_id: &str, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+    /// Check whether the current account has the role
+    ///
+    /// ``env`` is the environment of the request (the client and forwarded addresses), checked by the environments of the account role relationship.
+    pub async fn need_role(role_id: &str, env: Option<RbumRelCheckEnvReq>, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
         let exist = RbumRelServ::check_rel(
             &mut RbumRelCheckReq {
                 tag: IamRelKind::IamAccountRole.to_string(),
@@ -368,6 +415,7 @@ impl<'a> IamRoleServ {
                 to_rbum_item_id: role_id.to_string(),
                 from_attrs: Default::default(),
                 to_attrs: Default::default(),
+                env,
             },
             funs,
             ctx,
@@ -49,6 +49,7 @@ impl<'a> RbumItemCrudOperation<'a, iam_tenant::ActiveModel, IamTenantAddReq, Iam
             sort: Set(add_req.sort.unwrap_or(0)),
             contact_phone: Set(add_req.contact_phone.as_ref().unwrap_or(&"".to_string()).to_string()),
             note: Set(add_req.note.as_ref().unwrap_or(&"".to_string()).to_string()),
+            template: Set(add_req.template.unwrap_or(false)),
             ..Default::default()
         })
     }
@@ -104,9 +105,13 @@ impl<'a> RbumItemCrudOperation<'a, iam_tenant::ActiveModel, IamTenantAddReq, Iam
         query.column((iam_tenant::Entity, iam_tenant::Column::Sort));
         query.column((iam_tenant::Entity, iam_tenant::Column::ContactPhone));
         query.column((iam_tenant::Entity, iam_tenant::Column::Note));
+        query.column((iam_tenant::Entity, iam_tenant::Column::Template));
         if let Some(contact_phone) = &filter.contact_phone {
             query.and_where(Expr::col(iam_tenant::Column::ContactPhone).eq(contact_phone.as_str()));
         }
+        if let Some(template) = filter.template {
+            query.and_where(Expr::col(iam_tenant::Column::Template).eq(template));
+        }
         Ok(())
     }
 }
@@ -116,6 +121,21 @@ impl<'a> IamTenantServ {
         TardisFuns::field.nanoid_len(RBUM_ITEM_ID_TENANT_LEN as usize)
     }
 
+    /// Designate the tenant as a template or not, only the system administrator should call it
+    pub async fn modify_template(id: &str, template: bool, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
+        Self::peek_item(id, &IamTenantFilterReq::default(), funs, ctx).await?;
+        funs.db()
+            .update_one(
+                iam_tenant::ActiveModel {
+                    id: Set(id.to_string()),
+                    template: Set(template),
+                    ..Default::default()
+                },
+                ctx,
+            )
+            .await
+    }
+
     pub fn get_id_by_ctx(ctx: &TardisContext, funs: &TardisFunsInst<'a>) -> TardisResult<String> {
         if ctx.own_paths.is_empty() {
             Ok("".to_string())
@@ -4,7 +4,7 @@ use tardis::web::context_extractor::TardisContextExtractor;
 use tardis::web::poem_openapi::OpenApi;
 use tardis::web::web_resp::{TardisApiResult, TardisResp};
 
-use bios_basic::rbum::dto::rbum_kind_attr_dto::RbumKindAttrSummaryResp;
+use bios_basic::rbum::dto::rbum_kind_attr_dto::{RbumKindAttrSchemaResp, RbumKindAttrSummaryResp};
 
 use crate::basic::serv::iam_attr_serv::IamAttrServ;
 use crate::iam_constants;
@@ -22,6 +22,14 @@ impl IamCpAccountAttrApi {
         TardisResp::ok(result)
     }
 
+    /// Get Account Attr Schema By Current Tenant
+    #[oai(path = "/schema", method = "get")]
+    async fn get_attr_schema(&self, ctx: TardisContextExtractor) -> TardisApiResult<RbumKindAttrSchemaResp> {
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamAttrServ::get_account_attr_schema(&funs, &ctx.0).await?;
+        TardisResp::ok(result)
+    }
+
     /// Find Account Ext Attr Values By Current Account
     #[oai(path = "/value", method = "get")]
     async fn find_account_attr_values(&self, ctx: TardisContextExtractor) -> TardisApiResult<HashMap<String, String>> {
@@ -4,4 +4,5 @@ pub mod iam_cs_cert_api;
 pub mod iam_cs_cert_conf_api;
 pub mod iam_cs_res_api;
 pub mod iam_cs_role_api;
+pub mod iam_cs_scope_api;
 pub mod iam_cs_tenant_api;
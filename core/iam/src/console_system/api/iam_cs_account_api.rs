@@ -1,12 +1,15 @@
 use tardis::web::context_extractor::TardisContextExtractor;
-use tardis::web::poem_openapi::{param::Path, param::Query, payload::Json, OpenApi};
+use tardis::web::poem;
+use tardis::web::poem_openapi::{param::Header, param::Path, param::Query, payload::Json, payload::Response, OpenApi};
 use tardis::web::web_resp::{TardisApiResult, TardisPage, TardisResp, Void};
 
 use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumItemRelFilterReq};
 use bios_basic::rbum::dto::rbum_rel_dto::RbumRelBoneResp;
 use bios_basic::rbum::dto::rbum_set_dto::RbumSetPathResp;
+use bios_basic::rbum::helper::rbum_etag_helper;
 use bios_basic::rbum::rbum_enumeration::RbumRelFromKind;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 
 use crate::basic::dto::iam_account_dto::{IamAccountAggAddReq, IamAccountAggModifyReq, IamAccountDetailResp, IamAccountSummaryResp};
 use crate::basic::dto::iam_filer_dto::IamAccountFilterReq;
@@ -33,11 +36,24 @@ impl IamCsAccountApi {
     }
 
     /// Modify Account By Account Id
+    ///
+    /// When the `If-Match` header (the `ETag` returned by get) is present, a stale write is rejected with a conflict error.
     #[oai(path = "/:id", method = "put")]
-    async fn modify(&self, id: Path<String>, tenant_id: Query<Option<String>>, modify_req: Json<IamAccountAggModifyReq>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+    async fn modify(
+        &self,
+        id: Path<String>,
+        tenant_id: Query<Option<String>>,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        modify_req: Json<IamAccountAggModifyReq>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<Void> {
         let ctx = IamCertServ::try_use_tenant_ctx(ctx.0, tenant_id.0)?;
+        let expected_version = rbum_etag_helper::parse_if_match(if_match.0.as_deref())?;
         let mut funs = iam_constants::get_tardis_inst();
         funs.begin().await?;
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(&id.0, expected_version, &funs, &ctx).await?;
+        }
         IamAccountServ::modify_account_agg(&id.0, &modify_req.0, &funs, &ctx).await?;
         funs.commit().await?;
         TardisResp::ok(Void {})
@@ -45,8 +61,9 @@ impl IamCsAccountApi {
 
     /// Get Account By Account Id
     #[oai(path = "/:id", method = "get")]
-    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<IamAccountDetailResp> {
+    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> poem::Result<Response<Json<TardisResp<IamAccountDetailResp>>>> {
         let funs = iam_constants::get_tardis_inst();
+        let etag = rbum_etag_helper::to_etag(RbumItemServ::get_rbum_version(&id.0, &funs, &ctx.0).await?);
         let result = IamAccountServ::get_item(
             &id.0,
             &IamAccountFilterReq {
@@ -60,7 +77,7 @@ impl IamCsAccountApi {
             &ctx.0,
         )
         .await?;
-        TardisResp::ok(result)
+        Ok(Response::new(TardisResp::ok(result)?).header(rbum_etag_helper::HEADER_ETAG, etag))
     }
 
     /// Find Accounts
@@ -1,12 +1,16 @@
+use bios_basic::rbum::dto::rbum_recycle_dto::RbumDeletedResp;
 use bios_basic::rbum::dto::rbum_rel_dto::RbumRelBoneResp;
 use tardis::web::context_extractor::TardisContextExtractor;
-use tardis::web::poem_openapi::{param::Path, param::Query, payload::Json, OpenApi};
-use tardis::web::web_resp::{TardisApiResult, TardisResp, Void};
+use tardis::web::poem;
+use tardis::web::poem_openapi::{param::Header, param::Path, param::Query, payload::Json, payload::Response, OpenApi};
+use tardis::web::web_resp::{TardisApiResult, TardisPage, TardisResp, Void};
 
 use crate::basic::dto::iam_filer_dto::IamResFilterReq;
 use crate::basic::dto::iam_res_dto::{IamResAggAddReq, IamResDetailResp, IamResModifyReq};
 use bios_basic::rbum::dto::rbum_set_cate_dto::RbumSetTreeResp;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::helper::rbum_etag_helper;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 
 use crate::basic::dto::iam_set_dto::{IamSetCateAddReq, IamSetCateModifyReq};
 use crate::basic::serv::iam_rel_serv::IamRelServ;
@@ -74,10 +78,22 @@ impl IamCsResApi {
     }
 
     /// Modify Res By Res Id
+    ///
+    /// When the `If-Match` header (the `ETag` returned by get) is present, a stale write is rejected with a conflict error.
     #[oai(path = "/:id", method = "put")]
-    async fn modify(&self, id: Path<String>, mut modify_req: Json<IamResModifyReq>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+    async fn modify(
+        &self,
+        id: Path<String>,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        mut modify_req: Json<IamResModifyReq>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<Void> {
+        let expected_version = rbum_etag_helper::parse_if_match(if_match.0.as_deref())?;
         let mut funs = iam_constants::get_tardis_inst();
         funs.begin().await?;
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(&id.0, expected_version, &funs, &ctx.0).await?;
+        }
         IamResServ::modify_item(&id.0, &mut modify_req.0, &funs, &ctx.0).await?;
         funs.commit().await?;
         TardisResp::ok(Void {})
@@ -85,10 +101,11 @@ impl IamCsResApi {
 
     /// Get Res By Res Id
     #[oai(path = "/:id", method = "get")]
-    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<IamResDetailResp> {
+    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> poem::Result<Response<Json<TardisResp<IamResDetailResp>>>> {
         let funs = iam_constants::get_tardis_inst();
+        let etag = rbum_etag_helper::to_etag(RbumItemServ::get_rbum_version(&id.0, &funs, &ctx.0).await?);
         let result = IamResServ::get_item(&id.0, &IamResFilterReq::default(), &funs, &ctx.0).await?;
-        TardisResp::ok(result)
+        Ok(Response::new(TardisResp::ok(result)?).header(rbum_etag_helper::HEADER_ETAG, etag))
     }
 
     /// Delete Res By Res Id
@@ -101,6 +118,24 @@ impl IamCsResApi {
         TardisResp::ok(Void {})
     }
 
+    /// Find Deleted Res
+    #[oai(path = "/deleted", method = "get")]
+    async fn paginate_deleted(&self, page_number: Query<u64>, page_size: Query<u64>, ctx: TardisContextExtractor) -> TardisApiResult<TardisPage<RbumDeletedResp>> {
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamResServ::paginate_deleted_items(true, page_number.0, page_size.0, &funs, &ctx.0).await?;
+        TardisResp::ok(result)
+    }
+
+    /// Restore Deleted Res By Res Id
+    #[oai(path = "/:id/restore", method = "put")]
+    async fn restore(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        IamResServ::restore_item(&id.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(Void {})
+    }
+
     /// Find Rel Roles By Res Id
     #[oai(path = "/:id/role", method = "get")]
     async fn find_rel_roles(
@@ -1,10 +1,13 @@
 use tardis::web::context_extractor::TardisContextExtractor;
-use tardis::web::poem_openapi::{param::Path, param::Query, payload::Json, OpenApi};
+use tardis::web::poem;
+use tardis::web::poem_openapi::{param::Header, param::Path, param::Query, payload::Json, payload::Response, OpenApi};
 use tardis::web::web_resp::{TardisApiResult, TardisPage, TardisResp, Void};
 
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
 use bios_basic::rbum::dto::rbum_rel_dto::RbumRelBoneResp;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::helper::rbum_etag_helper;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 
 use crate::basic::dto::iam_filer_dto::IamRoleFilterReq;
 use crate::basic::dto::iam_role_dto::{IamRoleAggAddReq, IamRoleAggModifyReq, IamRoleDetailResp, IamRoleSummaryResp};
@@ -28,10 +31,22 @@ impl IamCsRoleApi {
     }
 
     /// Modify Role By Role Id
+    ///
+    /// When the `If-Match` header (the `ETag` returned by get) is present, a stale write is rejected with a conflict error.
     #[oai(path = "/:id", method = "put")]
-    async fn modify(&self, id: Path<String>, mut modify_req: Json<IamRoleAggModifyReq>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+    async fn modify(
+        &self,
+        id: Path<String>,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        mut modify_req: Json<IamRoleAggModifyReq>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<Void> {
+        let expected_version = rbum_etag_helper::parse_if_match(if_match.0.as_deref())?;
         let mut funs = iam_constants::get_tardis_inst();
         funs.begin().await?;
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(&id.0, expected_version, &funs, &ctx.0).await?;
+        }
         IamRoleServ::modify_role_agg(&id.0, &mut modify_req.0, &funs, &ctx.0).await?;
         funs.commit().await?;
         TardisResp::ok(Void {})
@@ -39,10 +54,11 @@ impl IamCsRoleApi {
 
     /// Get Role By Role Id
     #[oai(path = "/:id", method = "get")]
-    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<IamRoleDetailResp> {
+    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> poem::Result<Response<Json<TardisResp<IamRoleDetailResp>>>> {
         let funs = iam_constants::get_tardis_inst();
+        let etag = rbum_etag_helper::to_etag(RbumItemServ::get_rbum_version(&id.0, &funs, &ctx.0).await?);
         let result = IamRoleServ::get_item(&id.0, &IamRoleFilterReq::default(), &funs, &ctx.0).await?;
-        TardisResp::ok(result)
+        Ok(Response::new(TardisResp::ok(result)?).header(rbum_etag_helper::HEADER_ETAG, etag))
     }
 
     /// Find Roles
@@ -0,0 +1,50 @@
+use tardis::basic::dto::TardisContext;
+use tardis::web::context_extractor::TardisContextExtractor;
+use tardis::web::poem_openapi::{param::Query, OpenApi};
+use tardis::web::web_resp::{TardisApiResult, TardisResp};
+
+use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
+use bios_basic::rbum::dto::rbum_scope_dto::RbumScopeExplainResp;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::RbumItemServ;
+
+use crate::iam_constants;
+
+pub struct IamCsScopeApi;
+
+/// System Console Scope API
+#[OpenApi(prefix_path = "/cs/scope", tag = "crate::iam_enumeration::Tag::System")]
+impl IamCsScopeApi {
+    /// Explain Whether The Record Is Visible To The Own Paths
+    ///
+    /// The table must be one of the scoped rbum tables: rbum_domain, rbum_kind, rbum_kind_attr, rbum_item, rbum_set, rbum_set_cate, others are rejected with 400.
+    #[oai(path = "/explain", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn explain(
+        &self,
+        table_name: Query<String>,
+        id: Query<String>,
+        own_paths: Query<String>,
+        filter_own_paths: Query<Option<String>>,
+        with_sub_own_paths: Query<Option<bool>>,
+        ignore_scope: Query<Option<bool>>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<RbumScopeExplainResp> {
+        let funs = iam_constants::get_tardis_inst();
+        let ctx = TardisContext { own_paths: own_paths.0, ..ctx.0 };
+        let result = RbumItemServ::explain_scope(
+            &id.0,
+            &table_name.0,
+            &RbumBasicFilterReq {
+                own_paths: filter_own_paths.0,
+                with_sub_own_paths: with_sub_own_paths.0.unwrap_or(false),
+                ignore_scope: ignore_scope.0.unwrap_or(false),
+                ..Default::default()
+            },
+            &funs,
+            &ctx,
+        )
+        .await?;
+        TardisResp::ok(result)
+    }
+}
@@ -1,14 +1,20 @@
 use tardis::web::context_extractor::TardisContextExtractor;
-use tardis::web::poem_openapi::{param::Path, param::Query, payload::Json, OpenApi};
+use tardis::web::poem;
+use tardis::web::poem_openapi::{param::Header, param::Path, param::Query, payload::Json, payload::Response, OpenApi};
 use tardis::web::web_resp::{TardisApiResult, TardisPage, TardisResp, Void};
 
+use bios_basic::rbum::dto::rbum_archive_dto::RbumArchive;
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::helper::rbum_etag_helper;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 
 use crate::basic::dto::iam_filer_dto::IamTenantFilterReq;
 use crate::basic::dto::iam_tenant_dto::{IamTenantDetailResp, IamTenantModifyReq, IamTenantSummaryResp};
+use crate::basic::serv::iam_account_serv::IamAccountServ;
+use crate::basic::serv::iam_app_serv::IamAppServ;
 use crate::basic::serv::iam_tenant_serv::IamTenantServ;
-use crate::console_system::dto::iam_cs_tenant_dto::IamCsTenantAddReq;
+use crate::console_system::dto::iam_cs_tenant_dto::{IamCsTenantAddReq, IamCsTenantImportResp};
 use crate::console_system::serv::iam_cs_tenant_serv::IamCsTenantServ;
 use crate::iam_constants;
 
@@ -28,10 +34,22 @@ impl IamCsTenantApi {
     }
 
     /// Modify Tenant By Tenant Id
+    ///
+    /// When the `If-Match` header (the `ETag` returned by get) is present, a stale write is rejected with a conflict error.
     #[oai(path = "/:id", method = "put")]
-    async fn modify(&self, id: Path<String>, mut modify_req: Json<IamTenantModifyReq>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+    async fn modify(
+        &self,
+        id: Path<String>,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        mut modify_req: Json<IamTenantModifyReq>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<Void> {
+        let expected_version = rbum_etag_helper::parse_if_match(if_match.0.as_deref())?;
         let mut funs = iam_constants::get_tardis_inst();
         funs.begin().await?;
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(&id.0, expected_version, &funs, &ctx.0).await?;
+        }
         IamTenantServ::modify_item(&id.0, &mut modify_req.0, &funs, &ctx.0).await?;
         funs.commit().await?;
         TardisResp::ok(Void {})
@@ -39,8 +57,9 @@ impl IamCsTenantApi {
 
     /// Get Tenant By Tenant Id
     #[oai(path = "/:id", method = "get")]
-    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<IamTenantDetailResp> {
+    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> poem::Result<Response<Json<TardisResp<IamTenantDetailResp>>>> {
         let funs = iam_constants::get_tardis_inst();
+        let etag = rbum_etag_helper::to_etag(RbumItemServ::get_rbum_version(&id.0, &funs, &ctx.0).await?);
         let result = IamTenantServ::get_item(
             &id.0,
             &IamTenantFilterReq {
@@ -54,7 +73,7 @@ impl IamCsTenantApi {
             &ctx.0,
         )
         .await?;
-        TardisResp::ok(result)
+        Ok(Response::new(TardisResp::ok(result)?).header(rbum_etag_helper::HEADER_ETAG, etag))
     }
 
     /// Find Tenants
@@ -63,6 +82,7 @@ impl IamCsTenantApi {
         &self,
         id: Query<Option<String>>,
         name: Query<Option<String>>,
+        template: Query<Option<bool>>,
         desc_by_create: Query<Option<bool>>,
         desc_by_update: Query<Option<bool>>,
         page_number: Query<u64>,
@@ -78,6 +98,7 @@ impl IamCsTenantApi {
                     with_sub_own_paths: true,
                     ..Default::default()
                 },
+                template: template.0,
                 ..Default::default()
             },
             page_number.0,
@@ -90,4 +111,58 @@ impl IamCsTenantApi {
         .await?;
         TardisResp::ok(result)
     }
+
+    /// Modify Tenant Template Flag By Tenant Id
+    #[oai(path = "/:id/template", method = "put")]
+    async fn modify_template(&self, id: Path<String>, template: Query<bool>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        IamTenantServ::modify_template(&id.0, template.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(Void {})
+    }
+
+    /// Move Account Into Tenant By Tenant Id
+    ///
+    /// Accounts can only be moved between tenants, the apps of the account are changed by the account app relationships.
+    #[oai(path = "/:id/account/:account_id", method = "put")]
+    async fn move_account(&self, id: Path<String>, account_id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        IamAccountServ::move_item(&account_id.0, &id.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(Void {})
+    }
+
+    /// Move App Into Tenant By Tenant Id
+    ///
+    /// The resources of the app are moved together, the relationships to the resources of the old tenant have to be deleted first.
+    #[oai(path = "/:id/app/:app_id", method = "put")]
+    async fn move_app(&self, id: Path<String>, app_id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        IamAppServ::move_app(&app_id.0, &id.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(Void {})
+    }
+
+    /// Export Tenant By Tenant Id
+    #[oai(path = "/:id/export", method = "get")]
+    async fn export(&self, id: Path<String>, with_certs: Query<Option<bool>>, ctx: TardisContextExtractor) -> TardisApiResult<RbumArchive> {
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamCsTenantServ::export_tenant(&id.0, with_certs.0.unwrap_or(false), &funs, &ctx.0).await?;
+        TardisResp::ok(result)
+    }
+
+    /// Import Tenant
+    ///
+    /// A new tenant is created if ``tenant_id`` is not specified.
+    #[oai(path = "/import", method = "post")]
+    async fn import(&self, tenant_id: Query<Option<String>>, archive: Json<RbumArchive>, ctx: TardisContextExtractor) -> TardisApiResult<IamCsTenantImportResp> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        let result = IamCsTenantServ::import_tenant(tenant_id.0, archive.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(result)
+    }
 }
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use tardis::basic::field::TrimString;
 use tardis::web::poem_openapi::Object;
 
+use bios_basic::rbum::dto::rbum_archive_dto::RbumArchiveImportResp;
+
 use crate::basic::dto::iam_cert_conf_dto::{IamMailVCodeCertConfAddOrModifyReq, IamPhoneVCodeCertConfAddOrModifyReq, IamUserPwdCertConfAddOrModifyReq};
 
 #[derive(Object, Serialize, Deserialize, Debug)]
@@ -28,4 +30,14 @@ pub struct IamCsTenantAddReq {
     pub cert_conf_by_mail_vcode: Option<IamMailVCodeCertConfAddOrModifyReq>,
 
     pub disabled: Option<bool>,
+    /// Copy the roles, the resource and organization sets, the kind attributes and the certificate configurations from the template tenant,
+    /// the certificate configurations of the request are ignored
+    pub template_tenant_id: Option<String>,
+}
+
+#[derive(Object, Serialize, Deserialize, Debug)]
+pub struct IamCsTenantImportResp {
+    pub tenant_id: String,
+    /// Nothing is imported when there are conflicts
+    pub result: RbumArchiveImportResp,
 }
@@ -1,21 +1,33 @@
+use std::collections::{HashMap, HashSet};
+
 use tardis::basic::dto::TardisContext;
 use tardis::basic::field::TrimString;
 use tardis::basic::result::TardisResult;
+use tardis::serde_json::Value;
 use tardis::{TardisFuns, TardisFunsInst};
 
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::dto::rbum_archive_dto::RbumArchive;
+use bios_basic::rbum::helper::rbum_archive_helper::{RbumArchiveReader, RbumArchiveWriter};
+use bios_basic::rbum::serv::rbum_archive_serv::RbumArchiveServ;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
+use bios_basic::rbum::serv::rbum_set_serv::RbumSetServ;
 
 use crate::basic::dto::iam_account_dto::IamAccountAggAddReq;
+use crate::basic::dto::iam_filer_dto::IamTenantFilterReq;
 use crate::basic::dto::iam_tenant_dto::IamTenantAddReq;
 use crate::basic::serv::iam_account_serv::IamAccountServ;
 use crate::basic::serv::iam_cert_serv::IamCertServ;
 use crate::basic::serv::iam_set_serv::IamSetServ;
 use crate::basic::serv::iam_tenant_serv::IamTenantServ;
-use crate::console_system::dto::iam_cs_tenant_dto::IamCsTenantAddReq;
+use crate::console_system::dto::iam_cs_tenant_dto::{IamCsTenantAddReq, IamCsTenantImportResp};
 use crate::iam_config::IamBasicConfigApi;
 use crate::iam_constants;
 use crate::iam_constants::RBUM_SCOPE_LEVEL_TENANT;
 
+/// Fields that reference other records, see ``IamCsTenantServ::copy_from_template``
+const TEMPLATE_REF_FIELDS: [&str; 4] = ["rel_rbum_item_id", "from_rbum_id", "to_rbum_item_id", "rel_rbum_rel_id"];
+
 pub struct IamCsTenantServ;
 
 impl<'a> IamCsTenantServ {
@@ -39,23 +51,28 @@ impl<'a> IamCsTenantServ {
                 disabled: add_req.disabled,
                 scope_level: Some(iam_constants::RBUM_SCOPE_LEVEL_GLOBAL),
                 note: add_req.tenant_note.clone(),
+                template: None,
             },
             funs,
             &tenant_ctx,
         )
         .await?;
 
-        IamSetServ::init_set(true, RBUM_SCOPE_LEVEL_TENANT, funs, &tenant_ctx).await?;
-        IamSetServ::init_set(false, RBUM_SCOPE_LEVEL_TENANT, funs, &tenant_ctx).await?;
+        if let Some(template_tenant_id) = &add_req.template_tenant_id {
+            Self::copy_from_template(template_tenant_id, funs, &tenant_ctx).await?;
+        } else {
+            IamSetServ::init_set(true, RBUM_SCOPE_LEVEL_TENANT, funs, &tenant_ctx).await?;
+            IamSetServ::init_set(false, RBUM_SCOPE_LEVEL_TENANT, funs, &tenant_ctx).await?;
 
-        IamCertServ::init_default_ident_conf(
-            add_req.cert_conf_by_user_pwd.clone(),
-            add_req.cert_conf_by_phone_vcode.clone(),
-            add_req.cert_conf_by_mail_vcode.clone(),
-            funs,
-            &tenant_ctx,
-        )
-        .await?;
+            IamCertServ::init_default_ident_conf(
+                add_req.cert_conf_by_user_pwd.clone(),
+                add_req.cert_conf_by_phone_vcode.clone(),
+                add_req.cert_conf_by_mail_vcode.clone(),
+                funs,
+                &tenant_ctx,
+            )
+            .await?;
+        }
 
         let pwd = if let Some(admin_password) = &add_req.admin_password {
             admin_password.to_string()
@@ -84,4 +101,119 @@ impl<'a> IamCsTenantServ {
 
         Ok((tenant_id, pwd))
     }
+
+    /// Copy the tenant level roles, resources, sets, kind attributes and certificate configurations of the template tenant
+    ///
+    /// Accounts, apps and everything bound to them are not copied.
+    async fn copy_from_template(template_tenant_id: &str, funs: &TardisFunsInst<'a>, tenant_ctx: &TardisContext) -> TardisResult<()> {
+        let sys_ctx = TardisContext {
+            own_paths: "".to_string(),
+            ..tenant_ctx.clone()
+        };
+        IamTenantServ::peek_item(
+            template_tenant_id,
+            &IamTenantFilterReq {
+                template: Some(true),
+                ..Default::default()
+            },
+            funs,
+            &sys_ctx,
+        )
+        .await
+        .map_err(|_| funs.err().not_found(&IamTenantServ::get_obj_name(), "add", &format!("not found template tenant {}", template_tenant_id)))?;
+        let mut archive = RbumArchiveServ::export(template_tenant_id, false, funs, &sys_ctx).await?;
+        let copied_kind_ids = [funs.iam_basic_kind_role_id(), funs.iam_basic_kind_res_id()];
+        // Ids of the records that are not copied, the records that reference them are not copied either
+        let mut ignored_ids = HashSet::new();
+        for table in archive.tables.iter_mut() {
+            let is_item_table = table.table_name == RbumItemServ::get_table_name();
+            table.records.retain(|record| {
+                let get_str = |field: &str| record.get(field).and_then(|value| value.as_str()).unwrap_or_default();
+                let id = get_str("id");
+                // Records of the apps are not copied
+                let copied = get_str("own_paths") == template_tenant_id
+                    && (!is_item_table || copied_kind_ids.iter().any(|kind_id| kind_id == get_str("rel_rbum_kind_id")))
+                    && !ignored_ids.contains(id)
+                    && !TEMPLATE_REF_FIELDS.iter().any(|field| ignored_ids.contains(get_str(field)));
+                // The template tenant itself is replaced by the new tenant
+                if !copied && id != template_tenant_id {
+                    ignored_ids.insert(id.to_string());
+                }
+                copied
+            });
+        }
+        let resp = Self::import_tenant(Some(tenant_ctx.own_paths.clone()), archive, funs, tenant_ctx).await?;
+        if !resp.result.conflicts.is_empty() {
+            let conflicts = resp.result.conflicts.iter().map(|conflict| format!("{}:{}", conflict.table_name, conflict.message)).collect::<Vec<String>>();
+            return Err(funs.err().conflict(&IamTenantServ::get_obj_name(), "add", &format!("copy from template tenant failed: {}", conflicts.join(";"))));
+        }
+        Ok(())
+    }
+
+    /// Export the tenant and all records under it
+    pub async fn export_tenant(tenant_id: &str, with_certs: bool, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<RbumArchive> {
+        IamTenantServ::peek_item(tenant_id, &IamTenantFilterReq::default(), funs, ctx).await?;
+        RbumArchiveServ::export(tenant_id, with_certs, funs, ctx).await
+    }
+
+    /// Export the tenant and all records under it to the writer, e.g. a bundle, see ``rbum_archive_helper``
+    pub async fn export_tenant_to<W: RbumArchiveWriter + Send>(
+        tenant_id: &str,
+        with_certs: bool,
+        writer: &mut W,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<()> {
+        IamTenantServ::peek_item(tenant_id, &IamTenantFilterReq::default(), funs, ctx).await?;
+        RbumArchiveServ::export_to(tenant_id, with_certs, writer, funs, ctx).await?;
+        Ok(())
+    }
+
+    /// Import the archive of a tenant, the tenant is created if it does not exist, a new tenant id is generated if ``tenant_id`` is not specified
+    pub async fn import_tenant(tenant_id: Option<String>, mut archive: RbumArchive, funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<IamCsTenantImportResp> {
+        Self::import_tenant_from(tenant_id, &mut archive, funs, ctx).await
+    }
+
+    /// Import the archive of a tenant from the reader, e.g. a bundle, see ``import_tenant``
+    pub async fn import_tenant_from<R: RbumArchiveReader + Send>(
+        tenant_id: Option<String>,
+        reader: &mut R,
+        funs: &TardisFunsInst<'a>,
+        ctx: &TardisContext,
+    ) -> TardisResult<IamCsTenantImportResp> {
+        let source_tenant_id = reader.manifest()?.own_paths;
+        if source_tenant_id.is_empty() || source_tenant_id.contains('/') {
+            return Err(funs.err().bad_request(&IamTenantServ::get_obj_name(), "import", "archive is not exported from a tenant"));
+        }
+        let tenant_id = tenant_id.unwrap_or_else(IamTenantServ::get_new_id);
+        // The codes of the default sets contain the own paths
+        let set_codes = HashMap::from([
+            (
+                IamSetServ::get_default_org_code_by_own_paths(&source_tenant_id),
+                IamSetServ::get_default_org_code_by_own_paths(&tenant_id),
+            ),
+            (
+                IamSetServ::get_default_res_code_by_own_paths(&source_tenant_id),
+                IamSetServ::get_default_res_code_by_own_paths(&tenant_id),
+            ),
+        ]);
+        let set_table_name = RbumSetServ::get_table_name();
+        let result = RbumArchiveServ::import_with_mapper(
+            reader,
+            &tenant_id,
+            &|table_name, record| {
+                if table_name == set_table_name {
+                    if let Some(Value::String(code)) = record.get_mut("code") {
+                        if let Some(new_code) = set_codes.get(code.as_str()) {
+                            *code = new_code.to_string();
+                        }
+                    }
+                }
+            },
+            funs,
+            ctx,
+        )
+        .await?;
+        Ok(IamCsTenantImportResp { tenant_id, result })
+    }
 }
@@ -1,12 +1,17 @@
 use tardis::web::context_extractor::TardisContextExtractor;
-use tardis::web::poem_openapi::{param::Path, param::Query, payload::Json, OpenApi};
+use tardis::web::poem;
+use tardis::web::poem_openapi::{param::Header, param::Path, param::Query, payload::Json, payload::Response, OpenApi};
 use tardis::web::web_resp::{TardisApiResult, TardisPage, TardisResp, Void};
 
+use bios_basic::rbum::dto::rbum_cursor_dto::{RbumCursorPage, RbumCursorReq};
 use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumItemRelFilterReq};
+use bios_basic::rbum::dto::rbum_recycle_dto::RbumDeletedResp;
 use bios_basic::rbum::dto::rbum_rel_dto::RbumRelBoneResp;
 use bios_basic::rbum::dto::rbum_set_dto::RbumSetPathResp;
-use bios_basic::rbum::rbum_enumeration::RbumRelFromKind;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::helper::rbum_etag_helper;
+use bios_basic::rbum::rbum_enumeration::{RbumCursorSortKind, RbumRelFromKind};
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 
 use crate::basic::dto::iam_account_dto::{IamAccountAggAddReq, IamAccountAggModifyReq, IamAccountDetailResp, IamAccountSummaryResp};
 use crate::basic::dto::iam_filer_dto::IamAccountFilterReq;
@@ -33,11 +38,24 @@ impl IamCtAccountApi {
     }
 
     /// Modify Account By Account Id
+    ///
+    /// When the `If-Match` header (the `ETag` returned by get) is present, a stale write is rejected with a conflict error.
     #[oai(path = "/:id", method = "put")]
-    async fn modify(&self, id: Path<String>, app_id: Query<Option<String>>, modify_req: Json<IamAccountAggModifyReq>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+    async fn modify(
+        &self,
+        id: Path<String>,
+        app_id: Query<Option<String>>,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        modify_req: Json<IamAccountAggModifyReq>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<Void> {
         let ctx = IamCertServ::try_use_app_ctx(ctx.0, app_id.0)?;
+        let expected_version = rbum_etag_helper::parse_if_match(if_match.0.as_deref())?;
         let mut funs = iam_constants::get_tardis_inst();
         funs.begin().await?;
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(&id.0, expected_version, &funs, &ctx).await?;
+        }
         IamAccountServ::modify_account_agg(&id.0, &modify_req.0, &funs, &ctx).await?;
         funs.commit().await?;
         TardisResp::ok(Void {})
@@ -45,8 +63,9 @@ impl IamCtAccountApi {
 
     /// Get Account By Account Id
     #[oai(path = "/:id", method = "get")]
-    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<IamAccountDetailResp> {
+    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> poem::Result<Response<Json<TardisResp<IamAccountDetailResp>>>> {
         let funs = iam_constants::get_tardis_inst();
+        let etag = rbum_etag_helper::to_etag(RbumItemServ::get_rbum_version(&id.0, &funs, &ctx.0).await?);
         let result = IamAccountServ::get_item(
             &id.0,
             &IamAccountFilterReq {
@@ -60,7 +79,7 @@ impl IamCtAccountApi {
             &ctx.0,
         )
         .await?;
-        TardisResp::ok(result)
+        Ok(Response::new(TardisResp::ok(result)?).header(rbum_etag_helper::HEADER_ETAG, etag))
     }
 
     /// Find Accounts
@@ -108,6 +127,51 @@ impl IamCtAccountApi {
         TardisResp::ok(result)
     }
 
+    /// Find Accounts By Cursor
+    #[oai(path = "/cursor", method = "get")]
+    async fn paginate_by_cursor(
+        &self,
+        name: Query<Option<String>>,
+        role_id: Query<Option<String>>,
+        app_id: Query<Option<String>>,
+        with_sub: Query<Option<bool>>,
+        sort_by: Query<Option<RbumCursorSortKind>>,
+        desc: Query<Option<bool>>,
+        page_size: Query<u64>,
+        cursor: Query<Option<String>>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<RbumCursorPage<IamAccountSummaryResp>> {
+        let ctx = IamCertServ::try_use_app_ctx(ctx.0, app_id.0)?;
+        let funs = iam_constants::get_tardis_inst();
+        let rel = role_id.0.map(|role_id| RbumItemRelFilterReq {
+            rel_by_from: true,
+            tag: Some(IamRelKind::IamAccountRole.to_string()),
+            from_rbum_kind: Some(RbumRelFromKind::Item),
+            rel_item_id: Some(role_id),
+        });
+        let result = IamAccountServ::paginate_items_by_cursor(
+            &IamAccountFilterReq {
+                basic: RbumBasicFilterReq {
+                    name: name.0,
+                    with_sub_own_paths: with_sub.0.unwrap_or(false),
+                    ..Default::default()
+                },
+                rel,
+                ..Default::default()
+            },
+            &RbumCursorReq {
+                sort_by: sort_by.0.unwrap_or(RbumCursorSortKind::CreateTime),
+                desc: desc.0.unwrap_or(false),
+                page_size: page_size.0,
+                cursor: cursor.0,
+            },
+            &funs,
+            &ctx,
+        )
+        .await?;
+        TardisResp::ok(result)
+    }
+
     /// Delete Account By Account Id
     #[oai(path = "/:id", method = "delete")]
     async fn delete(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
@@ -118,6 +182,24 @@ impl IamCtAccountApi {
         TardisResp::ok(Void {})
     }
 
+    /// Find Deleted Accounts
+    #[oai(path = "/deleted", method = "get")]
+    async fn paginate_deleted(&self, page_number: Query<u64>, page_size: Query<u64>, ctx: TardisContextExtractor) -> TardisApiResult<TardisPage<RbumDeletedResp>> {
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamAccountServ::paginate_deleted_items(true, page_number.0, page_size.0, &funs, &ctx.0).await?;
+        TardisResp::ok(result)
+    }
+
+    /// Restore Deleted Account By Account Id
+    #[oai(path = "/:id/restore", method = "put")]
+    async fn restore(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        IamAccountServ::restore_item(&id.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(Void {})
+    }
+
     /// Find Rel Roles By Account Id
     #[oai(path = "/:id/role", method = "get")]
     async fn find_rel_roles(
@@ -4,7 +4,7 @@ use tardis::web::context_extractor::TardisContextExtractor;
 use tardis::web::poem_openapi::{param::Path, param::Query, payload::Json, OpenApi};
 use tardis::web::web_resp::{TardisApiResult, TardisResp, Void};
 
-use bios_basic::rbum::dto::rbum_kind_attr_dto::{RbumKindAttrDetailResp, RbumKindAttrModifyReq, RbumKindAttrSummaryResp};
+use bios_basic::rbum::dto::rbum_kind_attr_dto::{RbumKindAttrDetailResp, RbumKindAttrModifyReq, RbumKindAttrSchemaResp, RbumKindAttrSummaryResp};
 
 use crate::basic::dto::iam_attr_dto::IamKindAttrAddReq;
 use crate::basic::serv::iam_attr_serv::IamAttrServ;
@@ -53,6 +53,14 @@ impl IamCtAccountAttrApi {
         TardisResp::ok(result)
     }
 
+    /// Get Account Attr Schema By Current Tenant
+    #[oai(path = "/schema", method = "get")]
+    async fn get_attr_schema(&self, ctx: TardisContextExtractor) -> TardisApiResult<RbumKindAttrSchemaResp> {
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamAttrServ::get_account_attr_schema(&funs, &ctx.0).await?;
+        TardisResp::ok(result)
+    }
+
     /// Delete Account Attr By Account Attr Id
     #[oai(path = "/:id", method = "delete")]
     async fn delete_attr(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
@@ -1,9 +1,13 @@
 use tardis::web::context_extractor::TardisContextExtractor;
-use tardis::web::poem_openapi::{param::Path, param::Query, payload::Json, OpenApi};
+use tardis::web::poem;
+use tardis::web::poem_openapi::{param::Header, param::Path, param::Query, payload::Json, payload::Response, OpenApi};
 use tardis::web::web_resp::{TardisApiResult, TardisPage, TardisResp, Void};
 
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::dto::rbum_recycle_dto::RbumDeletedResp;
+use bios_basic::rbum::helper::rbum_etag_helper;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 
 use crate::basic::dto::iam_app_dto::{IamAppDetailResp, IamAppModifyReq, IamAppSummaryResp};
 use crate::basic::dto::iam_filer_dto::IamAppFilterReq;
@@ -28,10 +32,22 @@ impl IamCtAppApi {
     }
 
     /// Modify App By Id
+    ///
+    /// When the `If-Match` header (the `ETag` returned by get) is present, a stale write is rejected with a conflict error.
     #[oai(path = "/:id", method = "put")]
-    async fn modify(&self, id: Path<String>, modify_req: Json<IamCtAppModifyReq>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+    async fn modify(
+        &self,
+        id: Path<String>,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        modify_req: Json<IamCtAppModifyReq>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<Void> {
+        let expected_version = rbum_etag_helper::parse_if_match(if_match.0.as_deref())?;
         let mut funs = iam_constants::get_tardis_inst();
         funs.begin().await?;
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(&id.0, expected_version, &funs, &ctx.0).await?;
+        }
         IamAppServ::modify_item(
             &id.0,
             &mut IamAppModifyReq {
@@ -52,10 +68,11 @@ impl IamCtAppApi {
 
     /// Get App By Id
     #[oai(path = "/:id", method = "get")]
-    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<IamAppDetailResp> {
+    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> poem::Result<Response<Json<TardisResp<IamAppDetailResp>>>> {
         let funs = iam_constants::get_tardis_inst();
+        let etag = rbum_etag_helper::to_etag(RbumItemServ::get_rbum_version(&id.0, &funs, &ctx.0).await?);
         let result = IamAppServ::get_item(&id.0, &IamAppFilterReq::default(), &funs, &ctx.0).await?;
-        TardisResp::ok(result)
+        Ok(Response::new(TardisResp::ok(result)?).header(rbum_etag_helper::HEADER_ETAG, etag))
     }
 
     /// Find Apps
@@ -101,4 +118,22 @@ impl IamCtAppApi {
         funs.commit().await?;
         TardisResp::ok(Void {})
     }
+
+    /// Find Deleted Apps
+    #[oai(path = "/deleted", method = "get")]
+    async fn paginate_deleted(&self, page_number: Query<u64>, page_size: Query<u64>, ctx: TardisContextExtractor) -> TardisApiResult<TardisPage<RbumDeletedResp>> {
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamAppServ::paginate_deleted_items(true, page_number.0, page_size.0, &funs, &ctx.0).await?;
+        TardisResp::ok(result)
+    }
+
+    /// Restore Deleted App By App Id
+    #[oai(path = "/:id/restore", method = "put")]
+    async fn restore(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        IamAppServ::restore_item(&id.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(Void {})
+    }
 }
@@ -1,10 +1,17 @@
 use tardis::web::context_extractor::TardisContextExtractor;
-use tardis::web::poem_openapi::{param::Path, param::Query, payload::Json, OpenApi};
+use tardis::web::poem;
+use tardis::web::poem_openapi::{param::Header, param::Path, param::Query, payload::Json, payload::Response, OpenApi};
 use tardis::web::web_resp::{TardisApiResult, TardisPage, TardisResp, Void};
 
+use bios_basic::rbum::dto::rbum_batch_dto::RbumBatchResp;
+use bios_basic::rbum::dto::rbum_cursor_dto::{RbumCursorPage, RbumCursorReq};
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
+use bios_basic::rbum::dto::rbum_recycle_dto::RbumDeletedResp;
 use bios_basic::rbum::dto::rbum_rel_dto::RbumRelBoneResp;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::helper::rbum_etag_helper;
+use bios_basic::rbum::rbum_enumeration::RbumCursorSortKind;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 
 use crate::basic::dto::iam_filer_dto::IamRoleFilterReq;
 use crate::basic::dto::iam_role_dto::{IamRoleAggAddReq, IamRoleAggModifyReq, IamRoleDetailResp, IamRoleSummaryResp};
@@ -28,10 +35,22 @@ impl IamCtRoleApi {
     }
 
     /// Modify Role By Role Id
+    ///
+    /// When the `If-Match` header (the `ETag` returned by get) is present, a stale write is rejected with a conflict error.
     #[oai(path = "/:id", method = "put")]
-    async fn modify(&self, id: Path<String>, mut modify_req: Json<IamRoleAggModifyReq>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+    async fn modify(
+        &self,
+        id: Path<String>,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        mut modify_req: Json<IamRoleAggModifyReq>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<Void> {
+        let expected_version = rbum_etag_helper::parse_if_match(if_match.0.as_deref())?;
         let mut funs = iam_constants::get_tardis_inst();
         funs.begin().await?;
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(&id.0, expected_version, &funs, &ctx.0).await?;
+        }
         IamRoleServ::modify_role_agg(&id.0, &mut modify_req.0, &funs, &ctx.0).await?;
         funs.commit().await?;
         TardisResp::ok(Void {})
@@ -39,10 +58,11 @@ impl IamCtRoleApi {
 
     /// Get Role By Role Id
     #[oai(path = "/:id", method = "get")]
-    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<IamRoleDetailResp> {
+    async fn get(&self, id: Path<String>, ctx: TardisContextExtractor) -> poem::Result<Response<Json<TardisResp<IamRoleDetailResp>>>> {
         let funs = iam_constants::get_tardis_inst();
+        let etag = rbum_etag_helper::to_etag(RbumItemServ::get_rbum_version(&id.0, &funs, &ctx.0).await?);
         let result = IamRoleServ::get_item(&id.0, &IamRoleFilterReq::default(), &funs, &ctx.0).await?;
-        TardisResp::ok(result)
+        Ok(Response::new(TardisResp::ok(result)?).header(rbum_etag_helper::HEADER_ETAG, etag))
     }
 
     /// Find Roles
@@ -82,6 +102,43 @@ impl IamCtRoleApi {
         TardisResp::ok(result)
     }
 
+    /// Find Roles By Cursor
+    #[oai(path = "/cursor", method = "get")]
+    async fn paginate_by_cursor(
+        &self,
+        name: Query<Option<String>>,
+        app_id: Query<Option<String>>,
+        with_sub: Query<Option<bool>>,
+        sort_by: Query<Option<RbumCursorSortKind>>,
+        desc: Query<Option<bool>>,
+        page_size: Query<u64>,
+        cursor: Query<Option<String>>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<RbumCursorPage<IamRoleSummaryResp>> {
+        let ctx = IamCertServ::try_use_app_ctx(ctx.0, app_id.0)?;
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamRoleServ::paginate_items_by_cursor(
+            &IamRoleFilterReq {
+                basic: RbumBasicFilterReq {
+                    name: name.0,
+                    with_sub_own_paths: with_sub.0.unwrap_or(false),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &RbumCursorReq {
+                sort_by: sort_by.0.unwrap_or(RbumCursorSortKind::CreateTime),
+                desc: desc.0.unwrap_or(false),
+                page_size: page_size.0,
+                cursor: cursor.0,
+            },
+            &funs,
+            &ctx,
+        )
+        .await?;
+        TardisResp::ok(result)
+    }
+
     /// Delete Role By Role Id
     #[oai(path = "/:id", method = "delete")]
     async fn delete(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
@@ -92,6 +149,24 @@ impl IamCtRoleApi {
         TardisResp::ok(Void {})
     }
 
+    /// Find Deleted Roles
+    #[oai(path = "/deleted", method = "get")]
+    async fn paginate_deleted(&self, page_number: Query<u64>, page_size: Query<u64>, ctx: TardisContextExtractor) -> TardisApiResult<TardisPage<RbumDeletedResp>> {
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamRoleServ::paginate_deleted_items(true, page_number.0, page_size.0, &funs, &ctx.0).await?;
+        TardisResp::ok(result)
+    }
+
+    /// Restore Deleted Role By Role Id
+    #[oai(path = "/:id/restore", method = "put")]
+    async fn restore(&self, id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        IamRoleServ::restore_item(&id.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(Void {})
+    }
+
     /// Add Rel Account
     #[oai(path = "/:id/account/:account_id", method = "put")]
     async fn add_rel_account(&self, id: Path<String>, account_id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
@@ -102,6 +177,16 @@ impl IamCtRoleApi {
         TardisResp::ok(Void {})
     }
 
+    /// Add Rel Accounts In Batch
+    #[oai(path = "/:id/account/batch", method = "put")]
+    async fn add_rel_accounts(&self, id: Path<String>, account_ids: Json<Vec<String>>, ctx: TardisContextExtractor) -> TardisApiResult<RbumBatchResp> {
+        let mut funs = iam_constants::get_tardis_inst();
+        funs.begin().await?;
+        let result = IamRoleServ::add_rel_accounts(&id.0, &account_ids.0, &funs, &ctx.0).await?;
+        funs.commit().await?;
+        TardisResp::ok(result)
+    }
+
     /// Delete Rel Account
     #[oai(path = "/:id/account/:account_id", method = "delete")]
     async fn delete_rel_account(&self, id: Path<String>, account_id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
@@ -120,6 +205,33 @@ impl IamCtRoleApi {
         TardisResp::ok(result)
     }
 
+    /// Find Rel Accounts By Cursor
+    #[oai(path = "/:id/account/cursor", method = "get")]
+    async fn paginate_rel_accounts_by_cursor(
+        &self,
+        id: Path<String>,
+        sort_by: Query<Option<RbumCursorSortKind>>,
+        desc: Query<Option<bool>>,
+        page_size: Query<u64>,
+        cursor: Query<Option<String>>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<RbumCursorPage<RbumRelBoneResp>> {
+        let funs = iam_constants::get_tardis_inst();
+        let result = IamRoleServ::paginate_simple_rel_accounts_by_cursor(
+            &id.0,
+            &RbumCursorReq {
+                sort_by: sort_by.0.unwrap_or(RbumCursorSortKind::CreateTime),
+                desc: desc.0.unwrap_or(false),
+                page_size: page_size.0,
+                cursor: cursor.0,
+            },
+            &funs,
+            &ctx.0,
+        )
+        .await?;
+        TardisResp::ok(result)
+    }
+
     /// Add Rel Res
     #[oai(path = "/:id/res/:res_id", method = "put")]
     async fn add_rel_res(&self, id: Path<String>, res_id: Path<String>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
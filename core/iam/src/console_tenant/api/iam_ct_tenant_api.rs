@@ -1,7 +1,10 @@
 use crate::basic::dto::iam_filer_dto::IamTenantFilterReq;
-use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::helper::rbum_etag_helper;
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
+use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 use tardis::web::context_extractor::TardisContextExtractor;
-use tardis::web::poem_openapi::{payload::Json, OpenApi};
+use tardis::web::poem;
+use tardis::web::poem_openapi::{param::Header, payload::Json, payload::Response, OpenApi};
 use tardis::web::web_resp::{TardisApiResult, TardisResp, Void};
 
 use crate::basic::dto::iam_tenant_dto::{IamTenantDetailResp, IamTenantModifyReq};
@@ -15,18 +18,32 @@ pub struct IamCtTenantApi;
 impl IamCtTenantApi {
     /// Get Current Tenant
     #[oai(path = "/", method = "get")]
-    async fn get(&self, ctx: TardisContextExtractor) -> TardisApiResult<IamTenantDetailResp> {
+    async fn get(&self, ctx: TardisContextExtractor) -> poem::Result<Response<Json<TardisResp<IamTenantDetailResp>>>> {
         let funs = iam_constants::get_tardis_inst();
-        let result = IamTenantServ::get_item(&IamTenantServ::get_id_by_ctx(&ctx.0, &funs)?, &IamTenantFilterReq::default(), &funs, &ctx.0).await?;
-        TardisResp::ok(result)
+        let id = IamTenantServ::get_id_by_ctx(&ctx.0, &funs)?;
+        let etag = rbum_etag_helper::to_etag(RbumItemServ::get_rbum_version(&id, &funs, &ctx.0).await?);
+        let result = IamTenantServ::get_item(&id, &IamTenantFilterReq::default(), &funs, &ctx.0).await?;
+        Ok(Response::new(TardisResp::ok(result)?).header(rbum_etag_helper::HEADER_ETAG, etag))
     }
 
     /// Modify Current Tenant
+    ///
+    /// When the `If-Match` header (the `ETag` returned by get) is present, a stale write is rejected with a conflict error.
     #[oai(path = "/", method = "put")]
-    async fn modify(&self, mut modify_req: Json<IamTenantModifyReq>, ctx: TardisContextExtractor) -> TardisApiResult<Void> {
+    async fn modify(
+        &self,
+        #[oai(name = "If-Match")] if_match: Header<Option<String>>,
+        mut modify_req: Json<IamTenantModifyReq>,
+        ctx: TardisContextExtractor,
+    ) -> TardisApiResult<Void> {
+        let expected_version = rbum_etag_helper::parse_if_match(if_match.0.as_deref())?;
         let mut funs = iam_constants::get_tardis_inst();
         funs.begin().await?;
-        IamTenantServ::modify_item(&IamTenantServ::get_id_by_ctx(&ctx.0, &funs)?, &mut modify_req.0, &funs, &ctx.0).await?;
+        let id = IamTenantServ::get_id_by_ctx(&ctx.0, &funs)?;
+        if let Some(expected_version) = expected_version {
+            RbumItemServ::check_rbum_version(&id, expected_version, &funs, &ctx.0).await?;
+        }
+        IamTenantServ::modify_item(&id, &mut modify_req.0, &funs, &ctx.0).await?;
         funs.commit().await?;
         TardisResp::ok(Void {})
     }
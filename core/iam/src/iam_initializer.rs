@@ -1,19 +1,23 @@
 use tardis::basic::dto::TardisContext;
 use tardis::basic::field::TrimString;
 use tardis::basic::result::TardisResult;
-use tardis::db::reldb_client::TardisActiveModel;
-use tardis::log::info;
+use tardis::db::sea_orm::DbBackend;
+use tardis::db::sea_query::ColumnDef;
+use tardis::log::{info, warn};
 use tardis::web::web_server::TardisWebServer;
 use tardis::{TardisFuns, TardisFunsInst};
 
 use bios_basic::rbum::dto::rbum_domain_dto::RbumDomainAddReq;
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
 use bios_basic::rbum::dto::rbum_kind_dto::RbumKindAddReq;
+use bios_basic::rbum::helper::rbum_event_helper;
+use bios_basic::rbum::helper::rbum_migration_helper::{self, RbumMigrationPendingResp, RbumMigrationStatement, RbumMigrationStep};
 use bios_basic::rbum::rbum_initializer::get_first_account_context;
 use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
 use bios_basic::rbum::serv::rbum_domain_serv::RbumDomainServ;
 use bios_basic::rbum::serv::rbum_item_serv::{RbumItemCrudOperation, RbumItemServ};
 use bios_basic::rbum::serv::rbum_kind_serv::RbumKindServ;
+use bios_basic::rbum::serv::rbum_rel_serv::RbumRelServ;
 
 use crate::basic::domain::{iam_account, iam_app, iam_res, iam_role, iam_tenant};
 use crate::basic::dto::iam_account_dto::{IamAccountAggAddReq, IamAccountAggModifyReq};
@@ -26,7 +30,9 @@ use crate::basic::serv::iam_set_serv::IamSetServ;
 use crate::console_app::api::{iam_ca_account_api, iam_ca_account_attr_api, iam_ca_app_api, iam_ca_cert_api, iam_ca_res_api, iam_ca_role_api};
 use crate::console_common::api::{iam_cc_account_api, iam_cc_role_api};
 use crate::console_passport::api::{iam_cp_account_api, iam_cp_account_attr_api, iam_cp_cert_api, iam_cp_tenant_api};
-use crate::console_system::api::{iam_cs_account_api, iam_cs_account_attr_api, iam_cs_cert_api, iam_cs_cert_conf_api, iam_cs_res_api, iam_cs_role_api, iam_cs_tenant_api};
+use crate::console_system::api::{
+    iam_cs_account_api, iam_cs_account_attr_api, iam_cs_cert_api, iam_cs_cert_conf_api, iam_cs_res_api, iam_cs_role_api, iam_cs_scope_api, iam_cs_tenant_api,
+};
 use crate::console_tenant::api::{
     iam_ct_account_api, iam_ct_account_attr_api, iam_ct_app_api, iam_ct_cert_api, iam_ct_cert_conf_api, iam_ct_org_api, iam_ct_res_api, iam_ct_role_api, iam_ct_tenant_api,
 };
@@ -36,9 +42,16 @@ use crate::iam_constants;
 pub async fn init(web_server: &TardisWebServer) -> TardisResult<()> {
     let funs = iam_constants::get_tardis_inst();
     init_db(funs).await?;
+    init_event().await?;
     init_api(web_server).await
 }
 
+async fn init_event() -> TardisResult<()> {
+    let funs = iam_constants::get_tardis_inst();
+    rbum_event_helper::subscribe(vec![RbumRelServ::check_rel_cache_subscriber(iam_constants::COMPONENT_CODE)], &funs).await?;
+    Ok(())
+}
+
 async fn init_api(web_server: &TardisWebServer) -> TardisResult<()> {
     web_server
         .add_module(
@@ -59,6 +72,7 @@ async fn init_api(web_server: &TardisWebServer) -> TardisResult<()> {
                     iam_cs_cert_conf_api::IamCsCertConfApi,
                     iam_cs_role_api::IamCsRoleApi,
                     iam_cs_res_api::IamCsResApi,
+                    iam_cs_scope_api::IamCsScopeApi,
                 ),
                 (
                     iam_ct_tenant_api::IamCtTenantApi,
@@ -86,18 +100,19 @@ async fn init_api(web_server: &TardisWebServer) -> TardisResult<()> {
 }
 
 pub async fn init_db(mut funs: TardisFunsInst<'_>) -> TardisResult<Option<(String, String)>> {
-    bios_basic::rbum::rbum_initializer::init(funs.module_code(), funs.conf::<IamConfig>().rbum.clone()).await?;
+    let rbum_config = funs.conf::<IamConfig>().rbum.clone();
+    let migrate_on_startup = rbum_config.migrate_on_startup;
+    bios_basic::rbum::rbum_initializer::init(funs.module_code(), rbum_config).await?;
+    let pending_steps = migrate(!migrate_on_startup).await?;
+    if !migrate_on_startup && !pending_steps.is_empty() {
+        warn!("{} iam migrations are pending, please apply them by the migrate command", pending_steps.len());
+    }
     funs.begin().await?;
     let ctx = get_first_account_context(iam_constants::RBUM_KIND_CODE_IAM_ACCOUNT, iam_constants::COMPONENT_CODE, &funs).await?;
     let sysadmin_info = if let Some(ctx) = ctx {
         init_basic_info(&funs, &ctx).await?;
         None
     } else {
-        funs.db().create_table_and_index(&iam_tenant::ActiveModel::create_table_and_index_statement(TardisFuns::reldb().backend())).await?;
-        funs.db().create_table_and_index(&iam_app::ActiveModel::create_table_and_index_statement(TardisFuns::reldb().backend())).await?;
-        funs.db().create_table_and_index(&iam_role::ActiveModel::create_table_and_index_statement(TardisFuns::reldb().backend())).await?;
-        funs.db().create_table_and_index(&iam_account::ActiveModel::create_table_and_index_statement(TardisFuns::reldb().backend())).await?;
-        funs.db().create_table_and_index(&iam_res::ActiveModel::create_table_and_index_statement(TardisFuns::reldb().backend())).await?;
         let (name, password) = init_rbum_data(&funs).await?;
         Some((name, password))
     };
@@ -105,6 +120,37 @@ pub async fn init_db(mut funs: TardisFunsInst<'_>) -> TardisResult<Option<(Strin
     Ok(sysadmin_info)
 }
 
+/// Apply the pending iam migrations, see ``rbum_migration_helper::migrate``
+pub async fn migrate(dry_run: bool) -> TardisResult<Vec<RbumMigrationPendingResp>> {
+    rbum_migration_helper::migrate(iam_constants::COMPONENT_CODE, &get_migration_steps(TardisFuns::reldb().backend()), dry_run).await
+}
+
+/// Migration steps of the iam tables, append new steps instead of modifying the released ones
+pub fn get_migration_steps(db_kind: DbBackend) -> Vec<RbumMigrationStep> {
+    vec![
+        RbumMigrationStep::new(
+            1,
+            "create iam tables",
+            vec![
+                RbumMigrationStatement::create_table::<iam_tenant::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<iam_app::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<iam_role::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<iam_account::ActiveModel>(db_kind),
+                RbumMigrationStatement::create_table::<iam_res::ActiveModel>(db_kind),
+            ],
+        ),
+        RbumMigrationStep::new(
+            2,
+            "add template to iam_tenant",
+            vec![RbumMigrationStatement::add_column(
+                iam_tenant::Entity,
+                iam_tenant::Column::Template,
+                ColumnDef::new(iam_tenant::Column::Template).not_null().boolean().default(false).to_owned(),
+            )],
+        ),
+    ]
+}
+
 async fn init_basic_info<'a>(funs: &TardisFunsInst<'a>, ctx: &TardisContext) -> TardisResult<()> {
     let kind_tenant_id = RbumKindServ::get_rbum_kind_id_by_code(iam_constants::RBUM_KIND_CODE_IAM_TENANT, funs)
         .await?
@@ -46,6 +46,7 @@ pub async fn test(_context: &TardisContext) -> TardisResult<(TardisContext, Tard
 
             cert_conf_by_mail_vcode: Some(IamMailVCodeCertConfAddOrModifyReq { ak_note: None, ak_rule: None }),
             disabled: None,
+            template_tenant_id: None,
         },
         &funs,
     )
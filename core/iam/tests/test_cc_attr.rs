@@ -4,9 +4,13 @@ use tardis::basic::result::TardisResult;
 use tardis::log::info;
 use tardis::TardisFunsInst;
 
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumItemAttrPredicateReq};
 use bios_basic::rbum::dto::rbum_kind_attr_dto::RbumKindAttrModifyReq;
-use bios_basic::rbum::rbum_enumeration::{RbumDataTypeKind, RbumScopeLevelKind, RbumWidgetTypeKind};
+use bios_basic::rbum::rbum_enumeration::{RbumDataTypeKind, RbumOperatorKind, RbumScopeLevelKind, RbumWidgetTypeKind};
+use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
 use bios_iam::basic::dto::iam_attr_dto::IamKindAttrAddReq;
+use bios_iam::basic::dto::iam_filer_dto::IamAccountFilterReq;
+use bios_iam::basic::serv::iam_account_serv::IamAccountServ;
 use bios_iam::basic::serv::iam_attr_serv::IamAttrServ;
 use bios_iam::iam_constants;
 
@@ -44,6 +48,12 @@ async fn test_single_level(context: &TardisContext, another_context: &TardisCont
     assert_eq!(IamAttrServ::find_account_attrs(&funs, another_context).await?.len(), 0);
     assert_eq!(IamAttrServ::find_account_attrs(&funs, context).await?.len(), 1);
 
+    info!("【test_cc_attr】 : test_single_level : Get Account Attr Schema");
+    let schema = IamAttrServ::get_account_attr_schema(&funs, context).await?;
+    assert_eq!(schema.json_schema["properties"]["attr1"]["title"], "attr1_modify");
+    assert_eq!(schema.form.len(), 1);
+    assert!(IamAttrServ::get_account_attr_schema(&funs, another_context).await?.json_schema["properties"].get("attr1").is_none());
+
     info!("【test_cc_attr】 : test_single_level : Add Account Attr value");
     assert!(IamAttrServ::add_account_attr_value("x".to_string(), &attr1, &another_context.owner, &funs, another_context).await.is_err());
     assert!(IamAttrServ::add_account_attr_value("x".to_string(), &attr1, &context.owner, &funs, another_context).await.is_err());
@@ -57,6 +67,62 @@ async fn test_single_level(context: &TardisContext, another_context: &TardisCont
     assert!(IamAttrServ::get_account_attr_value(&attr1_value, false, &funs, another_context).await.is_err());
     assert_eq!(IamAttrServ::get_account_attr_value(&attr1_value, false, &funs, context).await?.value, "attr1_value_modify");
 
+    info!("【test_cc_attr】 : test_single_level : Find Accounts By Attr value");
+    let find_accounts_by_attr = |attr_name: &str, op: RbumOperatorKind, value: &str| IamAccountFilterReq {
+        basic: RbumBasicFilterReq {
+            ids: Some(vec![context.owner.clone()]),
+            own_paths: Some("".to_string()),
+            with_sub_own_paths: true,
+            ..Default::default()
+        },
+        attrs: Some(vec![RbumItemAttrPredicateReq {
+            attr_name: attr_name.to_string(),
+            op,
+            values: vec![value.to_string()],
+        }]),
+        ..Default::default()
+    };
+    assert_eq!(
+        IamAccountServ::count_items(&find_accounts_by_attr("attr1", RbumOperatorKind::Eq, "attr1_value_modify"), &funs, context).await?,
+        1
+    );
+    assert_eq!(
+        IamAccountServ::count_items(&find_accounts_by_attr("attr1", RbumOperatorKind::Eq, "attr1_value"), &funs, context).await?,
+        0
+    );
+    assert_eq!(
+        IamAccountServ::count_items(&find_accounts_by_attr("attr1", RbumOperatorKind::Like, "value_mod"), &funs, context).await?,
+        1
+    );
+    assert!(IamAccountServ::count_items(&find_accounts_by_attr("not_exist", RbumOperatorKind::Eq, "xx"), &funs, context).await.is_err());
+
+    info!("【test_cc_attr】 : test_single_level : Find Accounts By Number Attr value");
+    let attr_num = IamAttrServ::add_account_attr(
+        &IamKindAttrAddReq {
+            data_type: RbumDataTypeKind::Number,
+            ..package_test_attr_add_req("attr_num", None)
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    let attr_num_value = IamAttrServ::add_account_attr_value("9".to_string(), &attr_num, &context.owner, &funs, context).await?;
+    // Compared as numbers, "9" > "10" as strings
+    assert_eq!(
+        IamAccountServ::count_items(&find_accounts_by_attr("attr_num", RbumOperatorKind::Gt, "10"), &funs, context).await?,
+        0
+    );
+    assert_eq!(
+        IamAccountServ::count_items(&find_accounts_by_attr("attr_num", RbumOperatorKind::Lt, "10"), &funs, context).await?,
+        1
+    );
+    assert_eq!(
+        IamAccountServ::count_items(&find_accounts_by_attr("attr_num", RbumOperatorKind::Eq, "9.0"), &funs, context).await?,
+        1
+    );
+    IamAttrServ::delete_account_attr_value(&attr_num_value, &funs, context).await?;
+    IamAttrServ::delete_account_attr(&attr_num, &funs, context).await?;
+
     info!("【test_cc_attr】 : test_single_level : Delete Account Attr value");
     assert!(IamAttrServ::delete_account_attr_value(&attr1_value, &funs, another_context).await.is_err());
     IamAttrServ::delete_account_attr_value(&attr1_value, &funs, context).await?;
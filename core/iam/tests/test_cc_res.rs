@@ -4,7 +4,8 @@ use tardis::basic::result::TardisResult;
 use tardis::log::info;
 use tardis::TardisFunsInst;
 
-use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumSortReq};
+use bios_basic::rbum::rbum_enumeration::RbumSortFieldKind;
 use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
 use bios_iam::basic::dto::iam_filer_dto::IamResFilterReq;
 use bios_iam::basic::dto::iam_res_dto::{IamResAddReq, IamResModifyReq};
@@ -135,6 +136,47 @@ async fn test_single_level(context: &TardisContext, another_context: &TardisCont
     assert_eq!(res.page_size, 10);
     assert!(res.records.iter().any(|i| i.name == "测试资源"));
 
+    info!("【test_cc_res】 : test_single_level : Find Res By Kind Ids And Sorts");
+    let res = IamResServ::paginate_items(
+        &IamResFilterReq {
+            basic: RbumBasicFilterReq {
+                rbum_kind_ids: Some(vec![IamResServ::get_rbum_kind_id()]),
+                desc_by_sort: Some(true),
+                sorts: Some(vec![RbumSortReq {
+                    field: RbumSortFieldKind::Name,
+                    desc: false,
+                }]),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        1,
+        10,
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    assert!(res.records.iter().any(|i| i.name == "测试资源"));
+    let res = IamResServ::paginate_items(
+        &IamResFilterReq {
+            basic: RbumBasicFilterReq {
+                rbum_kind_ids: Some(vec!["11111".to_string()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        1,
+        10,
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(res.total_size, 0);
+
     info!("【test_cc_res】 : test_single_level : Find Rel Roles By Res Id");
     let res_roles = IamResServ::paginate_simple_rel_roles(&IamRelKind::IamResRole,&res_id1, false, 1, 10, None, None, &funs, context).await?;
     assert_eq!(res_roles.total_size, 0);
@@ -5,15 +5,21 @@ use tardis::log::info;
 use tardis::TardisFunsInst;
 
 use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
+use bios_basic::rbum::dto::rbum_rel_dto::{RbumRelCheckEnvReq, RbumRelFindReq};
+use bios_basic::rbum::dto::rbum_rel_env_dto::RbumRelEnvAddReq;
+use bios_basic::rbum::rbum_enumeration::{RbumRelEnvKind, RbumRelFromKind};
+use bios_basic::rbum::serv::rbum_crud_serv::RbumCrudOperation;
 use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
+use bios_basic::rbum::serv::rbum_rel_serv::{RbumRelEnvServ, RbumRelServ};
 use bios_iam::basic::dto::iam_filer_dto::IamRoleFilterReq;
 use bios_iam::basic::dto::iam_res_dto::IamResAddReq;
 use bios_iam::basic::dto::iam_role_dto::{IamRoleAddReq, IamRoleModifyReq};
 use bios_iam::basic::serv::iam_res_serv::IamResServ;
 use bios_iam::basic::serv::iam_role_serv::IamRoleServ;
+use bios_iam::iam_config::IamBasicConfigApi;
 use bios_iam::iam_constants;
 use bios_iam::iam_constants::{RBUM_ITEM_NAME_SYS_ADMIN_ACCOUNT, RBUM_SCOPE_LEVEL_GLOBAL, RBUM_SCOPE_LEVEL_TENANT};
-use bios_iam::iam_enumeration::IamResKind;
+use bios_iam::iam_enumeration::{IamRelKind, IamResKind};
 
 pub async fn test(
     sys_context: &TardisContext,
@@ -28,6 +34,54 @@ pub async fn test(
     test_multi_level_by_sys_context(sys_context, t1_context, t2_context, t2_a1_context, t2_a2_context).await?;
     test_multi_level_by_tenant_context(sys_context, t1_context, t2_context, t2_a1_context, t2_a2_context).await?;
     test_multi_level_by_app_context(sys_context, t1_context, t2_context, t2_a1_context, t2_a2_context).await?;
+    test_need_role(sys_context, t1_context).await?;
+    Ok(())
+}
+
+fn package_env(client_ip: &str, forwarded_ips: Vec<&str>) -> Option<RbumRelCheckEnvReq> {
+    Some(RbumRelCheckEnvReq {
+        client_ip: Some(client_ip.to_string()),
+        forwarded_ips: Some(forwarded_ips.into_iter().map(|ip| ip.to_string()).collect()),
+    })
+}
+
+async fn test_need_role(sys_context: &TardisContext, t1_context: &TardisContext) -> TardisResult<()> {
+    let mut funs = iam_constants::get_tardis_inst();
+    funs.begin().await?;
+
+    info!("【test_cc_role】 : test_need_role : Need Role");
+    IamRoleServ::need_sys_admin(None, &funs, sys_context).await?;
+    assert!(IamRoleServ::need_sys_admin(None, &funs, t1_context).await.is_err());
+
+    info!("【test_cc_role】 : test_need_role : Need Role With Ips Env");
+    let rel_ids = RbumRelServ::find_rel_ids(
+        &RbumRelFindReq {
+            tag: Some(IamRelKind::IamAccountRole.to_string()),
+            from_rbum_kind: Some(RbumRelFromKind::Item),
+            from_rbum_id: Some(sys_context.owner.to_string()),
+            to_rbum_item_id: Some(funs.iam_basic_role_sys_admin_id()),
+        },
+        &funs,
+        sys_context,
+    )
+    .await?;
+    RbumRelEnvServ::add_rbum(
+        &mut RbumRelEnvAddReq {
+            kind: RbumRelEnvKind::Ips,
+            value1: "10.1.0.0/16".to_string(),
+            value2: None,
+            rel_rbum_rel_id: rel_ids.get(0).unwrap().to_string(),
+        },
+        &funs,
+        sys_context,
+    )
+    .await?;
+    IamRoleServ::need_sys_admin(package_env("10.1.0.1", vec![]), &funs, sys_context).await?;
+    assert!(IamRoleServ::need_sys_admin(package_env("10.2.0.1", vec![]), &funs, sys_context).await.is_err());
+    // The forwarded addresses are not followed without trusted proxies
+    assert!(IamRoleServ::need_sys_admin(package_env("10.2.0.2", vec!["10.1.0.1"]), &funs, sys_context).await.is_err());
+
+    funs.rollback().await?;
     Ok(())
 }
 
@@ -48,6 +48,7 @@ pub async fn test(sysadmin_info: (&str, &str), system_admin_context: &TardisCont
             cert_conf_by_phone_vcode: Some(IamPhoneVCodeCertConfAddOrModifyReq { ak_note: None, ak_rule: None }),
             cert_conf_by_mail_vcode: Some(IamMailVCodeCertConfAddOrModifyReq { ak_note: None, ak_rule: None }),
             disabled: None,
+            template_tenant_id: None,
         },
         &funs,
     )
@@ -3,16 +3,25 @@ use tardis::basic::field::TrimString;
 use tardis::basic::result::TardisResult;
 use tardis::log::info;
 
-use bios_basic::rbum::dto::rbum_filer_dto::RbumBasicFilterReq;
+use bios_basic::rbum::dto::rbum_filer_dto::{RbumBasicFilterReq, RbumCertFilterReq};
 use bios_basic::rbum::serv::rbum_item_serv::RbumItemCrudOperation;
 use bios_iam::basic::dto::iam_cert_conf_dto::{IamMailVCodeCertConfAddOrModifyReq, IamPhoneVCodeCertConfAddOrModifyReq, IamUserPwdCertConfAddOrModifyReq};
-use bios_iam::basic::dto::iam_filer_dto::IamTenantFilterReq;
+use bios_iam::basic::dto::iam_filer_dto::{IamAccountFilterReq, IamAppFilterReq, IamTenantFilterReq};
+use bios_iam::basic::dto::iam_set_dto::IamSetCateAddReq;
 use bios_iam::basic::dto::iam_tenant_dto::IamTenantModifyReq;
+use bios_iam::basic::serv::iam_account_serv::IamAccountServ;
+use bios_iam::basic::serv::iam_app_serv::IamAppServ;
 use bios_iam::basic::serv::iam_cert_serv::IamCertServ;
+use bios_iam::basic::serv::iam_role_serv::IamRoleServ;
+use bios_iam::basic::serv::iam_set_serv::IamSetServ;
 use bios_iam::basic::serv::iam_tenant_serv::IamTenantServ;
 use bios_iam::console_system::dto::iam_cs_tenant_dto::IamCsTenantAddReq;
 use bios_iam::console_system::serv::iam_cs_tenant_serv::IamCsTenantServ;
+use bios_iam::console_tenant::dto::iam_ct_app_dto::IamCtAppAddReq;
+use bios_iam::console_tenant::serv::iam_ct_app_serv::IamCtAppServ;
+use bios_iam::iam_config::IamBasicConfigApi;
 use bios_iam::iam_constants;
+use bios_iam::iam_enumeration::IamCertKind;
 
 pub async fn test(context: &TardisContext) -> TardisResult<()> {
     let mut funs = iam_constants::get_tardis_inst();
@@ -27,6 +36,7 @@ pub async fn test(context: &TardisContext) -> TardisResult<()> {
             tenant_note: None,
             admin_username: TrimString("admin".to_string()),
             disabled: None,
+            template_tenant_id: None,
             admin_name: TrimString("测试管理员".to_string()),
             admin_password: None,
             cert_conf_by_user_pwd: IamUserPwdCertConfAddOrModifyReq {
@@ -53,6 +63,7 @@ pub async fn test(context: &TardisContext) -> TardisResult<()> {
             admin_username: TrimString("admin".to_string()),
             tenant_note: None,
             disabled: None,
+            template_tenant_id: None,
             admin_name: TrimString("测试管理员".to_string()),
             admin_password: None,
             cert_conf_by_user_pwd: IamUserPwdCertConfAddOrModifyReq {
@@ -79,6 +90,7 @@ pub async fn test(context: &TardisContext) -> TardisResult<()> {
             tenant_note: None,
             admin_username: TrimString("admin1".to_string()),
             disabled: None,
+            template_tenant_id: None,
             admin_name: TrimString("测试管理员".to_string()),
             admin_password: None,
             cert_conf_by_user_pwd: IamUserPwdCertConfAddOrModifyReq {
@@ -183,6 +195,188 @@ pub async fn test(context: &TardisContext) -> TardisResult<()> {
     .await?;
     assert_eq!(tenants.len(), 2);
 
+    info!("【test_cs_tenant】 : Move Account Into Tenant");
+    let account_filter = |own_paths: &str| IamAccountFilterReq {
+        basic: RbumBasicFilterReq {
+            own_paths: Some(own_paths.to_string()),
+            ignore_scope: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let account_id = IamAccountServ::find_id_items(&account_filter(&tenant_id2), None, None, &funs, context).await?.pop().unwrap();
+    assert!(IamAccountServ::move_item(&account_id, "xxxx", &funs, context).await.is_err());
+    IamAccountServ::move_item(&account_id, &tenant_id, &funs, context).await?;
+    assert!(IamAccountServ::find_id_items(&account_filter(&tenant_id2), None, None, &funs, context).await?.is_empty());
+    assert_eq!(IamAccountServ::find_id_items(&account_filter(&tenant_id), None, None, &funs, context).await?.len(), 2);
+    let certs = IamCertServ::find_certs(
+        &RbumCertFilterReq {
+            basic: RbumBasicFilterReq {
+                with_sub_own_paths: true,
+                ..Default::default()
+            },
+            rel_rbum_id: Some(account_id.clone()),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    let cert_conf_id = IamCertServ::get_cert_conf_id_by_code(IamCertKind::UserPwd.to_string().as_str(), Some(tenant_id.clone()), &funs).await?;
+    assert!(!certs.is_empty());
+    assert!(certs.iter().all(|cert| cert.own_paths == tenant_id && cert.rel_rbum_cert_conf_id.as_ref() == Some(&cert_conf_id)));
+
+    info!("【test_cs_tenant】 : Export Tenant");
+    assert!(IamCsTenantServ::export_tenant("xxxx", false, &funs, context).await.is_err());
+    let archive = IamCsTenantServ::export_tenant(&tenant_id, true, &funs, context).await?;
+    assert_eq!(archive.own_paths, tenant_id);
+    assert!(archive.tables.iter().any(|table| table.table_name == "rbum_cert" && !table.records.is_empty()));
+
+    info!("【test_cs_tenant】 : Import Tenant");
+    let resp = IamCsTenantServ::import_tenant(None, archive, &funs, context).await?;
+    assert!(resp.result.conflicts.is_empty());
+    assert_ne!(resp.tenant_id, tenant_id);
+    let tenant = IamTenantServ::get_item(&resp.tenant_id, &IamTenantFilterReq::default(), &funs, context).await?;
+    assert_eq!(tenant.name, "测试租户1");
+    assert_eq!(IamAccountServ::find_id_items(&account_filter(&resp.tenant_id), None, None, &funs, context).await?.len(), 2);
+    IamCertServ::get_cert_conf_id_by_code(IamCertKind::UserPwd.to_string().as_str(), Some(resp.tenant_id.clone()), &funs).await?;
+    // The default sets of the tenant already exist
+    let archive = IamCsTenantServ::export_tenant(&tenant_id, false, &funs, context).await?;
+    let resp = IamCsTenantServ::import_tenant(Some(resp.tenant_id), archive, &funs, context).await?;
+    assert!(resp.result.imported.is_empty());
+    assert!(resp.result.conflicts.iter().any(|conflict| conflict.table_name == "rbum_set"));
+
+    info!("【test_cs_tenant】 : Add Tenant From Template");
+    let template_ctx = TardisContext {
+        own_paths: tenant_id2.clone(),
+        ..context.clone()
+    };
+    let org_set_id = IamSetServ::get_default_set_id_by_ctx(true, &funs, &template_ctx).await?;
+    IamSetServ::add_set_cate(
+        &org_set_id,
+        &IamSetCateAddReq {
+            name: TrimString("研发部".to_string()),
+            scope_level: None,
+            bus_code: None,
+            icon: None,
+            sort: None,
+            ext: None,
+            rbum_parent_cate_id: None,
+        },
+        &funs,
+        &template_ctx,
+    )
+    .await?;
+    let add_from_template_req = || IamCsTenantAddReq {
+        tenant_name: TrimString("测试租户3".to_string()),
+        tenant_icon: None,
+        tenant_contact_phone: None,
+        tenant_note: None,
+        admin_username: TrimString("admin".to_string()),
+        disabled: None,
+        template_tenant_id: Some(tenant_id2.clone()),
+        admin_name: TrimString("测试管理员".to_string()),
+        admin_password: None,
+        cert_conf_by_user_pwd: IamUserPwdCertConfAddOrModifyReq {
+            ak_note: None,
+            ak_rule: None,
+            sk_note: None,
+            sk_rule: None,
+            repeatable: Some(true),
+            expire_sec: None,
+        },
+        cert_conf_by_phone_vcode: None,
+        cert_conf_by_mail_vcode: None,
+    };
+    // The tenant is not a template
+    assert!(IamCsTenantServ::add_tenant(&mut add_from_template_req(), &funs).await.is_err());
+    IamTenantServ::modify_template(&tenant_id2, true, &funs, context).await?;
+    let templates = IamTenantServ::find_items(
+        &IamTenantFilterReq {
+            template: Some(true),
+            ..Default::default()
+        },
+        None,
+        None,
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].id, tenant_id2);
+
+    let tenant_id3 = IamCsTenantServ::add_tenant(&mut add_from_template_req(), &funs).await?.0;
+    let tenant_ctx3 = TardisContext {
+        own_paths: tenant_id3.clone(),
+        ..context.clone()
+    };
+    let org_tree = IamSetServ::get_tree(&IamSetServ::get_default_set_id_by_ctx(true, &funs, &tenant_ctx3).await?, None, &funs, &tenant_ctx3).await?;
+    assert!(org_tree.iter().any(|cate| cate.name == "研发部" && cate.own_paths == tenant_id3));
+    let res_tree = IamSetServ::get_tree(&IamSetServ::get_default_set_id_by_ctx(false, &funs, &tenant_ctx3).await?, None, &funs, &tenant_ctx3).await?;
+    assert!(res_tree.iter().any(|cate| cate.bus_code == "menus"));
+    // The cert confs are copied from the template, including the phone vcode conf that is not in the request
+    IamCertServ::get_cert_conf_id_by_code(IamCertKind::PhoneVCode.to_string().as_str(), Some(tenant_id3.clone()), &funs).await?;
+    // Only the new admin is created, the accounts of the template are not copied
+    assert_eq!(IamAccountServ::find_id_items(&account_filter(&tenant_id3), None, None, &funs, context).await?.len(), 1);
+
+    info!("【test_cs_tenant】 : Move App Into Tenant");
+    let tenant_ctx = TardisContext {
+        own_paths: tenant_id.clone(),
+        owner: account_id.clone(),
+        ..context.clone()
+    };
+    let app_id = IamCtAppServ::add_app(
+        &mut IamCtAppAddReq {
+            app_name: TrimString("测试应用".to_string()),
+            app_icon: None,
+            app_sort: None,
+            app_contact_phone: None,
+            disabled: None,
+            admin_id: account_id.clone(),
+        },
+        &funs,
+        &tenant_ctx,
+    )
+    .await?;
+    let app_ctx = TardisContext {
+        own_paths: format!("{}/{}", tenant_id, app_id),
+        ..tenant_ctx.clone()
+    };
+    let app_org_set_id = IamSetServ::get_default_set_id_by_ctx(true, &funs, &app_ctx).await?;
+    assert!(IamAppServ::move_item(&app_id, &tenant_id2, &funs, context).await.is_err());
+    assert!(IamAppServ::move_app(&app_id, "xxxx", &funs, context).await.is_err());
+    // The app admin belongs to the old tenant
+    assert!(IamAppServ::move_app(&app_id, &tenant_id2, &funs, context).await.is_err());
+    IamAppServ::delete_rel_account(&app_id, &account_id, &funs, &app_ctx).await?;
+    IamRoleServ::delete_rel_account(&funs.iam_basic_role_app_admin_id(), &account_id, &funs, &app_ctx).await?;
+    IamAppServ::move_app(&app_id, &tenant_id2, &funs, context).await?;
+    let app = IamAppServ::get_item(
+        &app_id,
+        &IamAppFilterReq {
+            basic: RbumBasicFilterReq {
+                with_sub_own_paths: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        &funs,
+        context,
+    )
+    .await?;
+    assert_eq!(app.own_paths, format!("{}/{}", tenant_id2, app_id));
+    let moved_app_ctx = TardisContext {
+        own_paths: app.own_paths.clone(),
+        ..context.clone()
+    };
+    // The default sets are moved with the app
+    assert_eq!(IamSetServ::get_default_set_id_by_ctx(true, &funs, &moved_app_ctx).await?, app_org_set_id);
+    IamSetServ::get_default_set_id_by_ctx(false, &funs, &moved_app_ctx).await?;
+    assert!(IamSetServ::get_default_set_id_by_ctx(true, &funs, &app_ctx).await.is_err());
+    // Accounts can only be moved between tenants
+    assert!(IamAccountServ::move_item(&account_id, &app.own_paths, &funs, context).await.is_err());
+
     funs.rollback().await?;
 
     Ok(())
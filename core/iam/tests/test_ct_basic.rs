@@ -28,6 +28,7 @@ pub async fn test(_context: &TardisContext) -> TardisResult<(TardisContext, Tard
             tenant_note: None,
             admin_username: TrimString("bios".to_string()),
             disabled: None,
+            template_tenant_id: None,
             admin_name: TrimString("测试管理员1".to_string()),
             admin_password: None,
             cert_conf_by_user_pwd: IamUserPwdCertConfAddOrModifyReq {
@@ -74,6 +75,7 @@ pub async fn test(_context: &TardisContext) -> TardisResult<(TardisContext, Tard
             tenant_note: None,
             admin_username: TrimString("bios".to_string()),
             disabled: None,
+            template_tenant_id: None,
             admin_name: TrimString("测试管理员2".to_string()),
             admin_password: None,
             cert_conf_by_user_pwd: IamUserPwdCertConfAddOrModifyReq {
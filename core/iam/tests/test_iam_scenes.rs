@@ -110,6 +110,7 @@ pub async fn sys_console_tenant_mgr_page(client: &mut BIOSWebTestClient) -> Tard
                 cert_conf_by_phone_vcode: Some(IamPhoneVCodeCertConfAddOrModifyReq { ak_note: None, ak_rule: None }),
                 cert_conf_by_mail_vcode: None,
                 disabled: None,
+                template_tenant_id: None,
             },
         )
         .await;
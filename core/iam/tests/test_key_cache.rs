@@ -60,6 +60,7 @@ pub async fn test(system_admin_context: &TardisContext) -> TardisResult<()> {
             cert_conf_by_phone_vcode: Some(IamPhoneVCodeCertConfAddOrModifyReq { ak_note: None, ak_rule: None }),
             cert_conf_by_mail_vcode: Some(IamMailVCodeCertConfAddOrModifyReq { ak_note: None, ak_rule: None }),
             disabled: None,
+            template_tenant_id: None,
         },
         &funs,
     )
@@ -939,6 +940,21 @@ pub async fn test(system_admin_context: &TardisContext) -> TardisResult<()> {
     assert_eq!(funs.cache().hlen(&funs.conf::<IamConfig>().cache_key_res_info).await?, exists_res_counter + 1);
     assert!(funs.cache().hget(&funs.conf::<IamConfig>().cache_key_res_info, &package_uri_mixed("ca/**", "*")).await?.unwrap().contains(r##""roles":"#""##));
 
+    info!("【test_key_cache】 Delete and restore res, expected the role rel record is restored");
+    IamRoleServ::add_rel_res(&role_id1, &res_ca_id, &funs, &app_admin_context).await?;
+    IamResServ::delete_item(&res_ca_id, &funs, system_admin_context).await?;
+    assert_eq!(funs.cache().hlen(&funs.conf::<IamConfig>().cache_key_res_info).await?, exists_res_counter);
+    assert!(funs.cache().hget(&funs.conf::<IamConfig>().cache_key_res_info, &package_uri_mixed("ca/**", "*")).await?.is_none());
+    IamResServ::restore_item(&res_ca_id, &funs, system_admin_context).await?;
+    assert_eq!(funs.cache().hlen(&funs.conf::<IamConfig>().cache_key_res_info).await?, exists_res_counter + 1);
+    assert!(funs.cache().hget(&funs.conf::<IamConfig>().cache_key_res_info, &package_uri_mixed("ca/**", "*")).await?.unwrap().contains(&format!("#{}#", role_id1)));
+
+    info!("【test_key_cache】 Delete and restore role, expected the role record is restored");
+    IamRoleServ::delete_item(&role_id1, &funs, &app_admin_context).await?;
+    assert!(funs.cache().get(&format!("{}{}", funs.conf::<IamConfig>().cache_key_role_info_, role_id1)).await?.is_none());
+    IamRoleServ::restore_item(&role_id1, &funs, &app_admin_context).await?;
+    assert!(funs.cache().get(&format!("{}{}", funs.conf::<IamConfig>().cache_key_role_info_, role_id1)).await?.unwrap().contains(&role_id1));
+
     Ok(())
 }
 
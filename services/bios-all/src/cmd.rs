@@ -0,0 +1,126 @@
+//! Command line tools
+//!
+//! ```sh
+//! bios-serv-all export-tenant <tenant id> <archive file> [--with-certs]
+//! bios-serv-all import-tenant <archive file> [tenant id]
+//! bios-serv-all migrate [--dry-run]
+//! ```
+//!
+//! The archive file is a bundle if its extension is ``.zip``, otherwise it is a JSON archive.
+//! Large tenants should be exported to bundles, the JSON archives are held in memory, see ``rbum_archive_helper``.
+//!
+//! The server is started if the command is not specified or unknown, e.g. the arguments for other tools.
+use std::fs::{self, File};
+
+use tardis::basic::dto::TardisContext;
+use tardis::basic::error::TardisError;
+use tardis::basic::result::TardisResult;
+use tardis::log::info;
+use tardis::{TardisFuns, TardisFunsInst};
+
+use bios_basic::rbum::dto::rbum_archive_dto::RbumArchive;
+use bios_basic::rbum::helper::rbum_archive_helper::{RbumArchiveBundleReader, RbumArchiveBundleWriter};
+use bios_basic::rbum::helper::rbum_migration_helper;
+use bios_basic::rbum::rbum_initializer::{self, get_first_account_context};
+use bios_iam::console_system::serv::iam_cs_tenant_serv::IamCsTenantServ;
+use bios_iam::iam_constants;
+use bios_iam::iam_initializer;
+
+pub const CMD_EXPORT_TENANT: &str = "export-tenant";
+pub const CMD_IMPORT_TENANT: &str = "import-tenant";
+pub const CMD_MIGRATE: &str = "migrate";
+
+/// Run the command if specified, return ``false`` if the server should be started
+pub async fn exec(args: &[String]) -> TardisResult<bool> {
+    match args.first().map(|cmd| cmd.as_str()) {
+        Some(CMD_EXPORT_TENANT) => {
+            let (tenant_id, file) = match (args.get(1), args.get(2)) {
+                (Some(tenant_id), Some(file)) => (tenant_id, file),
+                _ => return Err(TardisError::BadRequest(format!("usage: {} <tenant id> <archive file> [--with-certs]", CMD_EXPORT_TENANT))),
+            };
+            let with_certs = args.iter().skip(3).any(|arg| arg == "--with-certs");
+            export_tenant(tenant_id, file, with_certs).await?;
+            Ok(true)
+        }
+        Some(CMD_IMPORT_TENANT) => {
+            let file = args.get(1).ok_or_else(|| TardisError::BadRequest(format!("usage: {} <archive file> [tenant id]", CMD_IMPORT_TENANT)))?;
+            import_tenant(file, args.get(2).cloned()).await?;
+            Ok(true)
+        }
+        Some(CMD_MIGRATE) => {
+            migrate(args.iter().skip(1).any(|arg| arg == "--dry-run")).await?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+async fn export_tenant(tenant_id: &str, file: &str, with_certs: bool) -> TardisResult<()> {
+    let funs = iam_constants::get_tardis_inst();
+    let ctx = get_sys_admin_context(&funs).await?;
+    if is_bundle(file) {
+        let mut writer = RbumArchiveBundleWriter::new(File::create(file)?);
+        IamCsTenantServ::export_tenant_to(tenant_id, with_certs, &mut writer, &funs, &ctx).await?;
+    } else {
+        let archive = IamCsTenantServ::export_tenant(tenant_id, with_certs, &funs, &ctx).await?;
+        fs::write(file, TardisFuns::json.obj_to_string(&archive)?)?;
+    }
+    info!("[Bios] tenant {} is exported to {}", tenant_id, file);
+    Ok(())
+}
+
+async fn import_tenant(file: &str, tenant_id: Option<String>) -> TardisResult<()> {
+    let mut funs = iam_constants::get_tardis_inst();
+    let ctx = get_sys_admin_context(&funs).await?;
+    funs.begin().await?;
+    let resp = if is_bundle(file) {
+        let mut reader = RbumArchiveBundleReader::new(File::open(file)?)?;
+        IamCsTenantServ::import_tenant_from(tenant_id, &mut reader, &funs, &ctx).await?
+    } else {
+        let archive = TardisFuns::json.str_to_obj::<RbumArchive>(&fs::read_to_string(file)?)?;
+        IamCsTenantServ::import_tenant(tenant_id, archive, &funs, &ctx).await?
+    };
+    funs.commit().await?;
+    if resp.result.conflicts.is_empty() {
+        info!("[Bios] {} is imported into tenant {}", file, resp.tenant_id);
+        Ok(())
+    } else {
+        Err(TardisError::Conflict(TardisFuns::json.obj_to_string(&resp.result.conflicts)?))
+    }
+}
+
+fn is_bundle(file: &str) -> bool {
+    file.to_lowercase().ends_with(".zip")
+}
+
+async fn migrate(dry_run: bool) -> TardisResult<()> {
+    let funs = iam_constants::get_tardis_inst();
+    let mut pending_steps = rbum_initializer::migrate(dry_run).await?;
+    pending_steps.extend(iam_initializer::migrate(dry_run).await?);
+    for step in &pending_steps {
+        info!(
+            "[Bios] migration {}:{} {} is {}:\n{}",
+            step.module,
+            step.version,
+            step.name,
+            if dry_run { "pending" } else { "applied" },
+            step.sqls.join(";\n")
+        );
+    }
+    for module in [rbum_initializer::MIGRATION_MODULE, iam_constants::COMPONENT_CODE] {
+        for history in rbum_migration_helper::find_histories(module, funs.db()).await? {
+            info!(
+                "[Bios] migration {}:{} {} was applied at {}",
+                history.module, history.version, history.name, history.create_time
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn get_sys_admin_context<'a>(funs: &TardisFunsInst<'a>) -> TardisResult<TardisContext> {
+    iam_initializer::init_db(iam_constants::get_tardis_inst()).await?;
+    get_first_account_context(iam_constants::RBUM_KIND_CODE_IAM_ACCOUNT, iam_constants::COMPONENT_CODE, funs)
+        .await?
+        .ok_or_else(|| TardisError::NotFound("system administrator is not found".to_string()))
+}
@@ -2,15 +2,22 @@ use tardis::basic::result::TardisResult;
 use tardis::tokio;
 use tardis::TardisFuns;
 
+mod cmd;
 mod config;
 mod initializer;
 
 ///
 /// Visit: http://127.0.0.1:8081/
 ///
+/// Run ``bios-serv-all <command> [args]`` to execute a command instead, see ``cmd``.
+///
 #[tokio::main]
 async fn main() -> TardisResult<()> {
     TardisFuns::init("config").await?;
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if cmd::exec(&args).await? {
+        return Ok(());
+    }
     let web_server = TardisFuns::web_server();
     initializer::init(web_server).await?;
     web_server.start().await